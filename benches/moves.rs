@@ -0,0 +1,118 @@
+//! Measures the work every engine feature leans on: checking and playing
+//! moves, playing whole games out, and hashing positions, on 9x9, 13x13
+//! and 19x19 boards.
+//!
+//! Run with `cargo bench --bench moves`, criterion keeps the results of the
+//! last run under `target/criterion` to compare against.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use go_rs::estimate::ownership;
+use go_rs::game::{GoPosition, Player, Position};
+
+const BOARD_SIZES: [usize; 3] = [9, 13, 19];
+
+/// A position from the middle of a game: half the points tried with
+/// alternating colors, from a fixed seed so every run sees the same one.
+fn middle_game(size: usize) -> GoPosition {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut position = GoPosition::new(size);
+    let mut player = Player::Black;
+    for _ in 0..size * size / 2 {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let index = (state >> 33) as usize % (size * size);
+        let (x, y) = position.index_to_coord(index);
+        if position.is_valid_move(x, y, player) {
+            position.play(x, y, player);
+            player = match player {
+                Player::Black => Player::White,
+                _ => Player::Black,
+            };
+        }
+    }
+    position
+}
+
+/// Checks every point of the board for black.
+fn is_valid_move(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_valid_move");
+    for size in BOARD_SIZES {
+        let position = middle_game(size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &position,
+            |b, position| {
+                b.iter(|| {
+                    (0..position.len())
+                        .filter(|index| {
+                            let (x, y) = position.index_to_coord(*index);
+                            position.is_valid_move(black_box(x), black_box(y), Player::Black)
+                        })
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Plays one move, captures included, on a fresh copy of the position.
+fn process_move(c: &mut Criterion) {
+    let mut group = c.benchmark_group("process_move");
+    for size in BOARD_SIZES {
+        let position = middle_game(size);
+        let (x, y) = (0..position.len())
+            .map(|index| position.index_to_coord(index))
+            .find(|(x, y)| position.is_valid_move(*x, *y, Player::Black))
+            .expect("a middle game has empty points");
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &position,
+            |b, position| {
+                b.iter_batched(
+                    || position.clone(),
+                    |mut position| position.play(black_box(x), black_box(y), Player::Black),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Plays an empty board out with random moves that don't fill eyes, as the
+/// estimator and the built-in engine do for every visit.
+fn playout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("playout");
+    group.sample_size(20);
+    for size in BOARD_SIZES {
+        let position = GoPosition::new(size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &position,
+            |b, position| b.iter(|| ownership(black_box(position), Player::Black, 1)),
+        );
+    }
+    group.finish();
+}
+
+/// Hashes the stones of a position, as is done after every move of a game
+/// to check for superko and to cache estimates.
+fn hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash");
+    for size in BOARD_SIZES {
+        let position = middle_game(size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &position,
+            |b, position| b.iter(|| black_box(position).save().hash()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, is_valid_move, process_move, playout, hash);
+criterion_main!(benches);
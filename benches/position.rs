@@ -0,0 +1,83 @@
+//! Compares the `Vec<Player>` backed `GoPosition` with the bitboard backed
+//! `BitPosition` by running the same pseudo-random playouts on both.
+//!
+//! Run with `cargo bench --bench position`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use go_rs::bitboard::BitPosition;
+use go_rs::game::{GoPosition, Player, Position};
+
+const BOARD_SIZE: usize = 19;
+const PLAYOUTS: usize = 200;
+const MOVES_PER_PLAYOUT: usize = 400;
+
+/// Candidate points for each playout, from a fixed seed so every run and every
+/// representation sees the same moves.
+fn candidate_moves() -> Vec<Vec<usize>> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    (0..PLAYOUTS)
+        .map(|_| {
+            (0..MOVES_PER_PLAYOUT)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    (state >> 33) as usize % (BOARD_SIZE * BOARD_SIZE)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Plays every valid candidate move, alternating colors, and returns the
+/// number of stones captured along the way.
+fn playout<P: Position>(mut position: P, moves: &[usize]) -> usize {
+    let mut player = Player::Black;
+    let mut captured = 0;
+    for &index in moves {
+        let (x, y) = (index % BOARD_SIZE, index / BOARD_SIZE);
+        if position.is_valid_move(x, y, player) {
+            captured += position.play(x, y, player).len();
+            player = match player {
+                Player::Black => Player::White,
+                _ => Player::Black,
+            };
+        }
+    }
+    captured
+}
+
+fn bench<P: Position>(name: &str, new: impl Fn() -> P, moves: &[Vec<usize>]) -> usize {
+    let start = Instant::now();
+    let captured: usize = moves
+        .iter()
+        .map(|playout_moves| playout(black_box(new()), black_box(playout_moves)))
+        .sum();
+    let elapsed = start.elapsed();
+    println!(
+        "{:<12} {:>10.2?} total, {:>10.2?} per playout ({} captures)",
+        name,
+        elapsed,
+        elapsed / PLAYOUTS as u32,
+        captured,
+    );
+    black_box(captured)
+}
+
+fn main() {
+    let moves = candidate_moves();
+    // Warm up so the first measurement isn't penalized
+    let warm_up = Instant::now();
+    while warm_up.elapsed() < Duration::from_millis(200) {
+        black_box(playout(GoPosition::new(BOARD_SIZE), &moves[0]));
+    }
+
+    let vec_captures = bench("GoPosition", || GoPosition::new(BOARD_SIZE), &moves);
+    let bit_captures = bench("BitPosition", || BitPosition::new(BOARD_SIZE), &moves);
+    assert_eq!(
+        vec_captures, bit_captures,
+        "the representations disagree on the playouts"
+    );
+}
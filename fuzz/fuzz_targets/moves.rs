@@ -0,0 +1,64 @@
+//! Plays arbitrary moves, passes and undos on a game under any ruleset,
+//! checking after each that the game holds together: no panics, no group
+//! without liberties, and undo giving back the game as it was.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use go_rs::game::{GoGame, Player, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use go_rs::rules;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Action {
+    Play { x: u8, y: u8 },
+    Pass,
+    Resign,
+    Undo,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    width: u8,
+    height: u8,
+    ruleset: u8,
+    actions: Vec<Action>,
+}
+
+fuzz_target!(|input: Input| {
+    let sizes = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+    let size = |value: u8| MIN_BOARD_SIZE + value as usize % sizes.clone().count();
+    let (width, height) = (size(input.width), size(input.height));
+    let ruleset = rules::ALL[input.ruleset as usize % rules::ALL.len()];
+    let mut game = GoGame::rectangular(width, height).with_rules(ruleset);
+    for action in input.actions {
+        let before = game.clone();
+        match action {
+            // Points off the board are tried as well, they have to be refused
+            Action::Play { x, y } => {
+                if game.play_move(x as usize, y as usize).is_err() {
+                    assert_eq!(game.position(), before.position());
+                    continue;
+                }
+            }
+            Action::Pass => game.pass(),
+            Action::Resign => game.resign(),
+            Action::Undo => {
+                game.undo();
+                continue;
+            }
+        }
+        for group in game.groups() {
+            assert!(group.liberty_count() > 0, "{:?} has no liberties", group);
+        }
+        let mut undone = game.clone();
+        if game.move_count() > before.move_count() {
+            assert!(undone.undo());
+            assert_eq!(undone.position(), before.position());
+            assert_eq!(undone.next_player, before.next_player);
+            for player in [Player::Black, Player::White] {
+                assert_eq!(undone.captures(player), before.captures(player));
+            }
+        }
+    }
+});
@@ -0,0 +1,26 @@
+//! Feeds arbitrary text to the SGF reader, and to the problem reader that
+//! walks every variation. Records that can't be read must be refused with
+//! an error rather than a panic, and those that can must read back the
+//! same once written out again.
+
+#![no_main]
+
+use go_rs::problem::Problem;
+use go_rs::sgf::{read_sgf, write_sgf};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+    let _ = Problem::read(String::new(), text);
+    let game = match read_sgf(text) {
+        Ok(game) => game,
+        Err(_) => return,
+    };
+    let written = write_sgf(&game).expect("a record read in can be written");
+    let again = read_sgf(&written).expect("a written record reads back");
+    assert_eq!(again.position(), game.position());
+    assert_eq!(again.move_names(), game.move_names());
+});
@@ -0,0 +1,50 @@
+// Keeps a copy of everything the app loads so it starts without a network.
+// Trunk gives the wasm and js files new names on every build, so nothing
+// is listed up front: files are cached the first time they are fetched.
+
+// Bump to drop the files cached by older versions
+const CACHE = "go-rs-v1";
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    caches
+      .open(CACHE)
+      .then((cache) => cache.addAll(["./", "index.html", "manifest.webmanifest", "imgs/icon.svg"]))
+      .then(() => self.skipWaiting())
+  );
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+      .then(() => self.clients.claim())
+  );
+});
+
+// Answer from the cache right away when possible and refresh the copy in the
+// background, so the app is quick offline and catches up once back online.
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  // Relay connections and other sites are left alone
+  if (request.method !== "GET" || url.origin !== self.location.origin) {
+    return;
+  }
+  event.respondWith(
+    caches.open(CACHE).then((cache) =>
+      cache.match(request).then((cached) => {
+        const fetched = fetch(request)
+          .then((response) => {
+            if (response.ok) {
+              cache.put(request, response.clone());
+            }
+            return response;
+          })
+          .catch(() => cached);
+        return cached || fetched;
+      })
+    )
+  );
+});
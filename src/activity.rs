@@ -0,0 +1,72 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gloo_utils::document;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::Event;
+use yew::Callback;
+
+/// Events that count as the user being at the page
+const INPUT_EVENTS: [&str; 4] = ["pointerdown", "pointermove", "keydown", "touchstart"];
+
+/// Watches the page for signs of whether the user is there: calls back with
+/// `true` when the page is hidden and `false` when it is shown again, and
+/// notes the time of the last input. Stops watching when dropped.
+pub struct ActivityWatcher {
+    last_input: Rc<Cell<f64>>,
+    on_visibility: Closure<dyn FnMut()>,
+    on_input: Closure<dyn FnMut(Event)>,
+}
+
+impl ActivityWatcher {
+    pub fn new(on_hidden: Callback<bool>) -> Self {
+        let last_input = Rc::new(Cell::new(js_sys::Date::now()));
+
+        let on_visibility = Closure::wrap(
+            Box::new(move || on_hidden.emit(document().hidden())) as Box<dyn FnMut()>
+        );
+        let on_input = {
+            let last_input = last_input.clone();
+            Closure::wrap(
+                Box::new(move |_: Event| last_input.set(js_sys::Date::now()))
+                    as Box<dyn FnMut(Event)>,
+            )
+        };
+
+        let document = document();
+        let _ = document.add_event_listener_with_callback(
+            "visibilitychange",
+            on_visibility.as_ref().unchecked_ref(),
+        );
+        for event in INPUT_EVENTS {
+            let _ =
+                document.add_event_listener_with_callback(event, on_input.as_ref().unchecked_ref());
+        }
+
+        ActivityWatcher {
+            last_input,
+            on_visibility,
+            on_input,
+        }
+    }
+
+    /// When the user last gave any input, in milliseconds since the epoch.
+    pub fn last_input(&self) -> f64 {
+        self.last_input.get()
+    }
+}
+
+impl Drop for ActivityWatcher {
+    fn drop(&mut self) {
+        let document = document();
+        let _ = document.remove_event_listener_with_callback(
+            "visibilitychange",
+            self.on_visibility.as_ref().unchecked_ref(),
+        );
+        for event in INPUT_EVENTS {
+            let _ = document
+                .remove_event_listener_with_callback(event, self.on_input.as_ref().unchecked_ref());
+        }
+    }
+}
@@ -0,0 +1,38 @@
+/// How good a move is judged to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evaluation {
+    Good,
+    Mistake,
+    Interesting,
+}
+
+impl Evaluation {
+    pub const ALL: [Evaluation; 3] = [
+        Evaluation::Good,
+        Evaluation::Mistake,
+        Evaluation::Interesting,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Evaluation::Good => "Good move",
+            Evaluation::Mistake => "Mistake",
+            Evaluation::Interesting => "Interesting",
+        }
+    }
+}
+
+/// What a reviewer wrote about one position of a game and the move that led
+/// to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Annotation {
+    /// Empty when there is none
+    pub comment: String,
+    pub evaluation: Option<Evaluation>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.comment.is_empty() && self.evaluation.is_none()
+    }
+}
@@ -0,0 +1,10 @@
+//! The parts of `App` that belong to a single feature, each an `impl App`
+//! block along with the messages it handles, see `Msg`, and the reducer
+//! that holds `App`, see `state`.
+
+pub mod correspondence;
+pub mod ogs;
+pub mod sections;
+pub mod sessions;
+pub mod state;
+pub mod views;
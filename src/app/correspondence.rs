@@ -0,0 +1,375 @@
+use go_rs::broadcast::Frame;
+use go_rs::chat::{ChatChannel, ChatMessage};
+use go_rs::conditional::ConditionalMoves;
+use go_rs::game::{GoGame, Player};
+use go_rs::room::Presence;
+use go_rs::sgf::read_sgf;
+use yew::prelude::*;
+
+use crate::app::state::AppContext;
+use crate::correspondence::{notify, request_notifications, Correspondence, Received, SavedGame};
+use crate::correspondence_panel::{CorrespondenceControl, CorrespondencePanel, CurrentGame};
+use crate::relay::Incoming;
+use crate::{online_game, App, Msg};
+
+/// What happens to the correspondence games played on this device, each
+/// known by its id, see `App::update_correspondence`.
+pub enum CorrespondenceMsg {
+    /// A button of the correspondence panel was pressed
+    Control(CorrespondenceControl),
+    /// A frame from the opponent
+    Frame(String, Frame),
+    /// A chat message
+    Chat(String, ChatMessage),
+    /// A spectator announced itself
+    Presence(String, Presence),
+    /// Send the latest move again
+    Resend(String),
+    /// The connection was lost and opened again, so both players send their
+    /// games
+    Resync(String),
+}
+
+impl App {
+    pub(crate) fn update_correspondence(
+        &mut self,
+        ctx: &AppContext,
+        msg: CorrespondenceMsg,
+    ) -> bool {
+        match msg {
+            CorrespondenceMsg::Control(CorrespondenceControl::Start(color)) => {
+                // Random enough to keep separate games apart
+                let id = format!("{:x}", (js_sys::Math::random() * 1e12) as u64);
+                let board = match self.board.board_size() {
+                    0 => GoGame::default(),
+                    _ => GoGame::rectangular(self.board.width(), self.board.height()),
+                }
+                .with_rules(self.settings.rules.ruleset())
+                .with_handicap(self.new_handicap, self.new_handicap_placement);
+                self.open_correspondence(ctx, id, color, board, None);
+                true
+            }
+            CorrespondenceMsg::Control(CorrespondenceControl::Join(id, color)) => {
+                if let Some(index) = self.correspondence_tab(&id) {
+                    self.switch_session(index);
+                    return true;
+                }
+                // The board is taken from the first frame of the opponent
+                self.open_correspondence(ctx, id, color, GoGame::new(0), None);
+                true
+            }
+            CorrespondenceMsg::Control(CorrespondenceControl::Failed(e)) => {
+                self.toast = Some(e);
+                true
+            }
+            CorrespondenceMsg::Control(CorrespondenceControl::Open(id)) => {
+                if let Some(index) = self.correspondence_tab(&id) {
+                    self.switch_session(index);
+                    return true;
+                }
+                let saved = match self.correspondence_games.iter().find(|game| game.id == id) {
+                    Some(saved) => saved.clone(),
+                    None => return false,
+                };
+                match read_sgf(&saved.sgf) {
+                    Ok(board) => {
+                        self.relay_url = saved.relay_url.clone();
+                        self.open_correspondence(ctx, id, saved.color(), board, Some(&saved));
+                    }
+                    Err(e) => self.toast = Some(e.to_string()),
+                }
+                true
+            }
+            CorrespondenceMsg::Control(CorrespondenceControl::Remove(id)) => {
+                if self.correspondence.as_ref().is_some_and(|c| c.id == id) {
+                    self.correspondence = None;
+                }
+                for session in &mut self.sessions {
+                    if session.correspondence.as_ref().is_some_and(|c| c.id == id) {
+                        session.correspondence = None;
+                    }
+                }
+                SavedGame::remove(&id);
+                self.correspondence_games = SavedGame::load_all();
+                true
+            }
+            CorrespondenceMsg::Control(CorrespondenceControl::Leave) => {
+                self.correspondence.take().is_some()
+            }
+            CorrespondenceMsg::Control(CorrespondenceControl::SetConditionalMoves(text)) => {
+                let correspondence = match &mut self.correspondence {
+                    Some(correspondence) => correspondence,
+                    None => return false,
+                };
+                let game = match &self.analysis {
+                    Some(analysis) => &analysis.game,
+                    None => &self.board,
+                };
+                match ConditionalMoves::parse(&text, game) {
+                    Ok(conditional) => {
+                        correspondence.conditional = conditional;
+                        correspondence.saved(game).save();
+                    }
+                    Err(e) => self.toast = Some(e),
+                }
+                true
+            }
+            CorrespondenceMsg::Frame(id, frame) => {
+                let active = self.correspondence.as_ref().is_some_and(|c| c.id == id);
+                let (correspondence, game) = if active {
+                    (
+                        &mut self.correspondence,
+                        online_game(&mut self.board, &mut self.analysis),
+                    )
+                } else {
+                    match self.correspondence_tab(&id) {
+                        Some(index) => {
+                            let session = &mut self.sessions[index];
+                            (
+                                &mut session.correspondence,
+                                online_game(&mut session.board, &mut session.analysis),
+                            )
+                        }
+                        None => return false,
+                    }
+                };
+                let received = match correspondence {
+                    Some(correspondence) => correspondence.receive(game, &frame),
+                    None => return false,
+                };
+                let text = match received {
+                    Received::Nothing => return false,
+                    Received::OurMove => format!("Your move in correspondence game {}", id),
+                    Received::Answered(point) => format!(
+                        "Your opponent moved in correspondence game {}, answered at {} as planned",
+                        id, point
+                    ),
+                    Received::Ended => format!("Correspondence game {} is over", id),
+                };
+                if active && self.analysis.is_none() {
+                    self.suggest_dead_stones();
+                    self.show_result();
+                }
+                if !notify(&text) {
+                    self.toast = Some(text);
+                }
+                self.correspondence_games = SavedGame::load_all();
+                true
+            }
+            CorrespondenceMsg::Chat(id, message) => {
+                let active = self.correspondence.as_ref().is_some_and(|c| c.id == id);
+                let (correspondence, game) = if active {
+                    (
+                        &mut self.correspondence,
+                        online_game(&mut self.board, &mut self.analysis),
+                    )
+                } else {
+                    match self.correspondence_tab(&id) {
+                        Some(index) => {
+                            let session = &mut self.sessions[index];
+                            (
+                                &mut session.correspondence,
+                                online_game(&mut session.board, &mut session.analysis),
+                            )
+                        }
+                        None => return false,
+                    }
+                };
+                let correspondence = match correspondence {
+                    Some(correspondence) => correspondence,
+                    None => return false,
+                };
+                let text = format!("{}: {}", message.sender, message.text);
+                let players = message.channel == ChatChannel::Players;
+                if !correspondence.receive_chat(message) {
+                    return false;
+                }
+                if players {
+                    correspondence.saved(game).save();
+                    notify(&text);
+                }
+                active
+            }
+            CorrespondenceMsg::Presence(id, presence) => {
+                let now = js_sys::Date::now();
+                if let Some(correspondence) = &mut self.correspondence {
+                    if correspondence.id == id {
+                        correspondence.viewers.seen(&presence.viewer, now);
+                        return true;
+                    }
+                }
+                if let Some(index) = self.correspondence_tab(&id) {
+                    if let Some(correspondence) = &mut self.sessions[index].correspondence {
+                        correspondence.viewers.seen(&presence.viewer, now);
+                    }
+                }
+                false
+            }
+            CorrespondenceMsg::Resend(id) => {
+                if self.correspondence.as_ref().is_some_and(|c| c.id == id) {
+                    self.send_correspondence();
+                } else if let Some(index) = self.correspondence_tab(&id) {
+                    let session = &mut self.sessions[index];
+                    if let Some(correspondence) = &session.correspondence {
+                        let game = online_game(&mut session.board, &mut session.analysis);
+                        correspondence.send(game);
+                    }
+                }
+                false
+            }
+            CorrespondenceMsg::Resync(id) => {
+                if self.correspondence.as_ref().is_some_and(|c| c.id == id) {
+                    self.send_correspondence();
+                    if let Some(correspondence) = &self.correspondence {
+                        correspondence.request_resync(&self.board);
+                    }
+                } else if let Some(index) = self.correspondence_tab(&id) {
+                    let session = &mut self.sessions[index];
+                    if let Some(correspondence) = &session.correspondence {
+                        let game = online_game(&mut session.board, &mut session.analysis);
+                        correspondence.send(game);
+                        correspondence.request_resync(game);
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    /// Connects to correspondence game `id`, playing `color`, and shows
+    /// `board` for it on this tab. The moves in advance and the chat are
+    /// taken from `saved` when reopening a stored game.
+    fn open_correspondence(
+        &mut self,
+        ctx: &AppContext,
+        id: String,
+        color: Player,
+        board: GoGame,
+        saved: Option<&SavedGame>,
+    ) {
+        let link = ctx.link().clone();
+        let message_id = id.clone();
+        let resend_id = id.clone();
+        let connected = Correspondence::connect(
+            id,
+            color,
+            self.relay_url.clone(),
+            Callback::from(move |message| {
+                let id = message_id.clone();
+                link.send_message(Msg::Correspondence(match message {
+                    Incoming::Frame(frame) => CorrespondenceMsg::Frame(id, frame),
+                    Incoming::Chat(message) => CorrespondenceMsg::Chat(id, message),
+                    Incoming::Presence(presence) => CorrespondenceMsg::Presence(id, presence),
+                    // Answered by sending our game, which is all we have
+                    Incoming::Resync(_) => CorrespondenceMsg::Resend(id),
+                    Incoming::Reconnected => CorrespondenceMsg::Resync(id),
+                }))
+            }),
+            ctx.link().callback(move |_| {
+                Msg::Correspondence(CorrespondenceMsg::Resend(resend_id.clone()))
+            }),
+            ctx.link().callback(Msg::RelayError),
+        );
+        let mut correspondence = match connected {
+            Ok(correspondence) => correspondence,
+            Err(e) => {
+                self.toast = Some(e);
+                return;
+            }
+        };
+        if let Some(saved) = saved {
+            correspondence.conditional =
+                ConditionalMoves::parse(&saved.conditional, &board).unwrap_or_default();
+            correspondence.chat = saved.chat();
+            if !saved.session.is_empty() {
+                correspondence.session = saved.session.clone();
+            }
+        }
+        request_notifications();
+        self.result_shown = false;
+        self.rated = false;
+        self.tournament_game = None;
+        self.hints_used = 0;
+        self.board = board;
+        self.bot = None;
+        self.practice = None;
+        self.problem = None;
+        self.analysis = None;
+        self.demo = None;
+        self.viewing = None;
+        self.preview = None;
+        self.ogs = None;
+        correspondence.request_resync(&self.board);
+        self.correspondence = Some(correspondence);
+        self.send_correspondence();
+        self.correspondence_games = SavedGame::load_all();
+    }
+
+    /// The index of the tab that isn't shown and plays correspondence game
+    /// `id`.
+    fn correspondence_tab(&self, id: &str) -> Option<usize> {
+        self.sessions.iter().position(|session| {
+            session
+                .correspondence
+                .as_ref()
+                .is_some_and(|correspondence| correspondence.id == id)
+        })
+    }
+
+    /// Stores the correspondence game on this board and sends it, unless it
+    /// is still waiting for the opponent's first frame.
+    fn send_correspondence(&mut self) {
+        match &self.correspondence {
+            Some(correspondence) if self.board.board_size() != 0 => {
+                correspondence.save_and_send(&self.board);
+                self.correspondence_games = SavedGame::load_all();
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends a move made on this board to the correspondence opponent.
+    pub(crate) fn send_correspondence_move(&mut self) {
+        if self.analysis.is_none() {
+            self.send_correspondence();
+        }
+    }
+
+    /// Renders the correspondence game on this board, or the controls to
+    /// start or join one, along with the stored games.
+    pub(crate) fn render_correspondence(&self, ctx: &AppContext) -> Html {
+        let current = self.correspondence.as_ref().map(|correspondence| {
+            let status = if self.board.board_size() == 0 {
+                "Waiting for your opponent to connect"
+            } else if self.board.is_over() {
+                "The game is over"
+            } else if correspondence.our_turn(&self.board) {
+                "Your move"
+            } else {
+                "Waiting for your opponent's move"
+            };
+            let game = match &self.analysis {
+                Some(analysis) => &analysis.game,
+                None => &self.board,
+            };
+            CurrentGame {
+                id: correspondence.id.clone(),
+                color: correspondence.color,
+                status,
+                viewers: correspondence.viewers.count(js_sys::Date::now()),
+                spectate_link: correspondence.spectate_link(),
+                conditional: correspondence.conditional.to_text(game),
+            }
+        });
+        let action_cb = ctx
+            .link()
+            .callback(|control| Msg::Correspondence(CorrespondenceMsg::Control(control)));
+        html! {
+            <CorrespondencePanel
+                { current }
+                games={ self.correspondence_games.clone() }
+                onaction={ action_cb }
+            />
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use go_rs::game::Player;
+use go_rs::sgf::read_sgf;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::app::state::AppContext;
+use crate::library::LibraryEntry;
+use crate::ogs::{fetch_sgf, OgsPlay};
+use crate::ogs_panel::{OgsControl, OgsPanel};
+use crate::{online_game, App, Msg};
+
+/// What happens to the OGS games played on this device, see
+/// `App::update_ogs`.
+pub enum OgsMsg {
+    /// A button of the OGS panel was pressed
+    Control(OgsControl),
+    /// Fetch the OGS game with the given id again for new moves
+    Poll(u64),
+    /// The record of an OGS game arrived, to be opened on this board,
+    /// imported or checked for new moves
+    Fetched {
+        id: u64,
+        sgf: Result<String, String>,
+        purpose: OgsFetch,
+    },
+    /// How sending a move to OGS went
+    MoveSent(Result<(), String>),
+}
+
+/// What an OGS game was fetched for, see `OgsMsg::Fetched`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OgsFetch {
+    Open,
+    Import,
+    Poll,
+}
+
+impl App {
+    pub(crate) fn update_ogs(&mut self, ctx: &AppContext, msg: OgsMsg) -> bool {
+        match msg {
+            OgsMsg::Control(OgsControl::Login(login)) => {
+                self.ogs_login = login;
+                true
+            }
+            OgsMsg::Control(OgsControl::Open(id)) => {
+                if let Some(index) = self.ogs_tab(id) {
+                    self.switch_session(index);
+                    return true;
+                }
+                self.fetch_ogs_game(ctx, id, OgsFetch::Open);
+                false
+            }
+            OgsMsg::Control(OgsControl::Import(id)) => {
+                self.fetch_ogs_game(ctx, id, OgsFetch::Import);
+                false
+            }
+            OgsMsg::Control(OgsControl::Failed(e)) => {
+                self.toast = Some(e);
+                true
+            }
+            OgsMsg::Poll(id) => {
+                self.fetch_ogs_game(ctx, id, OgsFetch::Poll);
+                false
+            }
+            OgsMsg::Fetched { id, sgf, purpose } => self.ogs_game_fetched(ctx, id, sgf, purpose),
+            OgsMsg::MoveSent(Ok(())) => false,
+            OgsMsg::MoveSent(Err(e)) => {
+                // Take back a move that didn't go through
+                if let Some(ogs) = &self.ogs {
+                    ctx.link().send_message(Msg::Ogs(OgsMsg::Poll(ogs.id)));
+                }
+                self.toast = Some(e);
+                true
+            }
+        }
+    }
+
+    /// Opens, imports or catches up with the record `sgf` of OGS game `id`
+    /// as `purpose` says.
+    fn ogs_game_fetched(
+        &mut self,
+        ctx: &AppContext,
+        id: u64,
+        sgf: Result<String, String>,
+        purpose: OgsFetch,
+    ) -> bool {
+        let sgf = match sgf {
+            Ok(sgf) => sgf,
+            Err(e) => {
+                // A missed poll is made up for by the next one
+                if purpose != OgsFetch::Poll {
+                    self.toast = Some(e);
+                }
+                return purpose != OgsFetch::Poll;
+            }
+        };
+        let game = match read_sgf(&sgf) {
+            Ok(game) => game,
+            Err(e) => {
+                self.toast = Some(e.to_string());
+                return true;
+            }
+        };
+        match purpose {
+            OgsFetch::Open => {
+                let color = self
+                    .ogs_login
+                    .as_ref()
+                    .map_or(Player::None, |login| login.color_in(&game));
+                let poll_cb = ctx.link().callback(move |_| Msg::Ogs(OgsMsg::Poll(id)));
+                self.result_shown = false;
+                self.rated = false;
+                self.tournament_game = None;
+                self.hints_used = 0;
+                self.board = game;
+                self.bot = None;
+                self.practice = None;
+                self.problem = None;
+                self.analysis = None;
+                self.demo = None;
+                self.viewing = None;
+                self.preview = None;
+                self.correspondence = None;
+                self.ogs = Some(OgsPlay::new(
+                    id,
+                    color,
+                    poll_cb,
+                    ctx.link().callback(|sent| Msg::Ogs(OgsMsg::MoveSent(sent))),
+                ));
+                self.sidebar_shown = false;
+                if color == Player::None {
+                    self.toast = Some(String::from("You don't play in this game, only watching"));
+                }
+            }
+            OgsFetch::Import => match LibraryEntry::from_sgf(format!("OGS game {}", id), sgf) {
+                Ok(entry) => {
+                    self.add_to_library(entry);
+                    self.toast = Some(format!("Added OGS game {} to the library", id));
+                }
+                Err(e) => self.toast = Some(e),
+            },
+            OgsFetch::Poll => {
+                let active = self.ogs.as_ref().is_some_and(|ogs| ogs.id == id);
+                let board = if active {
+                    online_game(&mut self.board, &mut self.analysis)
+                } else {
+                    match self.ogs_tab(id) {
+                        Some(index) => {
+                            let session = &mut self.sessions[index];
+                            online_game(&mut session.board, &mut session.analysis)
+                        }
+                        None => return false,
+                    }
+                };
+                // OGS has the final say over the moves of the game
+                if board.move_names() == game.move_names() {
+                    return false;
+                }
+                let was_over = board.is_over() || board.info.recorded_result.is_some();
+                *board = game;
+                if active && self.analysis.is_none() {
+                    self.suggest_dead_stones();
+                    self.show_result();
+                    // Only games seen to end here are rated
+                    if !was_over {
+                        self.settle_game();
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Sends a move made on this board to OGS, `None` for a pass.
+    pub(crate) fn send_ogs_move(&self, point: Option<(usize, usize)>) {
+        if let (Some(ogs), Some(login), None) = (&self.ogs, &self.ogs_login, &self.analysis) {
+            ogs.send_move(login, point);
+        }
+    }
+
+    /// Resigns the OGS game on this board.
+    pub(crate) fn send_ogs_resignation(&self) {
+        if let (Some(ogs), Some(login), None) = (&self.ogs, &self.ogs_login, &self.analysis) {
+            ogs.send_resignation(login);
+        }
+    }
+
+    /// Fetches OGS game `id` in the background, see `OgsMsg::Fetched`.
+    fn fetch_ogs_game(&self, ctx: &AppContext, id: u64, purpose: OgsFetch) {
+        let login = match &self.ogs_login {
+            Some(login) => login.clone(),
+            None => return,
+        };
+        let link = ctx.link().clone();
+        spawn_local(async move {
+            let sgf = fetch_sgf(&login, id).await;
+            link.send_message(Msg::Ogs(OgsMsg::Fetched { id, sgf, purpose }));
+        });
+    }
+
+    /// The index of the tab that isn't shown and plays OGS game `id`.
+    fn ogs_tab(&self, id: u64) -> Option<usize> {
+        self.sessions
+            .iter()
+            .position(|session| session.ogs.as_ref().is_some_and(|ogs| ogs.id == id))
+    }
+
+    /// Renders the OGS login, or the games of the account once logged in.
+    pub(crate) fn render_ogs(&self, ctx: &AppContext) -> Html {
+        let action_cb = ctx
+            .link()
+            .callback(|control| Msg::Ogs(OgsMsg::Control(control)));
+        html! {
+            <OgsPanel login={ self.ogs_login.clone() } onaction={ action_cb } />
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Sections of the sidebar shown while playing, each a function component
+//! reading the app from `use_app`.
+
+use gloo_utils::window;
+use go_rs::game::Corner;
+use go_rs::rating::INITIAL_RATING;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::app::state::use_app;
+use crate::{goal_text, Msg};
+
+/// Renders the broadcast controls, or the link for viewers while
+/// broadcasting.
+#[function_component]
+pub(crate) fn BroadcastSection() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    match &app.broadcast {
+        None => {
+            let url_cb = ctx.link().callback(|e: InputEvent| {
+                Msg::SetRelayUrl(e.target_unchecked_into::<HtmlInputElement>().value())
+            });
+            let start_cb = ctx.link().callback(|_: MouseEvent| Msg::StartBroadcast);
+            html! {
+                <div class="broadcast">
+                    <input
+                        type="url"
+                        placeholder="Relay address (ws:// or wss://)"
+                        value={ app.relay_url.clone() }
+                        oninput={ url_cb }
+                    />
+                    <button onclick={ start_cb }>{ "Start broadcast" }</button>
+                </div>
+            }
+        }
+        Some(broadcast) => {
+            let location = window().location();
+            let link = format!(
+                "{}{}#watch={}@{}",
+                location.origin().unwrap_or_default(),
+                location.pathname().unwrap_or_default(),
+                broadcast.channel,
+                app.relay_url,
+            );
+            let share_cb = ctx
+                .link()
+                .callback(|_: MouseEvent| Msg::ToggleShareLiberties);
+            let stop_cb = ctx.link().callback(|_: MouseEvent| Msg::StopBroadcast);
+            html! {
+                <div class="broadcast">
+                    <span>{ "Viewers can watch at:" }</span>
+                    <input type="text" readonly=true value={ link } />
+                    <button onclick={ share_cb }>
+                        { if broadcast.share_liberties {
+                            "Hide liberties from viewers"
+                        } else {
+                            "Show liberties to viewers"
+                        } }
+                    </button>
+                    <button onclick={ stop_cb }>{ "Stop broadcast" }</button>
+                </div>
+            }
+        }
+    }
+}
+
+/// Renders the goal and streak of practice games, with a button to start
+/// one.
+#[function_component]
+pub(crate) fn PracticeSection() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let goal_cb = ctx.link().batch_callback(|e: Event| {
+        let value = e.target_unchecked_into::<HtmlInputElement>().value();
+        value.trim().parse().ok().map(Msg::SetPracticeGoal)
+    });
+    let start_cb = ctx.link().callback(|_: MouseEvent| Msg::StartPractice);
+    html! {
+        <div class="practice">
+            <label>
+                { "Goal margin " }
+                <input
+                    type="number"
+                    value={ app.practice_goal.to_string() }
+                    onchange={ goal_cb }
+                />
+            </label>
+            <span>{ goal_text(app.practice_goal) }</span>
+            <span>{ format!("Goals met in a row: {}", app.practice_streak) }</span>
+            <button onclick={ start_cb }>{ "Start practice game" }</button>
+        </div>
+    }
+}
+
+/// Renders the rating of every profile on every board size it has
+/// played, with how it went over time.
+#[function_component]
+pub(crate) fn RatingsSection() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let rows: Vec<Html> = app
+        .ratings
+        .all()
+        .map(|(profile, size, history)| {
+            html! {
+                <li>
+                    <span class="rating-name">{ format!("{}, {}", profile, size) }</span>
+                    { sparkline(&history.ratings) }
+                    <span class="rating-value">{ format!("{:.0}", history.current()) }</span>
+                </li>
+            }
+        })
+        .collect();
+    if rows.is_empty() {
+        return html! {
+            <p class="ratings">
+                { "Finish a game against the library bot, or an OGS game against a ranked player, to get a rating." }
+            </p>
+        };
+    }
+    html! {
+        <ul class="ratings">{ for rows }</ul>
+    }
+}
+
+/// Lets a teacher keep play to part of the board.
+#[function_component]
+pub(crate) fn TeachingSection() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let corners = [
+        (Corner::TopLeft, "Top left"),
+        (Corner::TopRight, "Top right"),
+        (Corner::BottomLeft, "Bottom left"),
+        (Corner::BottomRight, "Bottom right"),
+    ]
+    .into_iter()
+    .map(|(corner, name)| {
+        let lock_cb = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::LockCorner(corner));
+        html! { <button onclick={ lock_cb }>{ name }</button> }
+    });
+    let pick_cb = ctx
+        .link()
+        .callback(|_: MouseEvent| Msg::ToggleRegionPicking);
+    let unlock_cb = ctx.link().callback(|_: MouseEvent| Msg::UnlockBoard);
+    html! {
+        <div class="teaching">
+            <span>{ "Keep play to a corner:" }</span>
+            { for corners }
+            <button onclick={ pick_cb }>
+                { if app.picking_region { "Done picking points" } else { "Pick open points" } }
+            </button>
+            if app.region().is_some() {
+                <button onclick={ unlock_cb }>{ "Unlock the board" }</button>
+            }
+            if app.picking_region {
+                <span>{ "Click points on the board to open or lock them." }</span>
+            }
+        </div>
+    }
+}
+
+/// Draws how a rating went over its games as a line, starting from the
+/// rating before the first game.
+fn sparkline(ratings: &[f64]) -> Html {
+    let values: Vec<f64> = std::iter::once(INITIAL_RATING)
+        .chain(ratings.iter().copied())
+        .collect();
+    let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (high - low).max(1.0);
+    let step = 100.0 / (values.len() - 1) as f64;
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            format!(
+                "{:.1},{:.1}",
+                i as f64 * step,
+                20.0 - (value - low) / range * 20.0
+            )
+        })
+        .collect();
+    html! {
+        <svg class="sparkline" viewBox="0 -1 100 22" preserveAspectRatio="none" aria-hidden="true">
+            <polyline points={ points.join(" ") } />
+        </svg>
+    }
+}
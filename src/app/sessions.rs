@@ -0,0 +1,293 @@
+use go_rs::game::{GoGame, GoPosition};
+use go_rs::tactics::Tactic;
+use yew::prelude::*;
+
+use crate::app::state::AppContext;
+use crate::app::views::navigate;
+use crate::clock::{Clock, Pause};
+use crate::correspondence::Correspondence;
+use crate::demo::Demo;
+use crate::engine_review::{EngineReview, MistakeReview};
+use crate::ogs::OgsPlay;
+use crate::route::Route;
+use crate::tab_bar::TabBar;
+use crate::{Analysis, App, Bot, Broadcast, Msg, Practice, ProblemSession};
+
+/// What is done with the tabs, see `App::update_tabs`.
+pub enum TabMsg {
+    /// Open a tab for another game, starting at the start screen
+    New,
+    /// Show the game of the tab with the given index
+    Switch(usize),
+    /// Close the tab with the given index, along with its game
+    Close(usize),
+}
+
+/// A game open in a tab along with everything that goes with it. The game
+/// of the shown tab is spread over the fields of `App` instead, see
+/// `App::take_session`.
+#[derive(Default)]
+pub struct Session {
+    pub board: GoGame,
+    pub viewing: Option<(usize, GoPosition)>,
+    pub bot: Option<Bot>,
+    pub practice: Option<Practice>,
+    pub problem: Option<ProblemSession>,
+    pub analysis: Option<Analysis>,
+    pub demo: Option<Demo>,
+    pub clock: Clock,
+    pub result_shown: bool,
+    pub rated: bool,
+    pub tournament_game: Option<(usize, usize)>,
+    pub hints_used: usize,
+    pub correspondence: Option<Correspondence>,
+    pub ogs: Option<OgsPlay>,
+    pub engine: Option<EngineReview>,
+    pub mistake_review: Option<MistakeReview>,
+    pub tactics: Option<((usize, u64), Vec<Tactic>)>,
+    pub tactic_shown: Option<usize>,
+    pub ownership_estimate: Option<(u64, Vec<f64>)>,
+    pub broadcast: Option<Broadcast>,
+}
+
+impl Session {
+    /// The name of the tab of the session, see `session_name`.
+    pub fn name(&self) -> String {
+        session_name(
+            &self.board,
+            self.bot.is_some(),
+            self.practice.is_some(),
+            self.problem.is_some(),
+            self.analysis.is_some(),
+            self.demo.is_some(),
+            online_kind(self.correspondence.is_some(), self.ogs.is_some()),
+        )
+    }
+}
+
+impl App {
+    pub(crate) fn update_tabs(&mut self, ctx: &AppContext, msg: TabMsg) -> bool {
+        match msg {
+            TabMsg::New => {
+                self.open_session(Session {
+                    board: GoGame::new(0),
+                    ..Session::default()
+                });
+                navigate(ctx, Route::Home);
+                true
+            }
+            TabMsg::Switch(index) => {
+                if index == self.active_session || index >= self.sessions.len() {
+                    return false;
+                }
+                self.switch_session(index);
+                if ctx.link().route() != Some(Route::Review) {
+                    navigate(ctx, Route::Play);
+                }
+                true
+            }
+            TabMsg::Close(index) if index >= self.sessions.len() => false,
+            TabMsg::Close(index) => {
+                if self.sessions.len() == 1 {
+                    // The last tab goes back to the start screen instead
+                    self.take_session();
+                    self.board = GoGame::new(0);
+                    return true;
+                }
+                if index == self.active_session {
+                    let next = if index + 1 < self.sessions.len() {
+                        index + 1
+                    } else {
+                        index - 1
+                    };
+                    self.switch_session(next);
+                }
+                self.sessions.remove(index);
+                if self.active_session > index {
+                    self.active_session -= 1;
+                }
+                true
+            }
+        }
+    }
+
+    /// Packs the shown game and everything that goes with it into a session,
+    /// leaving a fresh game in its place. Its clock is paused until it is
+    /// shown again, and so is the built-in engine.
+    fn take_session(&mut self) -> Session {
+        self.preview = None;
+        self.hover = None;
+        self.cursor = None;
+        self.rejection = None;
+        self.engine_step = None;
+        self.clock.pause(Pause::Hidden, js_sys::Date::now());
+        Session {
+            board: std::mem::take(&mut self.board),
+            viewing: self.viewing.take(),
+            bot: self.bot.take(),
+            practice: self.practice.take(),
+            problem: self.problem.take(),
+            analysis: self.analysis.take(),
+            demo: self.demo.take(),
+            clock: std::mem::take(&mut self.clock),
+            result_shown: std::mem::take(&mut self.result_shown),
+            rated: std::mem::take(&mut self.rated),
+            tournament_game: self.tournament_game.take(),
+            hints_used: std::mem::take(&mut self.hints_used),
+            correspondence: self.correspondence.take(),
+            ogs: self.ogs.take(),
+            engine: self.engine.take(),
+            mistake_review: self.mistake_review.take(),
+            tactics: self.tactics.take(),
+            tactic_shown: self.tactic_shown.take(),
+            ownership_estimate: self.ownership_estimate.take(),
+            broadcast: self.broadcast.take(),
+        }
+    }
+
+    /// Shows the game of `session`, see `take_session`.
+    fn put_session(&mut self, session: Session) {
+        self.board = session.board;
+        self.viewing = session.viewing;
+        self.bot = session.bot;
+        self.practice = session.practice;
+        self.problem = session.problem;
+        self.analysis = session.analysis;
+        self.demo = session.demo;
+        self.clock = session.clock;
+        self.result_shown = session.result_shown;
+        self.rated = session.rated;
+        self.tournament_game = session.tournament_game;
+        self.hints_used = session.hints_used;
+        self.hint = None;
+        self.correspondence = session.correspondence;
+        self.ogs = session.ogs;
+        self.engine = session.engine;
+        self.mistake_review = session.mistake_review;
+        self.tactics = session.tactics;
+        self.tactic_shown = session.tactic_shown;
+        self.ownership_estimate = session.ownership_estimate;
+        self.broadcast = session.broadcast;
+        if self.clock.paused() == Some(Pause::Hidden) {
+            self.clock.resume(js_sys::Date::now());
+        }
+        // Sounds were already played for the moves on this board
+        self.sounded_moves = self.game().move_count();
+    }
+
+    /// Opens `session` in a new tab and shows it.
+    pub(crate) fn open_session(&mut self, session: Session) {
+        self.sessions.push(session);
+        self.switch_session(self.sessions.len() - 1);
+    }
+
+    /// Opens a new tab for a game about to be set up when the shown tab
+    /// already holds one, so starting a game from the home view doesn't
+    /// throw away the one being played.
+    pub(crate) fn keep_shown_game(&mut self) {
+        if self.board.board_size() != 0 && self.watching.is_none() {
+            self.open_session(Session::default());
+        }
+    }
+
+    /// Puts the shown game away in its tab and shows the tab at `index`.
+    pub(crate) fn switch_session(&mut self, index: usize) {
+        let current = self.take_session();
+        self.sessions[self.active_session] = current;
+        let next = std::mem::take(&mut self.sessions[index]);
+        self.active_session = index;
+        self.put_session(next);
+    }
+
+    /// Renders a tab for every open game, while playing on this device.
+    pub(crate) fn render_tab_bar(&self, ctx: &AppContext) -> Html {
+        if self.watching.is_some() {
+            return html! {};
+        }
+        let tabs = self
+            .sessions
+            .iter()
+            .enumerate()
+            .map(|(i, session)| {
+                if i == self.active_session {
+                    self.session_name()
+                } else {
+                    session.name()
+                }
+            })
+            .collect::<Vec<_>>();
+        html! {
+            <TabBar
+                tabs={ tabs }
+                active={ self.active_session }
+                onselect={ ctx.link().callback(|index| Msg::Tab(TabMsg::Switch(index))) }
+                onclose={ ctx.link().callback(|index| Msg::Tab(TabMsg::Close(index))) }
+                onnew={ ctx.link().callback(|_| Msg::Tab(TabMsg::New)) }
+            />
+        }
+    }
+
+    /// The name of the shown game's tab, see `Session::name`.
+    fn session_name(&self) -> String {
+        session_name(
+            &self.board,
+            self.bot.is_some(),
+            self.practice.is_some(),
+            self.problem.is_some(),
+            self.analysis.is_some(),
+            self.demo.is_some(),
+            online_kind(self.correspondence.is_some(), self.ogs.is_some()),
+        )
+    }
+}
+
+/// Names a tab after what is going on in its game, like `vs bot 19x19, move
+/// 34`.
+fn session_name(
+    board: &GoGame,
+    bot: bool,
+    practice: bool,
+    problem: bool,
+    analysis: bool,
+    demo: bool,
+    online: Option<&'static str>,
+) -> String {
+    if board.board_size() == 0 {
+        return String::from("New game");
+    }
+    let kind = if problem {
+        "Problem"
+    } else if analysis {
+        "Analysis"
+    } else if demo {
+        "Demo"
+    } else if practice {
+        "Practice"
+    } else if let Some(online) = online {
+        online
+    } else if bot {
+        "vs bot"
+    } else if board.is_over() {
+        "Review"
+    } else {
+        "Game"
+    };
+    format!(
+        "{} {}x{}, move {}",
+        kind,
+        board.width(),
+        board.height(),
+        board.move_count()
+    )
+}
+
+/// The kind of online game a tab plays, if any, for `session_name`.
+fn online_kind(correspondence: bool, ogs: bool) -> Option<&'static str> {
+    if correspondence {
+        Some("Correspondence")
+    } else if ogs {
+        Some("OGS")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,227 @@
+//! `App` held by a reducer: every `Msg` goes through `AppState::reduce`,
+//! which hands it to `App::update`. The methods of `App` reach the rest of
+//! the page through an `AppContext`, standing in for the context of a
+//! struct component.
+
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::route::Route;
+use crate::settings::Settings;
+use crate::{App, AppProps, Msg};
+
+/// What the methods of `App` are given to send messages, make callbacks and
+/// navigate with, see `AppLink`.
+#[derive(Clone)]
+pub(crate) struct AppContext {
+    props: Rc<AppProps>,
+    link: AppLink,
+}
+
+impl AppContext {
+    pub fn props(&self) -> &AppProps {
+        &self.props
+    }
+
+    pub fn link(&self) -> &AppLink {
+        &self.link
+    }
+}
+
+/// Sends messages to the reducer holding `App`. Messages sent while one is
+/// being handled, or before the reducer is there, wait in a queue and are
+/// handled in order once it is done, the way a struct component's
+/// messages were.
+#[derive(Clone, Default)]
+pub(crate) struct AppLink {
+    inner: Rc<LinkInner>,
+}
+
+#[derive(Default)]
+struct LinkInner {
+    dispatcher: RefCell<Option<UseReducerDispatcher<AppState>>>,
+    queue: RefCell<VecDeque<Msg>>,
+    /// Set while `App` is borrowed to handle a message or after a render
+    busy: Cell<bool>,
+    /// The route shown at the last render, see `AppLink::route`
+    route: Cell<Option<Route>>,
+    navigator: RefCell<Option<Navigator>>,
+}
+
+impl AppLink {
+    pub fn send_message(&self, msg: Msg) {
+        self.inner.queue.borrow_mut().push_back(msg);
+        self.flush();
+    }
+
+    /// A callback sending the message `f` makes of its input.
+    pub fn callback<IN, F>(&self, f: F) -> Callback<IN>
+    where
+        F: Fn(IN) -> Msg + 'static,
+    {
+        let link = self.clone();
+        Callback::from(move |input| link.send_message(f(input)))
+    }
+
+    /// A callback sending the message `f` makes of its input, if any.
+    pub fn batch_callback<IN, F>(&self, f: F) -> Callback<IN>
+    where
+        F: Fn(IN) -> Option<Msg> + 'static,
+    {
+        let link = self.clone();
+        Callback::from(move |input| {
+            if let Some(msg) = f(input) {
+                link.send_message(msg);
+            }
+        })
+    }
+
+    /// The route of the shown view.
+    pub fn route(&self) -> Option<Route> {
+        self.inner.route.get()
+    }
+
+    pub fn navigator(&self) -> Option<Navigator> {
+        self.inner.navigator.borrow().clone()
+    }
+
+    /// Keeps what the router says at a render of `AppView`.
+    fn sync(&self, route: Option<Route>, navigator: Option<Navigator>) {
+        self.inner.route.set(route);
+        *self.inner.navigator.borrow_mut() = navigator;
+    }
+
+    /// Sends the messages to `dispatcher` from now on. The first one is
+    /// kept, it is the same at every render.
+    fn bind(&self, dispatcher: UseReducerDispatcher<AppState>) {
+        self.inner.dispatcher.borrow_mut().get_or_insert(dispatcher);
+    }
+
+    /// Runs `f` with messages held back until it is done.
+    fn hold<R>(&self, f: impl FnOnce() -> R) -> R {
+        let held = self.inner.busy.replace(true);
+        let result = f();
+        self.inner.busy.set(held);
+        self.flush();
+        result
+    }
+
+    /// Handles the waiting messages, unless one is being handled already.
+    fn flush(&self) {
+        let dispatcher = match &*self.inner.dispatcher.borrow() {
+            Some(dispatcher) => dispatcher.clone(),
+            None => return,
+        };
+        if self.inner.busy.replace(true) {
+            return;
+        }
+        loop {
+            let msg = self.inner.queue.borrow_mut().pop_front();
+            match msg {
+                Some(msg) => dispatcher.dispatch(msg),
+                None => break,
+            }
+        }
+        self.inner.busy.set(false);
+    }
+}
+
+/// The state of `AppView`. Every version shares the one `App`, a new
+/// version is made when a message changed what is shown.
+pub(crate) struct AppState {
+    app: Rc<RefCell<App>>,
+    ctx: AppContext,
+    version: u64,
+}
+
+impl PartialEq for AppState {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.app, &other.app) && self.version == other.version
+    }
+}
+
+impl Reducible for AppState {
+    type Action = Msg;
+
+    fn reduce(self: Rc<Self>, msg: Msg) -> Rc<Self> {
+        let changed = self
+            .ctx
+            .link
+            .hold(|| self.app.borrow_mut().update(&self.ctx, msg));
+        if !changed {
+            return self;
+        }
+        Rc::new(AppState {
+            app: self.app.clone(),
+            ctx: self.ctx.clone(),
+            version: self.version + 1,
+        })
+    }
+}
+
+impl AppState {
+    fn new(ctx: AppContext) -> Self {
+        let app = ctx.link.hold(|| App::create(&ctx));
+        AppState {
+            app: Rc::new(RefCell::new(app)),
+            ctx,
+            version: 0,
+        }
+    }
+
+    pub fn app(&self) -> Ref<'_, App> {
+        self.app.borrow()
+    }
+
+    pub fn ctx(&self) -> &AppContext {
+        &self.ctx
+    }
+}
+
+/// The app state provided by `AppView`, for the components that show parts
+/// of it.
+#[hook]
+pub(crate) fn use_app() -> UseReducerHandle<AppState> {
+    use_context::<UseReducerHandle<AppState>>().expect("rendered inside AppView")
+}
+
+/// The app: `App` in a reducer, with the settings and the state provided to
+/// the components below.
+#[function_component]
+pub(crate) fn AppView(props: &AppProps) -> Html {
+    let link = (*use_memo((), |_| AppLink::default())).clone();
+    link.sync(use_route::<Route>(), use_navigator());
+    let state = {
+        let ctx = AppContext {
+            props: Rc::new(props.clone()),
+            link: link.clone(),
+        };
+        use_reducer_eq(move || AppState::new(ctx))
+    };
+    link.bind(state.dispatcher());
+
+    let first_render = use_mut_ref(|| true);
+    {
+        let state = state.clone();
+        use_effect(move || {
+            let first = first_render.replace(false);
+            let ctx = state.ctx();
+            ctx.link
+                .hold(|| state.app.borrow_mut().rendered(ctx, first));
+        });
+    }
+
+    let settings = state.app().settings.clone();
+    let page = state.app().render_page(state.ctx());
+    html! {
+        <ContextProvider<Settings> context={ settings }>
+            <ContextProvider<UseReducerHandle<AppState>> context={ state }>
+                { page }
+            </ContextProvider<UseReducerHandle<AppState>>>
+        </ContextProvider<Settings>>
+    }
+}
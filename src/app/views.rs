@@ -0,0 +1,444 @@
+use go_rs::game::{HandicapPlacement, Player, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use go_rs::handicap::MAX_STONES;
+use go_rs::stats::Tally;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::app::state::{use_app, AppContext};
+use crate::route::{NavBar, Route};
+use crate::settings_panel::SettingsPanel;
+use crate::sidebar::SidebarSection;
+use crate::{player_name, App, Msg};
+
+impl App {
+    /// The view of the current route, under the navigation and tab bars.
+    pub(crate) fn render_page(&self, ctx: &AppContext) -> Html {
+        let route = ctx.link().route().unwrap_or(Route::Home);
+        let full_page = self.watching.is_none()
+            && matches!(route, Route::Library | Route::Problems | Route::Settings);
+        let page = match route {
+            // Someone else's game is all there is to show while watching it
+            _ if self.watching.is_some() => self.render_game(ctx, false),
+            Route::Library | Route::Problems | Route::Settings => {
+                html! { <ViewPage route={ route } /> }
+            }
+            Route::Play | Route::Review if self.board.board_size() != 0 => {
+                self.render_game(ctx, route == Route::Review)
+            }
+            _ => html! { <StartScreen /> },
+        };
+        html! {
+            <div class={ classes!("page", self.layout.class(), full_page.then_some("page-full")) }>
+                if self.watching.is_none() {
+                    <NavBar />
+                }
+                { self.render_tab_bar(ctx) }
+                { page }
+            </div>
+        }
+    }
+}
+
+/// The start screen, to set up a new game.
+#[function_component]
+fn StartScreen() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let button_onclick = ctx.link().callback(move |_| Msg::MakeBoard {
+        width: 19,
+        height: 19,
+    });
+    let (width, height) = app.new_board_size;
+    let width_cb = ctx.link().batch_callback(move |e: Event| {
+        let value = e.target_unchecked_into::<HtmlInputElement>().value();
+        let width = value.trim().parse().ok()?;
+        Some(Msg::SetNewBoardSize(width, height))
+    });
+    let height_cb = ctx.link().batch_callback(move |e: Event| {
+        let value = e.target_unchecked_into::<HtmlInputElement>().value();
+        let height = value.trim().parse().ok()?;
+        Some(Msg::SetNewBoardSize(width, height))
+    });
+    let handicap_cb = ctx.link().batch_callback(|e: Event| {
+        let value = e.target_unchecked_into::<HtmlInputElement>().value();
+        value.trim().parse().ok().map(Msg::SetNewHandicap)
+    });
+    let free_cb = ctx.link().callback(|_: Event| Msg::ToggleFreeHandicap);
+    let three_cb = ctx.link().callback(|_: Event| Msg::ToggleThreePlayers);
+    let rank_cb = |player: Player| {
+        ctx.link().callback(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            Msg::SetNewRank(player, value)
+        })
+    };
+    let make_cb = ctx
+        .link()
+        .callback(move |_| Msg::MakeBoard { width, height });
+    html! {
+        <main>
+            <Presets />
+            <button onclick={ button_onclick }>{ "Default" }</button>
+            <label>
+                { "Width " }
+                <input
+                    type="number"
+                    min={ MIN_BOARD_SIZE.to_string() }
+                    max={ MAX_BOARD_SIZE.to_string() }
+                    value={ width.to_string() }
+                    onchange={ width_cb }
+                />
+            </label>
+            <label>
+                { "Height " }
+                <input
+                    type="number"
+                    min={ MIN_BOARD_SIZE.to_string() }
+                    max={ MAX_BOARD_SIZE.to_string() }
+                    value={ height.to_string() }
+                    onchange={ height_cb }
+                />
+            </label>
+            <label>
+                { "Handicap " }
+                <input
+                    type="number"
+                    min="0"
+                    max={ MAX_STONES.to_string() }
+                    value={ app.new_handicap.to_string() }
+                    onchange={ handicap_cb }
+                />
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    checked={ app.new_handicap_placement == HandicapPlacement::Free }
+                    onchange={ free_cb }
+                />
+                { "Place handicap freely" }
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    checked={ app.new_three_players }
+                    onchange={ three_cb }
+                />
+                { "Three players, with red" }
+            </label>
+            <label>
+                { "Black's rank " }
+                <input
+                    placeholder="5k"
+                    value={ app.new_ranks.0.clone() }
+                    onchange={ rank_cb(Player::Black) }
+                />
+            </label>
+            <label>
+                { "White's rank " }
+                <input
+                    placeholder="2d"
+                    value={ app.new_ranks.1.clone() }
+                    onchange={ rank_cb(Player::White) }
+                />
+            </label>
+            { app.render_handicap_suggestion() }
+            <button onclick={ make_cb }>{ format!("Start {}x{}", width, height) }</button>
+        </main>
+    }
+}
+
+/// The one-tap buttons of the presets on the start screen, with a way to
+/// keep the game set up below as another one.
+#[function_component]
+fn Presets() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let presets = app
+        .settings
+        .presets
+        .0
+        .iter()
+        .enumerate()
+        .map(|(index, preset)| {
+            let start_cb = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::StartPreset(index));
+            let remove_cb = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::RemovePreset(index));
+            let time = match preset.minutes {
+                Some(minutes) => format!("{} min", minutes),
+                None => String::from("no clock"),
+            };
+            html! {
+                <li>
+                    <button class="preset-start" onclick={ start_cb }>
+                        <strong>{ &preset.name }</strong>
+                        <span>{ format!("{}x{}, {}, {}", preset.width, preset.height, preset.rules.name(), time) }</span>
+                    </button>
+                    <button
+                        class="preset-remove"
+                        aria-label={ format!("Remove {}", preset.name) }
+                        onclick={ remove_cb }
+                    >
+                        { "×" }
+                    </button>
+                </li>
+            }
+        });
+    let save_cb = ctx.link().callback(|_: MouseEvent| Msg::SavePreset);
+    html! {
+        <div class="presets">
+            <ul>{ for presets }</ul>
+            <button onclick={ save_cb }>{ "Save the game below as a preset" }</button>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ViewPageProps {
+    /// The library, problems or settings
+    route: Route,
+}
+
+/// The library, problems or settings on a page of their own.
+#[function_component]
+fn ViewPage(props: &ViewPageProps) -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let sections = match props.route {
+        Route::Library => html! {
+            <>
+                <SidebarSection title="Library">
+                    <Library />
+                </SidebarSection>
+                <SidebarSection title="Stats">
+                    <LibraryStats />
+                </SidebarSection>
+            </>
+        },
+        Route::Problems => html! {
+            <SidebarSection title="Problems">
+                <ProblemList />
+            </SidebarSection>
+        },
+        _ => html! {
+            <SidebarSection title="Settings">
+                <SettingsPanel onchange={ ctx.link().callback(Msg::ChangeSettings) } />
+            </SidebarSection>
+        },
+    };
+    html! {
+        <>
+            <main class="view-page">{ sections }</main>
+            { app.render_toast(ctx) }
+        </>
+    }
+}
+
+/// The saved games along with the library actions.
+#[function_component]
+fn Library() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let save_cb = ctx.link().callback(|_: MouseEvent| Msg::SaveToLibrary);
+    let export_cb = ctx.link().callback(|_: MouseEvent| Msg::ExportLibrary);
+    let import_cb = ctx
+        .link()
+        .callback(|e: Event| Msg::ImportFiles(e.target_unchecked_into::<HtmlInputElement>()));
+    let bot_cb = ctx.link().callback(|_: MouseEvent| Msg::ToggleBot);
+    let bot_text = match &app.bot {
+        Some(bot) => format!("Stop bot ({} as {})", bot.name, player_name(bot.color)),
+        None => String::from("Play the library bot"),
+    };
+    let bot_player_cb = ctx.link().callback(|e: Event| {
+        Msg::SetBotPlayer(e.target_unchecked_into::<HtmlSelectElement>().value())
+    });
+    let bot_players = app.library_players.iter().map(|name| {
+        html! {
+            <option value={ name.clone() } selected={ app.bot_player.as_ref() == Some(name) }>
+                { name }
+            </option>
+        }
+    });
+    let entries = app.library.iter().enumerate().map(|(i, entry)| {
+        let compare_cb = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::PickForComparison(i));
+        let review_cb = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::ReviewLibraryEntry(i));
+        let compare_text = match app.compare_pick {
+            Some(pick) if pick == i => "Picked",
+            Some(_) => "Compare with picked",
+            None => "Compare",
+        };
+        let rating = entry.rating.map(
+            |rating| html! { <span class="library-rating">{ format!("{:.0}", rating) }</span> },
+        );
+        html! {
+            <li>
+                { &entry.name }
+                { for rating }
+                <button onclick={ review_cb }>{ "Review" }</button>
+                <button onclick={ compare_cb }>{ compare_text }</button>
+            </li>
+        }
+    });
+    html! {
+        <div class="library">
+            <ul>{ for entries }</ul>
+            <button onclick={ save_cb }>{ "Save game" }</button>
+            <button onclick={ export_cb } disabled={ app.library.is_empty() }>
+                { "Export all" }
+            </button>
+            <label class="button">
+                { "Import SGF" }
+                <input type="file" accept=".sgf" multiple=true onchange={ import_cb } />
+            </label>
+            <label>
+                { "Bot plays like " }
+                <select onchange={ bot_player_cb } disabled={ app.library_players.is_empty() }>
+                    <option value="" selected={ app.bot_player.is_none() }>{ "Pick a player" }</option>
+                    { for bot_players }
+                </select>
+            </label>
+            <button onclick={ bot_cb }>{ bot_text }</button>
+        </div>
+    }
+}
+
+/// What the games of the library add up to: how often each color
+/// won, overall and on each board size, and how the games went.
+#[function_component]
+fn LibraryStats() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let stats = &app.library_stats;
+    if stats.overall.games == 0 {
+        return html! {
+            <p class="stats">{ "Save or import games to the library to see how they went." }</p>
+        };
+    }
+    let percent = |rate: Option<f64>| match rate {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => String::from("-"),
+    };
+    let row = |label: String, tally: &Tally| {
+        html! {
+            <tr>
+                <th scope="row">{ label }</th>
+                <td>{ tally.games }</td>
+                <td>{ percent(tally.win_rate(Player::Black)) }</td>
+                <td>{ percent(tally.win_rate(Player::White)) }</td>
+            </tr>
+        }
+    };
+    let sizes = stats
+        .by_size
+        .iter()
+        .map(|((width, height), tally)| row(format!("{}x{}", width, height), tally));
+    let length = stats
+        .average_length()
+        .map_or_else(String::new, |moves| format!("{:.0} moves", moves));
+    let captures = match stats.average_capture_difference() {
+        Some(difference) if difference > 0.0 => format!("Black by {:.1}", difference),
+        Some(difference) if difference < 0.0 => format!("White by {:.1}", -difference),
+        _ => String::from("Even"),
+    };
+    let first_mistake = match stats.average_first_mistake() {
+        Some(moves) => format!(
+            "Move {:.0}, over {} reviewed games",
+            moves,
+            stats.games_with_mistakes()
+        ),
+        None => String::from("No mistakes marked yet"),
+    };
+    html! {
+        <div class="stats">
+            <table class="stats-wins">
+                <tr>
+                    <td />
+                    <th scope="col">{ "Games" }</th>
+                    <th scope="col">{ "Black wins" }</th>
+                    <th scope="col">{ "White wins" }</th>
+                </tr>
+                { row(String::from("All"), &stats.overall) }
+                { for sizes }
+            </table>
+            <dl>
+                <dt>{ "Average length" }</dt>
+                <dd>{ length }</dd>
+                <dt>{ "Average captures" }</dt>
+                <dd>{ captures }</dd>
+                <dt>{ "First mistake on average" }</dt>
+                <dd>{ first_mistake }</dd>
+            </dl>
+        </div>
+    }
+}
+
+/// Lists the bundled problems, with the state of the one being solved.
+#[function_component]
+fn ProblemList() -> Html {
+    let state = use_app();
+    let app = state.app();
+    let ctx = state.ctx();
+    let current = app.problem.as_ref().map(|session| session.index);
+    let problems = app.problems.iter().enumerate().map(|(index, problem)| {
+        let start_cb = ctx
+            .link()
+            .callback(move |_: MouseEvent| Msg::StartProblem(index));
+        html! {
+            <button
+                class={ classes!((current == Some(index)).then_some("problem-current")) }
+                onclick={ start_cb }
+            >
+                { &problem.name }
+            </button>
+        }
+    });
+    let status = app.problem.as_ref().map(|session| {
+        let text = match session.solved {
+            None => {
+                let player = player_name(app.problems[session.index].player);
+                match session.attempt.moves_left() {
+                    Some(1) => format!("{} to play, 1 move left", player),
+                    Some(moves) => format!("{} to play, {} moves left", player, moves),
+                    None => format!("{} to play", player),
+                }
+            }
+            Some(true) => String::from("Solved"),
+            Some(false) => String::from("Not solved"),
+        };
+        let retry_cb = ctx.link().callback(|_: MouseEvent| Msg::RetryProblem);
+        let end_cb = ctx.link().callback(|_: MouseEvent| Msg::EndProblem);
+        html! {
+            <>
+                <span>{ text }</span>
+                <button onclick={ retry_cb }>{ "Start over" }</button>
+                <button onclick={ end_cb }>{ "Stop solving" }</button>
+            </>
+        }
+    });
+    html! {
+        <div class="problems">
+            { for problems }
+            { for status }
+        </div>
+    }
+}
+
+/// Shows the view of `route`, keeping the one shown in the history for the
+/// back button.
+pub(crate) fn navigate(ctx: &AppContext, route: Route) {
+    if ctx.link().route() == Some(route) {
+        return;
+    }
+    if let Some(navigator) = ctx.link().navigator() {
+        navigator.push(&route);
+    }
+}
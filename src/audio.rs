@@ -0,0 +1,94 @@
+use wasm_bindgen::JsValue;
+use web_sys::{AudioBuffer, AudioContext, OscillatorType};
+
+/// The sound effects of the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    /// A stone put on the board
+    Place,
+    /// Stones taken off the board
+    Capture,
+    Pass,
+}
+
+/// Plays sound effects, synthesized with the Web Audio API so no sound files
+/// have to be shipped.
+///
+/// Browsers only let audio start after the user interacted with the page, so
+/// the audio context is created the first time a sound is played.
+#[derive(Default)]
+pub struct Audio {
+    context: Option<AudioContext>,
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        Audio::default()
+    }
+
+    /// Plays `sound`. Sounds are decoration, so failing to play one is
+    /// ignored.
+    pub fn play(&mut self, sound: Sound) {
+        let _ = self.try_play(sound);
+    }
+
+    fn try_play(&mut self, sound: Sound) -> Result<(), JsValue> {
+        let context = match &self.context {
+            Some(context) => context,
+            None => self.context.insert(AudioContext::new()?),
+        };
+        let now = context.current_time();
+        match sound {
+            Sound::Place => click(context, now, 0.6)?,
+            Sound::Capture => {
+                // A few quieter clicks close together, like stones being
+                // picked up
+                for (i, delay) in [0.0, 0.05, 0.08, 0.13, 0.17].into_iter().enumerate() {
+                    click(context, now + 0.1 + delay, 0.35 - 0.05 * i as f32)?;
+                }
+            }
+            Sound::Pass => tone(context, now, 330.0, 0.15)?,
+        }
+        Ok(())
+    }
+}
+
+/// Schedules a short burst of fading noise at `when`.
+fn click(context: &AudioContext, when: f64, volume: f32) -> Result<(), JsValue> {
+    const LENGTH: f32 = 0.04;
+    let buffer = noise_burst(context, LENGTH, volume)?;
+    let source = context.create_buffer_source()?;
+    source.set_buffer(Some(&buffer));
+    source.connect_with_audio_node(&context.destination())?;
+    source.start_with_when(when)
+}
+
+/// Makes a buffer of noise that fades out over `length` seconds.
+fn noise_burst(context: &AudioContext, length: f32, volume: f32) -> Result<AudioBuffer, JsValue> {
+    let sample_rate = context.sample_rate();
+    let samples = (sample_rate * length) as usize;
+    let data: Vec<f32> = (0..samples)
+        .map(|i| {
+            let fade = 1.0 - i as f32 / samples as f32;
+            (js_sys::Math::random() as f32 * 2.0 - 1.0) * fade * fade * fade * volume
+        })
+        .collect();
+    let buffer = context.create_buffer(1, samples as u32, sample_rate)?;
+    buffer.copy_to_channel(&data, 0)?;
+    Ok(buffer)
+}
+
+/// Schedules a soft sine tone at `when` that fades out over `length` seconds.
+fn tone(context: &AudioContext, when: f64, frequency: f32, length: f64) -> Result<(), JsValue> {
+    let oscillator = context.create_oscillator()?;
+    oscillator.set_type(OscillatorType::Sine);
+    oscillator.frequency().set_value_at_time(frequency, when)?;
+    let gain = context.create_gain()?;
+    gain.gain().set_value_at_time(0.2, when)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.001, when + length)?;
+    oscillator.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(&context.destination())?;
+    oscillator.start_with_when(when)?;
+    oscillator.stop_with_when(when + length)
+}
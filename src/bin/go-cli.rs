@@ -0,0 +1,433 @@
+//! Plays games in a terminal, without a browser.
+//!
+//! Each color is taken by a person typing moves or by a bot, so two bots
+//! can play each other for as many games as asked, which is handy when
+//! working on the engine or checking it in CI. Games can start from an SGF
+//! file and be saved to one.
+
+use std::io::{self, BufRead, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, process};
+
+use go_rs::engine::Search;
+use go_rs::estimate::probably_dead;
+use go_rs::eye::is_real_eye;
+use go_rs::game::{GoGame, Player, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use go_rs::replay::check_replay;
+use go_rs::sgf::{read_sgf, write_sgf};
+
+const USAGE: &str = "\
+Usage: go-cli [options]
+
+Options:
+  --size N        Board size, 19 by default
+  --komi K        Komi, that of the rules by default
+  --black PLAYER  Who plays black: human (default), engine[:VISITS] or random
+  --white PLAYER  Who plays white, the same choices
+  --load FILE     Start from the game in an SGF file
+  --save FILE     Save each game to an SGF file, numbered when there are several
+  --games N       How many games to play, 1 by default
+  --seed N        Seed of the random bot, for games that can be replayed
+  --quiet         Only print the results of games between bots
+  --check         Replay each game two ways once it is over, and fail if they
+                  disagree
+
+Moves are typed as points like D4, or pass, resign, undo, save FILE, quit.";
+
+/// The options followed by a value
+const VALUED_OPTIONS: [&str; 8] = [
+    "--size", "--komi", "--black", "--white", "--load", "--save", "--games", "--seed",
+];
+
+/// The playouts the engine spends on each move unless told otherwise
+const ENGINE_VISITS: usize = 200;
+
+/// How many moves a game between bots may last, per point of the board,
+/// before it is stopped, in case neither of them passes
+const MAX_MOVES_PER_POINT: usize = 3;
+
+/// Who sits at one side of the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Seat {
+    /// Someone typing moves
+    Human,
+    /// The built-in engine with the given playouts per move
+    Engine(usize),
+    /// Any legal move that doesn't fill an eye
+    Random,
+}
+
+impl Seat {
+    fn parse(text: &str) -> Result<Seat, String> {
+        let (name, visits) = match text.split_once(':') {
+            Some((name, visits)) => (name, Some(visits)),
+            None => (text, None),
+        };
+        match (name, visits) {
+            ("human", None) => Ok(Seat::Human),
+            ("random", None) => Ok(Seat::Random),
+            ("engine", None) => Ok(Seat::Engine(ENGINE_VISITS)),
+            ("engine", Some(visits)) => match visits.parse() {
+                Ok(visits) if visits > 0 => Ok(Seat::Engine(visits)),
+                _ => Err(format!("Not a number of visits: {}", visits)),
+            },
+            _ => Err(format!("Unknown player: {}", text)),
+        }
+    }
+}
+
+struct Options {
+    size: usize,
+    komi: Option<f64>,
+    black: Seat,
+    white: Seat,
+    load: Option<String>,
+    save: Option<String>,
+    games: usize,
+    seed: Option<u64>,
+    quiet: bool,
+    check: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        size: 19,
+        komi: None,
+        black: Seat::Human,
+        white: Seat::Human,
+        load: None,
+        save: None,
+        games: 1,
+        seed: None,
+        quiet: false,
+        check: false,
+    };
+    while let Some(arg) = args.next() {
+        if arg == "--quiet" || arg == "--check" {
+            options.quiet |= arg == "--quiet";
+            options.check |= arg == "--check";
+            continue;
+        }
+        if !VALUED_OPTIONS.contains(&arg.as_str()) {
+            return Err(format!("Unknown option: {}", arg));
+        }
+        let value = match args.next() {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", arg)),
+        };
+        let number = |what: &str| format!("Not {}: {}", what, value);
+        match arg.as_str() {
+            "--size" => {
+                options.size = value.parse().map_err(|_| number("a board size"))?;
+                if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&options.size) {
+                    return Err(format!(
+                        "Boards go from {} to {} points across",
+                        MIN_BOARD_SIZE, MAX_BOARD_SIZE
+                    ));
+                }
+            }
+            "--komi" => options.komi = Some(value.parse().map_err(|_| number("a komi"))?),
+            "--black" => options.black = Seat::parse(&value)?,
+            "--white" => options.white = Seat::parse(&value)?,
+            "--load" => options.load = Some(value),
+            "--save" => options.save = Some(value),
+            "--games" => options.games = value.parse().map_err(|_| number("a number of games"))?,
+            "--seed" => options.seed = Some(value.parse().map_err(|_| number("a seed"))?),
+            _ => return Err(format!("Unknown option: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let seed = options.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or(1)
+    });
+    let mut rng = Rng(seed | 1);
+    let mut wins = [0, 0];
+    for number in 1..=options.games {
+        let mut game = new_game(options)?;
+        if !play(&mut game, options, &mut rng)? {
+            return Ok(());
+        }
+        let result = game.result().unwrap_or_else(|| String::from("?"));
+        if options.games > 1 {
+            println!("Game {}: {}", number, result);
+        } else {
+            println!("Result: {}", result);
+        }
+        if result.starts_with("B+") {
+            wins[0] += 1;
+        } else if result.starts_with("W+") {
+            wins[1] += 1;
+        }
+        if let Some(path) = &options.save {
+            let path = match options.games {
+                1 => path.clone(),
+                _ => numbered(path, number),
+            };
+            save(&game, &path)?;
+        }
+        if options.check {
+            let moves = check_replay(&game)
+                .map_err(|e| format!("The replay of game {} went wrong. {}", number, e))?;
+            if !options.quiet {
+                println!("Replayed {} moves both ways, they agree", moves);
+            }
+        }
+    }
+    if options.games > 1 {
+        println!("Black won {}, white won {}", wins[0], wins[1]);
+    }
+    Ok(())
+}
+
+fn new_game(options: &Options) -> Result<GoGame, String> {
+    let mut game = match &options.load {
+        Some(path) => {
+            let text =
+                fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+            read_sgf(&text).map_err(|e| format!("Could not read {}: {}", path, e))?
+        }
+        None => GoGame::new(options.size),
+    };
+    if let Some(komi) = options.komi {
+        game.info.komi = komi;
+    }
+    Ok(game)
+}
+
+fn save(game: &GoGame, path: &str) -> Result<(), String> {
+    let sgf = write_sgf(game).map_err(|e| format!("Could not write {}: {}", path, e))?;
+    fs::write(path, sgf).map_err(|e| format!("Could not write {}: {}", path, e))
+}
+
+/// `path` with `number` before its extension, `game.sgf` becoming
+/// `game-2.sgf`.
+fn numbered(path: &str, number: usize) -> String {
+    match path.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{}-{}.{}", stem, number, extension)
+        }
+        _ => format!("{}-{}", path, number),
+    }
+}
+
+/// Plays `game` until it is over. Returns `false` if someone quit.
+fn play(game: &mut GoGame, options: &Options, rng: &mut Rng) -> Result<bool, String> {
+    let bots_only = options.black != Seat::Human && options.white != Seat::Human;
+    let limit = MAX_MOVES_PER_POINT * game.len();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    while !game.is_over() {
+        if bots_only && game.move_count() >= limit {
+            println!("Stopped after {} moves", game.move_count());
+            break;
+        }
+        let player = game.next_player;
+        let seat = match player {
+            Player::White => options.white,
+            _ => options.black,
+        };
+        let verbose = !(bots_only && options.quiet);
+        if verbose {
+            print_board(game);
+        }
+        match seat {
+            Seat::Human => {
+                print!("{} to play: ", color_name(player));
+                io::stdout().flush().map_err(|e| e.to_string())?;
+                let line = match lines.next() {
+                    Some(line) => line.map_err(|e| e.to_string())?,
+                    None => return Ok(false),
+                };
+                if !command(game, options, line.trim())? {
+                    return Ok(false);
+                }
+            }
+            Seat::Engine(visits) => bot_move(game, engine_move(game, visits), verbose),
+            Seat::Random => bot_move(game, random_move(game, rng), verbose),
+        }
+    }
+    if game.is_scoring() {
+        let dead = probably_dead(game, game.next_player);
+        game.set_dead_stones(dead);
+    }
+    if !(bots_only && options.quiet) {
+        print_board(game);
+    }
+    Ok(true)
+}
+
+/// Carries out what a person typed. Returns `false` to quit.
+fn command(game: &mut GoGame, options: &Options, text: &str) -> Result<bool, String> {
+    let (word, argument) = match text.split_once(' ') {
+        Some((word, argument)) => (word, argument.trim()),
+        None => (text, ""),
+    };
+    match word.to_ascii_lowercase().as_str() {
+        "" => {}
+        "quit" | "exit" => return Ok(false),
+        "pass" => game.pass(),
+        "resign" => game.resign(),
+        "help" => println!("{}", USAGE),
+        "undo" => {
+            // Take back the bot's answer as well, so it is the person's turn
+            // again
+            while game.undo() {
+                let seat = match game.next_player {
+                    Player::White => options.white,
+                    _ => options.black,
+                };
+                if seat == Seat::Human {
+                    break;
+                }
+            }
+        }
+        "save" if argument.is_empty() => println!("Save to which file?"),
+        "save" => match save(game, argument) {
+            Ok(()) => println!("Saved to {}", argument),
+            Err(e) => println!("{}", e),
+        },
+        _ => match game.parse_point_name(text) {
+            Some((x, y)) => {
+                if let Err(e) = game.play_move(x, y) {
+                    println!("{}", e);
+                }
+            }
+            None => println!("Not a move: {} (type help for the commands)", text),
+        },
+    }
+    Ok(true)
+}
+
+/// Plays the move a bot chose, passing when it found none, and says which
+/// when `verbose`.
+fn bot_move(game: &mut GoGame, point: Option<(usize, usize)>, verbose: bool) {
+    let player = game.next_player;
+    let said = match point {
+        Some((x, y)) if game.play_move(x, y).is_ok() => {
+            format!("{} plays {}", color_name(player), game.point_name(x, y))
+        }
+        _ => {
+            game.pass();
+            format!("{} passes", color_name(player))
+        }
+    };
+    if verbose {
+        println!("{}", said);
+    }
+}
+
+/// The move the built-in engine liked best after `visits` playouts, of
+/// those the rules of the game allow.
+fn engine_move(game: &GoGame, visits: usize) -> Option<(usize, usize)> {
+    let player = game.next_player;
+    let mut search = Search::new(game, player, game.compensation());
+    search.run(visits);
+    search
+        .evaluation()
+        .candidates
+        .into_iter()
+        .map(|candidate| candidate.point)
+        .find(|(x, y)| game.check_move(*x, *y, player).is_ok())
+}
+
+/// Any move the rules allow that doesn't fill one of the player's real eyes.
+fn random_move(game: &GoGame, rng: &mut Rng) -> Option<(usize, usize)> {
+    let player = game.next_player;
+    let moves: Vec<(usize, usize)> = (0..game.len())
+        .filter(|index| !is_real_eye(game, *index, player))
+        .map(|index| game.index_to_coord(index))
+        .filter(|(x, y)| game.check_move(*x, *y, player).is_ok())
+        .collect();
+    match moves.len() {
+        0 => None,
+        len => Some(moves[rng.below(len)]),
+    }
+}
+
+/// Prints the board with its coordinates, `X` for black and `O` for white,
+/// the last move in parentheses, and stones marked dead in lowercase.
+fn print_board(game: &GoGame) {
+    let last = game.last_move().and_then(|m| m.point());
+    let width = game.width();
+    let columns: String = (0..width)
+        .map(|x| game.point_name(x, 0).chars().next().unwrap_or(' '))
+        .map(|letter| format!(" {}", letter))
+        .collect();
+    println!();
+    println!("  {}", columns);
+    for y in 0..game.height() {
+        let row = game.height() - y;
+        let mut line = format!("{:>2}", row);
+        for x in 0..width {
+            let index = game.coord_to_index(x, y);
+            let dead = game.dead_stones().contains(&index);
+            line.push(if last == Some((x, y)) {
+                '('
+            } else if x > 0 && last == Some((x - 1, y)) {
+                ')'
+            } else {
+                ' '
+            });
+            line.push(match (game[index], dead) {
+                (Player::Black, false) => 'X',
+                (Player::Black, true) => 'x',
+                (Player::White, false) => 'O',
+                (Player::White, true) => 'o',
+                (Player::Red, false) => 'R',
+                (Player::Red, true) => 'r',
+                (Player::None, _) => '.',
+            });
+        }
+        line.push(if last == Some((width - 1, y)) {
+            ')'
+        } else {
+            ' '
+        });
+        println!("{}{}", line, row);
+    }
+    println!("  {}", columns);
+    println!(
+        "Captures: black {}, white {}",
+        game.captures(Player::Black),
+        game.captures(Player::White)
+    );
+}
+
+fn color_name(player: Player) -> &'static str {
+    match player {
+        Player::White => "White",
+        _ => "Black",
+    }
+}
+
+/// A small xorshift generator for the random bot.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
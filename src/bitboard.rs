@@ -0,0 +1,380 @@
+use std::ops::{BitAnd, BitOr};
+
+use crate::game::{empty_key, zobrist, Group, Player, Position, MAX_BOARD_SIZE};
+
+/// Enough 64 bit words to hold every point of the largest board.
+const WORDS: usize = (MAX_BOARD_SIZE * MAX_BOARD_SIZE).div_ceil(64);
+
+/// A set of board points, one bit per point, packed into 64 bit words.
+///
+/// Bit `i` corresponds to the point at index `i` (`y * board_size + x`). The
+/// words are stored inline, sized for the largest board, so sets are copied
+/// instead of allocated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bitboard {
+    words: [u64; WORDS],
+}
+
+impl Bitboard {
+    /// An empty set.
+    pub fn empty() -> Self {
+        Bitboard::default()
+    }
+
+    /// A set holding every point in `0..len`.
+    pub fn full(len: usize) -> Self {
+        let mut board = Bitboard::empty();
+        for index in 0..len {
+            board.set(index);
+        }
+        board
+    }
+
+    /// A set holding only the point at `index`.
+    pub fn point(index: usize) -> Self {
+        let mut board = Bitboard::empty();
+        board.set(index);
+        board
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.words[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// The number of points in the set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterates over the indicies of the points in the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    /// Returns `self & !other`.
+    pub fn and_not(&self, other: &Bitboard) -> Bitboard {
+        self.zip(other, |a, b| a & !b)
+    }
+
+    /// Combines the words of both sets pairwise with `f`.
+    fn zip(&self, other: &Bitboard, f: impl Fn(u64, u64) -> u64) -> Bitboard {
+        let mut words = [0; WORDS];
+        for (word, (a, b)) in words.iter_mut().zip(self.words.iter().zip(&other.words)) {
+            *word = f(*a, *b);
+        }
+        Bitboard { words }
+    }
+
+    /// Moves every point `n` indicies up, dropping bits shifted past the end
+    /// of the last word. `n` is a row at most, so less than a word.
+    fn shift_up(&self, n: usize) -> Bitboard {
+        debug_assert!(0 < n && n < 64);
+        let mut words = [0; WORDS];
+        words[0] = self.words[0] << n;
+        for (word, pair) in words[1..].iter_mut().zip(self.words.windows(2)) {
+            *word = pair[1] << n | pair[0] >> (64 - n);
+        }
+        Bitboard { words }
+    }
+
+    /// Moves every point `n` indicies down, dropping bits shifted below zero.
+    /// `n` is a row at most, so less than a word.
+    fn shift_down(&self, n: usize) -> Bitboard {
+        debug_assert!(0 < n && n < 64);
+        let mut words = [0; WORDS];
+        for (word, pair) in words.iter_mut().zip(self.words.windows(2)) {
+            *word = pair[0] >> n | pair[1] << (64 - n);
+        }
+        words[WORDS - 1] = self.words[WORDS - 1] >> n;
+        Bitboard { words }
+    }
+}
+
+impl BitAnd for &Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Self) -> Bitboard {
+        self.zip(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for &Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Self) -> Bitboard {
+        self.zip(rhs, |a, b| a | b)
+    }
+}
+
+/// A position stored as one bitboard per color plus the occupied points, so
+/// that flood fills and liberty counts work on whole words at a time.
+///
+/// Plays by the same rules as [`GoPosition`](crate::game::GoPosition),
+/// simple ko and suicides included, for the usual two colors only: it takes
+/// no moves of `Player::Red`. Keeps the same Zobrist key as well, updated
+/// stone by stone, see `GoPosition::key_of`.
+#[derive(Clone, Debug)]
+pub struct BitPosition {
+    board_size: usize,
+    black: Bitboard,
+    white: Bitboard,
+    occupied: Bitboard,
+    /// Every point on the board
+    board_mask: Bitboard,
+    /// Every point except the ones in the first column
+    not_first_column: Bitboard,
+    /// Every point except the ones in the last column
+    not_last_column: Bitboard,
+    /// The point that can't be played at because it would retake a ko at
+    /// once, or `board_size * board_size + 1` when there is none.
+    ko: usize,
+    key: u64,
+}
+
+impl BitPosition {
+    pub fn new(board_size: usize) -> Self {
+        let len = board_size * board_size;
+        let mut not_first_column = Bitboard::full(len);
+        let mut not_last_column = Bitboard::full(len);
+        for y in 0..board_size {
+            not_first_column.clear(y * board_size);
+            not_last_column.clear(y * board_size + board_size - 1);
+        }
+        BitPosition {
+            board_size,
+            black: Bitboard::empty(),
+            white: Bitboard::empty(),
+            occupied: Bitboard::empty(),
+            board_mask: Bitboard::full(len),
+            not_first_column,
+            not_last_column,
+            ko: len + 1,
+            key: empty_key(board_size, board_size),
+        }
+    }
+
+    /// The Zobrist key of the stones, the one a `GoPosition` holding the
+    /// same stones has.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    fn stones(&self, player: Player) -> &Bitboard {
+        match player {
+            Player::White => &self.white,
+            _ => &self.black,
+        }
+    }
+
+    fn stones_mut(&mut self, player: Player) -> &mut Bitboard {
+        match player {
+            Player::White => &mut self.white,
+            _ => &mut self.black,
+        }
+    }
+
+    /// Returns every point orthogonally adjacent to a point in `set`.
+    fn dilate(&self, set: &Bitboard) -> Bitboard {
+        // Moving right can't land in the first column without wrapping a row,
+        // and the same goes for moving left and the last column.
+        let right = &set.shift_up(1) & &self.not_first_column;
+        let left = &set.shift_down(1) & &self.not_last_column;
+        let down = set.shift_up(self.board_size);
+        let up = set.shift_down(self.board_size);
+        &(&(&right | &left) | &(&down | &up)) & &self.board_mask
+    }
+
+    /// Flood fills the chain containing the stone at `index`.
+    fn chain(&self, index: usize) -> Bitboard {
+        let own = self.stones(self.player_at(index));
+        let mut chain = Bitboard::point(index);
+        loop {
+            let grown = &(&self.dilate(&chain) & own) | &chain;
+            if grown == chain {
+                return chain;
+            }
+            chain = grown;
+        }
+    }
+
+    /// The empty points adjacent to `chain`.
+    fn liberties(&self, chain: &Bitboard) -> Bitboard {
+        self.dilate(chain).and_not(&self.occupied)
+    }
+
+    /// Puts a stone of `player` on the empty point at `index`.
+    fn place(&mut self, index: usize, player: Player) {
+        self.stones_mut(player).set(index);
+        self.occupied.set(index);
+        self.key ^= zobrist(index, player);
+    }
+
+    /// Takes the stones of `chain` off the board.
+    fn remove(&mut self, player: Player, chain: &Bitboard) {
+        let stones = self.stones(player).and_not(chain);
+        *self.stones_mut(player) = stones;
+        self.occupied = self.occupied.and_not(chain);
+        for index in chain.iter() {
+            self.key ^= zobrist(index, player);
+        }
+    }
+
+    fn neighbors(&self, index: usize) -> Bitboard {
+        self.dilate(&Bitboard::point(index))
+    }
+
+    /// Whether the stone at `index` touches an empty point other than
+    /// `besides`, in which case its chain has a liberty there without
+    /// flood filling it.
+    fn has_liberty_besides(&self, index: usize, besides: usize) -> bool {
+        let mut empty = self.neighbors(index).and_not(&self.occupied);
+        empty.clear(besides);
+        !empty.is_empty()
+    }
+}
+
+impl Position for BitPosition {
+    fn board_size(&self) -> usize {
+        self.board_size
+    }
+
+    fn player_at(&self, index: usize) -> Player {
+        if self.black.get(index) {
+            Player::Black
+        } else if self.white.get(index) {
+            Player::White
+        } else {
+            Player::None
+        }
+    }
+
+    fn is_valid_move(&self, x: usize, y: usize, player: Player) -> bool {
+        if x >= self.board_size
+            || y >= self.board_size
+            || matches!(player, Player::None | Player::Red)
+        {
+            return false;
+        }
+        let index = y * self.board_size + x;
+        if self.occupied.get(index) {
+            return false;
+        }
+        let ko_pos = index == self.ko;
+        let neighbors = self.neighbors(index);
+        // An empty neighbor is a liberty
+        if !neighbors.and_not(&self.occupied).is_empty() {
+            return true;
+        }
+        for side in neighbors.iter() {
+            // A stone with a liberty of its own is neither captured nor
+            // left without liberties by the move
+            let lone_liberty = !self.has_liberty_besides(side, index);
+            if self.player_at(side) == player {
+                // Connecting to a chain with another liberty
+                if !lone_liberty || self.liberties(&self.chain(side)).count() > 1 {
+                    return true;
+                }
+            } else if lone_liberty {
+                let chain = self.chain(side);
+                // Capturing, unless it is retaking a single stone at the ko point
+                if self.liberties(&chain).count() == 1 && (!ko_pos || chain.count() > 1) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn play(&mut self, x: usize, y: usize, player: Player) -> Vec<usize> {
+        let index = y * self.board_size + x;
+        let opp_player = match player {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+            Player::None => {
+                let stone = self.player_at(index);
+                if stone != Player::None {
+                    self.remove(stone, &Bitboard::point(index));
+                }
+                return vec![];
+            }
+            // Not a valid move, see `is_valid_move`
+            Player::Red => return vec![],
+        };
+        self.ko = self.board_size * self.board_size + 1;
+        self.place(index, player);
+
+        let mut captured = Vec::new();
+        let neighbors = self.neighbors(index);
+        let opp_neighbors = &neighbors & self.stones(opp_player);
+        for side in opp_neighbors.iter() {
+            // Already taken off with an earlier chain, or sure to have a
+            // liberty
+            if !self.occupied.get(side) || self.has_liberty_besides(side, index) {
+                continue;
+            }
+            let chain = self.chain(side);
+            if !self.liberties(&chain).is_empty() {
+                continue;
+            }
+            self.remove(opp_player, &chain);
+            captured.extend(chain.iter());
+        }
+        // A lone stone needs no flood fill to find its chain
+        let chain = if (&neighbors & self.stones(player)).is_empty() {
+            Bitboard::point(index)
+        } else {
+            self.chain(index)
+        };
+        let liberties = self.liberties(&chain);
+        // A suicide, which only gets here under rules that allow it, takes
+        // the whole chain off the board
+        if liberties.is_empty() {
+            self.remove(player, &chain);
+            return chain.iter().collect();
+        }
+        // Only a lone stone that took a lone stone, and whose last liberty
+        // is the point it took, could be taken straight back
+        if captured.len() == 1 && chain.count() == 1 && liberties.count() == 1 {
+            self.ko = captured[0];
+        }
+        captured
+    }
+
+    fn group_at(&self, index: usize) -> Group {
+        let player = self.player_at(index);
+        if player == Player::None {
+            return Group::default();
+        }
+        let chain = self.chain(index);
+        Group {
+            player,
+            liberties: self.liberties(&chain).iter().collect(),
+            stones: chain.iter().collect(),
+        }
+    }
+}
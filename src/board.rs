@@ -0,0 +1,299 @@
+use go_rs::game::{column_name, star_points, GoPosition, Player};
+use yew::prelude::*;
+
+/// Space between the outer lines and the edge of the board, in units of the
+/// distance between two lines
+pub const BOARD_MARGIN: f64 = 1.4;
+pub const STONE_RADIUS: f64 = 0.45;
+/// Colors used when the stylesheet doesn't override them
+pub const BOARD_COLOR: &str = "#AA9E63";
+pub const WHITE: &str = "#CCCCCC";
+pub const BLACK: &str = "#222222";
+pub const RED: &str = "#B03A2E";
+
+/// Length of a side of the viewBox of a board with `points` points along
+/// that side, the lines plus the margin on either side.
+pub fn view_size(points: usize) -> f64 {
+    (points - 1) as f64 + 2.0 * BOARD_MARGIN
+}
+
+/// How the board is turned and mirrored on screen. Only the view changes:
+/// the points keep their coordinates, whichever way they are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orientation {
+    /// Quarter turns clockwise, from 0 to 3
+    pub turns: u8,
+    /// Mirrored left to right, before turning
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    /// Turned another quarter clockwise.
+    pub fn rotated(self) -> Orientation {
+        Orientation {
+            turns: (self.turns + 1) % 4,
+            ..self
+        }
+    }
+
+    /// Turned upside down, as seen from across the board.
+    pub fn flipped(self) -> Orientation {
+        Orientation {
+            turns: (self.turns + 2) % 4,
+            ..self
+        }
+    }
+
+    pub fn toggle_mirror(self) -> Orientation {
+        Orientation {
+            mirrored: !self.mirrored,
+            ..self
+        }
+    }
+
+    /// Degrees the board is turned clockwise.
+    pub fn degrees(self) -> u32 {
+        self.turns as u32 % 4 * 90
+    }
+
+    /// The points across and down of a `width` by `height` board on screen.
+    pub fn shown_size(self, width: usize, height: usize) -> (usize, usize) {
+        if self.turns % 2 == 1 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Where `point` of a `width` by `height` board is shown, both in board
+    /// units.
+    pub fn show(self, width: usize, height: usize, (x, y): (f64, f64)) -> (f64, f64) {
+        let (last_x, last_y) = ((width - 1) as f64, (height - 1) as f64);
+        let x = if self.mirrored { last_x - x } else { x };
+        match self.turns % 4 {
+            0 => (x, y),
+            1 => (last_y - y, x),
+            2 => (last_x - x, last_y - y),
+            _ => (y, last_x - x),
+        }
+    }
+
+    /// The point of a `width` by `height` board shown at `shown`, undoing
+    /// `show`.
+    pub fn unshow(self, width: usize, height: usize, (u, v): (f64, f64)) -> (f64, f64) {
+        let (last_x, last_y) = ((width - 1) as f64, (height - 1) as f64);
+        let (x, y) = match self.turns % 4 {
+            0 => (u, v),
+            1 => (v, last_y - u),
+            2 => (last_x - u, last_y - v),
+            _ => (last_x - v, u),
+        };
+        (if self.mirrored { last_x - x } else { x }, y)
+    }
+
+    /// The step on the board that a step of (`dx`, `dy`) on screen makes,
+    /// for moving the keyboard cursor the way the arrows point.
+    pub fn unshow_step(self, dx: isize, dy: isize) -> (isize, isize) {
+        // The step doesn't depend on the size, any board will do
+        let (x0, y0) = self.unshow(2, 2, (0.0, 0.0));
+        let (x1, y1) = self.unshow(2, 2, (dx as f64, dy as f64));
+        ((x1 - x0).round() as isize, (y1 - y0).round() as isize)
+    }
+
+    /// The SVG transform that draws a `width` by `height` board this way.
+    fn svg_matrix(self, width: usize, height: usize) -> [f64; 6] {
+        let (e, f) = self.show(width, height, (0.0, 0.0));
+        let (ax, ay) = self.show(width, height, (1.0, 0.0));
+        let (cx, cy) = self.show(width, height, (0.0, 1.0));
+        [ax - e, ay - f, cx - e, cy - f, e, f]
+    }
+
+    /// Attributes for the group the points are drawn in: the transform,
+    /// and the opposite turn for the text in it, which the stylesheet
+    /// applies so labels stay readable.
+    pub fn group_attributes(self, width: usize, height: usize) -> (String, String) {
+        let [a, b, c, d, e, f] = self.svg_matrix(width, height);
+        (
+            format!("matrix({} {} {} {} {} {})", a, b, c, d, e, f),
+            // The turn and mirror only swap axes, so undoing them is the
+            // transpose
+            format!("--text-turn: matrix({}, {}, {}, {}, 0, 0);", a, c, b, d),
+        )
+    }
+}
+
+/// Renders the lines of a board of `width` by `height` points.
+pub fn render_grid(width: usize, height: usize) -> Html {
+    let last_x = (width - 1) as f64;
+    let last_y = (height - 1) as f64;
+    let rows = (0..height).map(|i| {
+        let i = i as f64;
+        html! {
+            <line x1="0" y1={ i.to_string() } x2={ last_x.to_string() } y2={ i.to_string() } />
+        }
+    });
+    let columns = (0..width).map(|i| {
+        let i = i as f64;
+        html! {
+            <line x1={ i.to_string() } y1="0" x2={ i.to_string() } y2={ last_y.to_string() } />
+        }
+    });
+    html! {
+        <g class="g-grid" stroke="black" stroke-width="0.03" stroke-linecap="square">
+            { for rows }
+            { for columns }
+        </g>
+    }
+}
+
+/// Draws the star points of a board, see `star_points`.
+pub fn render_star_points(width: usize, height: usize) -> Html {
+    let dots = star_points(width, height).into_iter().map(|(x, y)| {
+        html! {
+            <circle cx={ x.to_string() } cy={ y.to_string() } r="0.1" />
+        }
+    });
+    html! {
+        <g class="g-star-points" fill="black">{ for dots }</g>
+    }
+}
+
+/// Renders the names of the columns and rows in the margin around the lines:
+/// letters from the left, and numbers counting up from the bottom.
+pub fn render_coordinates(width: usize, height: usize) -> Html {
+    let last_x = (width - 1) as f64;
+    let last_y = (height - 1) as f64;
+    // Halfway between the outer lines and the edge of the board
+    let offset = BOARD_MARGIN / 2.0 + STONE_RADIUS / 2.0;
+    let label = |x: f64, y: f64, text: String| {
+        html! {
+            <text x={ x.to_string() } y={ y.to_string() }>{ text }</text>
+        }
+    };
+    let columns = (0..width).flat_map(|i| {
+        let name = column_name(i);
+        let x = i as f64;
+        [
+            label(x, -offset, name.clone()),
+            label(x, last_y + offset, name),
+        ]
+    });
+    let rows = (0..height).flat_map(|i| {
+        let name = (height - i).to_string();
+        let y = i as f64;
+        [
+            label(-offset, y, name.clone()),
+            label(last_x + offset, y, name),
+        ]
+    });
+    html! {
+        <g
+            class="g-coordinates"
+            fill="black"
+            font-size="0.4"
+            text-anchor="middle"
+            dominant-baseline="central"
+        >
+            { for columns }
+            { for rows }
+        </g>
+    }
+}
+
+/// Defines the gradients and shadow of the shaded stone styles, which the
+/// stylesheet refers to when one of them is picked.
+pub fn render_stone_defs() -> Html {
+    html! {
+        <defs>
+            <radialGradient id="stone-black-shaded" cx="0.35" cy="0.35" r="0.75">
+                <stop offset="0" stop-color="#555555" />
+                <stop offset="1" stop-color="#111111" />
+            </radialGradient>
+            <radialGradient id="stone-white-shaded" cx="0.35" cy="0.35" r="0.75">
+                <stop offset="0" stop-color="#FFFFFF" />
+                <stop offset="1" stop-color="#B8B8B8" />
+            </radialGradient>
+            <radialGradient id="stone-black-glossy" cx="0.35" cy="0.3" r="0.8">
+                <stop offset="0" stop-color="#8A8A8A" />
+                <stop offset="0.15" stop-color="#3A3A3A" />
+                <stop offset="1" stop-color="#000000" />
+            </radialGradient>
+            <radialGradient id="stone-white-glossy" cx="0.35" cy="0.3" r="0.8">
+                <stop offset="0" stop-color="#FFFFFF" />
+                <stop offset="0.5" stop-color="#EEEEEA" />
+                <stop offset="1" stop-color="#A9A9A2" />
+            </radialGradient>
+            <filter id="stone-shadow" x="-20%" y="-20%" width="150%" height="150%">
+                <feDropShadow dx="0.06" dy="0.08" stdDeviation="0.05" flood-opacity="0.5" />
+            </filter>
+        </defs>
+    }
+}
+
+/// Renders the stones of `position`
+pub fn render_stones(position: &GoPosition) -> Html {
+    let stones = position
+        .position()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, player)| {
+            let (class, fill) = match player {
+                Player::None => return None,
+                Player::White => ("stone-white", WHITE),
+                Player::Black => ("stone-black", BLACK),
+                Player::Red => ("stone-red", RED),
+            };
+            let (x, y) = position.index_to_coord(i);
+            Some(html! {
+                <circle
+                    class={ class }
+                    cx={ x.to_string() }
+                    cy={ y.to_string() }
+                    r={ STONE_RADIUS.to_string() }
+                    fill={ fill }
+                />
+            })
+        });
+    html! {
+        <g class="g-stones">{ for stones }</g>
+    }
+}
+
+/// Renders a board that only shows `position`, without any input or overlays
+/// besides a ring on the stone at `marked`.
+pub fn render_static_board(position: &GoPosition, marked: Option<(usize, usize)>) -> Html {
+    let (width, height) = (position.width(), position.height());
+    let mark = marked.map(|(x, y)| {
+        let stroke = match position[position.coord_to_index(x, y)] {
+            Player::Black => WHITE,
+            _ => BLACK,
+        };
+        html! {
+            <circle
+                class="g-last-move"
+                cx={ x.to_string() }
+                cy={ y.to_string() }
+                r="0.2"
+                fill="none"
+                stroke={ stroke }
+                stroke-width="0.06"
+            />
+        }
+    });
+    html! {
+        <svg
+            class="g-board"
+            viewBox={ format!("0 0 {} {}", view_size(width), view_size(height)) }
+            xmlns="http://www.w3.org/2000/svg"
+        >
+            <rect class="g-background" width="100%" height="100%" fill={ BOARD_COLOR } />
+            { render_stone_defs() }
+            <g transform={ format!("translate({0} {0})", BOARD_MARGIN) }>
+                { render_grid(width, height) }
+                { render_star_points(width, height) }
+                { render_stones(position) }
+                { for mark }
+            </g>
+        </svg>
+    }
+}
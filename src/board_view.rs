@@ -0,0 +1,402 @@
+use web_sys::{DomRect, Element, SvgElement, TouchList};
+use yew::prelude::*;
+
+use crate::board::*;
+use crate::resize::SizeObserver;
+use crate::settings::Settings;
+
+/// A finger on the board, in board units like the other board events.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardTouch {
+    Start { x: f64, y: f64 },
+    Move { x: f64, y: f64 },
+    End,
+    Cancel,
+}
+
+/// The most the board can be zoomed in, as the fraction of its width that
+/// is still shown
+const MAX_ZOOM: f64 = 0.25;
+
+/// The part of the board shown while zoomed in, in viewBox units. The part
+/// keeps the aspect ratio of the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Zoom {
+    x: f64,
+    y: f64,
+    width: f64,
+}
+
+impl Zoom {
+    fn whole(view_width: f64) -> Zoom {
+        Zoom {
+            x: 0.0,
+            y: 0.0,
+            width: view_width,
+        }
+    }
+
+    /// Keeps the part within the board and the zoom within `MAX_ZOOM`,
+    /// returning `None` when the whole board is shown.
+    fn clamped(self, view_width: f64, view_height: f64) -> Option<Zoom> {
+        let width = self.width.clamp(view_width * MAX_ZOOM, view_width);
+        if width >= view_width {
+            return None;
+        }
+        let height = width * view_height / view_width;
+        Some(Zoom {
+            x: self.x.clamp(0.0, view_width - width),
+            y: self.y.clamp(0.0, view_height - height),
+            width,
+        })
+    }
+}
+
+/// A gesture of fingers on the board that changes the zoom.
+#[derive(Clone, Copy, Debug)]
+enum Gesture {
+    /// Two fingers pinching, `distance` apart around `middle` when they
+    /// started, in client pixels
+    Pinch {
+        distance: f64,
+        middle: (f64, f64),
+        zoom: Zoom,
+    },
+    /// One finger dragging the zoomed board, from `from` in client pixels
+    Pan { from: (f64, f64), zoom: Zoom },
+}
+
+#[derive(Properties, PartialEq)]
+pub struct BoardViewProps {
+    /// The number of points across and down
+    pub width: usize,
+    pub height: usize,
+    /// A click on the board, in board units where the points are one unit
+    /// apart and point `(0, 0)` is at `(0.0, 0.0)`
+    pub onclick: Callback<(f64, f64)>,
+    /// The mouse moving over the board, or `None` when it leaves
+    pub onhover: Callback<Option<(f64, f64)>>,
+    pub ontouch: Callback<BoardTouch>,
+    pub onkeydown: Callback<KeyboardEvent>,
+    /// How the board is turned on screen. The layers are drawn and input
+    /// is reported the same way however it is turned.
+    #[prop_or_default]
+    pub orientation: Orientation,
+    /// Layers drawn on top of the lines, in board units
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// The board itself: draws the lines and fits the board to the space it is
+/// given, and turns input on it into board units. What is drawn on the
+/// points is up to its children. On touch screens it can be zoomed in by
+/// pinching and dragged around with one finger.
+#[function_component]
+pub fn BoardView(props: &BoardViewProps) -> Html {
+    let settings = use_context::<Settings>().unwrap_or_default();
+    let board_ref = use_node_ref();
+    // The element the board is fit into
+    let board_area_ref = use_node_ref();
+    // Size of the board area in pixels, the board is left to the stylesheet
+    // until it has been measured
+    let area_px = use_state_eq(|| None::<(f64, f64)>);
+    // Set when the browser can't watch element sizes
+    let resize_unsupported = use_state_eq(|| false);
+    // The part of the board shown, `None` for all of it
+    let zoom = use_state_eq(|| None::<Zoom>);
+    let gesture = use_mut_ref(|| None::<Gesture>);
+
+    {
+        let zoom = zoom.clone();
+        // A new board is shown whole, as is a board turned another way
+        use_effect_with((props.width, props.height, props.orientation), move |_| {
+            zoom.set(None)
+        });
+    }
+
+    {
+        let board_ref = board_ref.clone();
+        let board_area_ref = board_area_ref.clone();
+        let area_px = area_px.clone();
+        let resize_unsupported = resize_unsupported.clone();
+        use_effect_with((), move |_| {
+            // Take keyboard input right away on a new board
+            if let Some(board) = board_ref.cast::<SvgElement>() {
+                let _ = board.focus();
+            }
+            // Watch the board area while the board is shown
+            let on_resize = Callback::from(move |size: (f64, f64)| area_px.set(Some(size)));
+            let observer = board_area_ref.cast::<Element>().and_then(|area| {
+                match SizeObserver::observe(&area, on_resize) {
+                    Ok(observer) => Some(observer),
+                    // Keep the stylesheet's sizing
+                    Err(e) => {
+                        gloo_console::warn!(e);
+                        resize_unsupported.set(true);
+                        None
+                    }
+                }
+            });
+            move || drop(observer)
+        });
+    }
+
+    let (width, height, orientation) = (props.width, props.height, props.orientation);
+    let (shown_width, shown_height) = orientation.shown_size(width, height);
+    let (view_width, view_height) = (view_size(shown_width), view_size(shown_height));
+    // From where a point is shown to the point
+    let to_board = move |point| orientation.unshow(width, height, point);
+    let shown = zoom.unwrap_or(Zoom::whole(view_width));
+    // The empty board is laid out first and stays hidden until its size is
+    // known, so pieces never show up at a size they jump from
+    let board_style = match *area_px {
+        Some((area_width, area_height)) => {
+            // The largest board with the aspect ratio of the view that fits
+            let scale = (area_width / view_width).min(area_height / view_height);
+            format!(
+                "width: {}px; height: {}px;",
+                (view_width * scale).floor(),
+                (view_height * scale).floor()
+            )
+        }
+        None if *resize_unsupported => String::new(),
+        None => String::from("visibility: hidden;"),
+    };
+    let layers = if area_px.is_some() || *resize_unsupported {
+        html! {
+            <>
+                { render_star_points(props.width, props.height) }
+                { props.children.clone() }
+            </>
+        }
+    } else {
+        html! {}
+    };
+    let (oriented, text_turn) = orientation.group_attributes(width, height);
+    let coordinates = if settings.show_coordinates {
+        render_coordinates(props.width, props.height)
+    } else {
+        html! {}
+    };
+
+    let onclick = {
+        let board_ref = board_ref.clone();
+        let onclick = props.onclick.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            onclick.emit(to_board(board_coords(
+                &board_ref,
+                shown,
+                e.client_x(),
+                e.client_y(),
+            )));
+        })
+    };
+    let ontouchstart = {
+        let board_ref = board_ref.clone();
+        let ontouch = props.ontouch.clone();
+        let gesture = gesture.clone();
+        let zoomed = zoom.is_some();
+        Callback::from(move |e: TouchEvent| {
+            let touches = e.touches();
+            match touches.length() {
+                // Only single finger taps select points
+                1 => {
+                    if let Some(touch) = touches.get(0) {
+                        let (x, y) = to_board(board_coords(
+                            &board_ref,
+                            shown,
+                            touch.client_x(),
+                            touch.client_y(),
+                        ));
+                        ontouch.emit(BoardTouch::Start { x, y });
+                        *gesture.borrow_mut() = zoomed.then_some(Gesture::Pan {
+                            from: (touch.client_x() as f64, touch.client_y() as f64),
+                            zoom: shown,
+                        });
+                    }
+                }
+                2 => {
+                    ontouch.emit(BoardTouch::Cancel);
+                    if let Some((distance, middle)) = spread(&touches) {
+                        *gesture.borrow_mut() = Some(Gesture::Pinch {
+                            distance,
+                            middle,
+                            zoom: shown,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        })
+    };
+    let ontouchmove = {
+        let board_ref = board_ref.clone();
+        let ontouch = props.ontouch.clone();
+        let gesture = gesture.clone();
+        let zoom = zoom.clone();
+        Callback::from(move |e: TouchEvent| {
+            let touches = e.touches();
+            let rect = match board_ref.cast::<Element>() {
+                Some(board) => board.get_bounding_client_rect(),
+                None => return,
+            };
+            match *gesture.borrow() {
+                Some(Gesture::Pinch {
+                    distance,
+                    middle,
+                    zoom: start,
+                }) => {
+                    if let Some((now_distance, now_middle)) = spread(&touches) {
+                        e.prevent_default();
+                        let width = start.width * distance / now_distance.max(1.0);
+                        // The point that was under the fingers stays there
+                        let (x, y) = view_point(&rect, start, middle);
+                        let scale = width / rect.width();
+                        let pinched = Zoom {
+                            x: x - (now_middle.0 - rect.left()) * scale,
+                            y: y - (now_middle.1 - rect.top()) * scale,
+                            width,
+                        };
+                        zoom.set(pinched.clamped(view_width, view_height));
+                    }
+                    return;
+                }
+                Some(Gesture::Pan { from, zoom: start }) => {
+                    if let Some(touch) = touches.get(0) {
+                        e.prevent_default();
+                        let scale = start.width / rect.width();
+                        let panned = Zoom {
+                            x: start.x - (touch.client_x() as f64 - from.0) * scale,
+                            y: start.y - (touch.client_y() as f64 - from.1) * scale,
+                            ..start
+                        };
+                        zoom.set(panned.clamped(view_width, view_height));
+                    }
+                }
+                None => {}
+            }
+            if let Some(touch) = touches.get(0) {
+                let (x, y) = to_board(board_coords(
+                    &board_ref,
+                    shown,
+                    touch.client_x(),
+                    touch.client_y(),
+                ));
+                ontouch.emit(BoardTouch::Move { x, y });
+            }
+        })
+    };
+    let ontouchend = {
+        let ontouch = props.ontouch.clone();
+        let gesture = gesture.clone();
+        Callback::from(move |e: TouchEvent| {
+            // Keep the browser from following up with a click
+            e.prevent_default();
+            // Lifting one finger of a pinch doesn't start a drag
+            gesture.borrow_mut().take();
+            ontouch.emit(BoardTouch::End);
+        })
+    };
+    let ontouchcancel = props.ontouch.reform(|_: TouchEvent| BoardTouch::Cancel);
+    let onpointermove = {
+        let board_ref = board_ref.clone();
+        let onhover = props.onhover.clone();
+        Callback::from(move |e: PointerEvent| {
+            // Fingers and pens have no hover to follow
+            if e.pointer_type() == "mouse" {
+                onhover.emit(Some(to_board(board_coords(
+                    &board_ref,
+                    shown,
+                    e.client_x(),
+                    e.client_y(),
+                ))));
+            }
+        })
+    };
+    let onpointerleave = props.onhover.reform(|_: PointerEvent| None);
+    let reset_zoom = match *zoom {
+        Some(_) => {
+            let zoom = zoom.clone();
+            let onclick = Callback::from(move |_: MouseEvent| zoom.set(None));
+            html! {
+                <button class="zoom-reset" onclick={ onclick }>{ "Show whole board" }</button>
+            }
+        }
+        None => html! {},
+    };
+    // Zoomed in, every drag on the board moves it instead of the page
+    let touch_action = match *zoom {
+        Some(_) => "touch-action: none;",
+        None => "touch-action: pan-x pan-y;",
+    };
+
+    html! {
+        <div class="board-area" ref={ board_area_ref }>
+            <svg
+                ref={ board_ref }
+                onclick={ onclick }
+                ontouchstart={ ontouchstart }
+                ontouchmove={ ontouchmove }
+                ontouchend={ ontouchend }
+                ontouchcancel={ ontouchcancel }
+                onkeydown={ props.onkeydown.clone() }
+                onpointermove={ onpointermove }
+                onpointerleave={ onpointerleave }
+                tabindex="0"
+                aria-label="Go board"
+                class="g-board"
+                style={ format!("{} {}", board_style, touch_action) }
+                viewBox={ format!(
+                    "{} {} {} {}",
+                    shown.x,
+                    shown.y,
+                    shown.width,
+                    shown.width * view_height / view_width
+                ) }
+                xmlns="http://www.w3.org/2000/svg"
+            >
+                <rect class="g-background" width="100%" height="100%" fill={ BOARD_COLOR } />
+                { render_stone_defs() }
+                <g transform={ format!("translate({0} {0})", BOARD_MARGIN) }>
+                    <g class="g-oriented" transform={ oriented } style={ text_turn }>
+                        { render_grid(props.width, props.height) }
+                        { coordinates }
+                        { layers }
+                    </g>
+                </g>
+            </svg>
+            { reset_zoom }
+        </div>
+    }
+}
+
+/// Converts a position in client pixels to board units, where the points are
+/// one unit apart and point `(0, 0)` is at `(0.0, 0.0)`. `shown` is the
+/// part of the board in view.
+fn board_coords(board_ref: &NodeRef, shown: Zoom, client_x: i32, client_y: i32) -> (f64, f64) {
+    let board = board_ref.cast::<Element>().unwrap();
+    let rect = board.get_bounding_client_rect();
+    let (x, y) = view_point(&rect, shown, (client_x as f64, client_y as f64));
+    (x - BOARD_MARGIN, y - BOARD_MARGIN)
+}
+
+/// Converts a position in client pixels to viewBox units, for a board at
+/// `rect` showing `shown`.
+fn view_point(rect: &DomRect, shown: Zoom, (client_x, client_y): (f64, f64)) -> (f64, f64) {
+    // The board keeps its aspect ratio, so one scale fits both axes
+    let scale = shown.width / rect.width();
+    (
+        shown.x + (client_x - rect.left()) * scale,
+        shown.y + (client_y - rect.top()) * scale,
+    )
+}
+
+/// How far apart the first two fingers are and the point halfway between
+/// them, in client pixels.
+fn spread(touches: &TouchList) -> Option<(f64, (f64, f64))> {
+    let (a, b) = (touches.get(0)?, touches.get(1)?);
+    let (ax, ay) = (a.client_x() as f64, a.client_y() as f64);
+    let (bx, by) = (b.client_x() as f64, b.client_y() as f64);
+    Some(((ax - bx).hypot(ay - by), ((ax + bx) / 2.0, (ay + by) / 2.0)))
+}
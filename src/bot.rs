@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::engine::Engine;
+use crate::eye::is_real_eye;
+use crate::game::{GoGame, GoPosition, Player, Position};
+
+/// A bot that imitates one player of a set of games by favoring moves whose
+/// surroundings look like the places they played.
+///
+/// Every move of that player in the games it learns from is reduced to a
+/// pattern made of the 3x3 neighborhood around the point, seen from the
+/// mover's side, and the distance to the nearest edge. The bot then plays the
+/// legal move whose pattern came up the most.
+#[derive(Debug, Default)]
+pub struct MimicBot {
+    patterns: HashMap<u32, u32>,
+    games: usize,
+}
+
+impl MimicBot {
+    pub fn new() -> Self {
+        MimicBot::default()
+    }
+
+    /// The number of games learned from.
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Records the pattern of every move `player` played in `game`, finding
+    /// their color from the names of the players. Games `player` didn't
+    /// play are left out.
+    pub fn learn(&mut self, game: &GoGame, player: &str) {
+        let color = if game.info.black_name == player {
+            Player::Black
+        } else if game.info.white_name == player {
+            Player::White
+        } else {
+            return;
+        };
+        let mut position = game.initial_position().clone();
+        for m in game.history() {
+            if let Some((x, y)) = m.point() {
+                if m.player == color {
+                    let index = position.coord_to_index(x, y);
+                    *self
+                        .patterns
+                        .entry(pattern(&position, index, m.player))
+                        .or_insert(0) += 1;
+                }
+                position.play(x, y, m.player);
+            }
+        }
+        self.games += 1;
+    }
+}
+
+impl Engine for MimicBot {
+    /// Picks the move that looks most like the learned games, or `None` to
+    /// pass when no legal move matches anything learned.
+    fn choose_move(&self, game: &GoGame) -> Option<(usize, usize)> {
+        let player = game.next_player;
+        (0..game.len())
+            .filter_map(|index| {
+                let (x, y) = game.index_to_coord(index);
+                if !game.is_valid_move(x, y, player) || is_real_eye(game, index, player) {
+                    return None;
+                }
+                let count = self.patterns.get(&pattern(game, index, player))?;
+                Some((*count, index))
+            })
+            // Prefer the lowest index on ties so the choice is repeatable
+            .max_by_key(|(count, index)| (*count, std::cmp::Reverse(*index)))
+            .map(|(_, index)| game.index_to_coord(index))
+    }
+}
+
+/// Encodes the surroundings of `index` as seen by `player`: two bits for each
+/// of the eight surrounding points (empty, own stone, opposing stone or off
+/// the board) followed by the distance to the closest edge, capped at 4.
+fn pattern(position: &GoPosition, index: usize, player: Player) -> u32 {
+    let (width, height) = (position.width() as isize, position.height() as isize);
+    let (x, y) = position.index_to_coord(index);
+    let (x, y) = (x as isize, y as isize);
+    let mut key = 0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let (nx, ny) = (x + dx, y + dy);
+            let cell = if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                3
+            } else {
+                match position[(ny * width + nx) as usize] {
+                    Player::None => 0,
+                    p if p == player => 1,
+                    _ => 2,
+                }
+            };
+            key = (key << 2) | cell;
+        }
+    }
+    let edge_distance = x.min(y).min(width - 1 - x).min(height - 1 - y).min(4);
+    (key << 3) | edge_distance as u32
+}
@@ -0,0 +1,49 @@
+//! Frames of a live broadcast.
+//!
+//! A broadcast goes through a relay that only forwards text messages. The
+//! broadcaster opens a connection and sends `publish <channel>`, viewers send
+//! `watch <channel>`. After that, every frame the broadcaster sends is passed
+//! on to all of the viewers of the channel. Each frame carries the whole game,
+//! so viewers that join late or miss a frame catch up with the next one.
+
+/// The first line of every frame, so unrelated messages can be ignored.
+const FRAME_HEADER: &str = "go-rs-broadcast 1";
+
+/// A snapshot of the broadcast board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Increases with every frame, so stale frames can be dropped
+    pub sequence: u64,
+    /// Whether the broadcaster lets viewers see the liberty overlay
+    pub show_liberties: bool,
+    /// The game so far, as an SGF record
+    pub sgf: String,
+}
+
+impl Frame {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}\nsequence {}\nliberties {}\n{}",
+            FRAME_HEADER, self.sequence, self.show_liberties as u8, self.sgf
+        )
+    }
+
+    /// Reads a frame from a relay message, returning `None` if it isn't one.
+    pub fn decode(text: &str) -> Option<Frame> {
+        let mut lines = text.splitn(4, '\n');
+        if lines.next()? != FRAME_HEADER {
+            return None;
+        }
+        let sequence = lines.next()?.strip_prefix("sequence ")?.parse().ok()?;
+        let show_liberties = match lines.next()?.strip_prefix("liberties ")? {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        Some(Frame {
+            sequence,
+            show_liberties,
+            sgf: lines.next()?.to_owned(),
+        })
+    }
+}
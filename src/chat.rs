@@ -0,0 +1,76 @@
+//! Chat messages of an online game.
+//!
+//! Chat goes through the relay on the same connections as the frames of the
+//! game, see `broadcast`, and is told apart from them by its first line. The
+//! players talk on their own channels, while spectators have a channel of
+//! their own that the players may look at as well.
+
+/// The first line of every chat message, so it isn't mistaken for a frame.
+const CHAT_HEADER: &str = "go-rs-chat 1";
+
+/// The replies offered with a single click.
+pub const QUICK_REPLIES: [&str; 4] = [
+    "Hello, have a good game!",
+    "Good game",
+    "Thank you",
+    "Sorry, I have to go",
+];
+
+/// Who a chat message is meant for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChatChannel {
+    /// Between the two players
+    #[default]
+    Players,
+    /// Among the spectators, which the players may follow
+    Spectators,
+}
+
+impl ChatChannel {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChatChannel::Players => "players",
+            ChatChannel::Spectators => "spectators",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    pub channel: ChatChannel,
+    /// Who wrote the message, like `Black` or a spectator's name
+    pub sender: String,
+    pub text: String,
+}
+
+impl ChatMessage {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}\n{}\n{}\n{}",
+            CHAT_HEADER,
+            self.channel.as_str(),
+            // A name can't be allowed to spill into the text
+            self.sender.replace('\n', " "),
+            self.text
+        )
+    }
+
+    /// Reads a chat message from a relay message, returning `None` if it
+    /// isn't one.
+    pub fn decode(text: &str) -> Option<ChatMessage> {
+        let mut lines = text.splitn(4, '\n');
+        if lines.next()? != CHAT_HEADER {
+            return None;
+        }
+        let channel = match lines.next()? {
+            "players" => ChatChannel::Players,
+            "spectators" => ChatChannel::Spectators,
+            _ => return None,
+        };
+        Some(ChatMessage {
+            channel,
+            sender: lines.next()?.to_owned(),
+            text: lines.next()?.to_owned(),
+        })
+    }
+}
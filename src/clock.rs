@@ -0,0 +1,130 @@
+use go_rs::game::Player;
+
+/// Why the clock was stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pause {
+    /// Stopped by a player
+    Manual,
+    /// Stopped because the page was hidden, it starts again once the page is
+    /// shown
+    Hidden,
+}
+
+/// Keeps track of the time each player has spent on their moves. Times are
+/// in milliseconds, as given by `js_sys::Date::now`.
+#[derive(Debug, Default)]
+pub struct Clock {
+    /// Time used by black, white and red, not counting the running turn
+    used: [f64; 3],
+    /// The player to move, `None` before the game is followed and once it is
+    /// over
+    turn: Option<Player>,
+    /// When the time of `turn` last started running
+    started: f64,
+    /// The number of moves made when the turn started
+    moves: usize,
+    pause: Option<Pause>,
+    /// Time used in the running turn, not counting since `started`
+    turn_used: f64,
+    /// How long the move that ended the last turn took, see `last_turn`
+    last_turn: Option<f64>,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock::default()
+    }
+
+    /// Follows the game: starts the turn of `next_player` once a move has
+    /// been made, starts over when moves were taken back or the game was
+    /// replaced, and stops once the game is over. Returns whether the turn
+    /// changed.
+    pub fn sync(&mut self, next_player: Player, moves: usize, over: bool, now: f64) -> bool {
+        if moves < self.moves {
+            *self = Clock::new();
+        }
+        let turn = if over { None } else { Some(next_player) };
+        if turn == self.turn && moves == self.moves {
+            return false;
+        }
+        self.charge(now);
+        // Only a single move ends a turn that was timed
+        self.last_turn = match (self.turn, moves == self.moves + 1) {
+            (Some(_), true) => Some(self.turn_used),
+            _ => None,
+        };
+        self.turn_used = 0.0;
+        self.turn = turn;
+        self.started = now;
+        self.moves = moves;
+        true
+    }
+
+    /// How long the player thought about the move that ended the last turn,
+    /// `None` if that turn wasn't timed.
+    pub fn last_turn(&self) -> Option<f64> {
+        self.last_turn
+    }
+
+    pub fn pause(&mut self, reason: Pause, now: f64) {
+        if self.pause.is_none() {
+            self.charge(now);
+            self.pause = Some(reason);
+        }
+    }
+
+    pub fn resume(&mut self, now: f64) {
+        if self.pause.take().is_some() {
+            self.started = now;
+        }
+    }
+
+    pub fn paused(&self) -> Option<Pause> {
+        self.pause
+    }
+
+    /// Whether time is passing for one of the players.
+    pub fn is_running(&self) -> bool {
+        self.pause.is_none() && self.turn.is_some()
+    }
+
+    /// The time `player` has used so far.
+    pub fn used(&self, player: Player, now: f64) -> f64 {
+        let running = if self.is_running() && self.turn == Some(player) {
+            now - self.started
+        } else {
+            0.0
+        };
+        match slot(player) {
+            Some(slot) => self.used[slot] + running,
+            None => 0.0,
+        }
+    }
+
+    /// Adds the time since `started` to the player to move.
+    fn charge(&mut self, now: f64) {
+        if !self.is_running() {
+            return;
+        }
+        if let Some(slot) = self.turn.and_then(slot) {
+            self.used[slot] += now - self.started;
+            self.turn_used += now - self.started;
+        }
+        self.started = now;
+    }
+}
+
+fn slot(player: Player) -> Option<usize> {
+    match player {
+        Player::Black => Some(0),
+        Player::White => Some(1),
+        Player::Red => Some(2),
+        Player::None => None,
+    }
+}
+
+/// Formats a time in milliseconds as minutes and seconds.
+pub fn format_time(ms: f64) -> String {
+    let seconds = (ms / 1000.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,79 @@
+use go_rs::annotation::{Annotation, Evaluation};
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct CommentPaneProps {
+    /// How many moves led to the position commented on
+    pub moves: usize,
+    pub annotation: Annotation,
+    /// Whether the annotation can only be read, as on someone else's
+    /// broadcast
+    #[prop_or_default]
+    pub readonly: bool,
+    pub onchange: Callback<Annotation>,
+}
+
+/// The comment and evaluation of the shown position, which follow the
+/// board while stepping through a game.
+#[function_component]
+pub fn CommentPane(props: &CommentPaneProps) -> Html {
+    let title = match props.moves {
+        0 => String::from("Start"),
+        moves => format!("Move {}", moves),
+    };
+    if props.readonly {
+        return html! {
+            <div class="comment-pane">
+                <h2>{ title }</h2>
+                if let Some(evaluation) = props.annotation.evaluation {
+                    <span class="cp-evaluation">{ evaluation.name() }</span>
+                }
+                <p class="cp-comment">{ &props.annotation.comment }</p>
+            </div>
+        };
+    }
+
+    let comment_cb = {
+        let annotation = props.annotation.clone();
+        props.onchange.reform(move |e: Event| Annotation {
+            comment: e.target_unchecked_into::<HtmlTextAreaElement>().value(),
+            ..annotation.clone()
+        })
+    };
+    // Only moves are evaluated, not the starting position
+    let evaluations = (props.moves > 0).then(|| {
+        Evaluation::ALL.map(|evaluation| {
+            let active = props.annotation.evaluation == Some(evaluation);
+            let annotation = props.annotation.clone();
+            // Picking the active evaluation again takes it off
+            let onclick = props.onchange.reform(move |_: MouseEvent| Annotation {
+                evaluation: (!active).then_some(evaluation),
+                ..annotation.clone()
+            });
+            html! {
+                <button
+                    class={ classes!(active.then_some("cp-active")) }
+                    aria-pressed={ active.to_string() }
+                    onclick={ onclick }
+                >
+                    { evaluation.name() }
+                </button>
+            }
+        })
+    });
+    html! {
+        <div class="comment-pane">
+            <h2>{ title }</h2>
+            <div class="cp-evaluations">
+                { for evaluations.into_iter().flatten() }
+            </div>
+            <textarea
+                class="cp-comment"
+                placeholder="Comment"
+                value={ props.annotation.comment.clone() }
+                onchange={ comment_cb }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,74 @@
+use go_rs::game::{GoGame, GoPosition};
+
+/// A game from the library opened for comparison, along with how far into it
+/// the board is.
+pub struct ComparedGame {
+    pub name: String,
+    game: GoGame,
+    /// The number of moves played on the shown position
+    moves: usize,
+    position: GoPosition,
+}
+
+impl ComparedGame {
+    pub fn new(name: String, game: GoGame) -> Self {
+        let position = game.initial_position().clone();
+        ComparedGame {
+            name,
+            game,
+            moves: 0,
+            position,
+        }
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    pub fn total_moves(&self) -> usize {
+        self.game.move_count()
+    }
+
+    pub fn position(&self) -> &GoPosition {
+        &self.position
+    }
+
+    /// Moves `delta` moves forward, or backward when negative, stopping at
+    /// either end of the game.
+    pub fn step(&mut self, delta: isize) {
+        let moves = self
+            .moves
+            .saturating_add_signed(delta)
+            .min(self.total_moves());
+        if moves == self.moves {
+            return;
+        }
+        self.moves = moves;
+        self.position = self.game.position_at(moves);
+    }
+}
+
+/// Two games shown side by side.
+pub struct Comparison {
+    pub games: [ComparedGame; 2],
+    /// When set, stepping through one game steps through the other as well
+    pub linked: bool,
+}
+
+impl Comparison {
+    pub fn new(left: ComparedGame, right: ComparedGame) -> Self {
+        Comparison {
+            games: [left, right],
+            linked: true,
+        }
+    }
+
+    /// Steps through the game at `side`, or both games while linked.
+    pub fn step(&mut self, side: usize, delta: isize) {
+        if self.linked {
+            self.games.iter_mut().for_each(|game| game.step(delta));
+        } else {
+            self.games[side].step(delta);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Moves entered in advance for a correspondence game.
+//!
+//! A sequence is written as point names taking turns, starting with a move
+//! the opponent might make: `Q16 R14 P17 Q17` answers at R14 if the
+//! opponent plays Q16, then at Q17 if they go on with P17. Several sequences
+//! can be entered, one per line. The first one that starts with the
+//! opponent's move is followed, and the ones that don't agree with it are
+//! dropped.
+
+use crate::game::GoPosition;
+
+/// A move the opponent might make and the answer to it.
+type Exchange = ((usize, usize), (usize, usize));
+
+/// The sequences of moves still waiting for the opponent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConditionalMoves {
+    sequences: Vec<Vec<Exchange>>,
+}
+
+impl ConditionalMoves {
+    /// Reads sequences of point names on `position`, one sequence per line.
+    /// Empty lines are skipped.
+    pub fn parse(text: &str, position: &GoPosition) -> Result<Self, String> {
+        let mut sequences = Vec::new();
+        for line in text.lines() {
+            let points = line
+                .split_whitespace()
+                .map(|name| {
+                    position
+                        .parse_point_name(name)
+                        .ok_or_else(|| format!("\"{}\" is not a point on the board", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if points.is_empty() {
+                continue;
+            }
+            if points.len() % 2 != 0 {
+                return Err(format!(
+                    "\"{}\" has no answer to its last move",
+                    line.trim()
+                ));
+            }
+            sequences.push(points.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+        }
+        Ok(ConditionalMoves { sequences })
+    }
+
+    /// Writes the sequences the way `parse` reads them.
+    pub fn to_text(&self, position: &GoPosition) -> String {
+        self.sequences
+            .iter()
+            .map(|sequence| {
+                sequence
+                    .iter()
+                    .flat_map(|(theirs, ours)| [theirs, ours])
+                    .map(|(x, y)| position.point_name(*x, *y))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    /// Finds the answer to the opponent playing at `point`, or `None` for a
+    /// pass. Sequences that start differently are dropped, and so are all of
+    /// them when none has an answer.
+    pub fn answer(&mut self, point: Option<(usize, usize)>) -> Option<(usize, usize)> {
+        let reply = self
+            .sequences
+            .iter()
+            .find(|sequence| Some(sequence[0].0) == point)
+            .map(|sequence| sequence[0].1);
+        self.sequences.retain_mut(|sequence| {
+            if Some(sequence[0]) != point.zip(reply) {
+                return false;
+            }
+            sequence.remove(0);
+            !sequence.is_empty()
+        });
+        reply
+    }
+}
@@ -0,0 +1,275 @@
+use gloo_timers::callback::Timeout;
+use go_rs::game::Player;
+use yew::prelude::*;
+
+use crate::board::Orientation;
+use crate::player_name;
+use crate::settings::{HintLimit, Settings};
+use crate::stone_layer::{MoveNumbers, StoneDisplay};
+
+/// How long a pass or resignation waits for the second tap with hot-seat on
+const CONFIRM_MS: u32 = 3000;
+
+/// The buttons of the controls bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Pass,
+    Resign,
+    ToggleLiberties,
+    /// Show or hide the common continuations of the corners
+    ToggleJoseki,
+    /// Show or hide how often each point was played in the opening
+    ToggleFuseki,
+    /// Show or hide who is likely to end up with each point
+    ToggleOwnership,
+    /// Show or hide the areas each player has a hold on
+    ToggleInfluence,
+    /// Show or hide which empty points are real or false eyes
+    ToggleEyes,
+    /// Turn the board a quarter clockwise on screen
+    RotateBoard,
+    /// Mirror the board on screen, or undo it
+    MirrorBoard,
+    CycleMoveNumbers,
+    /// Go on to the next way of drawing the stones, see `StoneDisplay`
+    CycleStoneDisplay,
+    /// Show the real stones while they are hidden or all one color, or hide
+    /// them again
+    ToggleReveal,
+    ToggleMute,
+    /// Point out a reasonable move for the player to move
+    Hint,
+    /// Leave an earlier position for the current one
+    ReturnToGame,
+    /// Play the previewed move
+    ConfirmMove,
+    /// Drop the previewed move
+    CancelMove,
+    /// Try out moves on a copy of the game
+    StartAnalysis,
+    /// Go back to the game, forgetting the tried out moves
+    DiscardAnalysis,
+    /// Go back to the game, saving the tried out moves to the library
+    SaveAnalysis,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ControlsBarProps {
+    pub next_player: Player,
+    /// Whether the game has ended, after which nobody is to move
+    pub over: bool,
+    /// Whether moves are being tried out on a copy of the game
+    #[prop_or_default]
+    pub analyzing: bool,
+    /// Whether a previewed move waits for the confirm button
+    #[prop_or_default]
+    pub move_pending: bool,
+    pub show_liberties: bool,
+    pub show_joseki: bool,
+    pub show_fuseki: bool,
+    pub show_ownership: bool,
+    pub show_influence: bool,
+    pub show_eyes: bool,
+    /// How the board is turned on screen
+    #[prop_or_default]
+    pub orientation: Orientation,
+    pub move_numbers: MoveNumbers,
+    #[prop_or_default]
+    pub stone_display: StoneDisplay,
+    /// Whether the real stones are shown in spite of `stone_display`
+    #[prop_or_default]
+    pub stones_revealed: bool,
+    /// How many hints were given in this game, see `HintLimit`
+    #[prop_or_default]
+    pub hints_used: usize,
+    /// Whether an earlier position is shown instead of the current one
+    pub viewing: bool,
+    /// Whether the game is someone else's broadcast, which has nothing to
+    /// control
+    pub watching: bool,
+    pub onaction: Callback<Control>,
+    /// Shown below the buttons, the clock
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// The buttons next to the board.
+#[function_component]
+pub fn ControlsBar(props: &ControlsBarProps) -> Html {
+    let settings = use_context::<Settings>().unwrap_or_default();
+    // The pass or resignation waiting for its second tap
+    let armed = use_state_eq(|| None::<Control>);
+    {
+        let armed = armed.clone();
+        use_effect_with(props.next_player, move |_| armed.set(None));
+    }
+    {
+        let armed = armed.clone();
+        use_effect_with(*armed, move |shown| {
+            let timeout = shown.map(|_| Timeout::new(CONFIRM_MS, move || armed.set(None)));
+            move || drop(timeout)
+        });
+    }
+    let turn = turn_indicator(props.next_player, props.over);
+    if props.watching {
+        return html! {
+            <div class="control-panel">
+                { turn }
+                { "Watching a broadcast" }
+            </div>
+        };
+    }
+
+    let button = |control: Control, text: &str| {
+        let onclick = props.onaction.reform(move |_: MouseEvent| control);
+        html! { <button onclick={ onclick }>{ text }</button> }
+    };
+    // With hot-seat on a stray touch doesn't end the game: the first tap
+    // asks for a second one
+    let confirmed = |control: Control, text: &str, confirm_text: &str| {
+        if !settings.hot_seat {
+            return button(control, text);
+        }
+        let is_armed = *armed == Some(control);
+        let onclick = {
+            let armed = armed.clone();
+            let onaction = props.onaction.clone();
+            Callback::from(move |_: MouseEvent| {
+                if is_armed {
+                    armed.set(None);
+                    onaction.emit(control);
+                } else {
+                    armed.set(Some(control));
+                }
+            })
+        };
+        html! {
+            <button class={ classes!(is_armed.then_some("button-armed")) } onclick={ onclick }>
+                { if is_armed { confirm_text } else { text } }
+            </button>
+        }
+    };
+    let liberties_text = if props.show_liberties {
+        "Hide liberties"
+    } else {
+        "Show liberties"
+    };
+    let joseki_text = if props.show_joseki {
+        "Hide joseki"
+    } else {
+        "Show joseki"
+    };
+    let fuseki_text = if props.show_fuseki {
+        "Hide opening stats"
+    } else {
+        "Show opening stats"
+    };
+    let ownership_text = if props.show_ownership {
+        "Hide ownership"
+    } else {
+        "Show ownership"
+    };
+    let influence_text = if props.show_influence {
+        "Hide influence"
+    } else {
+        "Show influence"
+    };
+    let eyes_text = if props.show_eyes {
+        "Hide eyes"
+    } else {
+        "Show eyes"
+    };
+    let rotate_text = format!("Rotate board ({}°)", props.orientation.degrees());
+    let mirror_text = if props.orientation.mirrored {
+        "Mirror board: on"
+    } else {
+        "Mirror board: off"
+    };
+    let numbers_text = match props.move_numbers {
+        MoveNumbers::Off => "Move numbers: off",
+        MoveNumbers::Last => "Move numbers: last 10",
+        MoveNumbers::All => "Move numbers: all",
+    };
+    let display_text = match props.stone_display {
+        StoneDisplay::Normal => "Stones: shown",
+        StoneDisplay::Blind => "Stones: hidden",
+        StoneDisplay::OneColor => "Stones: one color",
+    };
+    let reveal_text = if props.stones_revealed {
+        "Hide stones again"
+    } else {
+        "Reveal stones"
+    };
+    let mute_text = if settings.muted {
+        "Sound: off"
+    } else {
+        "Sound: on"
+    };
+    let hints_left = settings.hints.left(props.hints_used);
+    let hint = settings.hints != HintLimit::Off && !props.over && !props.viewing;
+    let hint_text = match hints_left {
+        Some(left) => format!("Hint ({} left)", left),
+        None => String::from("Hint"),
+    };
+    html! {
+        <div class="control-panel">
+            { turn }
+            if props.move_pending {
+                { button(Control::ConfirmMove, "Play move") }
+                { button(Control::CancelMove, "Cancel move") }
+            }
+            { confirmed(Control::Pass, "Pass", "Tap again to pass") }
+            { confirmed(Control::Resign, "Resign", "Tap again to resign") }
+            if hint {
+                <button
+                    onclick={ props.onaction.reform(|_: MouseEvent| Control::Hint) }
+                    disabled={ hints_left == Some(0) }
+                >
+                    { hint_text }
+                </button>
+            }
+            { button(Control::ToggleLiberties, liberties_text) }
+            { button(Control::ToggleJoseki, joseki_text) }
+            { button(Control::ToggleFuseki, fuseki_text) }
+            { button(Control::ToggleOwnership, ownership_text) }
+            { button(Control::ToggleInfluence, influence_text) }
+            { button(Control::ToggleEyes, eyes_text) }
+            { button(Control::RotateBoard, &rotate_text) }
+            { button(Control::MirrorBoard, mirror_text) }
+            { button(Control::CycleMoveNumbers, numbers_text) }
+            { button(Control::CycleStoneDisplay, display_text) }
+            if props.stone_display != StoneDisplay::Normal {
+                { button(Control::ToggleReveal, reveal_text) }
+            }
+            { button(Control::ToggleMute, mute_text) }
+            if props.viewing {
+                { button(Control::ReturnToGame, "Back to game") }
+            }
+            if props.analyzing {
+                <div class="analysis-note">{ "Analysis: these moves don't count" }</div>
+                { button(Control::DiscardAnalysis, "Discard and go back") }
+                { button(Control::SaveAnalysis, "Save and go back") }
+            } else {
+                { button(Control::StartAnalysis, "Analyze") }
+            }
+            { props.children.clone() }
+        </div>
+    }
+}
+
+/// Shows whose turn it is with a stone of their color.
+fn turn_indicator(next_player: Player, over: bool) -> Html {
+    let color = match next_player {
+        _ if over => return html! { <div class="turn-indicator">{ "Game over" }</div> },
+        Player::Black => "turn-black",
+        Player::White => "turn-white",
+        Player::Red => "turn-red",
+        Player::None => return html! {},
+    };
+    html! {
+        <div class={ classes!("turn-indicator", color) }>
+            <span class="turn-stone" />
+            { format!("{} to play", player_name(next_player)) }
+        </div>
+    }
+}
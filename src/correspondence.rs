@@ -0,0 +1,541 @@
+use gloo_storage::{LocalStorage, Storage};
+use gloo_timers::callback::Interval;
+use gloo_utils::{document, window};
+use go_rs::broadcast::Frame;
+use go_rs::chat::{ChatChannel, ChatMessage};
+use go_rs::conditional::ConditionalMoves;
+use go_rs::game::{GoGame, Player};
+use go_rs::protocol::Resync;
+use go_rs::room::{Presence, Viewers, PRESENCE_INTERVAL_MS};
+use go_rs::sgf::{read_sgf, write_sgf};
+use serde::{Deserialize, Serialize};
+use web_sys::{Notification, NotificationOptions, NotificationPermission};
+use yew::Callback;
+
+use crate::relay::{Incoming, RelayConnection};
+
+/// The local storage key the correspondence games are kept under
+const STORAGE_KEY: &str = "go-rs-correspondence";
+
+/// The local storage key of the id of the game last shown, which is opened
+/// again when the page is reloaded
+const SHOWN_KEY: &str = "go-rs-correspondence-shown";
+
+/// How often the latest move is sent again, for an opponent who wasn't
+/// connected when it was first sent. The relay doesn't keep anything, so
+/// this is how a returning player catches up.
+const RESEND_MS: u32 = 30 * 1000;
+
+/// A correspondence game as it is kept between visits, saved after every
+/// move.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub id: String,
+    pub relay_url: String,
+    /// Whether black is played on this device
+    pub black: bool,
+    /// The game so far, as an SGF record
+    pub sgf: String,
+    /// The moves entered in advance, see `ConditionalMoves::parse`
+    #[serde(default)]
+    pub conditional: String,
+    /// The chat between the players, each message as `ChatMessage::encode`
+    /// writes it
+    #[serde(default)]
+    pub chat: Vec<String>,
+    /// The token this device asks the opponent for the game with, see
+    /// `Correspondence::request_resync`
+    #[serde(default)]
+    pub session: String,
+}
+
+impl SavedGame {
+    /// Reads every stored correspondence game, none if they can't be read.
+    pub fn load_all() -> Vec<SavedGame> {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    /// Stores the game, replacing the earlier save of the same game.
+    pub fn save(&self) {
+        let mut games = SavedGame::load_all();
+        match games.iter_mut().find(|game| game.id == self.id) {
+            Some(game) => *game = self.clone(),
+            None => games.push(self.clone()),
+        }
+        // Not being able to store it only means it is lost on reload
+        let _ = LocalStorage::set(STORAGE_KEY, games);
+    }
+
+    pub fn remove(id: &str) {
+        let mut games = SavedGame::load_all();
+        games.retain(|game| game.id != id);
+        let _ = LocalStorage::set(STORAGE_KEY, games);
+    }
+
+    pub fn color(&self) -> Player {
+        if self.black {
+            Player::Black
+        } else {
+            Player::White
+        }
+    }
+
+    pub fn chat(&self) -> Vec<ChatMessage> {
+        self.chat
+            .iter()
+            .filter_map(|text| ChatMessage::decode(text))
+            .collect()
+    }
+
+    /// Whether the stored game waits for a move from this device.
+    pub fn our_turn(&self) -> bool {
+        read_sgf(&self.sgf).is_ok_and(|game| !game.is_over() && game.next_player == self.color())
+    }
+}
+
+/// The id of the correspondence game shown when the page was left, if any.
+pub fn shown_game() -> Option<String> {
+    LocalStorage::get(SHOWN_KEY).ok()
+}
+
+/// Remembers which correspondence game is shown, see `shown_game`. Storage
+/// is only written when it changed.
+pub fn remember_shown(id: Option<&str>) {
+    if shown_game().as_deref() == id {
+        return;
+    }
+    match id {
+        Some(id) => {
+            let _ = LocalStorage::set(SHOWN_KEY, id);
+        }
+        None => LocalStorage::delete(SHOWN_KEY),
+    }
+}
+
+/// A random token, unlikely to be picked twice.
+fn random_token() -> String {
+    format!("{:x}", (js_sys::Math::random() * 1e12) as u64)
+}
+
+/// The code the opponent enters to join game `id`, which tells them their
+/// color as well: `<id>-b` to play black, `<id>-w` for white.
+pub fn invite_code(id: &str, their_color: Player) -> String {
+    format!("{}-{}", id, channel_suffix(their_color))
+}
+
+/// Reads an invite code into the game id and the color it invites to play.
+pub fn parse_invite_code(code: &str) -> Option<(String, Player)> {
+    let (id, color) = code.trim().rsplit_once('-')?;
+    let color = match color {
+        "b" => Player::Black,
+        "w" => Player::White,
+        _ => return None,
+    };
+    (!id.is_empty()).then(|| (id.to_owned(), color))
+}
+
+/// The channel of the spectators of game `id`.
+pub fn spectator_channel(id: &str) -> String {
+    format!("{}-s", id)
+}
+
+fn channel_suffix(color: Player) -> &'static str {
+    match color {
+        Player::White => "w",
+        _ => "b",
+    }
+}
+
+/// What came of a frame from the opponent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Received {
+    /// Nothing new, or a game that doesn't follow from ours
+    Nothing,
+    /// The opponent moved and it is our turn
+    OurMove,
+    /// The opponent moved and was answered with a conditional move, at the
+    /// point named
+    Answered(String),
+    /// The opponent's move ended the game
+    Ended,
+}
+
+/// A correspondence game being played on this device. Each player publishes
+/// their moves on a relay channel of their own and watches the opponent's.
+pub struct Correspondence {
+    pub id: String,
+    /// The color played on this device
+    pub color: Player,
+    pub conditional: ConditionalMoves,
+    /// The chat between the players, oldest first
+    pub chat: Vec<ChatMessage>,
+    /// The spectators' chat, while the players follow it
+    pub spectator_chat: Option<SpectatorChat>,
+    /// The spectators watching the game
+    pub viewers: Viewers,
+    /// Stands for this device when asking for the game, kept between visits
+    pub session: String,
+    relay_url: String,
+    publish: RelayConnection,
+    _watch: RelayConnection,
+    /// Watches the spectators' channel, for their announcements and chat
+    _spectators: RelayConnection,
+    _resend: Interval,
+    on_error: Callback<String>,
+}
+
+/// The spectators' chat of a correspondence game, as followed by a player.
+pub struct SpectatorChat {
+    /// Oldest first
+    pub messages: Vec<ChatMessage>,
+    publish: RelayConnection,
+}
+
+impl Correspondence {
+    /// Connects to the channels of game `id`. Frames and chat from the
+    /// opponent and messages of the spectators are passed to `on_message`,
+    /// and `on_resend` is called every so often to send the latest move
+    /// again.
+    pub fn connect(
+        id: String,
+        color: Player,
+        relay_url: String,
+        on_message: Callback<Incoming>,
+        on_resend: Callback<()>,
+        on_error: Callback<String>,
+    ) -> Result<Self, String> {
+        let opponent = match color {
+            Player::Black => Player::White,
+            _ => Player::Black,
+        };
+        let channel = |color| format!("{}-{}", id, channel_suffix(color));
+        let publish = RelayConnection::publish(&relay_url, &channel(color), on_error.clone())?;
+        let watch = RelayConnection::watch(
+            &relay_url,
+            &channel(opponent),
+            on_message.clone(),
+            on_error.clone(),
+        )?;
+        let spectators = RelayConnection::watch(
+            &relay_url,
+            &spectator_channel(&id),
+            on_message,
+            on_error.clone(),
+        )?;
+        Ok(Correspondence {
+            id,
+            color,
+            conditional: ConditionalMoves::default(),
+            chat: Vec::new(),
+            spectator_chat: None,
+            viewers: Viewers::default(),
+            session: random_token(),
+            relay_url,
+            publish,
+            _watch: watch,
+            _spectators: spectators,
+            _resend: Interval::new(RESEND_MS, move || on_resend.emit(())),
+            on_error,
+        })
+    }
+
+    /// Starts or stops following the spectators' chat, which everyone
+    /// publishes to and watches.
+    pub fn follow_spectators(&mut self, follow: bool) -> Result<(), String> {
+        if !follow {
+            self.spectator_chat = None;
+            return Ok(());
+        }
+        if self.spectator_chat.is_none() {
+            self.spectator_chat = Some(SpectatorChat {
+                messages: Vec::new(),
+                publish: RelayConnection::publish(
+                    &self.relay_url,
+                    &spectator_channel(&self.id),
+                    self.on_error.clone(),
+                )?,
+            });
+        }
+        Ok(())
+    }
+
+    /// The link spectators open to watch the game.
+    pub fn spectate_link(&self) -> String {
+        let location = window().location();
+        format!(
+            "{}{}#spectate={}@{}",
+            location.origin().unwrap_or_default(),
+            location.pathname().unwrap_or_default(),
+            self.id,
+            self.relay_url,
+        )
+    }
+
+    /// Sends `text` on `channel`, signed with our color, and adds it to the
+    /// history.
+    pub fn send_chat(&mut self, channel: ChatChannel, text: String) {
+        let message = ChatMessage {
+            channel,
+            sender: self.sender_name(),
+            text,
+        };
+        match channel {
+            ChatChannel::Players => {
+                self.publish.send_chat(&message);
+                self.chat.push(message);
+            }
+            ChatChannel::Spectators => {
+                if let Some(spectators) = &mut self.spectator_chat {
+                    spectators.publish.send_chat(&message);
+                    spectators.messages.push(message);
+                }
+            }
+        }
+    }
+
+    /// The name our chat messages are signed with.
+    fn sender_name(&self) -> String {
+        String::from(match self.color {
+            Player::White => "White",
+            _ => "Black",
+        })
+    }
+
+    /// Adds a message from the relay to the history of its channel. Returns
+    /// whether it was kept.
+    pub fn receive_chat(&mut self, message: ChatMessage) -> bool {
+        // The relay may pass our own messages back
+        let ours = message.sender == self.sender_name();
+        match (message.channel, &mut self.spectator_chat) {
+            (ChatChannel::Players, _) => self.chat.push(message),
+            (ChatChannel::Spectators, Some(spectators)) if !ours => {
+                spectators.messages.push(message)
+            }
+            (ChatChannel::Spectators, _) => return false,
+        }
+        true
+    }
+
+    /// Whether `game` waits for a move from this device.
+    pub fn our_turn(&self, game: &GoGame) -> bool {
+        !game.is_over() && game.next_player == self.color
+    }
+
+    /// Sends `game` to the opponent.
+    pub fn send(&self, game: &GoGame) {
+        self.publish.send(&Frame {
+            sequence: game.move_count() as u64,
+            show_liberties: false,
+            sgf: write_sgf(game).expect("correspondence games have two players"),
+        });
+    }
+
+    /// Asks the opponent to send their game right away, after this device
+    /// was away or lost its connection. The relay keeps nothing, so the
+    /// opponent's game is the record of their moves; `receive` takes it in
+    /// and sends ours back if they are the one behind.
+    pub fn request_resync(&self, game: &GoGame) {
+        self.publish.send_resync(&Resync {
+            session: self.session.clone(),
+            move_number: game.move_count(),
+        });
+    }
+
+    /// Stores `game` and sends it to the opponent, after a move on this
+    /// device.
+    pub fn save_and_send(&self, game: &GoGame) {
+        self.saved(game).save();
+        self.send(game);
+    }
+
+    pub fn saved(&self, game: &GoGame) -> SavedGame {
+        SavedGame {
+            id: self.id.clone(),
+            relay_url: self.relay_url.clone(),
+            black: self.color == Player::Black,
+            sgf: write_sgf(game).expect("correspondence games have two players"),
+            conditional: self.conditional.to_text(game),
+            chat: self.chat.iter().map(ChatMessage::encode).collect(),
+            session: self.session.clone(),
+        }
+    }
+
+    /// Takes the opponent's moves from `frame` into `game` if it continues
+    /// the game with nothing but moves of theirs, answering them from the
+    /// conditional moves when one fits. Our moves are sent again when the
+    /// opponent is behind.
+    pub fn receive(&mut self, game: &mut GoGame, frame: &Frame) -> Received {
+        let count = game.move_count();
+        if frame.sequence < count as u64 {
+            self.send(game);
+            return Received::Nothing;
+        }
+        if frame.sequence == count as u64 {
+            return Received::Nothing;
+        }
+        let theirs = match read_sgf(&frame.sgf) {
+            Ok(theirs) => theirs,
+            Err(_) => return Received::Nothing,
+        };
+        let ours = game.move_names();
+        let moves = theirs.move_names();
+        if !moves.starts_with(&ours)
+            || moves[count..]
+                .iter()
+                .any(|(player, _)| *player == self.color)
+        {
+            return Received::Nothing;
+        }
+        *game = theirs;
+
+        if game.is_over() {
+            self.saved(game).save();
+            return Received::Ended;
+        }
+        if !self.our_turn(game) {
+            // Black placing free handicap stones one at a time
+            self.saved(game).save();
+            return Received::Nothing;
+        }
+        let last = moves
+            .last()
+            .and_then(|(_, name)| game.parse_point_name(name));
+        if let Some((x, y)) = self.conditional.answer(last) {
+            if game.play_move(x, y).is_ok() {
+                self.save_and_send(game);
+                return Received::Answered(game.point_name(x, y));
+            }
+            // A planned answer that turned out illegal ends the plan
+            self.conditional = ConditionalMoves::default();
+        }
+        self.saved(game).save();
+        Received::OurMove
+    }
+}
+
+/// Asks for permission to show notifications, for when it's our move in a
+/// correspondence game while the page is hidden.
+pub fn request_notifications() {
+    if notifications_supported() && Notification::permission() == NotificationPermission::Default {
+        let _ = Notification::request_permission();
+    }
+}
+
+/// Shows `text` as a notification if the page is hidden and notifications
+/// are allowed. Returns whether it was shown.
+pub fn notify(text: &str) -> bool {
+    if !document().hidden()
+        || !notifications_supported()
+        || Notification::permission() != NotificationPermission::Granted
+    {
+        return false;
+    }
+    let options = NotificationOptions::new();
+    options.set_body(text);
+    // One notification per game is plenty, later ones replace it
+    options.set_tag("go-rs-correspondence");
+    Notification::new_with_options("go-rs", &options).is_ok()
+}
+
+/// Some browsers have no notifications at all, and asking them about their
+/// permission throws.
+fn notifications_supported() -> bool {
+    js_sys::Reflect::has(&window(), &"Notification".into()).unwrap_or(false)
+}
+
+/// A correspondence game watched from this device, without taking part.
+pub struct Spectating {
+    pub id: String,
+    /// Stands for this spectator in announcements and chat
+    viewer: String,
+    /// The spectators watching the game, this one included
+    pub viewers: Viewers,
+    /// The spectators' chat, oldest first
+    pub chat: Vec<ChatMessage>,
+    _black: RelayConnection,
+    _white: RelayConnection,
+    spectators: RelayConnection,
+    _spectators_watch: RelayConnection,
+    _announce: Interval,
+}
+
+impl Spectating {
+    /// Connects to the channels of game `id`. Frames of both players and
+    /// messages of the other spectators are passed to `on_message`, and
+    /// `on_announce` is called whenever it's time to announce this
+    /// spectator again.
+    pub fn join(
+        id: String,
+        relay_url: &str,
+        on_message: Callback<Incoming>,
+        on_announce: Callback<()>,
+        on_error: Callback<String>,
+    ) -> Result<Self, String> {
+        let watch = |channel: &str| {
+            RelayConnection::watch(relay_url, channel, on_message.clone(), on_error.clone())
+        };
+        let channel = spectator_channel(&id);
+        let mut spectating = Spectating {
+            viewer: random_token(),
+            viewers: Viewers::default(),
+            chat: Vec::new(),
+            _black: watch(&format!("{}-{}", id, channel_suffix(Player::Black)))?,
+            _white: watch(&format!("{}-{}", id, channel_suffix(Player::White)))?,
+            spectators: RelayConnection::publish(relay_url, &channel, on_error.clone())?,
+            _spectators_watch: watch(&channel)?,
+            _announce: Interval::new(PRESENCE_INTERVAL_MS, move || on_announce.emit(())),
+            id,
+        };
+        spectating.announce(js_sys::Date::now());
+        Ok(spectating)
+    }
+
+    /// Lets the others know this spectator is still there.
+    pub fn announce(&mut self, now: f64) {
+        self.spectators.send_presence(&Presence {
+            viewer: self.viewer.clone(),
+        });
+        self.viewers.seen(&self.viewer, now);
+    }
+
+    /// Shows the game of a frame from either player unless it is behind
+    /// `game`, so the first frame heard replaces the empty board spectating
+    /// starts with. Returns whether it was shown.
+    pub fn receive(&self, game: &mut GoGame, frame: &Frame) -> bool {
+        if frame.sequence < game.move_count() as u64 {
+            return false;
+        }
+        match read_sgf(&frame.sgf) {
+            Ok(theirs) => {
+                *game = theirs;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// The name this spectator's chat messages are signed with.
+    fn sender_name(&self) -> String {
+        format!("Spectator {}", &self.viewer[..self.viewer.len().min(4)])
+    }
+
+    pub fn send_chat(&mut self, text: String) {
+        let message = ChatMessage {
+            channel: ChatChannel::Spectators,
+            sender: self.sender_name(),
+            text,
+        };
+        self.spectators.send_chat(&message);
+        self.chat.push(message);
+    }
+
+    /// Adds a message of another spectator to the chat. The players' own
+    /// chat is theirs alone. Returns whether it was kept.
+    pub fn receive_chat(&mut self, message: ChatMessage) -> bool {
+        if message.channel != ChatChannel::Spectators || message.sender == self.sender_name() {
+            return false;
+        }
+        self.chat.push(message);
+        true
+    }
+}
@@ -0,0 +1,175 @@
+use go_rs::game::Player;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+use crate::correspondence::{invite_code, parse_invite_code, SavedGame};
+use crate::player_name;
+
+/// What is asked of the app from the correspondence panel.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CorrespondenceControl {
+    /// Start a game on the relay, playing the given color
+    Start(Player),
+    /// Join the game with the given id from an invite code, playing the
+    /// given color
+    Join(String, Player),
+    /// Open the stored game with the given id
+    Open(String),
+    /// Delete the stored game with the given id
+    Remove(String),
+    /// Stop playing the game on this board, it stays stored
+    Leave,
+    /// Replace the moves entered in advance with the given text, see
+    /// `ConditionalMoves::parse`
+    SetConditionalMoves(String),
+    /// What was entered can't be used, with the reason
+    Failed(String),
+}
+
+/// The correspondence game on the board as the panel shows it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurrentGame {
+    pub id: String,
+    /// The color played on this device
+    pub color: Player,
+    /// Whose move it is, or how the game stands
+    pub status: &'static str,
+    /// How many spectators are watching
+    pub viewers: usize,
+    pub spectate_link: String,
+    /// The moves entered in advance, written out
+    pub conditional: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CorrespondencePanelProps {
+    /// The game on the board, if it is a correspondence game
+    pub current: Option<CurrentGame>,
+    /// The stored games, see `SavedGame::load_all`
+    pub games: Vec<SavedGame>,
+    pub onaction: Callback<CorrespondenceControl>,
+}
+
+/// The correspondence game on the board, or the controls to start or join
+/// one, along with the stored games.
+#[function_component]
+pub fn CorrespondencePanel(props: &CorrespondencePanelProps) -> Html {
+    let code = use_state(String::new);
+
+    let current = match &props.current {
+        Some(current) => {
+            let opponent = match current.color {
+                Player::Black => Player::White,
+                _ => Player::Black,
+            };
+            let conditional_cb = props.onaction.reform(|e: Event| {
+                CorrespondenceControl::SetConditionalMoves(
+                    e.target_unchecked_into::<HtmlTextAreaElement>().value(),
+                )
+            });
+            let leave_cb = props
+                .onaction
+                .reform(|_: MouseEvent| CorrespondenceControl::Leave);
+            html! {
+                <>
+                    <span>{ format!("You play {}. {}.", player_name(current.color), current.status) }</span>
+                    <span>{ format!("Invite code for {}:", player_name(opponent)) }</span>
+                    <input
+                        type="text"
+                        readonly=true
+                        value={ invite_code(&current.id, opponent) }
+                    />
+                    <span>
+                        { format!("Link for spectators, {} watching:", current.viewers) }
+                    </span>
+                    <input
+                        type="text"
+                        readonly=true
+                        value={ current.spectate_link.clone() }
+                    />
+                    <label for="conditional-moves">
+                        { "Moves in advance, one sequence per line, starting with your opponent's:" }
+                    </label>
+                    <textarea
+                        id="conditional-moves"
+                        placeholder="Q16 R14 P17 Q17"
+                        value={ current.conditional.clone() }
+                        onchange={ conditional_cb }
+                    />
+                    <button onclick={ leave_cb }>{ "Leave game" }</button>
+                </>
+            }
+        }
+        None => {
+            let black_cb = props
+                .onaction
+                .reform(|_: MouseEvent| CorrespondenceControl::Start(Player::Black));
+            let white_cb = props
+                .onaction
+                .reform(|_: MouseEvent| CorrespondenceControl::Start(Player::White));
+            let code_cb = {
+                let code = code.clone();
+                Callback::from(move |e: InputEvent| {
+                    code.set(e.target_unchecked_into::<HtmlInputElement>().value())
+                })
+            };
+            let join_cb = {
+                let code = code.clone();
+                let onaction = props.onaction.clone();
+                Callback::from(move |_: MouseEvent| match parse_invite_code(&code) {
+                    Some((id, color)) => {
+                        code.set(String::new());
+                        onaction.emit(CorrespondenceControl::Join(id, color));
+                    }
+                    None => onaction.emit(CorrespondenceControl::Failed(String::from(
+                        "That is not an invite code",
+                    ))),
+                })
+            };
+            html! {
+                <>
+                    <span>{ "Uses the relay address from Broadcast." }</span>
+                    <button onclick={ black_cb }>{ "Start as Black" }</button>
+                    <button onclick={ white_cb }>{ "Start as White" }</button>
+                    <input
+                        type="text"
+                        placeholder="Invite code"
+                        value={ (*code).clone() }
+                        oninput={ code_cb }
+                    />
+                    <button onclick={ join_cb }>{ "Join" }</button>
+                </>
+            }
+        }
+    };
+    let games = props.games.iter().map(|game| {
+        let open_id = game.id.clone();
+        let open_cb = props
+            .onaction
+            .reform(move |_: MouseEvent| CorrespondenceControl::Open(open_id.clone()));
+        let remove_id = game.id.clone();
+        let remove_cb = props
+            .onaction
+            .reform(move |_: MouseEvent| CorrespondenceControl::Remove(remove_id.clone()));
+        html! {
+            <li>
+                <span>
+                    { format!(
+                        "{} as {}{}",
+                        game.id,
+                        player_name(game.color()),
+                        if game.our_turn() { ", your move" } else { "" },
+                    ) }
+                </span>
+                <button onclick={ open_cb }>{ "Open" }</button>
+                <button onclick={ remove_cb }>{ "Delete" }</button>
+            </li>
+        }
+    });
+    html! {
+        <div class="correspondence">
+            { current }
+            <ul class="correspondence-games">{ for games }</ul>
+        </div>
+    }
+}
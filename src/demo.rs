@@ -0,0 +1,213 @@
+use go_rs::game::{GoGame, GoPosition, Player};
+use go_rs::markup::{Mark, Markup};
+use yew::prelude::*;
+
+/// What a click on the demo board does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DemoTool {
+    Black,
+    White,
+    /// Take stones and marks off the board
+    Erase,
+    Triangle,
+    Square,
+    Circle,
+    Cross,
+    /// Label points with the next unused letter
+    Letters,
+    /// Label points with the next unused number
+    Numbers,
+}
+
+impl DemoTool {
+    /// The tools that put down or take off stones
+    pub const STONES: [DemoTool; 3] = [DemoTool::Black, DemoTool::White, DemoTool::Erase];
+    /// The tools that draw marks
+    pub const MARKS: [DemoTool; 6] = [
+        DemoTool::Triangle,
+        DemoTool::Square,
+        DemoTool::Circle,
+        DemoTool::Cross,
+        DemoTool::Letters,
+        DemoTool::Numbers,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DemoTool::Black => "Black stones",
+            DemoTool::White => "White stones",
+            DemoTool::Erase => "Erase",
+            DemoTool::Triangle => "Triangle",
+            DemoTool::Square => "Square",
+            DemoTool::Circle => "Circle",
+            DemoTool::Cross => "Cross",
+            DemoTool::Letters => "Letters",
+            DemoTool::Numbers => "Numbers",
+        }
+    }
+
+    /// The stone the tool puts down, if any.
+    pub fn stone(self) -> Option<Player> {
+        match self {
+            DemoTool::Black => Some(Player::Black),
+            DemoTool::White => Some(Player::White),
+            _ => None,
+        }
+    }
+
+    /// The mark the tool draws next on a position with `markup`, if any.
+    fn mark(self, markup: &Markup) -> Option<Mark> {
+        match self {
+            DemoTool::Triangle => Some(Mark::Triangle),
+            DemoTool::Square => Some(Mark::Square),
+            DemoTool::Circle => Some(Mark::Circle),
+            DemoTool::Cross => Some(Mark::Cross),
+            DemoTool::Letters => Some(Mark::Label(markup.next_letter())),
+            DemoTool::Numbers => Some(Mark::Label(markup.next_number())),
+            _ => None,
+        }
+    }
+}
+
+/// A board where stones are put down and taken off freely, without turns or
+/// captures, and marks are drawn, see `App::demo`.
+pub struct Demo {
+    /// The real game, put aside while demonstrating
+    pub game: GoGame,
+    pub tool: DemoTool,
+    /// The boards before each change, for undo
+    history: Vec<GoGame>,
+}
+
+impl Demo {
+    pub fn new(game: GoGame) -> Self {
+        Demo {
+            game,
+            tool: DemoTool::Black,
+            history: Vec::new(),
+        }
+    }
+
+    /// Uses the tool at a point of `board`. Putting a stone or mark on one of
+    /// its own kind takes it off again. Returns whether the board changed.
+    pub fn apply(&mut self, board: &mut GoGame, x: usize, y: usize) -> bool {
+        let index = board.coord_to_index(x, y);
+        let mut stones = board.position().clone();
+        let mut markup = board.markup_at(0);
+        let mut next_player = board.next_player;
+        if let Some(mark) = self.tool.mark(&markup) {
+            markup.toggle(index, mark);
+        } else {
+            stones[index] = match self.tool.stone() {
+                Some(color) if stones[index] != color => color,
+                _ => Player::None,
+            };
+            if self.tool == DemoTool::Erase {
+                markup.set(index, None);
+            }
+            // Playing on from the demo continues with the other color
+            next_player = match stones[index] {
+                Player::Black => Player::White,
+                Player::White | Player::Red => Player::Black,
+                Player::None => next_player,
+            };
+        }
+        self.replace(board, &stones, markup, next_player)
+    }
+
+    /// Takes every stone and mark off `board`. Returns whether the board
+    /// changed.
+    pub fn clear(&mut self, board: &mut GoGame) -> bool {
+        let stones = vec![Player::None; board.position().len()];
+        self.replace(board, &stones, Markup::new(), Player::Black)
+    }
+
+    /// Goes back to the board before the last change. Returns whether there
+    /// was one.
+    pub fn undo(&mut self, board: &mut GoGame) -> bool {
+        match self.history.pop() {
+            Some(previous) => {
+                *board = previous;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets up `board` with new stones and marks, keeping the old one for
+    /// undo. The demo board never has moves, so its marks are those of the
+    /// starting position.
+    fn replace(
+        &mut self,
+        board: &mut GoGame,
+        stones: &[Player],
+        markup: Markup,
+        next_player: Player,
+    ) -> bool {
+        if board.position().as_slice() == stones && board.markup_at(0) == markup {
+            return false;
+        }
+        let position = GoPosition::from_stones(board.width(), board.height(), stones);
+        let mut changed = GoGame::from_position(position, next_player);
+        changed.info = board.info.clone();
+        changed.set_markup_at(0, markup);
+        self.history.push(std::mem::replace(board, changed));
+        true
+    }
+}
+
+/// The buttons of the demo board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DemoControl {
+    SetTool(DemoTool),
+    Undo,
+    Clear,
+    /// Go back to the game that was put aside
+    Leave,
+    /// Start a new game from the demonstrated position
+    PlayFromHere,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct DemoBarProps {
+    pub tool: DemoTool,
+    pub onaction: Callback<DemoControl>,
+}
+
+/// The buttons next to the board while demonstrating, in place of the
+/// controls bar.
+#[function_component]
+pub fn DemoBar(props: &DemoBarProps) -> Html {
+    let button = |control: DemoControl, text: &str| {
+        let onclick = props.onaction.reform(move |_: MouseEvent| control);
+        html! { <button onclick={ onclick }>{ text }</button> }
+    };
+    let tool_button = |tool: DemoTool| {
+        let onclick = props
+            .onaction
+            .reform(move |_: MouseEvent| DemoControl::SetTool(tool));
+        let active = tool == props.tool;
+        html! {
+            <button
+                class={ classes!(active.then_some("demo-tool-active")) }
+                aria-pressed={ active.to_string() }
+                onclick={ onclick }
+            >
+                { tool.name() }
+            </button>
+        }
+    };
+    html! {
+        <div class="control-panel">
+            <div class="analysis-note">{ "Demo board: place stones freely" }</div>
+            { for DemoTool::STONES.map(tool_button) }
+            <div class="demo-marks">
+                { for DemoTool::MARKS.map(tool_button) }
+            </div>
+            { button(DemoControl::Undo, "Undo") }
+            { button(DemoControl::Clear, "Clear board") }
+            { button(DemoControl::PlayFromHere, "Play from here") }
+            { button(DemoControl::Leave, "Back to game") }
+        </div>
+    }
+}
@@ -0,0 +1,297 @@
+//! A small built-in engine that judges positions by playing them out.
+//!
+//! The search tries the moves worth trying in a position, each followed by a
+//! random playout like those of `estimate`, and spends more of its visits on
+//! the moves that have won more often so far (UCB1). It is far from strong,
+//! but it gives a rough winrate and score and points at the obvious moves,
+//! with nothing to install.
+
+use crate::estimate::{owner, playout, Rng};
+use crate::eye::is_real_eye;
+use crate::game::{GoGame, GoPosition, Player, Position};
+
+/// How many candidates an evaluation lists, best first
+pub const SHOWN_CANDIDATES: usize = 5;
+
+/// How strongly the search explores moves it has tried less, the usual
+/// square root of two of UCB1
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// A move the search looked at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub point: (usize, usize),
+    /// How many playouts started with the move
+    pub visits: usize,
+    /// The share of those playouts won by the player to move
+    pub winrate: f64,
+    /// Black's lead after the move, like `Evaluation::score`
+    pub score: f64,
+}
+
+/// What the search thinks of a position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation {
+    /// Black's chance of winning, from 0.0 to 1.0
+    pub winrate: f64,
+    /// Black's lead in points at the end of the playouts, komi included,
+    /// negative when white leads
+    pub score: f64,
+    /// The moves visited most, at most `SHOWN_CANDIDATES` of them, most
+    /// visited first
+    pub candidates: Vec<Candidate>,
+}
+
+#[derive(Clone, Debug)]
+struct Stats {
+    index: usize,
+    visits: usize,
+    wins: usize,
+    /// Black's lead summed over the playouts of the move
+    lead_sum: f64,
+}
+
+/// A search of one position, run a few visits at a time so it can be spread
+/// over several frames.
+pub struct Search {
+    position: GoPosition,
+    player: Player,
+    komi: f64,
+    candidates: Vec<Stats>,
+    visits: usize,
+    /// Black's lead summed over every playout
+    score_sum: f64,
+    rng: Rng,
+}
+
+impl Search {
+    /// Starts a search of `position` with `player` to move, scored with
+    /// `komi`.
+    pub fn new(position: &GoPosition, player: Player, komi: f64) -> Self {
+        let indexes: Vec<usize> = (0..position.len())
+            .filter(|index| {
+                let (x, y) = position.index_to_coord(*index);
+                position.is_valid_move(x, y, player) && !is_real_eye(position, *index, player)
+            })
+            .collect();
+        let worth_trying: Vec<usize> = indexes
+            .iter()
+            .copied()
+            .filter(|index| worth_trying(position, *index))
+            .collect();
+        let candidates = if worth_trying.is_empty() {
+            indexes
+        } else {
+            worth_trying
+        };
+        Search {
+            position: position.clone(),
+            player,
+            komi,
+            candidates: candidates
+                .into_iter()
+                .map(|index| Stats {
+                    index,
+                    visits: 0,
+                    wins: 0,
+                    lead_sum: 0.0,
+                })
+                .collect(),
+            visits: 0,
+            score_sum: 0.0,
+            rng: Rng::new(position.save().hash()),
+        }
+    }
+
+    /// The playouts run so far.
+    pub fn visits(&self) -> usize {
+        self.visits
+    }
+
+    /// Runs `visits` more playouts.
+    pub fn run(&mut self, visits: usize) {
+        let opponent = opponent(self.player);
+        for _ in 0..visits {
+            let (after, chosen) = match self.pick() {
+                Some(chosen) => {
+                    let mut after = self.position.clone();
+                    let (x, y) = after.index_to_coord(self.candidates[chosen].index);
+                    after.play(x, y, self.player);
+                    (after, Some(chosen))
+                }
+                // Nothing to play, so the position is only played out
+                None => (self.position.clone(), None),
+            };
+            let end = playout(&after, opponent, &mut self.rng);
+            let lead = black_lead(&end, self.komi);
+            self.score_sum += lead;
+            self.visits += 1;
+            if let Some(chosen) = chosen {
+                let won = match self.player {
+                    Player::White => lead < 0.0,
+                    _ => lead > 0.0,
+                };
+                let stats = &mut self.candidates[chosen];
+                stats.visits += 1;
+                stats.wins += won as usize;
+                stats.lead_sum += lead;
+            }
+        }
+    }
+
+    /// The candidate to visit next: each is tried once, then UCB1 decides.
+    fn pick(&self) -> Option<usize> {
+        if let Some(untried) = self.candidates.iter().position(|stats| stats.visits == 0) {
+            return Some(untried);
+        }
+        let log_visits = (self.visits.max(1) as f64).ln();
+        self.candidates
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                let ucb = |stats: &Stats| {
+                    stats.wins as f64 / stats.visits as f64
+                        + EXPLORATION * (log_visits / stats.visits as f64).sqrt()
+                };
+                ucb(a).total_cmp(&ucb(b))
+            })
+            .map(|(chosen, _)| chosen)
+    }
+
+    /// What the playouts so far make of the position.
+    pub fn evaluation(&self) -> Evaluation {
+        let visited: usize = self.candidates.iter().map(|stats| stats.visits).sum();
+        let wins: usize = self.candidates.iter().map(|stats| stats.wins).sum();
+        // The search spends its visits on the better moves, so the share
+        // of all wins leans towards the best of them
+        let mover_winrate = match visited {
+            0 => 0.5,
+            _ => wins as f64 / visited as f64,
+        };
+        let mut candidates: Vec<Candidate> = self
+            .candidates
+            .iter()
+            .filter(|stats| stats.visits > 0)
+            .map(|stats| Candidate {
+                point: self.position.index_to_coord(stats.index),
+                visits: stats.visits,
+                winrate: stats.wins as f64 / stats.visits as f64,
+                score: stats.lead_sum / stats.visits as f64,
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            b.visits
+                .cmp(&a.visits)
+                .then(b.winrate.total_cmp(&a.winrate))
+        });
+        candidates.truncate(SHOWN_CANDIDATES);
+        Evaluation {
+            winrate: match self.player {
+                Player::White => 1.0 - mover_winrate,
+                _ => mover_winrate,
+            },
+            score: match self.visits {
+                0 => 0.0,
+                visits => self.score_sum / visits as f64,
+            },
+            candidates,
+        }
+    }
+}
+
+/// An opponent to play against, answering the moves of a game.
+pub trait Engine {
+    /// The move for the player to move in `game`, or `None` to pass.
+    fn choose_move(&self, game: &GoGame) -> Option<(usize, usize)>;
+}
+
+/// Plays the move the search likes best after a set number of visits.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchEngine {
+    pub visits: usize,
+}
+
+impl Engine for SearchEngine {
+    fn choose_move(&self, game: &GoGame) -> Option<(usize, usize)> {
+        let mut search = Search::new(game, game.next_player, game.compensation());
+        search.run(self.visits);
+        search
+            .evaluation()
+            .candidates
+            .first()
+            .map(|candidate| candidate.point)
+    }
+}
+
+/// A move that cost its player much of their chances of winning.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mistake {
+    /// How many moves were played before it
+    pub moves: usize,
+    pub player: Player,
+    /// How much the player's winrate dropped, from 0.0 to 1.0
+    pub loss: f64,
+}
+
+/// The `count` moves whose players' winrates dropped the most, biggest
+/// drop first. `winrates` are black's after each number of moves, `None`
+/// where there is no evaluation, and `players` made each move. Moves that
+/// didn't lose anything are never mistakes.
+pub fn mistakes(winrates: &[Option<f64>], players: &[Player], count: usize) -> Vec<Mistake> {
+    let mut mistakes: Vec<Mistake> = players
+        .iter()
+        .enumerate()
+        .filter_map(|(moves, player)| {
+            let before = (*winrates.get(moves)?)?;
+            let after = (*winrates.get(moves + 1)?)?;
+            let loss = match player {
+                Player::Black => before - after,
+                Player::White => after - before,
+                Player::None | Player::Red => return None,
+            };
+            (loss > 0.0).then_some(Mistake {
+                moves,
+                player: *player,
+                loss,
+            })
+        })
+        .collect();
+    mistakes.sort_by(|a, b| b.loss.total_cmp(&a.loss));
+    mistakes.truncate(count);
+    mistakes
+}
+
+/// Whether the search should bother with the empty point at `index`: points
+/// near stones, or on the third and fourth lines while that part of the
+/// board is still empty.
+fn worth_trying(position: &GoPosition, index: usize) -> bool {
+    let (x, y) = position.index_to_coord(index);
+    let (width, height) = (position.width(), position.height());
+    let near_stone = (x.saturating_sub(2)..=(x + 2).min(width - 1)).any(|near_x| {
+        (y.saturating_sub(2)..=(y + 2).min(height - 1))
+            .any(|near_y| position[position.coord_to_index(near_x, near_y)] != Player::None)
+    });
+    let line = x.min(y).min(width - 1 - x).min(height - 1 - y);
+    // Small boards have no room for the usual openings
+    near_stone || (2..=3).contains(&line) || width.min(height) < 9
+}
+
+/// Black's points minus white's and `komi`, counting area on a board that
+/// was played out.
+fn black_lead(position: &GoPosition, komi: f64) -> f64 {
+    let lead: i64 = (0..position.len())
+        .map(|index| match owner(position, index) {
+            Player::Black => 1,
+            Player::White => -1,
+            Player::None | Player::Red => 0,
+        })
+        .sum();
+    lead as f64 - komi
+}
+
+fn opponent(player: Player) -> Player {
+    match player {
+        Player::Black => Player::White,
+        _ => Player::Black,
+    }
+}
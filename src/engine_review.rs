@@ -0,0 +1,294 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use go_rs::engine::{mistakes, Evaluation, Mistake, Search};
+use go_rs::game::{GoGame, Player};
+use go_rs::katago::{Analysis, Query};
+
+use crate::katago_client::KataGoClient;
+
+/// How many playouts the built-in engine spends on each position of the
+/// game
+pub const VISITS_PER_POSITION: usize = 200;
+
+/// How many visits KataGo spends on each position of the game
+pub const KATAGO_VISITS: usize = 500;
+
+/// How long one step of the built-in engine may take, in milliseconds, so
+/// the page stays responsive while it thinks
+pub const STEP_MS: f64 = 30.0;
+
+/// How many of the biggest mistakes a mistake review goes through
+pub const REVIEWED_MISTAKES: usize = 5;
+
+/// The number of the next KataGo query. Queries are numbered across every
+/// review, so the results for a tab that isn't shown reach its own review.
+static NEXT_QUERY: AtomicU32 = AtomicU32::new(0);
+
+fn next_query() -> u32 {
+    NEXT_QUERY.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Going through the biggest mistakes of a game, see `go_rs::engine::mistakes`.
+pub struct MistakeReview {
+    /// Each mistake with the move the engine liked best instead, kept here
+    /// as the evaluations go while the position is practiced
+    pub mistakes: Vec<(Mistake, Option<(usize, usize)>)>,
+    /// The index of the mistake shown
+    pub current: usize,
+}
+
+/// What an engine made of one position of the game.
+pub struct Reviewed {
+    pub evaluation: Evaluation,
+    /// The playouts or visits the evaluation is based on
+    visits: usize,
+    /// How likely each point is to end up black's, see `Analysis`, empty if
+    /// the engine doesn't tell
+    pub ownership: Vec<f64>,
+    /// How likely the engine's first impression would play each point, see
+    /// `Analysis`, empty if the engine doesn't tell
+    pub policy: Vec<f64>,
+}
+
+/// The engine that does the thinking.
+enum Backend {
+    /// `go_rs::engine`, run a step at a time, with the position being
+    /// searched by the number of moves leading to it
+    BuiltIn(Option<(usize, Search)>),
+    /// KataGo over a WebSocket proxy. Queries are numbered, and results of
+    /// queries sent before the moves last changed are dropped, as their
+    /// moves may not be the game's anymore.
+    KataGo {
+        client: KataGoClient,
+        query: u32,
+        /// Whether each position was asked about since
+        requested: Vec<bool>,
+    },
+}
+
+/// An engine going through a game. Every position is evaluated, the shown
+/// one first, and the evaluations are kept as long as the moves leading to
+/// them are.
+pub struct EngineReview {
+    backend: Backend,
+    /// The start of the game that was evaluated, with its compensation
+    start: Option<(Vec<Player>, f64)>,
+    /// The moves that were evaluated, see `GoGame::move_names`
+    moves: Vec<(Player, String)>,
+    /// What was made of the position after each number of moves
+    reviewed: Vec<Option<Reviewed>>,
+}
+
+impl EngineReview {
+    /// Reviews with the built-in engine.
+    pub fn built_in() -> Self {
+        EngineReview::new(Backend::BuiltIn(None))
+    }
+
+    /// Reviews with KataGo, through `client`.
+    pub fn katago(client: KataGoClient) -> Self {
+        EngineReview::new(Backend::KataGo {
+            client,
+            query: next_query(),
+            requested: vec![],
+        })
+    }
+
+    fn new(backend: Backend) -> Self {
+        EngineReview {
+            backend,
+            start: None,
+            moves: vec![],
+            reviewed: vec![],
+        }
+    }
+
+    /// Whether KataGo does the thinking.
+    pub fn is_katago(&self) -> bool {
+        matches!(self.backend, Backend::KataGo { .. })
+    }
+
+    /// What was made of the position after `moves` moves so far.
+    pub fn reviewed(&self, moves: usize) -> Option<&Reviewed> {
+        self.reviewed.get(moves).and_then(Option::as_ref)
+    }
+
+    /// The latest evaluation of the position after `moves` moves.
+    pub fn evaluation(&self, moves: usize) -> Option<&Evaluation> {
+        self.reviewed(moves).map(|reviewed| &reviewed.evaluation)
+    }
+
+    /// Black's winrate after each number of moves, for as far as the game
+    /// has been evaluated.
+    pub fn winrates(&self) -> Vec<Option<f64>> {
+        (0..self.reviewed.len())
+            .map(|moves| self.evaluation(moves).map(|evaluation| evaluation.winrate))
+            .collect()
+    }
+
+    /// Whether every position of the game has been evaluated, but for the
+    /// one after a resignation, which KataGo isn't asked about.
+    pub fn complete(&self) -> bool {
+        let resigned = matches!(self.moves.last(), Some((_, name)) if name == "resign");
+        let positions = self.reviewed.len() - resigned as usize;
+        match self.backend {
+            Backend::BuiltIn(_) => (0..positions).all(|moves| self.finished(moves)),
+            Backend::KataGo { .. } => self.reviewed[..positions].iter().all(Option::is_some),
+        }
+    }
+
+    /// Starts going through the biggest mistakes found so far.
+    pub fn mistake_review(&self) -> MistakeReview {
+        let players: Vec<Player> = self.moves.iter().map(|(player, _)| *player).collect();
+        MistakeReview {
+            mistakes: mistakes(&self.winrates(), &players, REVIEWED_MISTAKES)
+                .into_iter()
+                .map(|mistake| {
+                    let suggestion = self
+                        .evaluation(mistake.moves)
+                        .and_then(|evaluation| evaluation.candidates.first())
+                        .map(|candidate| candidate.point);
+                    (mistake, suggestion)
+                })
+                .collect(),
+            current: 0,
+        }
+    }
+
+    /// Drops the evaluations that no longer fit `game`, keeping those of
+    /// the moves it still starts with. KataGo is asked about the positions
+    /// it hasn't been asked about yet.
+    pub fn sync(&mut self, game: &GoGame) {
+        let start = (game.position_at(0).position().clone(), game.compensation());
+        let moves = game.move_names();
+        let kept = if self.start.as_ref() == Some(&start) {
+            self.moves
+                .iter()
+                .zip(&moves)
+                .take_while(|(a, b)| a == b)
+                .count()
+        } else {
+            self.start = Some(start);
+            0
+        };
+        let changed = self.reviewed.len() > kept + 1;
+        self.reviewed.truncate(kept + 1);
+        self.reviewed.resize_with(moves.len() + 1, || None);
+        self.moves = moves;
+        match &mut self.backend {
+            Backend::BuiltIn(search) => {
+                if matches!(search, Some((searched, _)) if *searched > kept) {
+                    *search = None;
+                }
+            }
+            Backend::KataGo {
+                client,
+                query,
+                requested,
+            } => {
+                if changed {
+                    *query = next_query();
+                    requested.clear();
+                }
+                requested.resize(self.reviewed.len(), false);
+                for (moves, reviewed) in self.reviewed.iter().enumerate() {
+                    requested[moves] |= reviewed.is_some();
+                }
+                let turns: Vec<usize> = (0..requested.len())
+                    .filter(|moves| !requested[*moves])
+                    .collect();
+                if !turns.is_empty() {
+                    let id = format!("go-rs-{}", query);
+                    client.send(
+                        &Query::new(id, game, &turns, KATAGO_VISITS),
+                        game.initial_position(),
+                    );
+                    for moves in turns {
+                        requested[moves] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether `analysis` answers the last query sent to KataGo.
+    pub fn asked_for(&self, analysis: &Analysis) -> bool {
+        match &self.backend {
+            Backend::KataGo { query, .. } => analysis.id == format!("go-rs-{}", query),
+            Backend::BuiltIn(_) => false,
+        }
+    }
+
+    /// Takes in KataGo's analysis of a position, unless the moves changed
+    /// since it was asked for.
+    pub fn receive(&mut self, analysis: Analysis) {
+        if !self.asked_for(&analysis) || analysis.turn >= self.reviewed.len() {
+            return;
+        }
+        self.reviewed[analysis.turn] = Some(Reviewed {
+            evaluation: analysis.evaluation,
+            visits: KATAGO_VISITS,
+            ownership: analysis.ownership,
+            policy: analysis.policy,
+        });
+    }
+
+    /// Whether the built-in engine still has positions to search. KataGo
+    /// thinks on its own and is never waited for.
+    pub fn busy(&self) -> bool {
+        matches!(self.backend, Backend::BuiltIn(_))
+            && (0..self.reviewed.len()).any(|moves| !self.finished(moves))
+    }
+
+    fn finished(&self, moves: usize) -> bool {
+        matches!(
+            self.reviewed.get(moves),
+            Some(Some(reviewed)) if reviewed.visits >= VISITS_PER_POSITION
+        )
+    }
+
+    /// Has the built-in engine search `game`, which `sync` was called
+    /// with, until the clock passes `deadline`: the position after `shown`
+    /// moves first, then the others from the start.
+    pub fn step(&mut self, game: &GoGame, shown: usize, deadline: f64) {
+        let moves = if self.finished(shown) {
+            match (0..self.reviewed.len()).find(|moves| !self.finished(*moves)) {
+                Some(moves) => moves,
+                None => return,
+            }
+        } else {
+            shown
+        };
+        let searching = match &mut self.backend {
+            Backend::BuiltIn(searching) => searching,
+            Backend::KataGo { .. } => return,
+        };
+        let mut search = match searching.take() {
+            Some((searched, search)) if searched == moves => search,
+            _ => {
+                let player = match (self.moves.get(moves), self.moves.last()) {
+                    (Some((player, _)), _) => *player,
+                    // Nobody is to move once the game is over
+                    (None, Some((Player::Black, _))) if game.next_player == Player::None => {
+                        Player::White
+                    }
+                    (None, _) if game.next_player == Player::None => Player::Black,
+                    (None, _) => game.next_player,
+                };
+                Search::new(&game.position_at(moves), player, game.compensation())
+            }
+        };
+        while search.visits() < VISITS_PER_POSITION && js_sys::Date::now() < deadline {
+            search.run(1);
+        }
+        self.reviewed[moves] = Some(Reviewed {
+            evaluation: search.evaluation(),
+            visits: search.visits(),
+            ownership: vec![],
+            policy: vec![],
+        });
+        if search.visits() < VISITS_PER_POSITION {
+            *searching = Some((moves, search));
+        }
+    }
+}
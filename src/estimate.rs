@@ -0,0 +1,174 @@
+use crate::eye::is_real_eye;
+use crate::game::{GoPosition, Player, Position};
+
+/// How many games `probably_dead` plays out, enough to tell dead groups
+/// from living ones on a finished board while staying quick on 19x19.
+pub const PLAYOUTS: usize = 32;
+
+/// Estimates who ends up with each point of `position` by playing it out
+/// `playouts` times with random moves that don't fill eyes, `player` moving
+/// first. Each point gets a value from 1.0, black's in every playout, down
+/// to -1.0, white's in every playout. The playouts are seeded from the
+/// position, so the same position always gives the same estimate.
+pub fn ownership(position: &GoPosition, player: Player, playouts: usize) -> Vec<f64> {
+    let mut rng = Rng::new(position.save().hash());
+    let mut ownership = vec![0.0; position.len()];
+    for _ in 0..playouts {
+        let end = playout(position, player, &mut rng);
+        for (index, owned) in ownership.iter_mut().enumerate() {
+            *owned += match owner(&end, index) {
+                Player::Black => 1.0,
+                Player::White => -1.0,
+                Player::None | Player::Red => 0.0,
+            };
+        }
+    }
+    for owned in ownership.iter_mut() {
+        *owned /= playouts.max(1) as f64;
+    }
+    ownership
+}
+
+/// Suggests the stones of `position` that are dead, with `player` to move:
+/// those of the groups whose points end up as the opponent's in most
+/// playouts, see `ownership`. Sorted by index.
+pub fn probably_dead(position: &GoPosition, player: Player) -> Vec<usize> {
+    let ownership = ownership(position, player, PLAYOUTS);
+    let mut dead: Vec<usize> = position
+        .groups()
+        .filter(|group| {
+            let sign = match group.player {
+                Player::White => -1.0,
+                _ => 1.0,
+            };
+            let kept: f64 = group.stones.iter().map(|stone| ownership[*stone]).sum();
+            sign * kept < 0.0
+        })
+        .flat_map(|group| group.stones)
+        .collect();
+    dead.sort_unstable();
+    dead
+}
+
+/// How far along a game is, see `phase`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Opening,
+    MiddleGame,
+    Endgame,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Opening => "Opening",
+            Phase::MiddleGame => "Middle game",
+            Phase::Endgame => "Endgame",
+        }
+    }
+
+    /// The playouts worth spending on a move in this phase, out of
+    /// `visits` for a move of the middle game. Opening moves have many fair
+    /// choices and the longest playouts, while endgame playouts are short
+    /// and the points they decide are close.
+    pub fn visits(self, visits: usize) -> usize {
+        match self {
+            Phase::Opening => visits / 2,
+            Phase::MiddleGame => visits,
+            Phase::Endgame => visits * 2,
+        }
+    }
+}
+
+/// Guesses the phase of a game at `position` after `moves` moves: the
+/// opening lasts while under a tenth of the points are taken within the
+/// first moves, and the endgame starts once nearly half of them are, or
+/// as many moves were played.
+pub fn phase(position: &GoPosition, moves: usize) -> Phase {
+    let points = position.len();
+    let stones = position
+        .iter()
+        .filter(|stone| **stone != Player::None)
+        .count();
+    if stones * 20 >= points * 9 || moves * 2 >= points {
+        Phase::Endgame
+    } else if stones * 10 < points && moves * 12 < points {
+        Phase::Opening
+    } else {
+        Phase::MiddleGame
+    }
+}
+
+/// Plays random moves on a copy of `position` until both players pass,
+/// which they do once every move left would fill one of their own real
+/// eyes or is illegal.
+pub(crate) fn playout(position: &GoPosition, mut player: Player, rng: &mut Rng) -> GoPosition {
+    let mut position = position.clone();
+    let mut empty: Vec<usize> = (0..position.len())
+        .filter(|index| position[*index] == Player::None)
+        .collect();
+    let mut passes = 0;
+    // Long enough for the board to fill up a few times over
+    for _ in 0..position.len() * 3 {
+        if passes == 2 {
+            break;
+        }
+        // Try the empty points in random order, moving the tried ones to the
+        // end of the list
+        let mut untried = empty.len();
+        let mut played = false;
+        while untried > 0 {
+            empty.swap(rng.below(untried), untried - 1);
+            untried -= 1;
+            let index = empty[untried];
+            let (x, y) = position.index_to_coord(index);
+            if is_real_eye(&position, index, player) || !position.is_valid_move(x, y, player) {
+                continue;
+            }
+            empty.swap_remove(untried);
+            empty.extend(position.play(x, y, player));
+            played = true;
+            break;
+        }
+        passes = if played { 0 } else { passes + 1 };
+        player = match player {
+            Player::Black => Player::White,
+            _ => Player::Black,
+        };
+    }
+    position
+}
+
+/// Whose point `index` is at the end of a playout: the color of the stone
+/// on it, or of every stone around it for an eye.
+pub(crate) fn owner(position: &GoPosition, index: usize) -> Player {
+    if position[index] != Player::None {
+        return position[index];
+    }
+    let mut sides = position.neighbors(index).map(|side| position[side]);
+    let first = sides.next().unwrap_or_default();
+    if sides.all(|side| side == first) {
+        first
+    } else {
+        Player::None
+    }
+}
+
+/// A xorshift generator, plenty random for playouts and repeatable from its
+/// seed.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on zero
+        Rng(seed | 1)
+    }
+
+    /// A number from 0 up to but not including `n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
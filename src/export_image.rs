@@ -0,0 +1,195 @@
+use std::fmt::Write;
+
+use gloo_utils::document;
+use go_rs::game::{column_name, star_points, GoPosition, Player};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    Blob, BlobPropertyBag, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, Url,
+};
+use yew::Callback;
+
+use crate::board::{view_size, BLACK, BOARD_COLOR, BOARD_MARGIN, RED, STONE_RADIUS, WHITE};
+use crate::library::{download, download_url};
+
+/// Size of the space between two lines in exported images, in pixels
+const PIXELS_PER_LINE: f64 = 40.0;
+
+/// What goes on an exported image besides the board and stones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImageOptions {
+    pub coordinates: bool,
+    pub move_numbers: bool,
+}
+
+/// Draws `position` as the text of a standalone SVG image, in the default
+/// colors as stylesheets don't come along. `move_numbers` is the number of
+/// the move that placed each stone, see `GoGame::move_numbers`, and only
+/// drawn if `options` asks for it.
+pub fn board_svg(
+    position: &GoPosition,
+    move_numbers: &[Option<usize>],
+    options: ImageOptions,
+) -> String {
+    let (width, height) = (position.width(), position.height());
+    let (view_width, view_height) = (view_size(width), view_size(height));
+    let (last_x, last_y) = ((width - 1) as f64, (height - 1) as f64);
+    let mut svg = String::new();
+    // Writing to a String can't fail
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="{}" height="{}">"#,
+        view_width,
+        view_height,
+        view_width * PIXELS_PER_LINE,
+        view_height * PIXELS_PER_LINE,
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/><g transform="translate({1} {1})">"#,
+        BOARD_COLOR, BOARD_MARGIN,
+    );
+
+    svg.push_str(r#"<g stroke="black" stroke-width="0.03" stroke-linecap="square">"#);
+    for y in 0..height {
+        let _ = write!(
+            svg,
+            r#"<line x1="0" y1="{0}" x2="{1}" y2="{0}"/>"#,
+            y, last_x
+        );
+    }
+    for x in 0..width {
+        let _ = write!(
+            svg,
+            r#"<line x1="{0}" y1="0" x2="{0}" y2="{1}"/>"#,
+            x, last_y
+        );
+    }
+    svg.push_str("</g>");
+    for (x, y) in star_points(width, height) {
+        let _ = write!(svg, r#"<circle cx="{}" cy="{}" r="0.1"/>"#, x, y);
+    }
+
+    if options.coordinates {
+        // Halfway between the outer lines and the edge of the board
+        let offset = BOARD_MARGIN / 2.0 + STONE_RADIUS / 2.0;
+        svg.push_str(
+            r#"<g font-family="sans-serif" font-size="0.4" text-anchor="middle" dominant-baseline="central">"#,
+        );
+        for x in 0..width {
+            for y in [-offset, last_y + offset] {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}">{}</text>"#,
+                    x,
+                    y,
+                    column_name(x)
+                );
+            }
+        }
+        for y in 0..height {
+            for x in [-offset, last_x + offset] {
+                let _ = write!(svg, r#"<text x="{}" y="{}">{}</text>"#, x, y, height - y);
+            }
+        }
+        svg.push_str("</g>");
+    }
+
+    for (i, player) in position.iter().enumerate() {
+        let (fill, label) = match player {
+            Player::None => continue,
+            Player::White => (WHITE, BLACK),
+            Player::Black => (BLACK, WHITE),
+            Player::Red => (RED, WHITE),
+        };
+        let (x, y) = position.index_to_coord(i);
+        let _ = write!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="black" stroke-width="0.02"/>"#,
+            x, y, STONE_RADIUS, fill,
+        );
+        match move_numbers.get(i) {
+            Some(Some(number)) if options.move_numbers => {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}" fill="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    x,
+                    y,
+                    label,
+                    if *number < 100 { "0.45" } else { "0.35" },
+                    number,
+                );
+            }
+            _ => {}
+        }
+    }
+    svg.push_str("</g></svg>");
+    svg
+}
+
+/// Starts a download of `svg` as an SVG file.
+pub fn export_svg(svg: &str) -> Result<(), String> {
+    download("board.svg", svg.as_bytes(), "image/svg+xml")
+}
+
+/// Draws `svg` onto an offscreen canvas and starts a download of it as a
+/// PNG file. The image loads in the background, so `onerror` is called with
+/// what went wrong instead of returning it.
+pub fn export_png(svg: &str, onerror: Callback<String>) {
+    if let Err(e) = start_png_export(svg, onerror.clone()) {
+        onerror.emit(e);
+    }
+}
+
+fn start_png_export(svg: &str, onerror: Callback<String>) -> Result<(), String> {
+    let image = HtmlImageElement::new().map_err(|_| String::from("Could not create the image"))?;
+    let url = svg_url(svg)?;
+    let onload = {
+        let image = image.clone();
+        let url = url.clone();
+        Closure::once_into_js(move || {
+            let _ = Url::revoke_object_url(&url);
+            if let Err(e) = rasterize(&image) {
+                onerror.emit(e);
+            }
+        })
+    };
+    image.set_onload(Some(onload.unchecked_ref()));
+    image.set_src(&url);
+    Ok(())
+}
+
+/// A link to `svg` that images can load.
+fn svg_url(svg: &str) -> Result<String, String> {
+    let array = js_sys::Array::of1(&js_sys::JsString::from(svg));
+    let options = BlobPropertyBag::new();
+    options.set_type("image/svg+xml");
+    let blob = Blob::new_with_str_sequence_and_options(&array, &options)
+        .map_err(|_| String::from("Could not create the image"))?;
+    Url::create_object_url_with_blob(&blob)
+        .map_err(|_| String::from("Could not create a link to the image"))
+}
+
+/// Copies the loaded `image` onto a canvas of its size and downloads it.
+fn rasterize(image: &HtmlImageElement) -> Result<(), String> {
+    let canvas = document()
+        .create_element("canvas")
+        .ok()
+        .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+        .ok_or_else(|| String::from("Could not create a canvas"))?;
+    canvas.set_width(image.natural_width());
+    canvas.set_height(image.natural_height());
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or_else(|| String::from("Could not draw on the canvas"))?;
+    context
+        .draw_image_with_html_image_element(image, 0.0, 0.0)
+        .map_err(|_| String::from("Could not draw the board"))?;
+    let url = canvas
+        .to_data_url_with_type("image/png")
+        .map_err(|_| String::from("Could not save the image"))?;
+    download_url("board.png", &url)
+}
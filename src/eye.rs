@@ -0,0 +1,75 @@
+//! Telling real eyes from false ones.
+//!
+//! An empty point whose neighbors are all stones of one player has the
+//! shape of an eye. It is a real eye when the opponent can't cut those
+//! stones apart at its diagonals: away from the edge they need two of the
+//! four diagonal points, on the edge or in a corner a single one is enough.
+//! Otherwise it is a false eye, which the player will have to fill to keep
+//! their stones connected. This is the usual rule of thumb, it doesn't see
+//! the rare shapes where a group lives with false eyes only.
+
+use crate::game::{GoPosition, Player};
+
+/// What an empty point surrounded by one player's stones is to them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eye {
+    /// The surrounding stones can't be cut apart at it, so it stays an eye
+    Real,
+    /// The opponent holds enough of the diagonal points to cut the
+    /// surrounding stones apart, so it will have to be filled
+    False,
+}
+
+/// What the point at `index` is to `player`, `None` when it isn't an empty
+/// point surrounded by their stones.
+pub fn classify(position: &GoPosition, index: usize, player: Player) -> Option<Eye> {
+    if player == Player::None {
+        return None;
+    }
+    if position[index] != Player::None
+        || !position
+            .neighbors(index)
+            .all(|side| position[side] == player)
+    {
+        return None;
+    }
+    let diagonals = diagonals(position, index);
+    // Held by an opponent, of either other color in a three-color game
+    let taken = diagonals
+        .iter()
+        .filter(|diagonal| ![Player::None, player].contains(&position[**diagonal]))
+        .count();
+    let allowed = if diagonals.len() == 4 { 1 } else { 0 };
+    Some(if taken > allowed {
+        Eye::False
+    } else {
+        Eye::Real
+    })
+}
+
+/// Returns `true` if the point at `index` is a real eye of `player`, which
+/// they should never fill.
+pub fn is_real_eye(position: &GoPosition, index: usize, player: Player) -> bool {
+    classify(position, index, player) == Some(Eye::Real)
+}
+
+/// Every eye of `player`, real or false, by index.
+pub fn eyes(position: &GoPosition, player: Player) -> Vec<(usize, Eye)> {
+    (0..position.len())
+        .filter_map(|index| classify(position, index, player).map(|eye| (index, eye)))
+        .collect()
+}
+
+/// The points diagonally next to `index` that are on the board.
+fn diagonals(position: &GoPosition, index: usize) -> Vec<usize> {
+    let (x, y) = position.index_to_coord(index);
+    let (width, height) = (position.width(), position.height());
+    [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+        .into_iter()
+        .filter_map(|(dx, dy)| {
+            let x = x.checked_add_signed(dx).filter(|x| *x < width)?;
+            let y = y.checked_add_signed(dy).filter(|y| *y < height)?;
+            Some(position.coord_to_index(x, y))
+        })
+        .collect()
+}
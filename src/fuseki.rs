@@ -0,0 +1,100 @@
+use crate::game::{GoGame, GoPosition, Player};
+use crate::pattern::{CanonicalKey, MoveTable, Stone, Symmetry};
+
+/// The openings that come with the app, see `FusekiBook::read` for the
+/// format.
+pub const BUNDLED: &str = include_str!("../data/fuseki.txt");
+/// The board size the openings are played on
+pub const FUSEKI_BOARD_SIZE: usize = 19;
+/// How many moves of each game count as its opening
+pub const FUSEKI_MOVES: usize = 12;
+
+/// A point played next in some of the games that reached a position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candidate {
+    pub point: (usize, usize),
+    pub player: Player,
+    /// How many games continued with it
+    pub count: u32,
+    /// The part of the games reaching the position that continued with it
+    pub share: f64,
+}
+
+/// How often each move was played in the openings of a set of games.
+#[derive(Debug, Default)]
+pub struct FusekiBook {
+    moves: MoveTable,
+}
+
+impl FusekiBook {
+    /// Reads games, one per line, as the SGF points of their moves on a
+    /// 19x19 board, with black playing first. Only the first `FUSEKI_MOVES`
+    /// moves of each are kept. Empty lines and lines starting with `#` are
+    /// left out.
+    pub fn read(text: &str) -> Result<FusekiBook, String> {
+        let mut book = FusekiBook::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |reason: &str| format!("Line {}: {}", number + 1, reason);
+            let mut game = GoGame::new(FUSEKI_BOARD_SIZE);
+            for point in line.split_whitespace().take(FUSEKI_MOVES) {
+                let (x, y) = match point.as_bytes() {
+                    [x @ b'a'..=b's', y @ b'a'..=b's'] => {
+                        ((x - b'a') as usize, (y - b'a') as usize)
+                    }
+                    _ => return Err(error(&format!("\"{}\" is not a point", point))),
+                };
+                let key = board_key(&game);
+                book.moves
+                    .record(key, (x, y), game.next_player, FUSEKI_BOARD_SIZE);
+                game.play_move(x, y)
+                    .map_err(|e| error(&format!("\"{}\": {}", point, e)))?;
+            }
+        }
+        Ok(book)
+    }
+
+    pub fn bundled() -> FusekiBook {
+        FusekiBook::read(BUNDLED).expect("the bundled openings are valid")
+    }
+
+    /// The points played next in the games that reached `position`, most
+    /// common first. Only 19x19 boards are looked at.
+    pub fn candidates(&self, position: &GoPosition) -> Vec<Candidate> {
+        if !position.is_square() || position.board_size() != FUSEKI_BOARD_SIZE {
+            return Vec::new();
+        }
+        let key = board_key(position);
+        let mut candidates: Vec<Candidate> = self
+            .moves
+            .lookup(key, FUSEKI_BOARD_SIZE)
+            .map(|((point, player), count)| Candidate {
+                point,
+                player,
+                count,
+                share: 0.0,
+            })
+            .collect();
+        let total: u32 = candidates.iter().map(|candidate| candidate.count).sum();
+        for candidate in &mut candidates {
+            candidate.share = candidate.count as f64 / total as f64;
+        }
+        candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.count));
+        candidates
+    }
+}
+
+/// The canonical key of the stones on a whole board, up to turning and
+/// mirroring it. Colors are kept apart, since black moves first.
+fn board_key(position: &GoPosition) -> CanonicalKey {
+    let stones: Vec<Stone> = position
+        .iter()
+        .enumerate()
+        .filter(|(_, player)| **player != Player::None)
+        .map(|(index, player)| (position.index_to_coord(index), *player))
+        .collect();
+    CanonicalKey::new(&stones, position.board_size(), &Symmetry::ALL, false)
+}
@@ -1,41 +1,285 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::ops::{Deref, DerefMut};
 
-#[derive(Debug)]
+use crate::annotation::Annotation;
+use crate::handicap::HANDICAP_KOMI;
+use crate::markup::Markup;
+use crate::rules::{self, KoRule, Ruleset};
+use crate::score::{Breakdown, Score, KOMI};
+
+/// The smallest board a game can be played on.
+pub const MIN_BOARD_SIZE: usize = 2;
+/// The largest board a game can be played on, the most SGF points can name.
+pub const MAX_BOARD_SIZE: usize = 25;
+
+/// Letters used for the columns, `I` is left out so it can't be mistaken for
+/// `J` or a number
+const COLUMN_LETTERS: &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+/// The name of column `x` as boards label it, a letter counting from the
+/// left.
+pub fn column_name(x: usize) -> String {
+    COLUMN_LETTERS
+        .chars()
+        .nth(x)
+        .map_or_else(|| (x + 1).to_string(), String::from)
+}
+
+/// The star points of a board of `width` by `height` points, where
+/// handicap stones go. Boards with a side shorter than 7 points have none.
+/// Along each side the corner points are on the third line up to 11 points
+/// and on the fourth line above, with a point in the middle of odd sides
+/// from 15 points up. Boards with two odd sides also get a center point.
+pub fn star_points(width: usize, height: usize) -> Vec<(usize, usize)> {
+    if width.min(height) < 7 {
+        return vec![];
+    }
+    let columns = star_lines(width);
+    let rows = star_lines(height);
+    let mut points: Vec<(usize, usize)> = columns
+        .iter()
+        .flat_map(|x| rows.iter().map(move |y| (*x, *y)))
+        .collect();
+    let center = (width / 2, height / 2);
+    if width % 2 == 1 && height % 2 == 1 && !points.contains(&center) {
+        points.push(center);
+    }
+    points
+}
+
+/// The lines of a side of `length` points that have star points on them,
+/// see `star_points`.
+fn star_lines(length: usize) -> Vec<usize> {
+    let edge = if length < 12 { 2 } else { 3 };
+    let mut lines = vec![edge, length - 1 - edge];
+    if length % 2 == 1 && length >= 15 {
+        lines.insert(1, length / 2);
+    }
+    lines
+}
+
+/// The points of fixed handicap stones, in the order they are placed as
+/// the handicap grows, as GTP places them: the four corner star points
+/// first, then the center on boards with two odd sides, then the middle of
+/// the sides. At most 9 stones fit on boards with two odd sides of at least
+/// 9 points, 4 on other boards of at least 7 points and none on smaller
+/// ones, extra stones are left out.
+pub fn fixed_handicap_points(width: usize, height: usize, stones: usize) -> Vec<(usize, usize)> {
+    if width.min(height) < 7 {
+        return vec![];
+    }
+    let columns = star_lines(width);
+    let rows = star_lines(height);
+    let (left, right) = (columns[0], columns[columns.len() - 1]);
+    let (top, bottom) = (rows[0], rows[rows.len() - 1]);
+    let mut points = vec![(right, top), (left, bottom), (right, bottom), (left, top)];
+    if width % 2 == 1 && height % 2 == 1 && width.min(height) >= 9 {
+        let center = (width / 2, height / 2);
+        let sides = [(left, center.1), (right, center.1)];
+        let ends = [(center.0, top), (center.0, bottom)];
+        points.extend(match stones {
+            5 => vec![center],
+            6 => sides.to_vec(),
+            7 => [sides.as_slice(), &[center]].concat(),
+            8 => [sides, ends].concat(),
+            _ => [sides.as_slice(), &ends, &[center]].concat(),
+        });
+    }
+    points.truncate(stones);
+    points
+}
+
+/// How black's handicap stones are put on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandicapPlacement {
+    /// On the star points, see `fixed_handicap_points`
+    Fixed,
+    /// Wherever black likes, one stone per turn before white's first move
+    Free,
+}
+
+#[derive(Clone, Debug)]
 pub struct GoGame {
     move_history: Vec<Move>,
+    /// Notable events in the order they happened
+    events: Vec<GameEvent>,
+    /// The point of the last ko capture, if that ko hasn't been filled yet
+    ko_fight: Option<usize>,
+    /// The position the game started from, which the move history is
+    /// replayed on top of
+    initial_position: GoPosition,
     position: GoPosition,
-    first_turn: usize,
     turn: usize,
     half_turn: usize,
     pub first_player: Player,
     pub next_player: Player,
+    pub info: GameInfo,
+    /// The marks drawn on the position after each number of moves, see
+    /// `markup_at`
+    markup: BTreeMap<usize, Markup>,
+    /// The comments on the position after each number of moves, see
+    /// `annotation_at`
+    annotations: BTreeMap<usize, Annotation>,
+    /// The rules the game is played under, see `with_rules`
+    rules: &'static dyn Ruleset,
+    /// The key of every board position reached so far and the player to
+    /// move in it, to enforce superko
+    seen: Vec<(u64, Player)>,
+    /// The free handicap stones black still has to place, see
+    /// `with_handicap`
+    handicap_to_place: usize,
+    /// How many colors take turns: 2, or 3 with `Player::Red`, see
+    /// `with_three_players`
+    colors: usize,
+    /// The stones marked dead once the game ended by passing, sorted by
+    /// index, see `toggle_dead`
+    dead: Vec<usize>,
+}
+
+/// Who played a game and under which terms, as kept in game records.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameInfo {
+    /// Empty when not known
+    pub black_name: String,
+    pub white_name: String,
+    /// Ranks the way game records write them, like `3d` or `15k`, empty when
+    /// not known
+    pub black_rank: String,
+    pub white_rank: String,
+    pub komi: f64,
+    /// The number of handicap stones black was given, 0 for even games
+    pub handicap: usize,
+    /// The result given by a game record, in its notation like `B+12.5` or
+    /// `W+T`, for games that ended without being played out
+    pub recorded_result: Option<String>,
+    /// The main time each player had, in seconds as game records write it,
+    /// `None` for untimed games
+    pub main_time: Option<f64>,
 }
 
-#[derive(Debug)]
+impl Default for GameInfo {
+    fn default() -> Self {
+        GameInfo {
+            black_name: String::new(),
+            white_name: String::new(),
+            black_rank: String::new(),
+            white_rank: String::new(),
+            komi: KOMI,
+            handicap: 0,
+            recorded_result: None,
+            main_time: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 /// Holds the actual position as well as ko marks.
 pub struct GoPosition {
-    board_size: usize,
+    /// The number of points across and down, the same on square boards
+    width: usize,
+    height: usize,
     position: Vec<Player>,
-    /// The index of a just-captured singular piece.
+    /// The index of a single stone just taken by a single stone that has
+    /// no other liberty, see `process_move`.
     ///
     /// The next player will be unable to play here because of ko rules.
-    /// This will be set to `board_size * board_size + 1` for safe measure
-    /// when no such condition exists.
+    /// This will be set to `width * height + 1` for safe measure
+    /// when no such condition exists, and after a pass.
+    ko: usize,
+    /// Union-find parent of each stone, stones that are their own parent are
+    /// the root of their chain. Empty points are their own parent.
+    parent: Vec<usize>,
+    /// The chain each root stone represents. Only meaningful at roots.
+    chains: Vec<Chain>,
+    /// A Zobrist hash of the stones, updated as stones are placed and taken
+    /// off, see `key`.
+    key: u64,
+}
+
+/// The stones and ko point of a position without its chains, see
+/// `GoPosition::save`. Much smaller than a clone of the position, and
+/// positions can be told apart by their hash without comparing stones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    stones: Vec<Player>,
     ko: usize,
+    hash: u64,
+}
+
+impl Snapshot {
+    /// A hash of the stones, the same for snapshots of equal stones.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// The number a stone of `player` at `index` adds to the key of a position,
+/// see `GoPosition::key`. Worked out from the point and color instead of
+/// drawn from a table, so boards of any size have them and they stay the
+/// same across builds. Empty points add nothing.
+pub(crate) fn zobrist(index: usize, player: Player) -> u64 {
+    match player {
+        Player::None => 0,
+        player => mix((index as u64) << 2 | player as u64),
+    }
+}
+
+/// The key of an empty board, which tells boards of different sizes apart.
+pub(crate) fn empty_key(width: usize, height: usize) -> u64 {
+    mix((width as u64) << 48 | (height as u64) << 32)
+}
+
+/// The SplitMix64 finalizer, which spreads every bit of `seed` over the
+/// result.
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The stones and liberties of a chain, stored at its root in the union-find.
+#[derive(Clone, Debug, Default)]
+struct Chain {
+    stones: Vec<usize>,
+    liberties: HashSet<usize>,
 }
 
 impl GoPosition {
     pub fn new(board_size: usize) -> Self {
+        GoPosition::rectangular(board_size, board_size)
+    }
+
+    /// An empty board of `width` points across and `height` points down.
+    pub fn rectangular(width: usize, height: usize) -> Self {
         GoPosition {
-            board_size,
-            position: vec![Player::default(); board_size * board_size],
-            ko: board_size * board_size + 1,
+            width,
+            height,
+            position: vec![Player::default(); width * height],
+            ko: width * height + 1,
+            parent: (0..width * height).collect(),
+            chains: vec![Chain::default(); width * height],
+            key: empty_key(width, height),
         }
     }
 
+    /// The number of points along the sides of a square board. On
+    /// rectangular boards this is the width, see `width` and `height`.
     pub fn board_size(&self) -> usize {
-        self.board_size
+        self.width
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.width == self.height
     }
 
     pub fn position(&self) -> &Vec<Player> {
@@ -46,180 +290,515 @@ impl GoPosition {
     /// (`x` and `y` should be in `0..=18`) and propogates the effect of it to the entire board
     ///
     /// When calling this, there are no checks to see if a move at the coordinates are valid.
-    fn process_move(&mut self, x: usize, y: usize, player: Player) {
+    /// Returns the indicies of the stones that were captured by the move. After a suicide
+    /// these are the stones of `player` it took off the board, including the played one.
+    fn process_move(&mut self, x: usize, y: usize, player: Player) -> Vec<usize> {
         let index = self.coord_to_index(x, y);
-        self.position[index] = player;
-        // TODO: actually process the move
-        let opp_player = match player {
-            Player::White => Player::Black,
-            Player::Black => Player::White,
-            Player::None => Player::None,
-        };
         // Don't check for capture as it doesn't make sense to.
-        if let Player::None = opp_player {
-            return;
+        if let Player::None = player {
+            // Removing a stone can split a chain, which the union-find can't
+            // undo, so start over.
+            self.position[index] = player;
+            self.rebuild_chains();
+            return vec![];
         }
         // Reset ko
-        self.ko = self.board_size * self.board_size + 1;
-        // Get coordinates of the adjacent positions of played piece
-        let sides: Vec<_> = self
-            .get_surrounding_valid_indicies(self.coord_to_index(x, y))
-            .into_iter()
-            .filter(|i| self.position[*i] == opp_player)
-            .collect();
-        for s in sides {
-            let to_remove = self.check_for_capture(s);
-            // Set ko if necessary
-            if to_remove.len() == 1 {
-                self.ko = to_remove[0];
+        self.ko = self.position.len() + 1;
+
+        self.place_stone(index, player);
+
+        // Capture any opposing chains left without liberties, of either
+        // other color in a three-color game
+        let mut captured = Vec::new();
+        for s in self.neighbors(index) {
+            if self.position[s] == player || self.position[s] == Player::None {
+                continue;
+            }
+            let root = self.find_root(s);
+            if !self.chains[root].liberties.is_empty() {
+                continue;
+            }
+            captured.extend(self.remove_chain(root));
+        }
+        // A suicide, which only gets here under rules that allow it, takes
+        // the whole chain off the board
+        let root = self.find_root(index);
+        let chain = &self.chains[root];
+        if chain.liberties.is_empty() {
+            return self.remove_chain(root);
+        }
+        // Only a lone stone that took a lone stone, and whose last liberty
+        // is the point it took, could be taken straight back. Anything else,
+        // like a snapback, can be answered at once.
+        if captured.len() == 1 && chain.stones.len() == 1 && chain.liberties.len() == 1 {
+            self.ko = captured[0];
+        }
+        captured
+    }
+
+    /// A hash of the stones on the board, the same for equal positions.
+    fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// The key the position would have after `player` plays at `index`,
+    /// captures and suicides included, worked out from the chains around
+    /// the point instead of playing the move on a copy.
+    pub fn key_after(&self, index: usize, player: Player) -> u64 {
+        let mut key = self.key ^ zobrist(index, player);
+        // The chains next to the point, each once
+        let mut roots = [None; 4];
+        let mut has_liberty = false;
+        for (slot, side) in self.neighbor_array(index).into_iter().flatten().enumerate() {
+            let stone = self.position[side];
+            if stone == Player::None {
+                has_liberty = true;
+                continue;
+            }
+            let root = self.root(side);
+            if roots.contains(&Some(root)) {
+                continue;
+            }
+            roots[slot] = Some(root);
+            // The played stone is the last liberty of the chain
+            let in_atari = self.chains[root].liberties.len() == 1;
+            if stone == player {
+                has_liberty |= !in_atari;
+            } else if in_atari {
+                has_liberty = true;
+                for &captured in self.chains[root].stones.iter() {
+                    key ^= zobrist(captured, stone);
+                }
             }
-            for index in to_remove {
-                // indicies should already be verified
-                self.position[index] = Player::None;
+        }
+        // A suicide takes the played stone off again along with every
+        // chain it joined
+        if !has_liberty {
+            key ^= zobrist(index, player);
+            for root in roots
+                .into_iter()
+                .flatten()
+                .filter(|root| self.position[*root] == player)
+            {
+                for &stone in self.chains[root].stones.iter() {
+                    key ^= zobrist(stone, player);
+                }
             }
         }
+        key
+    }
+
+    /// The hash `save` gives for a board of `width` by `height` points
+    /// holding `stones`, computed from scratch instead of move by move.
+    pub fn key_of(width: usize, height: usize, stones: &[Player]) -> u64 {
+        stones
+            .iter()
+            .enumerate()
+            .fold(empty_key(width, height), |key, (index, player)| {
+                key ^ zobrist(index, *player)
+            })
+    }
+
+    /// The point the next player can't play at because it would retake a
+    /// ko at once, if any.
+    pub fn ko(&self) -> Option<usize> {
+        (self.ko < self.position.len()).then_some(self.ko)
+    }
+
+    /// Saves the stones and ko point of the position, to go back to with
+    /// `restore` after trying moves on it.
+    pub fn save(&self) -> Snapshot {
+        Snapshot {
+            stones: self.position.clone(),
+            ko: self.ko,
+            hash: self.key(),
+        }
+    }
+
+    /// Puts back the stones and ko point of a snapshot saved from a
+    /// position of the same size. The chains are rebuilt from the stones.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        debug_assert_eq!(snapshot.stones.len(), self.position.len());
+        self.position.clone_from(&snapshot.stones);
+        self.ko = snapshot.ko;
+        self.rebuild_chains();
+    }
+
+    /// Puts a stone of `player` at `index`, taking the point away from the
+    /// liberties of its neighbors and merging it with friendly chains.
+    fn place_stone(&mut self, index: usize, player: Player) {
+        self.key ^= zobrist(index, self.position[index]) ^ zobrist(index, player);
+        self.position[index] = player;
+        self.parent[index] = index;
+        self.chains[index] = Chain {
+            stones: vec![index],
+            liberties: HashSet::new(),
+        };
+        let sides = self.neighbor_array(index);
+        for side in sides.into_iter().flatten() {
+            match self.position[side] {
+                Player::None => {
+                    self.chains[index].liberties.insert(side);
+                }
+                _ => {
+                    let root = self.find_root(side);
+                    self.chains[root].liberties.remove(&index);
+                }
+            }
+        }
+        // Only merge once the liberties are settled, as `index` stops being a
+        // root after the first merge
+        for side in sides.into_iter().flatten() {
+            if self.position[side] == player {
+                self.union(index, side);
+            }
+        }
+    }
+
+    /// Removes every stone of the chain rooted at `root` from the board and
+    /// gives the freed points back to the neighboring chains as liberties.
+    /// Returns the indicies of the removed stones.
+    fn remove_chain(&mut self, root: usize) -> Vec<usize> {
+        let chain = std::mem::take(&mut self.chains[root]);
+        for &stone in chain.stones.iter() {
+            self.key ^= zobrist(stone, self.position[stone]);
+            self.position[stone] = Player::None;
+            self.parent[stone] = stone;
+        }
+        for &stone in chain.stones.iter() {
+            for side in self.neighbors(stone) {
+                if self.position[side] != Player::None {
+                    let side_root = self.find_root(side);
+                    self.chains[side_root].liberties.insert(stone);
+                }
+            }
+        }
+        chain.stones
+    }
+
+    /// Recomputes the union-find from the stones on the board.
+    fn rebuild_chains(&mut self) {
+        let stones: Vec<(usize, Player)> = self
+            .position
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, player)| *player != Player::None)
+            .collect();
+        self.position = vec![Player::None; self.position.len()];
+        self.parent = (0..self.position.len()).collect();
+        self.chains = vec![Chain::default(); self.position.len()];
+        self.key = empty_key(self.width, self.height);
+        for (index, player) in stones {
+            self.place_stone(index, player);
+        }
+    }
+
+    /// Returns the root of the chain containing the stone at `index`, halving
+    /// the path on the way up.
+    fn find_root(&mut self, mut index: usize) -> usize {
+        while self.parent[index] != index {
+            self.parent[index] = self.parent[self.parent[index]];
+            index = self.parent[index];
+        }
+        index
+    }
+
+    /// Same as `find_root` but without path compression, for use through a
+    /// shared reference.
+    fn root(&self, mut index: usize) -> usize {
+        while self.parent[index] != index {
+            index = self.parent[index];
+        }
+        index
+    }
+
+    /// Merges the chains rooted at `a` and `b`, attaching the smaller chain to
+    /// the larger one.
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find_root(a), self.find_root(b));
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.chains[a].stones.len() >= self.chains[b].stones.len() {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = big;
+        let small_chain = std::mem::take(&mut self.chains[small]);
+        let big_chain = &mut self.chains[big];
+        big_chain.stones.extend(small_chain.stones);
+        big_chain.liberties.extend(small_chain.liberties);
     }
 
     /// Check that a move is valid
     ///
-    /// Currently really computationally expensive, probably.
-    pub fn is_valid_move(&mut self, x: usize, y: usize, player: Player) -> bool {
+    /// Only looks at the liberties of the neighboring chains, so no stones
+    /// have to be placed or searched.
+    pub fn is_valid_move(&self, x: usize, y: usize, player: Player) -> bool {
+        self.check_move(x, y, player).is_ok()
+    }
+
+    /// What playing `player` at (`x`, `y`) would do, worked out on a copy
+    /// so the position is left as it is. Fails like `check_move` for moves
+    /// that can't be played.
+    pub fn move_effects(
+        &self,
+        x: usize,
+        y: usize,
+        player: Player,
+    ) -> Result<MoveEffects, MoveError> {
+        self.check_move(x, y, player)?;
+        let mut after = self.clone();
+        let mut captures = after.process_move(x, y, player);
+        captures.sort_unstable();
+        let chain = &after.chains[after.root(self.coord_to_index(x, y))];
+        Ok(MoveEffects {
+            captures,
+            self_atari: chain.liberties.len() == 1,
+            ko_created: after.ko(),
+        })
+    }
+
+    /// Checks that a move is valid, returning the reason if it isn't.
+    pub fn check_move(&self, x: usize, y: usize, player: Player) -> Result<(), MoveError> {
         // validate coordinates
         if !self.coord_is_valid(x, y) {
-            return false;
+            return Err(MoveError::OutOfBounds);
         }
         let index = self.coord_to_index(x, y);
         // check for piece at position
         if Player::None != self.position[index] {
-            return false;
+            return Err(MoveError::Occupied);
         }
         // check that player is not Player::None
         if let Player::None = player {
-            return false;
+            return Err(MoveError::NoPlayer);
         }
-        // ko possible?
-        let mut ko_pos = false;
         // check for ko
-        if index == self.ko {
-            ko_pos = true;
+        let ko_pos = index == self.ko;
+
+        let mut has_liberty = false;
+        let mut capturing = false;
+        let mut ko_stone = None;
+        for side in self.neighbors(index) {
+            match self.position[side] {
+                Player::None => has_liberty = true,
+                piece => {
+                    let chain = &self.chains[self.root(side)];
+                    // The played stone is the last liberty of the chain
+                    let in_atari = chain.liberties.len() == 1;
+                    if piece == player {
+                        has_liberty |= !in_atari;
+                    } else if in_atari {
+                        // Retaking a single stone at the ko point doesn't count
+                        if ko_pos && chain.stones.len() == 1 {
+                            ko_stone = Some(side);
+                        } else {
+                            capturing = true;
+                        }
+                    }
+                }
+            }
         }
-        // check for self-capture
-        // 1: play the move
-        self.position[index] = player;
-        // 2: check for self-capture
-        let check = self.check_for_capture(index);
-        // 2.5: check for captures on surrounding tiles
-        let check_surrounding = self
-            .get_surrounding_valid_indicies(index)
-            .into_iter()
-            .map(|i| {
-                let captures = self.check_for_capture(i);
-                let capturing = if !ko_pos {
-                    captures.is_empty()
-                } else {
-                    captures.len() <= 1
-                };
-                self.position[i] == player || capturing
-            })
-            .all(|x| x);
-        // 3: remove the move
-        self.position[index] = Player::None;
-        // 4: enforce check
-        if check_surrounding && !check.is_empty() {
-            return false;
+
+        if has_liberty || capturing {
+            return Ok(());
         }
+        match ko_stone {
+            Some(stone) => Err(MoveError::Ko { stone }),
+            // Self-capture is not allowed. The surrounding stones are only
+            // gathered here, as most moves checked are valid
+            None => {
+                let mut surrounding: Vec<usize> = self
+                    .neighbors(index)
+                    .filter(|side| self.position[*side] != player)
+                    .flat_map(|side| self.chains[self.root(side)].stones.iter().copied())
+                    .collect();
+                surrounding.sort_unstable();
+                surrounding.dedup();
+                Err(MoveError::Suicide {
+                    capturing: surrounding,
+                })
+            }
+        }
+    }
 
-        // All checks passed
-        true
+    /// Returns the chain of stones connected to the stone at `index` along
+    /// with the empty points adjacent to it. Returns an empty group if there
+    /// is no stone at `index`.
+    pub fn group_at(&self, index: usize) -> Group {
+        let player = match self.position.get(index) {
+            Some(Player::None) | None => return Group::default(),
+            Some(player) => *player,
+        };
+        let chain = &self.chains[self.root(index)];
+
+        let mut stones = chain.stones.clone();
+        let mut liberties: Vec<usize> = chain.liberties.iter().copied().collect();
+        liberties.sort_unstable();
+        stones.sort_unstable();
+        Group {
+            player,
+            stones,
+            liberties,
+        }
     }
 
-    /// Returns a Vec of all position indicies that would be removed as a result
-    /// of a capture on a group including the position at the specified
-    /// coordinates. Does not check for valid indicies.
-    fn check_for_capture(&self, index: usize) -> Vec<usize> {
-        let mut visited: HashSet<usize> = HashSet::new();
-        let mut queue: VecDeque<usize> = VecDeque::new();
-        let mut to_remove: Vec<usize> = Vec::new();
-        queue.push_front(index);
-
-        let this = self.position[index];
-        let opp = match this {
-            // no piece at index, so do nothing
-            Player::None => return vec![],
-            Player::White => Player::Black,
-            Player::Black => Player::White,
+    /// Returns every chain of stones on the board once, with its color,
+    /// stones and liberties as `group_at` gives them, in no particular
+    /// order.
+    pub fn groups(&self) -> impl Iterator<Item = Group> + '_ {
+        (0..self.position.len())
+            .filter(|index| self.position[*index] != Player::None && self.parent[*index] == *index)
+            .map(|root| self.group_at(root))
+    }
+
+    /// Creates a position from the player at each point, given in index
+    /// order, on a board of `width` by `height` points.
+    pub fn from_stones(width: usize, height: usize, stones: &[Player]) -> Self {
+        let mut position = GoPosition::rectangular(width, height);
+        position.position.copy_from_slice(stones);
+        position.rebuild_chains();
+        position
+    }
+
+    /// Copies the stones of this position onto an empty square board of
+    /// `board_size`, keeping `corner` of the two boards lined up. Stones that
+    /// don't fit on the new board are left off.
+    pub fn transplant(&self, board_size: usize, corner: Corner) -> GoPosition {
+        let mut transplanted = GoPosition::new(board_size);
+        // How far the stones move along each axis
+        let shift_x = board_size as isize - self.width as isize;
+        let shift_y = board_size as isize - self.height as isize;
+        let (shift_x, shift_y) = match corner {
+            Corner::TopLeft => (0, 0),
+            Corner::TopRight => (shift_x, 0),
+            Corner::BottomLeft => (0, shift_y),
+            Corner::BottomRight => (shift_x, shift_y),
         };
-        // loop through queue, adding to queue as necessary
-        loop {
-            // remove indirection (copy next index instead of referencing)
-            let next = queue.pop_back();
-            match next {
-                Some(index) => {
-                    // index should be verified already
-                    match self.position[index] {
-                        // continue matching group
-                        piece if this == piece => {
-                            // add to queue
-                            let sides = self.get_surrounding_valid_indicies(index);
-                            queue.extend(sides.into_iter().filter(|s| !visited.contains(s)));
-                            to_remove.push(index);
-                        }
-                        // match on a boundary made by opp piece
-                        piece if opp == piece => (),
-                        // matched on Player::None, no capture
-                        _ => {
-                            return vec![];
-                        }
-                    }
-                    visited.insert(index);
-                }
-                // if no more elements in queue, this side is captured
-                None => {
-                    // so remove all of those pieces
-                    return to_remove;
-                }
+        for (index, player) in self.position.iter().enumerate() {
+            if *player == Player::None {
+                continue;
             }
+            let (x, y) = self.index_to_coord(index);
+            let x = x as isize + shift_x;
+            let y = y as isize + shift_y;
+            if x < 0 || y < 0 || !transplanted.coord_is_valid(x as usize, y as usize) {
+                continue;
+            }
+            let new_index = transplanted.coord_to_index(x as usize, y as usize);
+            transplanted.position[new_index] = *player;
         }
+        transplanted.rebuild_chains();
+        transplanted
     }
 
-    /// Returns a Vec of all the indicies of the positions surrounding the position
-    /// specified by index. If a surrounding position would be off the game board,
-    /// this function does not return that index.
-    pub fn get_surrounding_valid_indicies(&self, index: usize) -> Vec<usize> {
+    /// Returns the indicies of the positions surrounding the position specified
+    /// by index, in the order left, right, up, down. Positions that would be
+    /// off the game board are `None`.
+    fn neighbor_array(&self, index: usize) -> [Option<usize>; 4] {
         let (x, y) = self.index_to_coord(index);
-        vec![
-            (x.overflowing_sub(1).0, y),
-            (x.overflowing_add(1).0, y),
-            (x, y.overflowing_sub(1).0),
-            (x, y.overflowing_add(1).0),
+        let width = self.width;
+        [
+            (x > 0).then(|| index - 1),
+            (x + 1 < width).then(|| index + 1),
+            (y > 0).then(|| index - width),
+            (y + 1 < self.height).then(|| index + width),
         ]
-        .into_iter()
-        .filter_map(|(x, y)| {
-            let index = self.coord_to_index(x, y);
-            if self.coord_is_valid(x, y) {
-                Some(index)
-            } else {
-                None
-            }
-        })
-        .collect()
+    }
+
+    /// Iterates over the indicies of the positions surrounding the position
+    /// specified by index, skipping any that would be off the game board.
+    ///
+    /// Doesn't allocate or borrow the position, so it can be used while
+    /// modifying the board.
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> {
+        self.neighbor_array(index).into_iter().flatten()
     }
 
     pub fn coord_to_index(&self, x: usize, y: usize) -> usize {
-        y * self.board_size + x
+        y * self.width + x
     }
 
     pub fn index_to_coord(&self, index: usize) -> (usize, usize) {
-        (index % self.board_size, index / self.board_size)
+        (index % self.width, index / self.width)
+    }
+
+    /// The name of the point at (`x`, `y`) as boards label it, like `Q16`:
+    /// the column letter followed by the row counting up from the bottom.
+    pub fn point_name(&self, x: usize, y: usize) -> String {
+        format!("{}{}", column_name(x), self.height - y)
+    }
+
+    /// The point named `name`, the other way around from `point_name`.
+    /// Column letters may be lowercase.
+    pub fn parse_point_name(&self, name: &str) -> Option<(usize, usize)> {
+        let letter = name.chars().next()?.to_ascii_uppercase();
+        let x = COLUMN_LETTERS.find(letter)?;
+        let row: usize = name[1..].parse().ok()?;
+        if x >= self.width || row == 0 || row > self.height {
+            return None;
+        }
+        Some((x, self.height - row))
     }
 
     /// Returns `true` if the coordinate is on the board
     fn coord_is_valid(&self, x: usize, y: usize) -> bool {
-        if x >= self.board_size || y >= self.board_size {
-            false
-        } else {
-            true
-        }
+        x < self.width && y < self.height
+    }
+}
+
+/// Operations shared by the different board representations, so they can be
+/// swapped for one another and compared.
+pub trait Position {
+    fn board_size(&self) -> usize;
+
+    /// The stone at `index`, or `Player::None` if the point is empty
+    fn player_at(&self, index: usize) -> Player;
+
+    /// Returns `true` if `player` may play at (`x`, `y`)
+    fn is_valid_move(&self, x: usize, y: usize, player: Player) -> bool;
+
+    /// Plays a stone without checking that the move is valid and returns the
+    /// indicies of the captured stones.
+    fn play(&mut self, x: usize, y: usize, player: Player) -> Vec<usize>;
+
+    /// The chain containing the stone at `index` and its liberties
+    fn group_at(&self, index: usize) -> Group;
+}
+
+impl Position for GoPosition {
+    fn board_size(&self) -> usize {
+        self.width
+    }
+
+    fn player_at(&self, index: usize) -> Player {
+        self.position[index]
+    }
+
+    fn is_valid_move(&self, x: usize, y: usize, player: Player) -> bool {
+        GoPosition::is_valid_move(self, x, y, player)
+    }
+
+    fn play(&mut self, x: usize, y: usize, player: Player) -> Vec<usize> {
+        self.process_move(x, y, player)
+    }
+
+    fn group_at(&self, index: usize) -> Group {
+        GoPosition::group_at(self, index)
+    }
+}
+
+/// Positions are equal when they have the same stones and the same point is
+/// forbidden by ko, the chains follow from the stones.
+impl PartialEq for GoPosition {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.ko == other.ko
+            && self.position == other.position
     }
 }
 
@@ -233,17 +812,124 @@ impl Deref for GoPosition {
 
 impl GoGame {
     pub fn new(board_size: usize) -> Self {
+        GoGame::from_position(GoPosition::new(board_size), Player::Black)
+    }
+
+    /// Starts a game on an empty board of `width` by `height` points.
+    pub fn rectangular(width: usize, height: usize) -> Self {
+        GoGame::from_position(GoPosition::rectangular(width, height), Player::Black)
+    }
+
+    /// Starts a game from a set up position, with `next_player` to move.
+    pub fn from_position(position: GoPosition, next_player: Player) -> Self {
         GoGame {
             move_history: vec![],
-            position: GoPosition::new(board_size),
-            first_turn: 0,
+            events: vec![],
+            ko_fight: None,
+            seen: vec![(position.key(), next_player)],
+            initial_position: position.clone(),
+            position,
             turn: 0,
             half_turn: 0,
-            first_player: Player::Black,
-            next_player: Player::Black,
+            first_player: next_player,
+            next_player,
+            info: GameInfo::default(),
+            markup: BTreeMap::new(),
+            annotations: BTreeMap::new(),
+            rules: rules::DEFAULT,
+            handicap_to_place: 0,
+            colors: 2,
+            dead: vec![],
         }
     }
 
+    /// Makes the game one of three players, where red moves after white
+    /// and before black again. Stones are captured by any other color, and
+    /// the game ends once all three pass in a row. Meant for games that
+    /// haven't started yet.
+    pub fn with_three_players(mut self) -> Self {
+        self.colors = 3;
+        self
+    }
+
+    /// How many colors take turns, 2 but for three-player games.
+    pub fn colors(&self) -> usize {
+        self.colors
+    }
+
+    /// Plays the game under `rules` instead of the default ones, with their
+    /// komi. Meant for games that haven't started yet, as moves already
+    /// played were checked against the old rules.
+    pub fn with_rules(mut self, rules: &'static dyn Ruleset) -> Self {
+        self.rules = rules;
+        self.info.komi = rules.komi();
+        self
+    }
+
+    pub fn rules(&self) -> &'static dyn Ruleset {
+        self.rules
+    }
+
+    /// Gives black `stones` handicap stones, with the komi of handicap
+    /// games. Fixed stones go on the star points right away, free ones are
+    /// placed by black with `play_move` before white's first move. Either
+    /// way they become set up stones of the initial position, and white
+    /// moves first. A single stone is no stone at all: black moves first
+    /// with the handicap komi. Boards too small for fixed stones stay even.
+    /// Meant for games that haven't started yet.
+    pub fn with_handicap(mut self, stones: usize, placement: HandicapPlacement) -> Self {
+        if stones == 0 {
+            return self;
+        }
+        if stones == 1 {
+            self.info.komi = HANDICAP_KOMI;
+            return self;
+        }
+        match placement {
+            HandicapPlacement::Fixed => {
+                let points = fixed_handicap_points(self.width(), self.height(), stones);
+                if points.is_empty() {
+                    return self;
+                }
+                self.info.komi = HANDICAP_KOMI;
+                self.info.handicap = points.len();
+                self.handicap_to_place = points.len();
+                for (x, y) in points {
+                    self.place_handicap_stone(x, y)
+                        .expect("star points are empty at the start");
+                }
+            }
+            HandicapPlacement::Free => {
+                self.info.komi = HANDICAP_KOMI;
+                self.info.handicap = stones;
+                self.handicap_to_place = stones;
+            }
+        }
+        self
+    }
+
+    /// The free handicap stones black still has to place before the game
+    /// starts.
+    pub fn handicap_to_place(&self) -> usize {
+        self.handicap_to_place
+    }
+
+    /// Puts a handicap stone into the initial position, handing the first
+    /// move to white once the last one is placed.
+    fn place_handicap_stone(&mut self, x: usize, y: usize) -> Result<(), MoveError> {
+        self.position.check_move(x, y, Player::Black)?;
+        self.position.process_move(x, y, Player::Black);
+        self.initial_position.process_move(x, y, Player::Black);
+        self.handicap_to_place -= 1;
+        if self.handicap_to_place == 0 {
+            self.first_player = Player::White;
+            self.next_player = Player::White;
+        }
+        // The game only starts from the finished placement
+        self.seen = vec![(self.position.key(), self.next_player)];
+        Ok(())
+    }
+
     fn incr_turn(&mut self) {
         if self.next_player == self.first_player {
             self.turn += 1;
@@ -251,41 +937,666 @@ impl GoGame {
         self.half_turn += 1;
 
         // Handle player and turn data
-        self.next_player = match self.next_player {
-            Player::White => Player::Black,
-            Player::Black => Player::White,
-            Player::None => Player::None,
+        self.next_player = self.next_player.next(self.colors);
+    }
+
+    /// The moves made so far, in order.
+    pub fn history(&self) -> &[Move] {
+        &self.move_history
+    }
+
+    /// The move made last, if any.
+    pub fn last_move(&self) -> Option<&Move> {
+        self.move_history.last()
+    }
+
+    /// Records when the last move was made, in milliseconds since the epoch.
+    /// The game keeps no time of its own, so this is up to whoever plays it.
+    pub fn stamp_last_move(&mut self, timestamp: f64) {
+        if let Some(last) = self.move_history.last_mut() {
+            last.timestamp = Some(timestamp);
+        }
+    }
+
+    /// Records how long the player thought about the last move, in
+    /// milliseconds, see `stamp_last_move`.
+    pub fn time_last_move(&mut self, thinking: f64) {
+        if let Some(last) = self.move_history.last_mut() {
+            last.thinking = Some(thinking);
+        }
+    }
+
+    /// The time `player` had left after each of their moves, in seconds,
+    /// counting down from the main time by the thinking times recorded.
+    /// `None` for untimed games and for moves without a thinking time.
+    pub fn time_left(&self) -> Vec<Option<f64>> {
+        let mut left = [self.info.main_time; 3];
+        self.move_history
+            .iter()
+            .map(|m| {
+                let slot = match m.player {
+                    Player::Black => &mut left[0],
+                    Player::White => &mut left[1],
+                    Player::Red => &mut left[2],
+                    Player::None => return None,
+                };
+                let thinking = m.thinking?;
+                *slot = slot.map(|left| left - thinking / 1000.0);
+                *slot
+            })
+            .collect()
+    }
+
+    /// Who made each move and what it was, in order: the name of the point
+    /// played, see `GoPosition::point_name`, or `pass` or `resign`.
+    pub fn move_names(&self) -> Vec<(Player, String)> {
+        self.move_history
+            .iter()
+            .map(|m| match m.kind {
+                MoveKind::Play(square) => (m.player, self.position.point_name(square.x, square.y)),
+                MoveKind::Pass => (m.player, String::from("pass")),
+                MoveKind::Resign => (m.player, String::from("resign")),
+            })
+            .collect()
+    }
+
+    /// The number of moves made so far, counting passes and resignations.
+    pub fn move_count(&self) -> usize {
+        self.move_history.len()
+    }
+
+    /// The position the game was started from.
+    pub fn initial_position(&self) -> &GoPosition {
+        &self.initial_position
+    }
+
+    /// Notable events of the game so far, in chronological order.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Returns `true` once a player has resigned, both players passed in a
+    /// row, or a player reached the capture goal of the rules.
+    pub fn is_over(&self) -> bool {
+        self.resigned().is_some() || self.is_scoring() || self.capture_winner().is_some()
+    }
+
+    /// Returns `true` once every player passed in a row, when the dead
+    /// stones are marked before the game is counted.
+    pub fn is_scoring(&self) -> bool {
+        self.move_history.len() >= self.colors
+            && self
+                .move_history
+                .iter()
+                .rev()
+                .take(self.colors)
+                .all(|m| m.kind == MoveKind::Pass)
+    }
+
+    /// The stones marked dead, sorted by index.
+    pub fn dead_stones(&self) -> &[usize] {
+        &self.dead
+    }
+
+    /// Marks `stones` dead in place of the stones marked before, for example
+    /// the ones `estimate::probably_dead` suggests. Does nothing unless the
+    /// game is being scored, see `is_scoring`.
+    pub fn set_dead_stones(&mut self, mut stones: Vec<usize>) {
+        if !self.is_scoring() {
+            return;
+        }
+        stones.retain(|stone| {
+            self.position
+                .get(*stone)
+                .is_some_and(|p| *p != Player::None)
+        });
+        stones.sort_unstable();
+        stones.dedup();
+        self.dead = stones;
+    }
+
+    /// Marks the group of the stone at `index` dead, or alive again if it
+    /// was marked dead. Returns `false` if there is no stone there or the
+    /// game isn't being scored.
+    pub fn toggle_dead(&mut self, index: usize) -> bool {
+        let group = self.position.group_at(index);
+        if !self.is_scoring() || group.stones.is_empty() {
+            return false;
+        }
+        if self.dead.binary_search(&index).is_ok() {
+            self.dead
+                .retain(|stone| group.stones.binary_search(stone).is_err());
+        } else {
+            self.dead.extend(group.stones);
+            self.dead.sort_unstable();
+        }
+        true
+    }
+
+    /// The player who won by capturing as many stones as the capture goal
+    /// of the rules asks for, if the rules have one and someone did.
+    pub fn capture_winner(&self) -> Option<Player> {
+        let goal = self.rules.capture_goal()?;
+        [Player::Black, Player::White, Player::Red]
+            .into_iter()
+            .take(self.colors)
+            .find(|player| self.captures(*player) >= goal)
+    }
+
+    /// The player who resigned, if the game ended that way.
+    pub fn resigned(&self) -> Option<Player> {
+        self.move_history
+            .last()
+            .filter(|m| m.kind == MoveKind::Resign)
+            .map(|m| m.player)
+    }
+
+    /// The number of stones `player` has captured so far.
+    pub fn captures(&self, player: Player) -> usize {
+        self.events
+            .iter()
+            .map(|event| match event {
+                GameEvent::Capture {
+                    player: capturer,
+                    count,
+                    ..
+                } if *capturer == player => *count,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// The score of the current position under the rules of the game. The
+    /// stones marked dead are taken off the board as captures, all others
+    /// are taken to be alive.
+    pub fn score(&self) -> Score {
+        self.rules
+            .score(&self.counted_position(), self.counted_captures())
+    }
+
+    /// The parts `score` adds up, for showing how a game was counted.
+    pub fn breakdown(&self) -> Breakdown {
+        let position = self.counted_position();
+        let stones = |player| position.iter().filter(|stone| **stone == player).count();
+        let territory = Score::territory_owners(&position);
+        let points = |player| territory.iter().filter(|owner| **owner == player).count();
+        Breakdown {
+            scoring: self.rules.scoring(),
+            territory: Score {
+                black: points(Player::Black),
+                white: points(Player::White),
+            },
+            captures: self.counted_captures(),
+            stones: Score {
+                black: stones(Player::Black),
+                white: stones(Player::White),
+            },
+            komi: self.compensation(),
+        }
+    }
+
+    /// The stones black and white captured, with the stones marked dead
+    /// counted as captured by their opponent in a two-player game.
+    fn counted_captures(&self) -> Score {
+        let mut captures = Score {
+            black: self.captures(Player::Black),
+            white: self.captures(Player::White),
+        };
+        // Only two colors have an opponent to credit
+        if self.colors > 2 {
+            return captures;
+        }
+        for stone in &self.dead {
+            match self.position[*stone] {
+                Player::Black => captures.white += 1,
+                Player::White => captures.black += 1,
+                Player::None | Player::Red => {}
+            }
+        }
+        captures
+    }
+
+    /// The stones and territory of each color once the game is counted,
+    /// for three-player games, which `Score` has no room for.
+    pub fn areas(&self) -> Vec<(Player, usize)> {
+        let position = self.counted_position();
+        let territory = Score::territory_owners(&position);
+        [Player::Black, Player::White, Player::Red]
+            .into_iter()
+            .take(self.colors)
+            .map(|player| {
+                let stones = position.iter().filter(|stone| **stone == player).count();
+                let points = territory.iter().filter(|owner| **owner == player).count();
+                (player, stones + points)
+            })
+            .collect()
+    }
+
+    /// Whose territory each point is when the game is counted, see
+    /// `Score::territory_owners`. The points of dead stones are counted as
+    /// if the stones had been taken off.
+    pub fn territory(&self) -> Vec<Player> {
+        Score::territory_owners(&self.counted_position())
+    }
+
+    /// The empty points that are nobody's territory once the game is
+    /// counted, the dame still to be filled.
+    pub fn neutral_points(&self) -> usize {
+        let position = self.counted_position();
+        Score::territory_owners(&position)
+            .iter()
+            .zip(position.iter())
+            .filter(|(owner, stone)| **owner == Player::None && **stone == Player::None)
+            .count()
+    }
+
+    /// The current position with the stones marked dead taken off, as it is
+    /// counted.
+    fn counted_position(&self) -> GoPosition {
+        let mut stones = self.position.position.clone();
+        for stone in &self.dead {
+            stones[*stone] = Player::None;
+        }
+        GoPosition::from_stones(self.width(), self.height(), &stones)
+    }
+
+    /// The points given to white: the komi of `info` plus what the rules
+    /// give for handicap stones.
+    pub fn compensation(&self) -> f64 {
+        self.info.komi + self.rules.handicap_compensation(self.info.handicap)
+    }
+
+    /// The result of the game in SGF notation, like `B+R` or `W+3.5`, or `0`
+    /// for a draw. Games that were played out are scored under their rules,
+    /// see `score` and `compensation`, others fall back to the recorded
+    /// result, if any. SGF has no notation for three-player games, which
+    /// have none, see `areas`.
+    pub fn result(&self) -> Option<String> {
+        if self.colors > 2 {
+            return None;
+        }
+        if let Some(loser) = self.resigned() {
+            let winner = match loser {
+                Player::Black => "W",
+                _ => "B",
+            };
+            return Some(format!("{}+R", winner));
+        }
+        // SGF has no reason for winning by capture, so none is given
+        if let Some(winner) = self.capture_winner() {
+            return Some(String::from(match winner {
+                Player::White => "W+",
+                _ => "B+",
+            }));
+        }
+        if !self.is_over() {
+            return self.info.recorded_result.clone();
+        }
+        let margin = self.score().margin(Player::Black, self.compensation());
+        Some(if margin > 0.0 {
+            format!("B+{}", margin)
+        } else if margin < 0.0 {
+            format!("W+{}", -margin)
+        } else {
+            String::from("0")
+        })
+    }
+
+    /// Returns the position as it was right after the move at `half_turn` was
+    /// played, by replaying the move history from an empty board.
+    pub fn position_after(&self, half_turn: usize) -> GoPosition {
+        let mut position = self.initial_position.clone();
+        for m in self.move_history.iter() {
+            if let MoveKind::Play(square) = m.kind {
+                if m.half_turn > half_turn {
+                    break;
+                }
+                position.process_move(square.x, square.y, m.player);
+            }
+        }
+        position
+    }
+
+    /// Returns the position after the first `moves` moves of the game.
+    pub fn position_at(&self, moves: usize) -> GoPosition {
+        match moves {
+            0 => self.initial_position.clone(),
+            _ => self.position_after(moves - 1),
+        }
+    }
+
+    /// The marks drawn on the position after the first `moves` moves.
+    pub fn markup_at(&self, moves: usize) -> Markup {
+        self.markup.get(&moves).cloned().unwrap_or_default()
+    }
+
+    /// Replaces the marks drawn on the position after the first `moves`
+    /// moves.
+    pub fn set_markup_at(&mut self, moves: usize, markup: Markup) {
+        if markup.is_empty() {
+            self.markup.remove(&moves);
+        } else {
+            self.markup.insert(moves, markup);
+        }
+    }
+
+    /// The comment and evaluation of the position after the first `moves`
+    /// moves, the evaluation being about the last of those moves.
+    pub fn annotation_at(&self, moves: usize) -> Annotation {
+        self.annotations.get(&moves).cloned().unwrap_or_default()
+    }
+
+    pub fn set_annotation_at(&mut self, moves: usize, annotation: Annotation) {
+        if annotation.is_empty() {
+            self.annotations.remove(&moves);
+        } else {
+            self.annotations.insert(moves, annotation);
+        }
+    }
+
+    /// For each point, the number of the move that placed the stone on it
+    /// after the first `moves` moves, counting from 1 and including passes.
+    /// Empty points and stones of the initial position have no number.
+    pub fn move_numbers(&self, moves: usize) -> Vec<Option<usize>> {
+        let mut numbers = vec![None; self.initial_position.len()];
+        for (i, m) in self.move_history.iter().take(moves).enumerate() {
+            if let MoveKind::Play(square) = m.kind {
+                numbers[self.coord_to_index(square.x, square.y)] = Some(i + 1);
+                // After a suicide this takes the number off the played stone
+                for stone in &m.captured {
+                    numbers[*stone] = None;
+                }
+            }
+        }
+        numbers
+    }
+
+    /// Takes back the last move, returns `false` if no move has been made.
+    /// The stones the move captured are put back from its record, so the
+    /// rest of the game isn't replayed.
+    pub fn undo(&mut self) -> bool {
+        let last = match self.move_history.pop() {
+            Some(last) => last,
+            None => return false,
         };
+        self.dead.clear();
+        if let MoveKind::Play(square) = last.kind {
+            let index = self.position.coord_to_index(square.x, square.y);
+            for (stone, color) in last.captured.iter().zip(&last.captured_colors) {
+                self.position.position[*stone] = *color;
+            }
+            // After a suicide the played stone was among the captured
+            self.position.position[index] = Player::None;
+            self.position.rebuild_chains();
+        }
+        self.position.ko = last.ko;
+        self.ko_fight = last.ko_fight;
+        // Resigning doesn't pass the turn
+        if last.kind != MoveKind::Resign {
+            self.undo_turn(last.player, last.half_turn);
+        }
+        let half_turn = last.half_turn;
+        while self
+            .events
+            .last()
+            .is_some_and(|event| event.half_turn() == half_turn)
+        {
+            self.events.pop();
+        }
+        let moves = self.move_count();
+        self.markup.retain(|at, _| *at <= moves);
+        self.annotations.retain(|at, _| *at <= moves);
+        true
+    }
+
+    /// Hands the turn back to `player`, who made the move at `half_turn`
+    /// that is being taken back.
+    fn undo_turn(&mut self, player: Player, half_turn: usize) {
+        self.seen.pop();
+        self.next_player = player;
+        self.half_turn = half_turn;
+        if player == self.first_player {
+            self.turn -= 1;
+        }
+    }
+
+    /// A copy of the game as it was after its first `moves` moves, with the
+    /// same info and the marks and comments of the positions it kept. Each
+    /// move is replayed by the player who made it, see `replay`. Fails if a
+    /// move can't be played again.
+    pub fn truncated(&self, moves: usize) -> Result<GoGame, MoveError> {
+        let mut game = GoGame::from_position(self.initial_position.clone(), self.first_player)
+            .with_rules(self.rules);
+        game.handicap_to_place = self.handicap_to_place;
+        game.colors = self.colors;
+        for m in self.move_history.iter().take(moves) {
+            game.replay(m)?;
+        }
+        game.info = self.info.clone();
+        game.markup = self
+            .markup
+            .range(..=moves)
+            .map(|(moves, markup)| (*moves, markup.clone()))
+            .collect();
+        game.annotations = self
+            .annotations
+            .range(..=moves)
+            .map(|(moves, annotation)| (*moves, annotation.clone()))
+            .collect();
+        Ok(game)
+    }
+
+    /// Plays a move taken from the history of another game, by the player
+    /// who made it, as game records can have moves out of turn, and with
+    /// its timing.
+    pub fn replay(&mut self, m: &Move) -> Result<(), MoveError> {
+        if self.handicap_to_place == 0 {
+            self.next_player = m.player;
+        }
+        match m.kind {
+            MoveKind::Pass => self.pass(),
+            MoveKind::Resign => self.resign(),
+            MoveKind::Play(square) => self.play_move(square.x, square.y)?,
+        }
+        if let Some(timestamp) = m.timestamp {
+            self.stamp_last_move(timestamp);
+        }
+        if let Some(thinking) = m.thinking {
+            self.time_last_move(thinking);
+        }
+        Ok(())
     }
 
     pub fn pass(&mut self) {
-        self.move_history.push(Move::Pass {
+        // Handicap stones can't be passed on
+        if self.is_over() || self.handicap_to_place > 0 {
+            return;
+        }
+        self.move_history.push(Move {
+            player: self.next_player,
+            half_turn: self.half_turn,
+            kind: MoveKind::Pass,
+            captured: vec![],
+            captured_colors: vec![],
+            timestamp: None,
+            thinking: None,
+            ko: self.position.ko,
+            ko_fight: self.ko_fight,
+        });
+        // A pass in between lifts the ban on retaking
+        self.position.ko = self.position.len() + 1;
+        self.events.push(GameEvent::Pass {
             player: self.next_player,
             half_turn: self.half_turn,
         });
         self.incr_turn();
+        self.seen.push((self.position.key(), self.next_player));
     }
 
-    pub fn play_move(&mut self, x: usize, y: usize) -> Result<(), String> {
-        match self.position.get(self.position.coord_to_index(x, y)) {
-            Some(piece) => match piece {
-                Player::None => {
-                    self.move_history.push(Move::Play {
-                        player: self.next_player,
-                        square: Square { x, y },
-                        half_turn: self.half_turn,
-                    });
-                    self.position.process_move(x, y, self.next_player);
-                    self.incr_turn();
-
-                    Ok(())
-                }
-                _ => Err(String::from("A piece is already at that coordinate")),
-            },
-            None => Err(format!(
-                "Invalid coordinate for board size {}",
-                self.position.board_size
-            )),
+    /// The player to move resigns, ending the game.
+    pub fn resign(&mut self) {
+        if self.is_over() {
+            return;
+        }
+        self.move_history.push(Move {
+            player: self.next_player,
+            half_turn: self.half_turn,
+            kind: MoveKind::Resign,
+            captured: vec![],
+            captured_colors: vec![],
+            timestamp: None,
+            thinking: None,
+            ko: self.position.ko,
+            ko_fight: self.ko_fight,
+        });
+        self.events.push(GameEvent::Resign {
+            player: self.next_player,
+            half_turn: self.half_turn,
+        });
+    }
+
+    /// Checks that a move is valid under the rules of the game, returning
+    /// the reason if it isn't. Unlike the check of the position, this knows
+    /// whether suicide is allowed and which earlier positions can't be
+    /// repeated.
+    pub fn check_move(&self, x: usize, y: usize, player: Player) -> Result<(), MoveError> {
+        match self.position.check_move(x, y, player) {
+            Err(MoveError::Suicide { .. })
+                if self.rules.allows_suicide(self.suicide_size(x, y, player)) => {}
+            result => result?,
+        }
+        let ko_rule = self.rules.ko_rule();
+        if ko_rule == KoRule::Simple {
+            return Ok(());
+        }
+        let key = self
+            .position
+            .key_after(self.position.coord_to_index(x, y), player);
+        let next_player = player.next(self.colors);
+        let repeated = self.seen.iter().any(|(seen, to_move)| {
+            *seen == key && (ko_rule == KoRule::PositionalSuperko || *to_move == next_player)
+        });
+        if repeated {
+            return Err(MoveError::Superko);
+        }
+        Ok(())
+    }
+
+    /// The points the player to move may play at under the rules of the
+    /// game, see `check_move`, row by row from the top left.
+    pub fn legal_moves(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let player = self.next_player;
+        (0..self.height())
+            .flat_map(move |y| (0..self.width()).map(move |x| (x, y)))
+            .filter(move |&(x, y)| self.check_move(x, y, player).is_ok())
+    }
+
+    pub fn play_move(&mut self, x: usize, y: usize) -> Result<(), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        if self.handicap_to_place > 0 {
+            return self.place_handicap_stone(x, y);
+        }
+        self.check_move(x, y, self.next_player)?;
+
+        let index = self.position.coord_to_index(x, y);
+        let ko = self.position.ko;
+        let ko_fight = self.ko_fight;
+        let before = self.position.position.clone();
+        let captured = self.position.process_move(x, y, self.next_player);
+        let suicide = self.position[index] == Player::None;
+        self.record_move_events(index, &captured, suicide);
+        // The played stone is still empty in `before` after a suicide
+        let captured_colors = captured
+            .iter()
+            .map(|stone| match before[*stone] {
+                Player::None => self.next_player,
+                color => color,
+            })
+            .collect();
+        self.move_history.push(Move {
+            player: self.next_player,
+            half_turn: self.half_turn,
+            kind: MoveKind::Play(Square { x, y }),
+            captured,
+            captured_colors,
+            timestamp: None,
+            thinking: None,
+            ko,
+            ko_fight,
+        });
+        self.incr_turn();
+        self.seen.push((self.position.key(), self.next_player));
+
+        Ok(())
+    }
+}
+
+impl GoGame {
+    /// The number of stones a suicide at a point would take off the board:
+    /// the played stone and the chains it joins.
+    fn suicide_size(&self, x: usize, y: usize, player: Player) -> usize {
+        let index = self.position.coord_to_index(x, y);
+        let mut chains: Vec<usize> = self
+            .position
+            .neighbors(index)
+            .filter(|side| self.position[*side] == player)
+            .flat_map(|side| self.position.group_at(side).stones)
+            .collect();
+        chains.sort_unstable();
+        chains.dedup();
+        chains.len() + 1
+    }
+
+    /// Records the events caused by the stone just played at `index` by
+    /// `next_player`, which took the stones at `captured` off the board.
+    /// After a `suicide` these are its own.
+    fn record_move_events(&mut self, index: usize, captured: &[usize], suicide: bool) {
+        let player = self.next_player;
+        let half_turn = self.half_turn;
+
+        // Stones lost to suicide count as captured by the player moving
+        // next, the opponent in a two-player game
+        if suicide {
+            self.events.push(GameEvent::Capture {
+                player: player.next(self.colors),
+                half_turn,
+                count: captured.len(),
+            });
+            return;
+        }
+
+        if let Some(ko_point) = self.ko_fight {
+            // Filling the ko point without retaking ends the fight
+            if index == ko_point && captured.len() != 1 {
+                self.ko_fight = None;
+                self.events.push(GameEvent::KoResolved {
+                    player,
+                    half_turn,
+                    index,
+                });
+            }
+        }
+
+        if captured.is_empty() {
+            return;
+        }
+        self.events.push(GameEvent::Capture {
+            player,
+            half_turn,
+            count: captured.len(),
+        });
+
+        if let Some(ko) = self.position.ko() {
+            if self.ko_fight.is_none() {
+                self.events.push(GameEvent::KoStarted {
+                    player,
+                    half_turn,
+                    index: ko,
+                });
+            }
+            self.ko_fight = Some(ko);
         }
     }
 }
@@ -310,34 +1621,198 @@ impl DerefMut for GoGame {
     }
 }
 
-#[derive(Debug)]
-enum Move {
+/// What a move would do, see `GoPosition::move_effects`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MoveEffects {
+    /// The indicies of the stones the move takes off the board, sorted
+    pub captures: Vec<usize>,
+    /// Whether the chain of the played stone is left with a single liberty
+    pub self_atari: bool,
+    /// The point the move leaves banned by ko, see `GoPosition::ko`
+    pub ko_created: Option<usize>,
+}
+
+/// The reason a move can't be played.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveError {
+    /// The coordinates are off the board
+    OutOfBounds,
+    /// A stone is already at the point
+    Occupied,
+    /// `Player::None` can't play moves
+    NoPlayer,
+    /// The move would immediately retake the ko, `stone` is the stone that
+    /// would be captured
+    Ko { stone: usize },
+    /// The move would leave its own chain without liberties, `capturing` are
+    /// the surrounding stones that would capture it
+    Suicide { capturing: Vec<usize> },
+    /// The move would repeat an earlier position, which the superko rule
+    /// of the game forbids
+    Superko,
+    /// A player has already resigned
+    GameOver,
+    /// The point is outside the part of the board open for play, see
+    /// `region::Region`
+    Locked,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            MoveError::OutOfBounds => "That point is off the board",
+            MoveError::Occupied => "A piece is already at that coordinate",
+            MoveError::NoPlayer => "Nobody is to move",
+            MoveError::Ko { .. } => "The ko can't be retaken immediately",
+            MoveError::Suicide { .. } => "The stone would have no liberties",
+            MoveError::Superko => "The move would repeat an earlier position",
+            MoveError::GameOver => "The game is already over",
+            MoveError::Locked => "That point is locked",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// A corner of the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// A chain of connected stones of the same color.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+    pub player: Player,
+    /// Indicies of the stones in the group, in ascending order
+    pub stones: Vec<usize>,
+    /// Indicies of the empty points adjacent to the group, in ascending order
+    pub liberties: Vec<usize>,
+}
+
+impl Group {
+    /// The number of liberties of the group, one in atari
+    pub fn liberty_count(&self) -> usize {
+        self.liberties.len()
+    }
+}
+
+/// Something notable that happened during a game, used for the timeline.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    /// `player` captured `count` stones
+    Capture {
+        player: Player,
+        half_turn: usize,
+        count: usize,
+    },
+    /// `player` took a ko, `index` is the point that can't be retaken
+    KoStarted {
+        player: Player,
+        half_turn: usize,
+        index: usize,
+    },
+    /// `player` filled the ko at `index`
+    KoResolved {
+        player: Player,
+        half_turn: usize,
+        index: usize,
+    },
     Pass {
         player: Player,
         half_turn: usize,
     },
-    Play {
+    Resign {
         player: Player,
-        square: Square,
         half_turn: usize,
     },
 }
 
-#[derive(Debug)]
-struct Square {
+impl GameEvent {
+    /// The half turn of the move that caused this event
+    pub fn half_turn(&self) -> usize {
+        match self {
+            GameEvent::Capture { half_turn, .. }
+            | GameEvent::KoStarted { half_turn, .. }
+            | GameEvent::KoResolved { half_turn, .. }
+            | GameEvent::Pass { half_turn, .. }
+            | GameEvent::Resign { half_turn, .. } => *half_turn,
+        }
+    }
+}
+
+/// A move of a game, see `GoGame::history`.
+#[derive(Clone, Debug)]
+pub struct Move {
+    pub player: Player,
+    /// The half turn the move was made on, counting from 0 as the events of
+    /// the game do, see `GameEvent::half_turn`
+    pub half_turn: usize,
+    pub kind: MoveKind,
+    /// The stones the move took off the board, those of `player` after a
+    /// suicide
+    pub captured: Vec<usize>,
+    /// When the move was made, in milliseconds since the epoch, if it was
+    /// recorded, see `GoGame::stamp_last_move`
+    pub timestamp: Option<f64>,
+    /// How long the player thought about the move, in milliseconds, if it
+    /// was recorded, see `GoGame::time_last_move`
+    pub thinking: Option<f64>,
+    /// The color of each stone of `captured`, to put them back on undo
+    captured_colors: Vec<Player>,
+    /// The ko point of the position before the move, see `GoPosition::ko`
+    ko: usize,
+    /// The ko fight going on before the move, see `GoGame::ko_fight`
+    ko_fight: Option<usize>,
+}
+
+impl Move {
+    /// The point played as (x, y), `None` for passes and resignations.
+    pub fn point(&self) -> Option<(usize, usize)> {
+        match self.kind {
+            MoveKind::Play(square) => Some((square.x, square.y)),
+            _ => None,
+        }
+    }
+}
+
+/// What a player did on their turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveKind {
+    Play(Square),
+    Pass,
+    Resign,
+}
+
+/// A point of the board, counting from the top left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Square {
     pub x: usize,
     pub y: usize,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Player {
     Black,
     White,
+    #[default]
     None,
+    /// The third color of a three-player game, see `GoGame::with_three_players`.
+    /// It comes last so the other colors keep their values in hashes.
+    Red,
 }
 
-impl Default for Player {
-    fn default() -> Self {
-        Player::None
+impl Player {
+    /// The color to move after this one in a game of `colors` colors, see
+    /// `GoGame::colors`.
+    pub fn next(self, colors: usize) -> Player {
+        match self {
+            Player::Black => Player::White,
+            Player::White if colors > 2 => Player::Red,
+            Player::White | Player::Red => Player::Black,
+            Player::None => Player::None,
+        }
     }
 }
@@ -0,0 +1,150 @@
+use go_rs::estimate::Phase;
+use go_rs::game::{GameEvent, GameInfo, Player};
+use yew::prelude::*;
+
+use crate::clock::format_time;
+use crate::player_name;
+
+#[derive(Properties, PartialEq)]
+pub struct GameInfoPanelProps {
+    pub info: GameInfo,
+    /// The name of the rules the game is played under
+    pub rules: &'static str,
+    /// The free handicap stones black still has to place
+    pub handicap_to_place: usize,
+    pub next_player: Player,
+    /// The result in SGF notation, see `GoGame::result`, `None` while the
+    /// game goes on
+    pub result: Option<String>,
+    /// How far along the game is, see `estimate::phase`
+    pub phase: Phase,
+    /// The empty points nobody owns, `Some` while the dead stones are
+    /// marked, see `GoGame::neutral_points`
+    pub neutral_points: Option<usize>,
+    /// How many colors take turns, see `GoGame::colors`
+    pub colors: usize,
+    /// Stones captured by black, white and red
+    pub captures: [usize; 3],
+    /// Time used by black, white and red, in milliseconds
+    pub times: [f64; 3],
+    /// The notable events of the game, see `GoGame::events`
+    pub events: Vec<GameEvent>,
+    /// Called with the half turn of an event picked from the timeline
+    pub onjump: Callback<usize>,
+}
+
+/// Who is playing and how the game stands, followed by a timeline of its
+/// notable events. Picking an event shows the position at that point.
+#[function_component]
+pub fn GameInfoPanel(props: &GameInfoPanelProps) -> Html {
+    let info = &props.info;
+    // Game records have no names or ranks for red
+    let nobody = String::new();
+    let players = [
+        (Player::Black, &info.black_name, &info.black_rank, 0),
+        (Player::White, &info.white_name, &info.white_rank, 1),
+        (Player::Red, &nobody, &nobody, 2),
+    ]
+    .into_iter()
+    .take(props.colors)
+    .map(|(player, name, rank, slot)| {
+        let name = match name.as_str() {
+            "" => player_name(player).to_owned(),
+            name => format!("{} ({})", name, player_name(player)),
+        };
+        let mut class = classes!("gi-player");
+        if props.result.is_none() && props.next_player == player {
+            class.push("gi-to-move");
+        }
+        html! {
+            <div class={ class }>
+                <span class="gi-name">{ name }</span>
+                if !rank.is_empty() {
+                    <span class="gi-rank">{ rank }</span>
+                }
+                <span>{ format!("Captures: {}", props.captures[slot]) }</span>
+                <span>{ format!("Time used: {}", format_time(props.times[slot])) }</span>
+            </div>
+        }
+    });
+    let status = match (&props.result, props.handicap_to_place) {
+        (Some(result), _) => result_text(result),
+        (None, 0) => format!("{} to play", player_name(props.next_player)),
+        (None, 1) => String::from("Black places the last handicap stone"),
+        (None, stones) => format!("Black places {} more handicap stones", stones),
+    };
+
+    let events = props.events.iter().map(|event| {
+        let half_turn = event.half_turn();
+        let text = match event {
+            GameEvent::Capture { player, count, .. } => format!(
+                "{} captured {} stone{}",
+                player_name(*player),
+                count,
+                if *count == 1 { "" } else { "s" },
+            ),
+            GameEvent::KoStarted { player, .. } => {
+                format!("{} started a ko", player_name(*player))
+            }
+            GameEvent::KoResolved { player, .. } => {
+                format!("{} resolved the ko", player_name(*player))
+            }
+            GameEvent::Pass { player, .. } => format!("{} passed", player_name(*player)),
+            GameEvent::Resign { player, .. } => format!("{} resigned", player_name(*player)),
+        };
+        let jump_cb = props.onjump.reform(move |_: MouseEvent| half_turn);
+        html! {
+            <li onclick={ jump_cb }>
+                <span class="tl-move">{ format!("Move {}", half_turn + 1) }</span>
+                { text }
+            </li>
+        }
+    });
+    html! {
+        <>
+            <div class="game-info">
+                <div class="gi-players">{ for players }</div>
+                <span>{ format!("Rules: {}", props.rules) }</span>
+                <span>{ format!("Komi: {}", info.komi) }</span>
+                if info.handicap > 0 {
+                    <span>{ format!("Handicap: {}", info.handicap) }</span>
+                }
+                <span class="gi-status">{ status }</span>
+                if props.result.is_none() {
+                    <span class="gi-phase">{ props.phase.name() }</span>
+                }
+                if let Some(points) = props.neutral_points {
+                    <span class="gi-neutral">
+                        { format!("Neutral points left: {}", points) }
+                    </span>
+                }
+            </div>
+            <ol class="timeline">{ for events }</ol>
+        </>
+    }
+}
+
+/// Spells out a result in SGF notation, like `W+3.5` or `B+R`.
+fn result_text(result: &str) -> String {
+    let (winner, how) = match result.split_once('+') {
+        Some(("B", how)) => ("Black", how),
+        Some(("W", how)) => ("White", how),
+        _ => {
+            return match result {
+                "0" | "Draw" => String::from("Draw"),
+                "Void" => String::from("No result"),
+                other => other.to_owned(),
+            }
+        }
+    };
+    match how {
+        "" => format!("{} wins", winner),
+        "R" | "Resign" => format!("{} wins by resignation", winner),
+        "T" | "Time" => format!("{} wins on time", winner),
+        "F" | "Forfeit" => format!("{} wins by forfeit", winner),
+        points => match points.parse::<f64>() {
+            Ok(points) => format!("{} wins by {} points", winner, points),
+            Err(_) => format!("{} wins ({})", winner, points),
+        },
+    }
+}
@@ -0,0 +1,85 @@
+//! Handicaps for games between players of different strength, worked out
+//! from their ranks by one of a few common tables.
+
+use serde::{Deserialize, Serialize};
+
+use crate::rating::rank_rating;
+
+/// The most handicap stones a game is given, however far apart the ranks
+pub const MAX_STONES: usize = 9;
+
+/// The komi of handicap games, and of games where taking black makes up for
+/// the difference
+pub const HANDICAP_KOMI: f64 = 0.5;
+
+/// How a difference in rank is turned into a handicap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HandicapTable {
+    /// A stone for each rank of difference, where a single rank is made up
+    /// for by taking black without komi
+    #[default]
+    Full,
+    /// One rank less than the difference, as many tournaments do
+    Reduced,
+    /// A rank for every two ranks of difference
+    Half,
+}
+
+impl HandicapTable {
+    pub const ALL: [HandicapTable; 3] = [
+        HandicapTable::Full,
+        HandicapTable::Reduced,
+        HandicapTable::Half,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HandicapTable::Full => "Full",
+            HandicapTable::Reduced => "One rank less",
+            HandicapTable::Half => "Half",
+        }
+    }
+
+    /// The handicap of a game between players `ranks` ranks apart, given to
+    /// the weaker player, who takes black.
+    pub fn handicap(self, ranks: usize) -> Handicap {
+        let ranks = match self {
+            HandicapTable::Full => ranks,
+            HandicapTable::Reduced => ranks.saturating_sub(1),
+            HandicapTable::Half => ranks / 2,
+        };
+        match ranks {
+            0 => Handicap {
+                stones: 0,
+                komi: None,
+            },
+            // Black already moves first, so one stone is only the komi
+            1 => Handicap {
+                stones: 0,
+                komi: Some(HANDICAP_KOMI),
+            },
+            _ => Handicap {
+                stones: ranks.min(MAX_STONES),
+                komi: Some(HANDICAP_KOMI),
+            },
+        }
+    }
+}
+
+/// The handicap stones and komi of a game.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Handicap {
+    /// 0 for no stones
+    pub stones: usize,
+    /// `None` for the komi of the rules the game is played under, as in
+    /// even games
+    pub komi: Option<f64>,
+}
+
+/// How many ranks the player ranked `white` is stronger than the one ranked
+/// `black`, negative when black is the stronger one. `None` unless both
+/// ranks can be read, see `rank_rating`.
+pub fn rank_difference(black: &str, white: &str) -> Option<i32> {
+    let difference = rank_rating(white)? - rank_rating(black)?;
+    Some((difference / 100.0).round() as i32)
+}
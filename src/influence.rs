@@ -0,0 +1,75 @@
+//! Seeing frameworks form, with Bouzy's dilation and erosion.
+//!
+//! Every stone starts as a strong value, positive for black and negative
+//! for white. Dilations spread these values onto the empty points next to
+//! them, as long as they don't meet the other player's, and erosions wear
+//! them down from the sides where they do meet them or reach neutral points.
+//! What is left after `DILATIONS` dilations and `EROSIONS` erosions, Bouzy's
+//! 5/21, is the area each player has a hold on: their territory and moyo.
+
+use crate::game::{GoPosition, Player};
+
+/// How many times the values are spread
+pub const DILATIONS: usize = 5;
+
+/// How many times the values are worn down
+pub const EROSIONS: usize = 21;
+
+/// The value each stone starts with
+const STONE: i32 = 128;
+
+/// How strongly each point of `position` is under a player's influence,
+/// positive for black and negative for white, by index. Zero where neither
+/// player has a hold.
+pub fn influence(position: &GoPosition) -> Vec<i32> {
+    let mut values: Vec<i32> = (0..position.len())
+        .map(|index| match position[index] {
+            Player::Black => STONE,
+            Player::White => -STONE,
+            Player::None | Player::Red => 0,
+        })
+        .collect();
+    for _ in 0..DILATIONS {
+        values = dilate(position, &values);
+    }
+    for _ in 0..EROSIONS {
+        values = erode(position, &values);
+    }
+    values
+}
+
+/// Raises each point not touching the other player's values by the number
+/// of neighbors with values of its own sign.
+fn dilate(position: &GoPosition, values: &[i32]) -> Vec<i32> {
+    (0..values.len())
+        .map(|index| {
+            let value = values[index];
+            let sides = || position.neighbors(index).map(|side| values[side]);
+            if value >= 0 && sides().all(|side| side >= 0) {
+                value + sides().filter(|side| *side > 0).count() as i32
+            } else if value <= 0 && sides().all(|side| side <= 0) {
+                value - sides().filter(|side| *side < 0).count() as i32
+            } else {
+                value
+            }
+        })
+        .collect()
+}
+
+/// Lowers each value by the number of neighbors without a value of its own
+/// sign, stopping at zero.
+fn erode(position: &GoPosition, values: &[i32]) -> Vec<i32> {
+    (0..values.len())
+        .map(|index| {
+            let value = values[index];
+            let sides = || position.neighbors(index).map(|side| values[side]);
+            if value > 0 {
+                (value - sides().filter(|side| *side <= 0).count() as i32).max(0)
+            } else if value < 0 {
+                (value + sides().filter(|side| *side >= 0).count() as i32).min(0)
+            } else {
+                0
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,131 @@
+use crate::game::{Corner, GoGame, GoPosition, Player};
+use crate::pattern::{CanonicalKey, MoveTable, Stone, Symmetry};
+
+/// The sequences that come with the app, see `JosekiBook::read` for the
+/// format.
+pub const BUNDLED: &str = include_str!("../data/joseki.txt");
+/// The board size the sequences are played on
+pub const JOSEKI_BOARD_SIZE: usize = 19;
+/// How many lines from the edges a corner reaches
+pub const CORNER_SIZE: usize = 9;
+
+/// A move that commonly follows the stones in a corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Continuation {
+    pub point: (usize, usize),
+    pub player: Player,
+    /// How many of the known sequences continue with it
+    pub count: u32,
+}
+
+/// Known corner sequences, by the canonical key of the corner before each
+/// of their moves.
+#[derive(Debug, Default)]
+pub struct JosekiBook {
+    moves: MoveTable,
+}
+
+impl JosekiBook {
+    /// Reads sequences, one per line, of SGF points counted from the top left
+    /// corner of a 19x19 board, with black playing first. Empty lines and
+    /// lines starting with `#` are left out.
+    pub fn read(text: &str) -> Result<JosekiBook, String> {
+        let mut book = JosekiBook::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |reason: &str| format!("Line {}: {}", number + 1, reason);
+            let mut game = GoGame::new(JOSEKI_BOARD_SIZE);
+            for point in line.split_whitespace() {
+                let (x, y) = match point.as_bytes() {
+                    [x @ b'a'..=b'z', y @ b'a'..=b'z'] => {
+                        ((x - b'a') as usize, (y - b'a') as usize)
+                    }
+                    _ => return Err(error(&format!("\"{}\" is not a point", point))),
+                };
+                if x >= CORNER_SIZE || y >= CORNER_SIZE {
+                    return Err(error(&format!("\"{}\" is outside the corner", point)));
+                }
+                let key = corner_key(&game, Corner::TopLeft).0;
+                book.moves
+                    .record(key, (x, y), game.next_player, CORNER_SIZE);
+                game.play_move(x, y)
+                    .map_err(|e| error(&format!("\"{}\": {}", point, e)))?;
+            }
+        }
+        Ok(book)
+    }
+
+    pub fn bundled() -> JosekiBook {
+        JosekiBook::read(BUNDLED).expect("the bundled sequences are valid")
+    }
+
+    /// The known moves that follow the stones in each corner of `position`,
+    /// most common first. Empty corners are left out, and only 19x19 boards
+    /// are looked at.
+    pub fn continuations(&self, position: &GoPosition) -> Vec<Continuation> {
+        if !position.is_square() || position.board_size() != JOSEKI_BOARD_SIZE {
+            return Vec::new();
+        }
+        let mut continuations = Vec::new();
+        for corner in [
+            Corner::TopLeft,
+            Corner::TopRight,
+            Corner::BottomLeft,
+            Corner::BottomRight,
+        ] {
+            let (key, stones) = corner_key(position, corner);
+            if stones == 0 {
+                continue;
+            }
+            for ((local, player), count) in self.moves.lookup(key, CORNER_SIZE) {
+                let point = corner_symmetry(corner).invert(local, JOSEKI_BOARD_SIZE);
+                let index = position.coord_to_index(point.0, point.1);
+                if position[index] == Player::None {
+                    continuations.push(Continuation {
+                        point,
+                        player,
+                        count,
+                    });
+                }
+            }
+        }
+        continuations.sort_by_key(|continuation| std::cmp::Reverse(continuation.count));
+        continuations
+    }
+}
+
+/// The flips that bring `corner` to the top left.
+fn corner_symmetry(corner: Corner) -> Symmetry {
+    Symmetry {
+        transpose: false,
+        flip_x: matches!(corner, Corner::TopRight | Corner::BottomRight),
+        flip_y: matches!(corner, Corner::BottomLeft | Corner::BottomRight),
+    }
+}
+
+/// The canonical key of the stones in a corner, as seen from the top left
+/// and up to mirroring along the diagonal and swapping colors, along with
+/// the number of stones.
+fn corner_key(position: &GoPosition, corner: Corner) -> (CanonicalKey, usize) {
+    let symmetry = corner_symmetry(corner);
+    let stones: Vec<Stone> = position
+        .iter()
+        .enumerate()
+        .filter(|(_, player)| **player != Player::None)
+        .map(|(index, player)| {
+            let point = position.index_to_coord(index);
+            (symmetry.apply(point, position.board_size()), *player)
+        })
+        .filter(|((x, y), _)| *x < CORNER_SIZE && *y < CORNER_SIZE)
+        .collect();
+    let key = CanonicalKey::new(
+        &stones,
+        CORNER_SIZE,
+        &[Symmetry::IDENTITY, Symmetry::TRANSPOSE],
+        true,
+    );
+    (key, stones.len())
+}
@@ -0,0 +1,225 @@
+//! Talking to KataGo's analysis engine.
+//!
+//! KataGo reads queries and writes results as JSON, one per line, see its
+//! `docs/Analysis_Engine.md`. Browsers can't start programs, so it is
+//! reached through a proxy that passes each WebSocket message on to it as a
+//! line, and each line it writes back as a message. A query asks about
+//! several positions of a game at once, and KataGo answers each of them on
+//! its own as soon as it is done.
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{Candidate, Evaluation, SHOWN_CANDIDATES};
+use crate::game::{GoGame, GoPosition, Player};
+use crate::rules::{KoRule, Scoring};
+
+/// A request to analyze some positions of a game.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Query {
+    /// Comes back with every result of the query
+    pub id: String,
+    /// The stones on the board before the first move, like `["B", "D4"]`
+    pub initial_stones: Vec<(String, String)>,
+    /// Who played where, `pass` for passes
+    pub moves: Vec<(String, String)>,
+    rules: Rules,
+    pub komi: f64,
+    pub board_x_size: usize,
+    pub board_y_size: usize,
+    /// The positions to analyze, by the number of moves leading to them
+    pub analyze_turns: Vec<usize>,
+    pub max_visits: usize,
+    pub include_ownership: bool,
+    pub include_policy: bool,
+    override_settings: OverrideSettings,
+}
+
+/// The rules of a game, spelled out for KataGo rather than named, so
+/// every ruleset of `rules` fits.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rules {
+    ko: &'static str,
+    scoring: &'static str,
+    suicide: bool,
+    /// Handicap compensation is already part of the komi sent
+    white_handicap_bonus: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OverrideSettings {
+    /// Always `BLACK`, so the results don't depend on how KataGo was set up
+    report_analysis_winrates_as: &'static str,
+}
+
+impl Query {
+    /// Asks for the positions of `game` after each number of moves in
+    /// `turns`, with up to `max_visits` visits each. Turns past the moves
+    /// KataGo can replay, such as a resignation, are left out.
+    pub fn new(id: String, game: &GoGame, turns: &[usize], max_visits: usize) -> Query {
+        let start = game.initial_position();
+        let initial_stones = (0..start.len())
+            .filter_map(|index| {
+                let (x, y) = start.index_to_coord(index);
+                color(start[index]).map(|color| (color, start.point_name(x, y)))
+            })
+            .collect();
+        let moves: Vec<(String, String)> = game
+            .move_names()
+            .into_iter()
+            .filter(|(_, name)| name != "resign")
+            .filter_map(|(player, name)| color(player).map(|color| (color, name)))
+            .collect();
+        let rules = game.rules();
+        Query {
+            id,
+            initial_stones,
+            analyze_turns: turns
+                .iter()
+                .copied()
+                .filter(|turn| *turn <= moves.len())
+                .collect(),
+            moves,
+            rules: Rules {
+                ko: match rules.ko_rule() {
+                    KoRule::Simple => "SIMPLE",
+                    KoRule::PositionalSuperko => "POSITIONAL",
+                    KoRule::SituationalSuperko => "SITUATIONAL",
+                },
+                scoring: match rules.scoring() {
+                    Scoring::Area => "AREA",
+                    Scoring::Territory => "TERRITORY",
+                },
+                suicide: rules.allows_suicide(2),
+                white_handicap_bonus: "0",
+            },
+            komi: game.compensation(),
+            board_x_size: start.width(),
+            board_y_size: start.height(),
+            max_visits,
+            include_ownership: true,
+            include_policy: true,
+            override_settings: OverrideSettings {
+                report_analysis_winrates_as: "BLACK",
+            },
+        }
+    }
+
+    /// The query as KataGo reads it, on a single line.
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).expect("queries always serialize")
+    }
+}
+
+fn color(player: Player) -> Option<String> {
+    match player {
+        Player::Black => Some(String::from("B")),
+        Player::White => Some(String::from("W")),
+        Player::None | Player::Red => None,
+    }
+}
+
+/// KataGo's analysis of one position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// The id of the query this answers
+    pub id: String,
+    /// The number of moves leading to the position
+    pub turn: usize,
+    pub evaluation: Evaluation,
+    /// How likely each point is to end up black's, from -1.0 for white to
+    /// 1.0 for black, by index
+    pub ownership: Vec<f64>,
+    /// How likely KataGo's first impression would play each point, by
+    /// index, negative where the move is illegal
+    pub policy: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    id: Option<String>,
+    error: Option<String>,
+    #[serde(default)]
+    turn_number: usize,
+    #[serde(default)]
+    move_infos: Vec<MoveInfo>,
+    root_info: Option<RootInfo>,
+    #[serde(default)]
+    ownership: Vec<f64>,
+    #[serde(default)]
+    policy: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MoveInfo {
+    #[serde(rename = "move")]
+    point: String,
+    visits: usize,
+    winrate: f64,
+    score_lead: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RootInfo {
+    winrate: f64,
+    score_lead: f64,
+    current_player: String,
+}
+
+impl Analysis {
+    /// Reads a line of KataGo's output about a game on `board`, which only
+    /// lends the size of the board. Returns `Ok(None)` for lines that are
+    /// no analysis, like warnings, and `Err` for the errors KataGo reports.
+    pub fn decode(text: &str, board: &GoPosition) -> Result<Option<Analysis>, String> {
+        let response: Response = serde_json::from_str(text)
+            .map_err(|e| format!("Could not read KataGo's answer: {}", e))?;
+        if let Some(error) = response.error {
+            return Err(format!("KataGo: {}", error));
+        }
+        let (id, root) = match (response.id, response.root_info) {
+            (Some(id), Some(root)) => (id, root),
+            _ => return Ok(None),
+        };
+        let to_move_is_white = root.current_player == "W";
+        let candidates = response
+            .move_infos
+            .iter()
+            .filter_map(|info| {
+                let point = board.parse_point_name(&info.point)?;
+                Some(Candidate {
+                    point,
+                    visits: info.visits,
+                    // Winrates are black's, see `OverrideSettings`
+                    winrate: if to_move_is_white {
+                        1.0 - info.winrate
+                    } else {
+                        info.winrate
+                    },
+                    score: info.score_lead,
+                })
+            })
+            .take(SHOWN_CANDIDATES)
+            .collect();
+        let points = board.len();
+        Ok(Some(Analysis {
+            id,
+            turn: response.turn_number,
+            evaluation: Evaluation {
+                winrate: root.winrate,
+                score: root.score_lead,
+                candidates,
+            },
+            ownership: match response.ownership.len() {
+                len if len == points => response.ownership,
+                _ => vec![],
+            },
+            // The last entry is for passing
+            policy: response.policy.into_iter().take(points).collect(),
+        }))
+    }
+}
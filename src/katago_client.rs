@@ -0,0 +1,100 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use go_rs::game::GoPosition;
+use go_rs::katago::{Analysis, Query};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{MessageEvent, WebSocket};
+use yew::Callback;
+
+/// A connection to KataGo's analysis engine through a WebSocket proxy, see
+/// `go_rs::katago`. Closed when dropped.
+pub struct KataGoClient {
+    socket: WebSocket,
+    /// The queries held back until the socket has opened, oldest first
+    pending: Rc<RefCell<Vec<String>>>,
+    /// The board the results are about, for reading their points
+    board: Rc<RefCell<GoPosition>>,
+    _onopen: Closure<dyn FnMut()>,
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+    _onerror: Closure<dyn FnMut()>,
+}
+
+impl KataGoClient {
+    /// Connects to the proxy at `url`, calling `on_analysis` with every
+    /// position KataGo has analyzed.
+    pub fn connect(
+        url: &str,
+        on_analysis: Callback<Analysis>,
+        on_error: Callback<String>,
+    ) -> Result<Self, String> {
+        let socket =
+            WebSocket::new(url).map_err(|_| format!("Could not connect to KataGo at {}", url))?;
+        let pending: Rc<RefCell<Vec<String>>> = Rc::default();
+        let board = Rc::new(RefCell::new(GoPosition::new(19)));
+
+        let onopen = {
+            let socket = socket.clone();
+            let pending = pending.clone();
+            Closure::wrap(Box::new(move || {
+                for text in pending.borrow_mut().drain(..) {
+                    let _ = socket.send_with_str(&text);
+                }
+            }) as Box<dyn FnMut()>)
+        };
+        let onmessage = {
+            let board = board.clone();
+            let on_error = on_error.clone();
+            Closure::wrap(Box::new(move |e: MessageEvent| {
+                let text = match e.data().as_string() {
+                    Some(text) => text,
+                    None => return,
+                };
+                match Analysis::decode(&text, &board.borrow()) {
+                    Ok(Some(analysis)) => on_analysis.emit(analysis),
+                    Ok(None) => {}
+                    Err(e) => on_error.emit(e),
+                }
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+        let onerror = Closure::wrap(Box::new(move || {
+            on_error.emit(String::from("The connection to KataGo failed"))
+        }) as Box<dyn FnMut()>);
+        socket.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+
+        Ok(KataGoClient {
+            socket,
+            pending,
+            board,
+            _onopen: onopen,
+            _onmessage: onmessage,
+            _onerror: onerror,
+        })
+    }
+
+    /// Sends `query` about a game on `board`, or keeps it until the
+    /// connection opens.
+    pub fn send(&self, query: &Query, board: &GoPosition) {
+        *self.board.borrow_mut() = board.clone();
+        let text = query.encode();
+        if self.socket.ready_state() == WebSocket::OPEN {
+            let _ = self.socket.send_with_str(&text);
+        } else {
+            self.pending.borrow_mut().push(text);
+        }
+    }
+}
+
+impl Drop for KataGoClient {
+    fn drop(&mut self) {
+        // The handlers go with the client, so the socket mustn't call them
+        // while it closes
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onerror(None);
+        let _ = self.socket.close();
+    }
+}
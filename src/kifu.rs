@@ -0,0 +1,76 @@
+use crate::game::{GoGame, GoPosition, MoveKind, Player};
+
+/// A game record the way it is printed: a single diagram of the starting
+/// stones with every move numbered on its point, and notes for the moves
+/// that can't be shown on the diagram.
+#[derive(Clone, Debug)]
+pub struct Kifu {
+    /// The starting stones plus the first stone played on each point, even
+    /// the ones captured later
+    pub diagram: GoPosition,
+    /// The number of the move that put each stone of `diagram` there, `None`
+    /// for starting stones and empty points
+    pub numbers: Vec<Option<usize>>,
+    /// The moves that aren't on the diagram, in the order they were played
+    pub notes: Vec<KifuNote>,
+}
+
+/// A move that isn't numbered on the diagram of a kifu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KifuNote {
+    /// The number of the move, counting from 1
+    pub number: usize,
+    pub player: Player,
+    pub kind: NoteKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteKind {
+    /// Played where the move of the given number had been played before
+    At(usize),
+    /// Played on the point at the given index, where a starting stone was
+    /// captured
+    AtStartingStone(usize),
+    Pass,
+    Resign,
+}
+
+impl Kifu {
+    /// Writes down the moves of `game` as a kifu.
+    pub fn new(game: &GoGame) -> Self {
+        let start = game.initial_position();
+        let mut stones = start.position().clone();
+        let mut numbers = vec![None; stones.len()];
+        let mut notes = Vec::new();
+        for (i, m) in game.history().iter().enumerate() {
+            let number = i + 1;
+            let player = m.player;
+            let kind = match m.kind {
+                MoveKind::Play(square) => {
+                    let index = start.coord_to_index(square.x, square.y);
+                    match (numbers[index], stones[index]) {
+                        (Some(first), _) => NoteKind::At(first),
+                        (None, Player::None) => {
+                            stones[index] = player;
+                            numbers[index] = Some(number);
+                            continue;
+                        }
+                        (None, _) => NoteKind::AtStartingStone(index),
+                    }
+                }
+                MoveKind::Pass => NoteKind::Pass,
+                MoveKind::Resign => NoteKind::Resign,
+            };
+            notes.push(KifuNote {
+                number,
+                player,
+                kind,
+            });
+        }
+        Kifu {
+            diagram: GoPosition::from_stones(start.width(), start.height(), &stones),
+            numbers,
+            notes,
+        }
+    }
+}
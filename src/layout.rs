@@ -0,0 +1,86 @@
+use gloo_utils::window;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::MediaQueryList;
+use yew::Callback;
+
+/// Windows at least this wide, and wider than tall, have room for the
+/// sidebar next to the board
+const WIDE_QUERY: &str = "(min-width: 1280px) and (orientation: landscape)";
+/// Windows taller than wide put the controls below the board
+const PORTRAIT_QUERY: &str = "(orientation: portrait)";
+
+/// How the board, the controls and the sidebar are arranged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Controls below the board, the sidebar slides in over them
+    Portrait,
+    /// Controls to the right of the board, the sidebar slides in over them
+    Landscape,
+    /// Like `Landscape`, with the sidebar always shown on the right
+    Wide,
+}
+
+impl Layout {
+    /// The layout that fits the window as it is now.
+    pub fn current() -> Layout {
+        if matches(WIDE_QUERY) {
+            Layout::Wide
+        } else if matches(PORTRAIT_QUERY) {
+            Layout::Portrait
+        } else {
+            Layout::Landscape
+        }
+    }
+
+    /// The class of the element around the page in this layout.
+    pub fn class(self) -> &'static str {
+        match self {
+            Layout::Portrait => "layout-portrait",
+            Layout::Landscape => "layout-landscape",
+            Layout::Wide => "layout-wide",
+        }
+    }
+}
+
+fn matches(query: &str) -> bool {
+    media_query(query).is_some_and(|list| list.matches())
+}
+
+fn media_query(query: &str) -> Option<MediaQueryList> {
+    window().match_media(query).ok().flatten()
+}
+
+/// Watches the window for crossing from one layout to another, calling back
+/// with the new one. Stops watching when dropped.
+pub struct LayoutWatcher {
+    queries: Vec<MediaQueryList>,
+    on_change: Closure<dyn FnMut()>,
+}
+
+impl LayoutWatcher {
+    pub fn new(on_layout: Callback<Layout>) -> Self {
+        let on_change =
+            Closure::wrap(Box::new(move || on_layout.emit(Layout::current())) as Box<dyn FnMut()>);
+        let queries: Vec<_> = [WIDE_QUERY, PORTRAIT_QUERY]
+            .into_iter()
+            .filter_map(media_query)
+            .collect();
+        for query in &queries {
+            let _ = query
+                .add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref());
+        }
+        LayoutWatcher { queries, on_change }
+    }
+}
+
+impl Drop for LayoutWatcher {
+    fn drop(&mut self) {
+        for query in &self.queries {
+            let _ = query.remove_event_listener_with_callback(
+                "change",
+                self.on_change.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
@@ -0,0 +1,31 @@
+pub mod annotation;
+pub mod bitboard;
+pub mod bot;
+pub mod broadcast;
+pub mod chat;
+pub mod conditional;
+pub mod engine;
+pub mod estimate;
+pub mod eye;
+pub mod fuseki;
+pub mod game;
+pub mod handicap;
+pub mod influence;
+pub mod joseki;
+pub mod katago;
+pub mod kifu;
+pub mod markup;
+pub mod pattern;
+pub mod problem;
+pub mod protocol;
+pub mod rating;
+pub mod region;
+pub mod replay;
+pub mod room;
+pub mod rules;
+pub mod score;
+pub mod sgf;
+pub mod stats;
+pub mod tactics;
+pub mod tournament;
+pub mod zip;
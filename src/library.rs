@@ -0,0 +1,134 @@
+use gloo_file::callbacks::{read_as_text, FileReader};
+use gloo_file::File;
+use gloo_utils::document;
+use go_rs::game::GoGame;
+use go_rs::sgf::{read_sgf, write_sgf, SgfError};
+use go_rs::zip::write_zip;
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Url};
+use yew::Callback;
+
+/// A game saved to the library, kept as its SGF record.
+pub struct LibraryEntry {
+    pub name: String,
+    pub sgf: String,
+    /// The rating of the player when the game was saved, see `App::rating_now`
+    pub rating: Option<f64>,
+}
+
+impl LibraryEntry {
+    pub fn from_game(name: String, game: &GoGame) -> Result<Self, SgfError> {
+        Ok(LibraryEntry {
+            name,
+            sgf: write_sgf(game)?,
+            rating: None,
+        })
+    }
+
+    /// Creates an entry from the text of an SGF file, checking that it can
+    /// be read first.
+    pub fn from_sgf(name: String, sgf: String) -> Result<Self, String> {
+        read_sgf(&sgf).map_err(|e| format!("{}: {}", name, e))?;
+        Ok(LibraryEntry {
+            name,
+            sgf,
+            rating: None,
+        })
+    }
+
+    /// Reads the game back out of the stored record.
+    pub fn game(&self) -> Option<GoGame> {
+        read_sgf(&self.sgf).ok()
+    }
+}
+
+/// Starts reading every file selected in `input` as text. `callback` is called
+/// with the name of each file and its contents once it has been read. The
+/// returned readers have to be kept alive until then.
+pub fn read_files(
+    input: &HtmlInputElement,
+    callback: Callback<(String, Result<String, String>)>,
+) -> Vec<FileReader> {
+    let files = match input.files() {
+        Some(files) => files,
+        None => return Vec::new(),
+    };
+    (0..files.length())
+        .filter_map(|i| files.get(i))
+        .map(|file| {
+            let file = File::from(file);
+            let name = file
+                .name()
+                .trim_end_matches(".sgf")
+                .trim_end_matches(".SGF")
+                .to_owned();
+            let callback = callback.clone();
+            read_as_text(&file, move |result| {
+                callback.emit((name, result.map_err(|e| e.to_string())))
+            })
+        })
+        .collect()
+}
+
+/// Starts a download of the SGF record of `game`, named after its players.
+pub fn download_sgf(game: &GoGame) -> Result<(), String> {
+    let name = match (game.info.black_name.as_str(), game.info.white_name.as_str()) {
+        ("", "") => String::from("game"),
+        (black, white) => file_name(&format!("{} vs {}", black, white)),
+    };
+    let sgf = write_sgf(game).map_err(|e| e.to_string())?;
+    download(
+        &format!("{}.sgf", name),
+        sgf.as_bytes(),
+        "application/x-go-sgf",
+    )
+}
+
+/// Packs every entry into a zip of SGF files and starts a download of it.
+pub fn export_all(entries: &[LibraryEntry]) -> Result<(), String> {
+    let files: Vec<(String, Vec<u8>)> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            // Prefix with the position in the library so names never clash
+            (
+                format!("{:03}-{}.sgf", i + 1, file_name(&entry.name)),
+                entry.sgf.clone().into_bytes(),
+            )
+        })
+        .collect();
+    download("library.zip", &write_zip(&files), "application/zip")
+}
+
+/// Keeps only the characters of `name` that are safe in a file name.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Offers `bytes` to the user as a file download called `name`.
+pub fn download(name: &str, bytes: &[u8], mime: &str) -> Result<(), String> {
+    let array = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = Blob::new_with_u8_array_sequence_and_options(&array, &options)
+        .map_err(|_| String::from("Could not create the file"))?;
+    let url = Url::create_object_url_with_blob(&blob)
+        .map_err(|_| String::from("Could not create a link to the file"))?;
+    download_url(name, &url)?;
+    Url::revoke_object_url(&url).map_err(|_| String::from("Could not clean up the download"))
+}
+
+/// Offers the file at `url` to the user as a download called `name`.
+pub fn download_url(name: &str, url: &str) -> Result<(), String> {
+    let anchor = document()
+        .create_element("a")
+        .map_err(|_| String::from("Could not create a download link"))?
+        .dyn_into::<HtmlAnchorElement>()
+        .map_err(|_| String::from("Could not create a download link"))?;
+    anchor.set_href(url);
+    anchor.set_download(name);
+    anchor.click();
+    Ok(())
+}
@@ -1,468 +1,4276 @@
-use gloo_utils::*;
-use web_sys::HtmlElement;
+use gloo_file::callbacks::FileReader;
+use gloo_timers::callback::{Interval, Timeout};
+use gloo_utils::window;
+use go_rs::annotation::Annotation;
+use go_rs::bot::MimicBot;
+use go_rs::broadcast::Frame;
+use go_rs::chat::{ChatChannel, ChatMessage, QUICK_REPLIES};
+use go_rs::engine::{Engine, SearchEngine};
+use go_rs::estimate::{ownership, phase, probably_dead, PLAYOUTS};
+use go_rs::fuseki::{FusekiBook, FUSEKI_MOVES};
+use go_rs::handicap::{rank_difference, MAX_STONES};
+use go_rs::influence::influence;
+use go_rs::joseki::JosekiBook;
+use go_rs::katago;
+use go_rs::kifu::{Kifu, NoteKind};
+use go_rs::problem::{Attempt, Outcome, Problem};
+use go_rs::rating::rank_rating;
+use go_rs::region::Region;
+use go_rs::room::Presence;
+use go_rs::rules::Scoring;
+use go_rs::score::Breakdown;
+use go_rs::sgf::{read_sgf, write_sgf, SgfError};
+use go_rs::stats::{result_winner, Stats};
+use go_rs::tactics::{find_tactics, Tactic};
+use go_rs::tournament::{GameResult, Tournament};
+use std::collections::{BTreeSet, VecDeque};
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
+use yew_router::prelude::*;
 
-mod game;
+mod activity;
+mod app;
+mod audio;
+mod board;
+mod board_view;
+mod clock;
+mod comment_pane;
+mod compare;
+mod controls_bar;
+mod correspondence;
+mod correspondence_panel;
+mod demo;
+mod engine_review;
+mod export_image;
+mod game_info;
+mod katago_client;
+mod layout;
+mod library;
+mod markup_layer;
+mod move_list;
+mod ogs;
+mod ogs_panel;
+mod ratings;
+mod relay;
+mod resize;
+mod route;
+mod settings;
+mod settings_panel;
 mod sidebar;
+mod stone_layer;
+mod tab_bar;
+mod theme;
+mod tournament_panel;
+#[cfg(all(test, target_arch = "wasm32"))]
+mod ui_tests;
+mod winrate_graph;
 
-use game::*;
-use sidebar::make_sidebar;
+use activity::ActivityWatcher;
+use app::correspondence::CorrespondenceMsg;
+use app::ogs::OgsMsg;
+use app::sections::{BroadcastSection, PracticeSection, RatingsSection, TeachingSection};
+use app::sessions::{Session, TabMsg};
+use app::state::{AppContext, AppView};
+use app::views::navigate;
+use audio::{Audio, Sound};
+use board::*;
+use board_view::{BoardTouch, BoardView};
+use clock::{format_time, Clock, Pause};
+use comment_pane::CommentPane;
+use compare::{ComparedGame, Comparison};
+use controls_bar::{Control, ControlsBar};
+use correspondence::{remember_shown, shown_game, Correspondence, SavedGame, Spectating};
+use correspondence_panel::CorrespondenceControl;
+use demo::{Demo, DemoBar, DemoControl};
+use engine_review::{EngineReview, MistakeReview, STEP_MS};
+use export_image::{board_svg, export_png, export_svg, ImageOptions};
+use game_info::GameInfoPanel;
+use go_rs::game::*;
+use katago_client::KataGoClient;
+use layout::{Layout, LayoutWatcher};
+use library::{download_sgf, export_all, read_files, LibraryEntry};
+use markup_layer::MarkupLayer;
+use move_list::MoveList;
+use ogs::{OgsLogin, OgsPlay};
+use ratings::{Ratings, BOT_PROFILE, LOCAL_PROFILE};
+use relay::{Incoming, RelayConnection};
+use route::Route;
+use settings::{ConfirmMode, Preset, Settings};
+use sidebar::{Sidebar, SidebarSection};
+use stone_layer::{MoveNumbers, StoneDisplay, StoneLayer};
+use tournament_panel::{TournamentControl, TournamentPanel};
+use winrate_graph::WinrateGraph;
 
 enum Msg {
-    /// Making the board with the field indicating the
+    /// Making a board of `width` by `height` points
     MakeBoard {
-        size: usize,
+        width: usize,
+        height: usize,
     },
-    /// A click on the go board, fields are client x
-    /// and y values of the click
+    /// Set the width and height of the board the next game is made on
+    SetNewBoardSize(usize, usize),
+    /// A tab was opened, picked or closed, see `App::update_tabs`
+    Tab(TabMsg),
+    /// Set the number of handicap stones of the next game
+    SetNewHandicap(usize),
+    /// Toggle whether the handicap of the next game is placed freely
+    ToggleFreeHandicap,
+    /// Toggle whether red joins black and white in the next game
+    ToggleThreePlayers,
+    /// Set the rank of a player of the next game, which suggests a handicap
+    SetNewRank(Player, String),
+    /// Start a game of the preset with the given index
+    StartPreset(usize),
+    /// Keep the board size, handicap and rules picked on the start screen as
+    /// a preset
+    SavePreset,
+    RemovePreset(usize),
+    /// A click on the go board, fields are the x and y values of the click
+    /// in board units, where point `(0, 0)` is at `(0.0, 0.0)` and the
+    /// lines are one unit apart
     Click {
-        x: i32,
-        y: i32,
+        x: f64,
+        y: f64,
     },
+    /// A mouse moved over the board, to the given point in board units
+    Hover {
+        x: f64,
+        y: f64,
+    },
+    /// The mouse left the board
+    HoverEnd,
+    /// A finger touched the board, at the given point in board units
+    TouchStart {
+        x: f64,
+        y: f64,
+    },
+    /// The touching finger moved, at the given point in board units
+    TouchMove {
+        x: f64,
+        y: f64,
+    },
+    /// The touching finger was lifted
+    TouchEnd,
+    /// The browser took over the touch, for example to scroll
+    TouchCancel,
+    /// The board has been touched long enough to cancel the preview
+    LongPress,
+    /// Move the keyboard cursor by the given number of points
+    MoveCursor {
+        dx: isize,
+        dy: isize,
+    },
+    /// Preview or play a move at the keyboard cursor
+    SelectCursor,
     /// A player passes
     Pass,
+    /// Take back the last move
+    Undo,
+    /// Show the position the given number of moves before or after the shown
+    /// one
+    StepHistory(isize),
     HideSidebar,
     ShowSidebar,
+    DismissToast,
+    /// Show a message that went wrong out of the way of the board
+    ShowToast(String),
+    /// Toggles the liberty count overlay
+    ToggleLiberties,
+    /// Toggles the common corner continuations
+    ToggleJoseki,
+    /// Toggles how often each point was played in the opening
+    ToggleFuseki,
+    /// Points out a reasonable move for the player to move
+    Hint,
+    HideHint,
+    /// Toggles the heatmap of who is likely to end up with each point
+    ToggleOwnership,
+    /// Estimates the ownership of the shown position, for the heatmap when
+    /// the engine hasn't told it
+    EstimateOwnership,
+    /// Toggles the shading of the areas each player has a hold on
+    ToggleInfluence,
+    /// Toggles the marks on real and false eyes
+    ToggleEyes,
+    /// Turn the board a quarter clockwise on screen
+    RotateBoard,
+    /// Mirror the board left to right on screen, or undo it
+    MirrorBoard,
+    /// Starts or stops the built-in engine going through the game
+    ToggleEngine,
+    /// Lets the engine think for a moment, see `EngineReview::step`
+    EngineStep,
+    SetKataGoUrl(String),
+    /// Go through the moves that lost the most, see `MistakeReview`
+    ReviewMistakes,
+    /// Show the position before the mistake at the given index of the
+    /// review
+    ShowMistake(usize),
+    /// Try out moves from the position before the shown mistake
+    PracticeMistake,
+    EndMistakeReview,
+    /// Look for ladders and snapbacks in the game, see `find_tactics`
+    FindTactics,
+    /// Show or hide how the tactic at the given index of those found plays
+    /// out
+    ExplainTactic(usize),
+    /// Has KataGo go through the game instead of the built-in engine
+    ConnectKataGo,
+    KataGoAnalysis(katago::Analysis),
+    /// Switch to the next way of numbering the stones
+    CycleMoveNumbers,
+    CycleStoneDisplay,
+    ToggleReveal,
+    /// The player to move resigns
+    Resign,
+    /// Both players agree on the dead stones, which ends the game
+    AcceptScore,
+    /// Close the result of the game to look back through its moves
+    ReviewGame,
+    /// Download the record of the game
+    SaveSgf,
+    /// Toggle the coordinates on exported images
+    ToggleImageCoordinates,
+    /// Toggle the move numbers on exported images
+    ToggleImageMoveNumbers,
+    /// Download the shown position as an image, a PNG or an SVG
+    ExportImage {
+        png: bool,
+    },
+    /// Print the game as a kifu
+    PrintKifu,
+    /// Start a new game with the same settings, with the colors swapped or
+    /// picked by nigiri
+    Rematch {
+        nigiri: bool,
+    },
+    /// Show the position right after the move at the given half turn
+    JumpTo(usize),
+    /// Show the position after the given number of moves
+    ShowMoves(usize),
+    /// Stop viewing an earlier position and go back to the current one
+    ReturnToGame,
+    /// Pick the next corner to line up when moving to another board size
+    CycleCorner,
+    /// Start a new game with the shown position copied onto a board of the
+    /// given size
+    Transplant(usize),
+    /// Save the current game to the library
+    SaveToLibrary,
+    /// Download every game in the library as a zip of SGF files
+    ExportLibrary,
+    /// Stop showing why the last move was rejected
+    ClearRejection,
+    /// Play the previewed move
+    ConfirmMove,
+    /// Only let moves be played in a corner of the board
+    LockCorner(Corner),
+    /// Start or stop picking the open points by clicking them
+    ToggleRegionPicking,
+    /// Open the whole board for play again
+    UnlockBoard,
+    /// Drop the previewed move
+    CancelMove,
+    /// Try out moves on a copy of the shown position
+    StartAnalysis,
+    /// Go back to the game, saving the tried out line to the library when
+    /// `save` is set
+    EndAnalysis {
+        save: bool,
+    },
+    /// Put stones down freely on a copy of the shown position
+    StartDemo,
+    /// A button of the demo board was pressed
+    Demo(DemoControl),
+    /// Replace the comment and evaluation of the shown position
+    Annotate(Annotation),
+    /// Read the SGF files picked in a file input into the library
+    ImportFiles(HtmlInputElement),
+    /// A file picked for import has been read
+    FileRead {
+        name: String,
+        contents: Result<String, String>,
+    },
+    /// Turn the bot that imitates the library games on or off
+    ToggleBot,
+    /// Pick the player of the library games the bot imitates
+    SetBotPlayer(String),
+    /// Pick the library entry at the given index for comparison, the
+    /// comparison opens once two are picked
+    PickForComparison(usize),
+    /// Step through a compared game, `side` is 0 for the left one and 1 for
+    /// the right one
+    CompareStep {
+        side: usize,
+        delta: isize,
+    },
+    /// Toggle whether the compared games are stepped through together
+    ToggleLinkedNavigation,
+    CloseComparison,
+    /// Set the margin to reach in practice games, see `Practice::goal`
+    SetPracticeGoal(i32),
+    /// Start a practice game against the library bot
+    StartPractice,
+    EndPractice,
+    /// Start solving the bundled problem at the given index
+    StartProblem(usize),
+    /// Start the problem being solved over
+    RetryProblem,
+    EndProblem,
+    /// A button of the tournament panel was pressed
+    Tournament(TournamentControl),
+    /// Open the library entry with the given index in a new tab to go over
+    ReviewLibraryEntry(usize),
+    /// Redraw the clock, sent every second
+    Tick,
+    /// The page was hidden, or shown again when `false`
+    PageHidden(bool),
+    /// The window crossed into another layout
+    LayoutChanged(Layout),
+    ToggleClockPause,
+    /// Pause the clock after being asked about being away
+    AcceptIdlePause,
+    ToggleMute,
+    ChangeSettings(Settings),
+    SetRelayUrl(String),
+    StartBroadcast,
+    StopBroadcast,
+    /// Toggle whether viewers of the broadcast see the liberty overlay
+    ToggleShareLiberties,
+    /// A viewer received a frame, which is shown after a delay
+    FrameReceived(Frame),
+    /// Show a received frame once its delay is over
+    ApplyFrame(Frame),
+    RelayError(String),
+    /// A spectator received a frame from one of the players
+    SpectatorFrame(Frame),
+    /// A spectator received a chat message
+    SpectatorChat(ChatMessage),
+    /// A spectator heard from another spectator
+    SpectatorPresence(Presence),
+    /// Let the others know this spectator is still there
+    AnnouncePresence,
+    SetChatDraft(String),
+    /// Send a chat message on the shown channel
+    SendChat(String),
+    /// Start or stop following the spectators' chat
+    ToggleSpectatorChat,
+    ShowChatChannel(ChatChannel),
+    /// Something happened to a correspondence game, see
+    /// `App::update_correspondence`
+    Correspondence(CorrespondenceMsg),
+    /// Something happened to an OGS game, see `App::update_ogs`
+    Ogs(OgsMsg),
+}
+
+/// How far from a point a click or tap may land and still select it, in the
+/// same units. Mouse clicks land within a stone's width of the point, so a
+/// click halfway between two points selects neither. Taps are more
+/// forgiving.
+const MOUSE_SNAP_RADIUS: f64 = 0.45;
+const TOUCH_SNAP_RADIUS: f64 = 1.0;
+/// How far a finger may move before the touch counts as a drag instead of a tap
+const TOUCH_SLOP: f64 = 0.5;
+const LONG_PRESS_MS: u32 = 500;
+/// How long without any input before offering to pause the clock
+const IDLE_MS: f64 = 2.0 * 60.0 * 1000.0;
+
+/// How long viewers wait before showing a broadcast frame
+const BROADCAST_DELAY_MS: u32 = 3000;
+
+/// How many playouts the search behind a hint gets in the middle game, when
+/// neither the engine nor the bot has a move ready, see `Phase::visits`
+const HINT_VISITS: usize = 64;
+/// How long the point of a hint pulses
+const HINT_MS: u32 = 2500;
+
+/// A finger on the board that may still turn into a tap.
+struct TouchState {
+    start: (f64, f64),
+    /// Fires `Msg::LongPress`, dropping it cancels the long press
+    _long_press: Timeout,
+}
+
+/// An engine the player plays against, see `App::library_bot`.
+struct Bot {
+    /// The color it plays
+    color: Player,
+    engine: Box<dyn Engine>,
+    /// Who it plays like, for the controls
+    name: String,
+}
+
+/// A game against the library bot with a goal for the final score.
+struct Practice {
+    /// The least the player has to be ahead by at the end, negative to allow
+    /// losing by less than that
+    goal: i32,
+    /// How far the player ended up ahead, once the game is over
+    margin: Option<f64>,
+}
+
+/// A problem being solved, see `App::problem`.
+struct ProblemSession {
+    /// The index of the problem in `App::problems`
+    index: usize,
+    attempt: Attempt,
+    /// Whether the problem was solved, once the attempt is over
+    solved: Option<bool>,
+}
+
+/// Moves being tried out on a copy of the game, see `App::analysis`.
+struct Analysis {
+    /// The real game, put aside while exploring
+    game: GoGame,
+    /// How many moves of the real game the copy started with
+    forked_at: usize,
+}
+
+/// A game followed from this device without taking part in it.
+enum Watching {
+    /// A broadcast, shown a little behind
+    Broadcast { _connection: RelayConnection },
+    /// A correspondence game, shown as it is played
+    Room(Spectating),
+}
+
+/// A broadcast of this board that is in progress.
+struct Broadcast {
+    connection: RelayConnection,
+    channel: String,
+    sequence: u64,
+    /// Whether viewers get to see the liberty overlay
+    share_liberties: bool,
+    /// The game record and overlay setting that were sent last
+    last_sent: Option<(String, bool)>,
+}
+
+struct App {
+    board: GoGame,
+    /// The games open in tabs, in tab order. The entry of the shown tab is
+    /// left empty while its game is in the fields of `App`
+    sessions: Vec<Session>,
+    /// The index of the shown tab in `sessions`
+    active_session: usize,
+    preview: Option<(usize, usize)>,
+    /// The legal point under the mouse, shown with a faint stone
+    hover: Option<(usize, usize)>,
+    /// The point highlighted for keyboard play, hidden until an arrow key is
+    /// pressed
+    cursor: Option<(usize, usize)>,
+    touch: Option<TouchState>,
+    sidebar_shown: bool,
+    toast: Option<String>,
+    show_liberties: bool,
+    /// Whether the known continuations of the corners are shown, from
+    /// `joseki`
+    show_joseki: bool,
+    joseki: JosekiBook,
+    /// Whether the moves played next in the openings of `fuseki` are shown,
+    /// during the first `FUSEKI_MOVES` moves
+    show_fuseki: bool,
+    fuseki: FusekiBook,
+    /// Whether the ownership heatmap is shown
+    show_ownership: bool,
+    /// The estimated ownership of the position with the given hash, for
+    /// the heatmap
+    ownership_estimate: Option<(u64, Vec<f64>)>,
+    /// Whether the influence of both players is shaded
+    show_influence: bool,
+    /// Whether real and false eyes are marked
+    show_eyes: bool,
+    /// How the board is turned on screen, see `Orientation`
+    orientation: Orientation,
+    /// Set while the built-in engine evaluates the positions of `board`
+    engine: Option<EngineReview>,
+    /// The next step of `engine`, waiting for the page to be drawn
+    engine_step: Option<Timeout>,
+    /// Address of the proxy KataGo is reached through, see `KataGoClient`
+    katago_url: String,
+    /// Set while going through the biggest mistakes of the game
+    mistake_review: Option<MistakeReview>,
+    /// The ladders and snapbacks of the reviewed game, along with the key
+    /// of the game they were found in, see `tactics_key`
+    tactics: Option<((usize, u64), Vec<Tactic>)>,
+    /// The index of the tactic whose moves are drawn on the board
+    tactic_shown: Option<usize>,
+    move_numbers: MoveNumbers,
+    /// How the stones are drawn, for blind and one-color go
+    stone_display: StoneDisplay,
+    /// Set while the real stones are shown in spite of `stone_display`
+    stones_revealed: bool,
+    /// An earlier position being looked at from the timeline, along with the
+    /// number of moves played on it.
+    viewing: Option<(usize, GoPosition)>,
+    transplant_corner: Corner,
+    /// The points a teacher left open for play, the rest are locked. Kept
+    /// for new games of the same size, see `region`.
+    region: Option<Region>,
+    /// Set while clicks on the board open and lock points of `region`
+    /// instead of playing
+    picking_region: bool,
+    library: Vec<LibraryEntry>,
+    /// Statistics over the games of the library, see `add_to_library`
+    library_stats: Stats,
+    /// The names of the players of the library games, see `add_to_library`
+    library_players: BTreeSet<String>,
+    ratings: Ratings,
+    /// Set once the game on the board has been rated, see `rate_game`
+    rated: bool,
+    /// The match or round-robin being run, kept between visits
+    tournament: Option<Tournament>,
+    /// The game of the tournament on the board, as its round and its index
+    /// in the round, until its result is recorded
+    tournament_game: Option<(usize, usize)>,
+    /// The point of the last rejected move and why it was rejected, shown on
+    /// the board until `rejection_timeout` fires.
+    rejection: Option<(usize, MoveError)>,
+    rejection_timeout: Option<Timeout>,
+    /// Files still being read for import
+    file_readers: Vec<FileReader>,
+    pending_imports: usize,
+    /// The bot playing against the player
+    bot: Option<Bot>,
+    /// The player of the library games the bot imitates, one of
+    /// `library_players`
+    bot_player: Option<String>,
+    /// Two library games shown side by side in place of the board
+    comparison: Option<Comparison>,
+    /// The library entry picked first for a comparison
+    compare_pick: Option<usize>,
+    practice: Option<Practice>,
+    /// The width and height of the board picked on the start screen
+    new_board_size: (usize, usize),
+    /// The handicap picked on the start screen, and how it is placed
+    new_handicap: usize,
+    new_handicap_placement: HandicapPlacement,
+    /// Whether the next game is one of three players, see
+    /// `GoGame::with_three_players`
+    new_three_players: bool,
+    /// The ranks of black and white entered on the start screen, empty when
+    /// not given
+    new_ranks: (String, String),
+    /// The komi suggested for the ranks, `None` for that of the rules
+    new_komi: Option<f64>,
+    /// The goal the next practice game is started with
+    practice_goal: i32,
+    /// The number of practice goals met in a row
+    practice_streak: u32,
+    /// Whether the result of the finished game is shown
+    result_shown: bool,
+    /// How many hints were given in the game, see `HintLimit`
+    hints_used: usize,
+    /// The point of the hint being shown, until it stops pulsing
+    hint: Option<((usize, usize), Timeout)>,
+    /// What goes on exported images besides the stones
+    image_options: ImageOptions,
+    /// Set while the kifu is drawn for printing, the print dialog opens
+    /// once it is on the page
+    printing_kifu: bool,
+    /// The problems that come with the app
+    problems: Vec<Problem>,
+    /// Set while solving a problem, which is then on `board`
+    problem: Option<ProblemSession>,
+    /// Set while trying out moves: `board` is then a scratch copy of the
+    /// game, which is left as it was
+    analysis: Option<Analysis>,
+    /// Set while using the board for a demonstration: `board` then holds the
+    /// demonstrated position, and the game is left as it was
+    demo: Option<Demo>,
+    clock: Clock,
+    _clock_tick: Interval,
+    activity: ActivityWatcher,
+    layout: Layout,
+    _layout_watcher: LayoutWatcher,
+    /// Set while asking whether to pause the clock after a long time without
+    /// input
+    idle_offer: bool,
+    settings: Settings,
+    audio: Audio,
+    /// The number of moves of the game that sounds were played for
+    sounded_moves: usize,
+    /// Address of the relay used to broadcast
+    relay_url: String,
+    broadcast: Option<Broadcast>,
+    /// Set when watching someone else's broadcast or game, in which case the
+    /// board is read-only
+    watching: Option<Watching>,
+    /// Delays of the frames waiting to be shown, oldest first
+    frame_timeouts: VecDeque<Timeout>,
+    /// Sequence number of the newest frame shown
+    last_frame: u64,
+    /// Set while playing a correspondence game on `board`
+    correspondence: Option<Correspondence>,
+    /// The stored correspondence games, see `SavedGame::load_all`
+    correspondence_games: Vec<SavedGame>,
+    /// The chat message being written
+    chat_draft: String,
+    /// Whose chat is shown, the spectators' only while following it
+    chat_channel: ChatChannel,
+    /// The OGS account logged into, see `ogs::log_in`
+    ogs_login: Option<OgsLogin>,
+    /// Set while playing an OGS game on `board`
+    ogs: Option<OgsPlay>,
 }
 
-struct App {
-    board_ref: NodeRef,
-    board: GoGame,
-    preview: Option<(usize, usize)>,
-    sidebar_shown: bool,
-}
+impl App {
+    fn create(ctx: &AppContext) -> Self {
+        // A link of the form `#watch=<channel>@<relay url>` opens a broadcast,
+        // and `#spectate=<game id>@<relay url>` a correspondence game
+        let link_to = |prefix: &str| {
+            ctx.props()
+                .hash
+                .strip_prefix(prefix)
+                .and_then(|link| link.split_once('@'))
+        };
+        let watching = if let Some((channel, url)) = link_to("#watch=") {
+            Some(
+                RelayConnection::watch(
+                    url,
+                    channel,
+                    ctx.link().batch_callback(|message| match message {
+                        Incoming::Frame(frame) => Some(Msg::FrameReceived(frame)),
+                        _ => None,
+                    }),
+                    ctx.link().callback(Msg::RelayError),
+                )
+                .map(|connection| Watching::Broadcast {
+                    _connection: connection,
+                }),
+            )
+        } else if let Some((id, url)) = link_to("#spectate=") {
+            Some(
+                Spectating::join(
+                    id.to_owned(),
+                    url,
+                    ctx.link().batch_callback(|message| match message {
+                        Incoming::Frame(frame) => Some(Msg::SpectatorFrame(frame)),
+                        Incoming::Chat(message) => Some(Msg::SpectatorChat(message)),
+                        Incoming::Presence(presence) => Some(Msg::SpectatorPresence(presence)),
+                        // The players send their games again every so often
+                        Incoming::Resync(_) | Incoming::Reconnected => None,
+                    }),
+                    ctx.link().callback(|_| Msg::AnnouncePresence),
+                    ctx.link().callback(Msg::RelayError),
+                )
+                .map(Watching::Room),
+            )
+        } else {
+            None
+        };
+        let settings = Settings::load();
+        theme::apply(&settings);
+        settings.language.apply();
+
+        let (watching, toast) = match watching {
+            Some(Ok(connection)) => (Some(connection), None),
+            Some(Err(e)) => (None, Some(e)),
+            None => (None, None),
+        };
+        // A correspondence game that was open when the page was left is
+        // opened again, and catches up with the opponent from there
+        if let (None, Some(id)) = (&watching, shown_game()) {
+            ctx.link()
+                .send_message(Msg::Correspondence(CorrespondenceMsg::Control(
+                    CorrespondenceControl::Open(id),
+                )));
+        }
+
+        App {
+            board: GoGame::new(if watching.is_some() { 19 } else { 0 }),
+            sessions: vec![Session::default()],
+            active_session: 0,
+            preview: None,
+            hover: None,
+            cursor: None,
+            touch: None,
+            sidebar_shown: false,
+            toast,
+            show_liberties: false,
+            show_joseki: false,
+            joseki: JosekiBook::bundled(),
+            show_fuseki: false,
+            fuseki: FusekiBook::bundled(),
+            show_ownership: false,
+            ownership_estimate: None,
+            show_influence: false,
+            show_eyes: false,
+            orientation: Orientation::default(),
+            engine: None,
+            engine_step: None,
+            katago_url: String::new(),
+            mistake_review: None,
+            tactics: None,
+            tactic_shown: None,
+            move_numbers: MoveNumbers::Off,
+            stone_display: StoneDisplay::Normal,
+            stones_revealed: false,
+            viewing: None,
+            transplant_corner: Corner::TopLeft,
+            region: None,
+            picking_region: false,
+            library: Vec::new(),
+            library_stats: Stats::new(),
+            library_players: BTreeSet::new(),
+            ratings: Ratings::load(),
+            rated: false,
+            tournament: tournament_panel::load(),
+            tournament_game: None,
+            rejection: None,
+            rejection_timeout: None,
+            file_readers: Vec::new(),
+            pending_imports: 0,
+            bot: None,
+            bot_player: None,
+            comparison: None,
+            compare_pick: None,
+            practice: None,
+            new_board_size: (19, 19),
+            new_handicap: 0,
+            new_handicap_placement: HandicapPlacement::Fixed,
+            new_three_players: false,
+            new_ranks: (String::new(), String::new()),
+            new_komi: None,
+            practice_goal: 0,
+            practice_streak: 0,
+            result_shown: false,
+            hints_used: 0,
+            hint: None,
+            image_options: ImageOptions::default(),
+            printing_kifu: false,
+            problems: Problem::bundled(),
+            problem: None,
+            analysis: None,
+            demo: None,
+            clock: Clock::new(),
+            _clock_tick: {
+                let link = ctx.link().clone();
+                Interval::new(1000, move || link.send_message(Msg::Tick))
+            },
+            activity: ActivityWatcher::new(ctx.link().callback(Msg::PageHidden)),
+            layout: Layout::current(),
+            _layout_watcher: LayoutWatcher::new(ctx.link().callback(Msg::LayoutChanged)),
+            idle_offer: false,
+            settings,
+            audio: Audio::new(),
+            sounded_moves: 0,
+            relay_url: String::new(),
+            broadcast: None,
+            watching,
+            frame_timeouts: VecDeque::new(),
+            last_frame: 0,
+            correspondence: None,
+            correspondence_games: SavedGame::load_all(),
+            chat_draft: String::new(),
+            chat_channel: ChatChannel::Players,
+            ogs_login: OgsLogin::load(),
+            ogs: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &AppContext, msg: Msg) -> bool {
+        match msg {
+            Msg::Tab(msg) => self.update_tabs(ctx, msg),
+            Msg::MakeBoard { width, height }
+                if [width, height]
+                    .iter()
+                    .all(|side| (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(side)) =>
+            {
+                self.keep_shown_game();
+                self.result_shown = false;
+                self.rated = false;
+                self.tournament_game = None;
+                self.hints_used = 0;
+                self.board = GoGame::rectangular(width, height)
+                    .with_rules(self.settings.rules.ruleset())
+                    .with_handicap(self.new_handicap, self.new_handicap_placement);
+                if self.new_three_players {
+                    self.board = self.board.clone().with_three_players();
+                }
+                if let Some(komi) = self.new_komi {
+                    self.board.info.komi = komi;
+                }
+                self.board.info.black_rank = self.new_ranks.0.trim().to_owned();
+                self.board.info.white_rank = self.new_ranks.1.trim().to_owned();
+                self.correspondence = None;
+                self.ogs = None;
+                self.analysis = None;
+                self.demo = None;
+                self.problem = None;
+                self.viewing = None;
+                navigate(ctx, Route::Play);
+                true
+            }
+            Msg::MakeBoard { .. } => {
+                self.keep_shown_game();
+                self.result_shown = false;
+                self.rated = false;
+                self.tournament_game = None;
+                self.hints_used = 0;
+                self.board = GoGame::default().with_rules(self.settings.rules.ruleset());
+                self.correspondence = None;
+                self.ogs = None;
+                self.analysis = None;
+                self.demo = None;
+                self.problem = None;
+                self.viewing = None;
+                navigate(ctx, Route::Play);
+                true
+            }
+            Msg::StartPreset(index) => {
+                let preset = match self.settings.presets.0.get(index) {
+                    Some(preset)
+                        if [preset.width, preset.height]
+                            .iter()
+                            .all(|side| (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(side)) =>
+                    {
+                        preset.clone()
+                    }
+                    _ => return false,
+                };
+                self.keep_shown_game();
+                self.result_shown = false;
+                self.rated = false;
+                self.tournament_game = None;
+                self.hints_used = 0;
+                self.board = GoGame::rectangular(preset.width, preset.height)
+                    .with_rules(preset.rules.ruleset())
+                    .with_handicap(preset.handicap.min(MAX_STONES), self.new_handicap_placement);
+                self.board.info.main_time = preset.minutes.map(|minutes| minutes as f64 * 60.0);
+                self.correspondence = None;
+                self.ogs = None;
+                self.analysis = None;
+                self.demo = None;
+                self.problem = None;
+                self.viewing = None;
+                navigate(ctx, Route::Play);
+                true
+            }
+            Msg::SavePreset => {
+                let (width, height) = self.new_board_size;
+                let rules = self.settings.rules;
+                let mut name = format!("{}x{} {}", width, height, rules.name());
+                if self.new_handicap > 0 {
+                    name.push_str(&format!(", {} stones", self.new_handicap));
+                }
+                self.settings.presets.0.push(Preset {
+                    name,
+                    width,
+                    height,
+                    rules,
+                    handicap: self.new_handicap,
+                    minutes: None,
+                });
+                self.settings.save();
+                true
+            }
+            Msg::RemovePreset(index) => {
+                if index < self.settings.presets.0.len() {
+                    self.settings.presets.0.remove(index);
+                    self.settings.save();
+                }
+                true
+            }
+            Msg::Click { x, y } => match self.snap(x, y, MOUSE_SNAP_RADIUS) {
+                Some((x, y)) => {
+                    self.cursor = None;
+                    self.select_point(ctx, x, y)
+                }
+                None => false,
+            },
+            Msg::Hover { x, y } => {
+                let hover = self
+                    .snap(x, y, MOUSE_SNAP_RADIUS)
+                    .filter(|&(x, y)| self.can_play_at(x, y));
+                let changed = hover != self.hover;
+                self.hover = hover;
+                changed
+            }
+            Msg::HoverEnd => self.hover.take().is_some(),
+            Msg::MoveCursor { dx, dy } => {
+                // The arrows point the way the board is shown
+                let (dx, dy) = self.shown_orientation().unshow_step(dx, dy);
+                let (last_x, last_y) = (self.board.width() - 1, self.board.height() - 1);
+                let (x, y) = self.cursor.unwrap_or((last_x / 2, last_y / 2));
+                self.cursor = Some((
+                    x.saturating_add_signed(dx).min(last_x),
+                    y.saturating_add_signed(dy).min(last_y),
+                ));
+                true
+            }
+            Msg::SelectCursor => match self.cursor {
+                Some((x, y)) => self.select_point(ctx, x, y),
+                None => false,
+            },
+            Msg::TouchStart { x, y } => {
+                let link = ctx.link().clone();
+                self.touch = Some(TouchState {
+                    start: (x, y),
+                    _long_press: Timeout::new(LONG_PRESS_MS, move || {
+                        link.send_message(Msg::LongPress)
+                    }),
+                });
+                false
+            }
+            Msg::TouchMove { x, y } => {
+                if let Some(touch) = &self.touch {
+                    let (start_x, start_y) = touch.start;
+                    if (x - start_x).hypot(y - start_y) > TOUCH_SLOP {
+                        self.touch = None;
+                    }
+                }
+                false
+            }
+            Msg::TouchEnd => match self.touch.take() {
+                Some(TouchState { start: (x, y), .. }) => {
+                    match self.snap(x, y, TOUCH_SNAP_RADIUS) {
+                        Some((x, y)) => self.select_point(ctx, x, y),
+                        None => false,
+                    }
+                }
+                None => false,
+            },
+            Msg::TouchCancel => {
+                self.touch = None;
+                false
+            }
+            Msg::LongPress => {
+                self.touch = None;
+                self.preview.take().is_some()
+            }
+            Msg::Pass | Msg::Undo if self.watching.is_some() => false,
+            Msg::Pass if self.demo.is_some() => false,
+            // Problems are started over instead
+            Msg::Pass | Msg::Undo | Msg::Resign if self.problem.is_some() => false,
+            Msg::Undo if self.demo.is_some() => self
+                .demo
+                .as_mut()
+                .is_some_and(|demo| demo.undo(&mut self.board)),
+            // A finished practice game counts as played
+            Msg::Undo if self.practice_finished() && self.analysis.is_none() => false,
+            // Analysis can't take back the moves of the game it started from
+            Msg::Undo
+                if self
+                    .analysis
+                    .as_ref()
+                    .is_some_and(|analysis| self.board.move_count() <= analysis.forked_at) =>
+            {
+                false
+            }
+            Msg::Pass => {
+                if self.waiting_for_opponent() {
+                    return false;
+                }
+                self.preview = None;
+                self.board.pass();
+                self.send_correspondence_move();
+                self.send_ogs_move(None);
+                self.play_bot_move();
+                self.pass_while_stuck();
+                self.suggest_dead_stones();
+                self.score_practice();
+                self.settle_game();
+                true
+            }
+            // The opponent already has the move
+            Msg::Undo
+                if (self.correspondence.is_some() || self.ogs.is_some())
+                    && self.analysis.is_none() =>
+            {
+                false
+            }
+            Msg::Undo => {
+                self.preview = None;
+                self.viewing = None;
+                self.rejection = None;
+                self.result_shown = false;
+                self.board.undo();
+                // Take back the bot's answer along with the move it answered
+                if let Some(bot) = self.bot.as_ref().filter(|_| self.analysis.is_none()) {
+                    if self.board.next_player == bot.color {
+                        self.board.undo();
+                    }
+                }
+                true
+            }
+            Msg::StepHistory(delta) => {
+                let move_count = self.board.move_count();
+                let shown = self
+                    .viewing
+                    .as_ref()
+                    .map_or(move_count, |(moves, _)| *moves);
+                let moves = shown.saturating_add_signed(delta).min(move_count);
+                self.preview = None;
+                self.viewing = if moves == move_count {
+                    None
+                } else {
+                    Some((moves, self.board.position_at(moves)))
+                };
+                moves != shown
+            }
+            Msg::HideSidebar => {
+                self.sidebar_shown = false;
+                true
+            }
+            Msg::ShowSidebar => {
+                self.sidebar_shown = true;
+                true
+            }
+            Msg::PrintKifu => {
+                self.printing_kifu = true;
+                true
+            }
+            Msg::ShowToast(message) => {
+                self.toast = Some(message);
+                true
+            }
+            Msg::DismissToast => {
+                self.toast = None;
+                true
+            }
+            Msg::CycleMoveNumbers => {
+                self.move_numbers = match self.move_numbers {
+                    MoveNumbers::Off => MoveNumbers::Last,
+                    MoveNumbers::Last => MoveNumbers::All,
+                    MoveNumbers::All => MoveNumbers::Off,
+                };
+                true
+            }
+            Msg::CycleStoneDisplay => {
+                self.stone_display = match self.stone_display {
+                    StoneDisplay::Normal => StoneDisplay::Blind,
+                    StoneDisplay::Blind => StoneDisplay::OneColor,
+                    StoneDisplay::OneColor => StoneDisplay::Normal,
+                };
+                self.stones_revealed = false;
+                true
+            }
+            Msg::ToggleReveal => {
+                self.stones_revealed = !self.stones_revealed;
+                true
+            }
+            Msg::ToggleLiberties => {
+                self.show_liberties = !self.show_liberties;
+                true
+            }
+            Msg::ToggleJoseki => {
+                self.show_joseki = !self.show_joseki;
+                true
+            }
+            Msg::ToggleFuseki => {
+                self.show_fuseki = !self.show_fuseki;
+                true
+            }
+            Msg::Hint => {
+                if self.settings.hints.left(self.hints_used) == Some(0)
+                    || self.viewing.is_some()
+                    || self.watching.is_some()
+                    || self.board.is_over()
+                    || self.waiting_for_opponent()
+                {
+                    return false;
+                }
+                match self.suggest_move() {
+                    Some(point) => {
+                        self.hints_used += 1;
+                        let link = ctx.link().clone();
+                        self.hint = Some((
+                            point,
+                            Timeout::new(HINT_MS, move || link.send_message(Msg::HideHint)),
+                        ));
+                    }
+                    None => {
+                        self.toast = Some(String::from(
+                            "There is nothing left to play, passing is fine.",
+                        ))
+                    }
+                }
+                true
+            }
+            Msg::HideHint => self.hint.take().is_some(),
+            Msg::ToggleOwnership => {
+                self.show_ownership = !self.show_ownership;
+                true
+            }
+            Msg::EstimateOwnership => {
+                let position = self.shown_position();
+                let hash = position.save().hash();
+                if self.ownership_estimate.as_ref().map(|(key, _)| *key) == Some(hash) {
+                    return false;
+                }
+                let player = self.player_after(self.shown_moves());
+                self.ownership_estimate = Some((hash, ownership(position, player, PLAYOUTS)));
+                true
+            }
+            Msg::ToggleInfluence => {
+                self.show_influence = !self.show_influence;
+                true
+            }
+            Msg::ToggleEyes => {
+                self.show_eyes = !self.show_eyes;
+                true
+            }
+            Msg::RotateBoard => {
+                self.orientation = self.orientation.rotated();
+                true
+            }
+            Msg::MirrorBoard => {
+                self.orientation = self.orientation.toggle_mirror();
+                true
+            }
+            Msg::ToggleEngine => {
+                self.engine = match self.engine {
+                    Some(_) => None,
+                    None => Some(EngineReview::built_in()),
+                };
+                self.engine_step = None;
+                true
+            }
+            Msg::ReviewMistakes => {
+                let review = match &self.engine {
+                    Some(engine) => engine.mistake_review(),
+                    None => return false,
+                };
+                if review.mistakes.is_empty() {
+                    self.toast = Some(String::from("No move lost anything worth reviewing."));
+                } else {
+                    ctx.link().send_message(Msg::ShowMistake(0));
+                    self.mistake_review = Some(review);
+                }
+                true
+            }
+            Msg::ShowMistake(index) => {
+                let moves = match &mut self.mistake_review {
+                    Some(review) if index < review.mistakes.len() => {
+                        review.current = index;
+                        review.mistakes[index].0.moves
+                    }
+                    _ => return false,
+                };
+                ctx.link().send_message(Msg::ShowMoves(moves));
+                true
+            }
+            Msg::PracticeMistake => {
+                let moves = match &self.mistake_review {
+                    Some(review) => review.mistakes[review.current].0.moves,
+                    None => return false,
+                };
+                ctx.link().send_message(Msg::ShowMoves(moves));
+                ctx.link().send_message(Msg::StartAnalysis);
+                false
+            }
+            Msg::EndMistakeReview => self.mistake_review.take().is_some(),
+            Msg::FindTactics => {
+                let key = self.tactics_key();
+                if self.tactics.as_ref().map(|(found, _)| *found) == Some(key) {
+                    return false;
+                }
+                self.tactics = Some((key, find_tactics(&self.board)));
+                self.tactic_shown = None;
+                true
+            }
+            Msg::ExplainTactic(index) => {
+                let tactic = match &self.tactics {
+                    Some((_, tactics)) => match tactics.get(index) {
+                        Some(tactic) => tactic,
+                        None => return false,
+                    },
+                    None => return false,
+                };
+                if self.tactic_shown == Some(index) && self.shown_moves() == tactic.moves {
+                    self.tactic_shown = None;
+                    self.toast = None;
+                    return true;
+                }
+                self.tactic_shown = Some(index);
+                self.toast = Some(String::from(tactic.kind.explanation()));
+                ctx.link().send_message(Msg::ShowMoves(tactic.moves));
+                true
+            }
+            Msg::SetKataGoUrl(url) => {
+                self.katago_url = url;
+                false
+            }
+            Msg::ConnectKataGo => {
+                match KataGoClient::connect(
+                    &self.katago_url,
+                    ctx.link().callback(Msg::KataGoAnalysis),
+                    ctx.link().callback(Msg::ShowToast),
+                ) {
+                    Ok(client) => {
+                        self.engine = Some(EngineReview::katago(client));
+                        self.engine_step = None;
+                    }
+                    Err(e) => self.toast = Some(e),
+                }
+                true
+            }
+            Msg::KataGoAnalysis(analysis) => {
+                if let Some(engine) = self.engine.as_mut().filter(|e| e.asked_for(&analysis)) {
+                    engine.receive(analysis);
+                    return true;
+                }
+                // The review of a tab that isn't shown keeps what it asked for
+                let hidden = self
+                    .sessions
+                    .iter_mut()
+                    .filter_map(|session| session.engine.as_mut())
+                    .find(|engine| engine.asked_for(&analysis));
+                if let Some(engine) = hidden {
+                    engine.receive(analysis);
+                }
+                false
+            }
+            Msg::EngineStep => {
+                self.engine_step = None;
+                let shown = self.shown_moves();
+                match &mut self.engine {
+                    Some(engine) => {
+                        engine.sync(&self.board);
+                        engine.step(&self.board, shown, js_sys::Date::now() + STEP_MS);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Msg::Resign => {
+                if self.waiting_for_opponent() {
+                    return false;
+                }
+                self.preview = None;
+                self.board.resign();
+                self.send_correspondence_move();
+                self.send_ogs_resignation();
+                self.score_practice();
+                self.show_result();
+                self.settle_game();
+                true
+            }
+            Msg::AcceptScore => {
+                self.result_shown = self.board.is_scoring();
+                self.settle_game();
+                true
+            }
+            Msg::ReviewGame => {
+                self.result_shown = false;
+                self.viewing = Some((0, self.board.position_at(0)));
+                navigate(ctx, Route::Review);
+                true
+            }
+            Msg::SaveSgf => {
+                if let Err(e) = download_sgf(&self.board) {
+                    self.toast = Some(e);
+                }
+                true
+            }
+            Msg::ToggleImageCoordinates => {
+                self.image_options.coordinates = !self.image_options.coordinates;
+                true
+            }
+            Msg::ToggleImageMoveNumbers => {
+                self.image_options.move_numbers = !self.image_options.move_numbers;
+                true
+            }
+            Msg::ExportImage { png } => {
+                let moves = self.shown_moves();
+                let svg = board_svg(
+                    self.shown_position(),
+                    &self.board.move_numbers(moves),
+                    self.image_options,
+                );
+                if png {
+                    export_png(&svg, ctx.link().callback(Msg::ShowToast));
+                } else if let Err(e) = export_svg(&svg) {
+                    self.toast = Some(e);
+                }
+                true
+            }
+            Msg::Rematch { nigiri } => {
+                // Nigiri is a guess at whether a handful of stones is odd,
+                // which comes down to a coin toss
+                let swap = !nigiri || js_sys::Math::random() < 0.5;
+                self.rematch(swap);
+                if nigiri {
+                    self.toast = Some(String::from(if swap {
+                        "Nigiri: the colors are swapped."
+                    } else {
+                        "Nigiri: everyone keeps their color."
+                    }));
+                }
+                true
+            }
+            Msg::JumpTo(half_turn) => {
+                self.preview = None;
+                self.viewing = Some((half_turn + 1, self.board.position_after(half_turn)));
+                true
+            }
+            Msg::ShowMoves(moves) => {
+                self.preview = None;
+                self.viewing = if moves >= self.board.move_count() {
+                    None
+                } else {
+                    Some((moves, self.board.position_at(moves)))
+                };
+                true
+            }
+            Msg::ReturnToGame => {
+                self.viewing = None;
+                true
+            }
+            Msg::CycleCorner => {
+                self.transplant_corner = match self.transplant_corner {
+                    Corner::TopLeft => Corner::TopRight,
+                    Corner::TopRight => Corner::BottomRight,
+                    Corner::BottomRight => Corner::BottomLeft,
+                    Corner::BottomLeft => Corner::TopLeft,
+                };
+                true
+            }
+            Msg::Transplant(size) => {
+                let position = self
+                    .shown_position()
+                    .transplant(size, self.transplant_corner);
+                self.result_shown = false;
+                self.rated = false;
+                self.tournament_game = None;
+                self.hints_used = 0;
+                self.board = GoGame::from_position(position, self.board.next_player)
+                    .with_rules(self.board.rules());
+                self.correspondence = None;
+                self.ogs = None;
+                self.analysis = None;
+                self.demo = None;
+                self.problem = None;
+                self.preview = None;
+                self.viewing = None;
+                true
+            }
+            Msg::SaveToLibrary => {
+                let name = format!("Game {}", self.library.len() + 1);
+                match LibraryEntry::from_game(name.clone(), &self.board) {
+                    Ok(mut entry) => {
+                        entry.rating = self.rating_now();
+                        self.add_to_library(entry);
+                        self.toast = Some(format!("Saved \"{}\" to the library.", name));
+                    }
+                    Err(e) => self.toast = Some(format!("Could not save the game: {}", e)),
+                }
+                true
+            }
+            Msg::ClearRejection => {
+                self.rejection = None;
+                self.rejection_timeout = None;
+                true
+            }
+            Msg::ConfirmMove => match self.preview.take() {
+                Some((x, y)) => {
+                    self.play_at(x, y);
+                    true
+                }
+                None => false,
+            },
+            Msg::CancelMove => self.preview.take().is_some(),
+            Msg::StartAnalysis => {
+                if self.analysis.is_some()
+                    || self.demo.is_some()
+                    || self.problem.is_some()
+                    || self.watching.is_some()
+                {
+                    return false;
+                }
+                let forked_at = self
+                    .viewing
+                    .as_ref()
+                    .map_or(self.board.move_count(), |(moves, _)| *moves);
+                let scratch = match self.board.truncated(forked_at) {
+                    Ok(scratch) => scratch,
+                    Err(e) => {
+                        self.toast = Some(format!("Could not start the analysis: {}", e));
+                        return true;
+                    }
+                };
+                self.viewing = None;
+                let game = std::mem::replace(&mut self.board, scratch);
+                self.analysis = Some(Analysis { game, forked_at });
+                self.preview = None;
+                self.sounded_moves = self.board.move_count();
+                true
+            }
+            Msg::EndAnalysis { save } => {
+                let analysis = match self.analysis.take() {
+                    Some(analysis) => analysis,
+                    None => return false,
+                };
+                let line = std::mem::replace(&mut self.board, analysis.game);
+                if save {
+                    let name = format!("Variation from move {}", analysis.forked_at);
+                    match LibraryEntry::from_game(name.clone(), &line) {
+                        Ok(entry) => {
+                            self.add_to_library(entry);
+                            self.toast = Some(format!("Saved \"{}\" to the library.", name));
+                        }
+                        Err(e) => self.toast = Some(format!("Could not save the variation: {}", e)),
+                    }
+                }
+                self.preview = None;
+                self.viewing = None;
+                self.sounded_moves = self.board.move_count();
+                true
+            }
+            Msg::StartDemo => {
+                if self.analysis.is_some()
+                    || self.demo.is_some()
+                    || self.problem.is_some()
+                    || self.watching.is_some()
+                {
+                    return false;
+                }
+                let position = self.shown_position().clone();
+                let mut scratch = GoGame::from_position(position, self.board.next_player);
+                scratch.info = self.board.info.clone();
+                scratch.set_markup_at(0, self.board.markup_at(self.shown_moves()));
+                let game = std::mem::replace(&mut self.board, scratch);
+                self.demo = Some(Demo::new(game));
+                self.preview = None;
+                self.viewing = None;
+                self.sidebar_shown = false;
+                self.sounded_moves = 0;
+                true
+            }
+            Msg::Demo(control) => {
+                let demo = match &mut self.demo {
+                    Some(demo) => demo,
+                    None => return false,
+                };
+                match control {
+                    DemoControl::SetTool(tool) => {
+                        demo.tool = tool;
+                        true
+                    }
+                    DemoControl::Undo => demo.undo(&mut self.board),
+                    DemoControl::Clear => demo.clear(&mut self.board),
+                    DemoControl::Leave => {
+                        if let Some(demo) = self.demo.take() {
+                            self.result_shown = false;
+                            self.board = demo.game;
+                        }
+                        self.sounded_moves = self.board.move_count();
+                        true
+                    }
+                    DemoControl::PlayFromHere => {
+                        // The demonstrated position becomes a new game
+                        self.demo = None;
+                        self.bot = None;
+                        self.practice = None;
+                        self.hints_used = 0;
+                        self.sounded_moves = 0;
+                        true
+                    }
+                }
+            }
+            Msg::Annotate(annotation) => {
+                if self.watching.is_some() {
+                    return false;
+                }
+                let moves = self.shown_moves();
+                self.board.set_annotation_at(moves, annotation);
+                true
+            }
+            Msg::ImportFiles(input) => {
+                let callback = ctx
+                    .link()
+                    .callback(|(name, contents)| Msg::FileRead { name, contents });
+                let readers = read_files(&input, callback);
+                self.pending_imports += readers.len();
+                self.file_readers.extend(readers);
+                // Allow the same files to be picked again
+                input.set_value("");
+                false
+            }
+            Msg::FileRead { name, contents } => {
+                let entry = contents.and_then(|sgf| LibraryEntry::from_sgf(name, sgf));
+                match entry {
+                    Ok(entry) => self.add_to_library(entry),
+                    Err(e) => self.toast = Some(e),
+                }
+                // Readers are done once their callback has run
+                self.pending_imports -= 1;
+                if self.pending_imports == 0 {
+                    self.file_readers.clear();
+                }
+                true
+            }
+            Msg::ToggleBot => {
+                self.practice = None;
+                if self.bot.take().is_none() {
+                    // The bot takes the side that isn't about to move
+                    let color = match self.board.next_player {
+                        Player::Black => Player::White,
+                        _ => Player::Black,
+                    };
+                    self.bot = self.library_bot(color);
+                }
+                true
+            }
+            Msg::SetBotPlayer(name) => {
+                self.bot_player = Some(name).filter(|name| self.library_players.contains(name));
+                true
+            }
+            Msg::SetNewHandicap(stones) => {
+                self.new_handicap = stones.min(MAX_STONES);
+                // A handicap picked by hand goes with the usual komi
+                self.new_komi = None;
+                true
+            }
+            Msg::SetNewRank(player, rank) => {
+                match player {
+                    Player::Black => self.new_ranks.0 = rank,
+                    _ => self.new_ranks.1 = rank,
+                }
+                self.suggest_handicap();
+                true
+            }
+            Msg::ToggleFreeHandicap => {
+                self.new_handicap_placement = match self.new_handicap_placement {
+                    HandicapPlacement::Fixed => HandicapPlacement::Free,
+                    HandicapPlacement::Free => HandicapPlacement::Fixed,
+                };
+                true
+            }
+            Msg::ToggleThreePlayers => {
+                self.new_three_players = !self.new_three_players;
+                true
+            }
+            Msg::SetNewBoardSize(width, height) => {
+                self.new_board_size = (
+                    width.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+                    height.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+                );
+                true
+            }
+            Msg::SetPracticeGoal(goal) => {
+                self.practice_goal = goal;
+                true
+            }
+            Msg::StartPractice => {
+                if let Some(bot) = self.library_bot(Player::White) {
+                    // The player takes black against the bot
+                    self.result_shown = false;
+                    self.rated = false;
+                    self.tournament_game = None;
+                    self.hints_used = 0;
+                    self.board = match self.board.board_size() {
+                        0 => GoGame::default(),
+                        _ => GoGame::rectangular(self.board.width(), self.board.height()),
+                    }
+                    .with_rules(self.settings.rules.ruleset());
+                    self.correspondence = None;
+                    self.ogs = None;
+                    self.analysis = None;
+                    self.demo = None;
+                    self.problem = None;
+                    self.viewing = None;
+                    self.preview = None;
+                    self.bot = Some(bot);
+                    self.practice = Some(Practice {
+                        goal: self.practice_goal,
+                        margin: None,
+                    });
+                    self.sidebar_shown = false;
+                }
+                true
+            }
+            Msg::EndPractice => {
+                self.practice = None;
+                self.bot = None;
+                true
+            }
+            Msg::StartProblem(index) => {
+                let problem = match self.problems.get(index) {
+                    Some(problem) => problem,
+                    None => return false,
+                };
+                if self.watching.is_some() || self.analysis.is_some() || self.demo.is_some() {
+                    return false;
+                }
+                self.result_shown = false;
+                self.rated = false;
+                self.tournament_game = None;
+                self.hints_used = 0;
+                self.board = problem.start();
+                self.correspondence = None;
+                self.ogs = None;
+                self.problem = Some(ProblemSession {
+                    index,
+                    attempt: problem.attempt(),
+                    solved: None,
+                });
+                self.toast = Some(format!("{}: {}", problem.name, problem.question));
+                self.bot = None;
+                self.practice = None;
+                self.viewing = None;
+                self.preview = None;
+                self.sidebar_shown = false;
+                navigate(ctx, Route::Play);
+                true
+            }
+            Msg::Tournament(control) => {
+                let play = matches!(control, TournamentControl::Play { .. });
+                let changed = self.control_tournament(control);
+                if play && changed {
+                    navigate(ctx, Route::Play);
+                }
+                changed
+            }
+            Msg::ReviewLibraryEntry(index) => {
+                if self.watching.is_some() {
+                    return false;
+                }
+                let game = match self.library.get(index).and_then(LibraryEntry::game) {
+                    Some(game) => game,
+                    None => {
+                        self.toast = Some(String::from("Could not open the game to review."));
+                        return true;
+                    }
+                };
+                self.open_session(Session {
+                    viewing: Some((0, game.position_at(0))),
+                    board: game,
+                    ..Session::default()
+                });
+                navigate(ctx, Route::Review);
+                true
+            }
+            Msg::LockCorner(corner) => {
+                let (width, height) = (self.board.width(), self.board.height());
+                let size = width.min(height).div_ceil(2);
+                self.region = Some(Region::corner(width, height, corner, size));
+                self.picking_region = false;
+                self.preview = None;
+                true
+            }
+            Msg::ToggleRegionPicking => {
+                self.picking_region = !self.picking_region;
+                if self.picking_region && self.region().is_none() {
+                    self.region = Some(Region::empty(self.board.width(), self.board.height()));
+                }
+                self.preview = None;
+                true
+            }
+            Msg::UnlockBoard => {
+                self.region = None;
+                self.picking_region = false;
+                true
+            }
+            Msg::RetryProblem => {
+                if let Some(session) = &self.problem {
+                    ctx.link().send_message(Msg::StartProblem(session.index));
+                }
+                false
+            }
+            Msg::EndProblem => self.problem.take().is_some(),
+            Msg::SetRelayUrl(url) => {
+                self.relay_url = url;
+                false
+            }
+            Msg::StartBroadcast => {
+                // Viewers are sent SGF records, which can't hold a third color
+                if self.board.colors() > 2 {
+                    self.toast = Some(SgfError::ThirdColor.to_string());
+                    return true;
+                }
+                // Random enough to keep separate broadcasts apart
+                let channel = format!("{:x}", (js_sys::Math::random() * 1e12) as u64);
+                match RelayConnection::publish(
+                    &self.relay_url,
+                    &channel,
+                    ctx.link().callback(Msg::RelayError),
+                ) {
+                    Ok(connection) => {
+                        self.broadcast = Some(Broadcast {
+                            connection,
+                            channel,
+                            sequence: 0,
+                            share_liberties: false,
+                            last_sent: None,
+                        })
+                    }
+                    Err(e) => self.toast = Some(e),
+                }
+                true
+            }
+            Msg::StopBroadcast => {
+                self.broadcast = None;
+                true
+            }
+            Msg::ToggleShareLiberties => {
+                if let Some(broadcast) = &mut self.broadcast {
+                    broadcast.share_liberties = !broadcast.share_liberties;
+                }
+                true
+            }
+            Msg::FrameReceived(frame) => {
+                let link = ctx.link().clone();
+                self.frame_timeouts
+                    .push_back(Timeout::new(BROADCAST_DELAY_MS, move || {
+                        link.send_message(Msg::ApplyFrame(frame))
+                    }));
+                false
+            }
+            Msg::ApplyFrame(frame) => {
+                self.frame_timeouts.pop_front();
+                if frame.sequence <= self.last_frame {
+                    return false;
+                }
+                match read_sgf(&frame.sgf) {
+                    Ok(game) => {
+                        self.last_frame = frame.sequence;
+                        self.result_shown = false;
+                        self.board = game;
+                        self.show_liberties = frame.show_liberties;
+                        self.viewing = None;
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }
+            Msg::RelayError(e) => {
+                self.toast = Some(e);
+                true
+            }
+            Msg::SpectatorFrame(frame) => match &self.watching {
+                Some(Watching::Room(room)) => room.receive(&mut self.board, &frame),
+                _ => false,
+            },
+            Msg::SpectatorChat(message) => match &mut self.watching {
+                Some(Watching::Room(room)) => room.receive_chat(message),
+                _ => false,
+            },
+            Msg::SpectatorPresence(presence) => match &mut self.watching {
+                Some(Watching::Room(room)) => {
+                    room.viewers.seen(&presence.viewer, js_sys::Date::now());
+                    true
+                }
+                _ => false,
+            },
+            Msg::AnnouncePresence => match &mut self.watching {
+                Some(Watching::Room(room)) => {
+                    room.announce(js_sys::Date::now());
+                    // Spectators that left drop out of the count
+                    true
+                }
+                _ => false,
+            },
+            Msg::SetChatDraft(text) => {
+                self.chat_draft = text;
+                false
+            }
+            Msg::SendChat(text) if matches!(self.watching, Some(Watching::Room(_))) => {
+                let text = text.trim().to_owned();
+                match &mut self.watching {
+                    Some(Watching::Room(room)) if !text.is_empty() => room.send_chat(text),
+                    _ => return false,
+                }
+                self.chat_draft.clear();
+                true
+            }
+            Msg::SendChat(text) => {
+                let text = text.trim().to_owned();
+                let correspondence = match &mut self.correspondence {
+                    Some(correspondence) if !text.is_empty() => correspondence,
+                    _ => return false,
+                };
+                correspondence.send_chat(self.chat_channel, text);
+                if self.chat_channel == ChatChannel::Players {
+                    let game = match &self.analysis {
+                        Some(analysis) => &analysis.game,
+                        None => &self.board,
+                    };
+                    correspondence.saved(game).save();
+                }
+                self.chat_draft.clear();
+                true
+            }
+            Msg::ToggleSpectatorChat => {
+                let correspondence = match &mut self.correspondence {
+                    Some(correspondence) => correspondence,
+                    None => return false,
+                };
+                let follow = correspondence.spectator_chat.is_none();
+                match correspondence.follow_spectators(follow) {
+                    Ok(()) => {
+                        self.chat_channel = if follow {
+                            ChatChannel::Spectators
+                        } else {
+                            ChatChannel::Players
+                        }
+                    }
+                    Err(e) => self.toast = Some(e),
+                }
+                true
+            }
+            Msg::ShowChatChannel(channel) => {
+                self.chat_channel = channel;
+                true
+            }
+            Msg::Correspondence(msg) => self.update_correspondence(ctx, msg),
+            Msg::Ogs(msg) => self.update_ogs(ctx, msg),
+            Msg::PickForComparison(index) => {
+                match self.compare_pick.take() {
+                    // Picking the same entry again unpicks it
+                    Some(first) if first == index => {}
+                    Some(first) => {
+                        let open = |index: usize| {
+                            let entry = &self.library[index];
+                            entry
+                                .game()
+                                .map(|game| ComparedGame::new(entry.name.clone(), game))
+                        };
+                        match (open(first), open(index)) {
+                            (Some(left), Some(right)) => {
+                                self.comparison = Some(Comparison::new(left, right));
+                                self.preview = None;
+                                self.sidebar_shown = false;
+                                navigate(ctx, Route::Review);
+                            }
+                            _ => {
+                                self.toast =
+                                    Some(String::from("Could not open the games to compare."))
+                            }
+                        }
+                    }
+                    None => self.compare_pick = Some(index),
+                }
+                true
+            }
+            Msg::CompareStep { side, delta } => {
+                if let Some(comparison) = &mut self.comparison {
+                    comparison.step(side, delta);
+                }
+                true
+            }
+            Msg::ToggleLinkedNavigation => {
+                if let Some(comparison) = &mut self.comparison {
+                    comparison.linked = !comparison.linked;
+                }
+                true
+            }
+            Msg::CloseComparison => {
+                self.comparison = None;
+                true
+            }
+            Msg::Tick => {
+                let idle = js_sys::Date::now() - self.activity.last_input() > IDLE_MS;
+                let offer_changed = self.idle_offer != (idle && self.clock.is_running());
+                self.idle_offer = idle && self.clock.is_running();
+                offer_changed || self.clock.is_running()
+            }
+            Msg::LayoutChanged(layout) => {
+                let changed = self.layout != layout;
+                self.layout = layout;
+                changed
+            }
+            Msg::PageHidden(true) => {
+                self.clock.pause(Pause::Hidden, js_sys::Date::now());
+                false
+            }
+            Msg::PageHidden(false) => {
+                if self.clock.paused() == Some(Pause::Hidden) {
+                    self.clock.resume(js_sys::Date::now());
+                }
+                self.idle_offer = false;
+                true
+            }
+            Msg::ToggleClockPause => {
+                match self.clock.paused() {
+                    Some(_) => self.clock.resume(js_sys::Date::now()),
+                    None => self.clock.pause(Pause::Manual, js_sys::Date::now()),
+                }
+                true
+            }
+            Msg::AcceptIdlePause => {
+                self.clock.pause(Pause::Manual, js_sys::Date::now());
+                self.idle_offer = false;
+                true
+            }
+            Msg::ChangeSettings(settings) => {
+                if settings.confirm_mode == ConfirmMode::Direct {
+                    self.preview = None;
+                }
+                let table_changed = settings.handicap_table != self.settings.handicap_table;
+                self.settings = settings;
+                self.apply_settings();
+                if table_changed {
+                    self.suggest_handicap();
+                }
+                true
+            }
+            Msg::ToggleMute => {
+                self.settings.muted = !self.settings.muted;
+                self.settings.save();
+                true
+            }
+            Msg::ExportLibrary => {
+                if let Err(e) = export_all(&self.library) {
+                    self.toast = Some(e);
+                }
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &AppContext, first_render: bool) {
+        if self.printing_kifu {
+            self.printing_kifu = false;
+            if window().print().is_err() {
+                ctx.link().send_message(Msg::ShowToast(String::from(
+                    "Could not open the print dialog",
+                )));
+            }
+        }
+        // The game to open again after a reload isn't shown yet the first
+        // time
+        if !first_render {
+            remember_shown(self.correspondence.as_ref().map(|c| c.id.as_str()));
+        }
+        self.send_broadcast_frame();
+        self.play_move_sounds();
+        self.schedule_engine_step(ctx);
+        if self.show_ownership && self.shown_ownership().is_empty() {
+            ctx.link().send_message(Msg::EstimateOwnership);
+        }
+        let reviewing = ctx.link().route() == Some(Route::Review);
+        if reviewing && self.tactics.as_ref().map(|(found, _)| *found) != Some(self.tactics_key()) {
+            ctx.link().send_message(Msg::FindTactics);
+        }
+        let game = self.game();
+        let turn_changed = self.clock.sync(
+            game.next_player,
+            game.move_count(),
+            // Nothing to time before a board is made, on someone else's board,
+            // while demonstrating or on problems
+            game.is_over()
+                || game.board_size() == 0
+                || self.watching.is_some()
+                || self.demo.is_some()
+                || self.problem.is_some(),
+            js_sys::Date::now(),
+        );
+        if turn_changed {
+            // Moves tried in analysis or a demonstration aren't part of the game played
+            if let (Some(thinking), None, None) =
+                (self.clock.last_turn(), &self.analysis, &self.demo)
+            {
+                self.board.time_last_move(thinking);
+                self.board.stamp_last_move(js_sys::Date::now());
+            }
+            ctx.link().send_message(Msg::Tick);
+        }
+    }
+}
+
+impl App {
+    /// The board and the sidebar, with the sections for going over a game
+    /// when `review` is set and for playing one otherwise.
+    fn render_game(&self, ctx: &AppContext, review: bool) -> Html {
+        let board_onclick = ctx.link().callback(|(x, y)| Msg::Click { x, y });
+        let board_onhover = ctx.link().callback(|point| match point {
+            Some((x, y)) => Msg::Hover { x, y },
+            None => Msg::HoverEnd,
+        });
+        let board_ontouch = ctx.link().callback(|touch| match touch {
+            BoardTouch::Start { x, y } => Msg::TouchStart { x, y },
+            BoardTouch::Move { x, y } => Msg::TouchMove { x, y },
+            BoardTouch::End => Msg::TouchEnd,
+            BoardTouch::Cancel => Msg::TouchCancel,
+        });
+        let board_onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            let modified = e.shift_key() || e.ctrl_key() || e.alt_key() || e.meta_key();
+            let msg = match e.key().as_str() {
+                // Arrows with a modifier step through the history
+                "ArrowLeft" | "ArrowUp" if modified => Msg::StepHistory(-1),
+                "ArrowRight" | "ArrowDown" if modified => Msg::StepHistory(1),
+                "ArrowLeft" => Msg::MoveCursor { dx: -1, dy: 0 },
+                "ArrowRight" => Msg::MoveCursor { dx: 1, dy: 0 },
+                "ArrowUp" => Msg::MoveCursor { dx: 0, dy: -1 },
+                "ArrowDown" => Msg::MoveCursor { dx: 0, dy: 1 },
+                "Enter" | " " => Msg::SelectCursor,
+                "p" | "P" => Msg::Pass,
+                "u" | "U" => Msg::Undo,
+                _ => return None,
+            };
+            // Keep the arrows and space from scrolling the page
+            e.prevent_default();
+            Some(msg)
+        });
+        let controls_onaction = ctx.link().callback(|control| match control {
+            Control::Pass => Msg::Pass,
+            Control::Resign => Msg::Resign,
+            Control::ToggleLiberties => Msg::ToggleLiberties,
+            Control::ToggleJoseki => Msg::ToggleJoseki,
+            Control::ToggleFuseki => Msg::ToggleFuseki,
+            Control::ToggleOwnership => Msg::ToggleOwnership,
+            Control::ToggleInfluence => Msg::ToggleInfluence,
+            Control::ToggleEyes => Msg::ToggleEyes,
+            Control::RotateBoard => Msg::RotateBoard,
+            Control::MirrorBoard => Msg::MirrorBoard,
+            Control::CycleMoveNumbers => Msg::CycleMoveNumbers,
+            Control::CycleStoneDisplay => Msg::CycleStoneDisplay,
+            Control::ToggleReveal => Msg::ToggleReveal,
+            Control::ToggleMute => Msg::ToggleMute,
+            Control::Hint => Msg::Hint,
+            Control::ReturnToGame => Msg::ReturnToGame,
+            Control::ConfirmMove => Msg::ConfirmMove,
+            Control::CancelMove => Msg::CancelMove,
+            Control::StartAnalysis => Msg::StartAnalysis,
+            Control::DiscardAnalysis => Msg::EndAnalysis { save: false },
+            Control::SaveAnalysis => Msg::EndAnalysis { save: true },
+        });
+        let shown_moves = self.shown_moves();
+
+        let toast = self.render_toast(ctx);
+
+        let sidebar_sections = if self.watching.is_some() {
+            html! {
+                <>
+                    <SidebarSection title="Info">
+                        { self.render_game_info(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Moves">
+                        { self.render_move_list(ctx, false) }
+                    </SidebarSection>
+                    { self.render_spectator_chat(ctx) }
+                </>
+            }
+        } else if review {
+            html! {
+                <>
+                    <SidebarSection title="Info">
+                        { self.render_game_info(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Moves">
+                        { self.render_move_list(ctx, true) }
+                    </SidebarSection>
+                    <SidebarSection title="Engine analysis">
+                        { self.render_engine(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Edit">
+                        { self.render_edit_actions(ctx) }
+                    </SidebarSection>
+                </>
+            }
+        } else {
+            html! {
+                <>
+                    <SidebarSection title="Info">
+                        { self.render_game_info(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Moves">
+                        { self.render_move_list(ctx, false) }
+                    </SidebarSection>
+                    <SidebarSection title="Engine analysis">
+                        { self.render_engine(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Broadcast">
+                        <BroadcastSection />
+                    </SidebarSection>
+                    { self.render_chat(ctx) }
+                    <SidebarSection title="Correspondence">
+                        { self.render_correspondence(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="OGS">
+                        { self.render_ogs(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Edit">
+                        { self.render_edit_actions(ctx) }
+                    </SidebarSection>
+                    <SidebarSection title="Practice">
+                        <PracticeSection />
+                    </SidebarSection>
+                    <SidebarSection title="Rating">
+                        <RatingsSection />
+                    </SidebarSection>
+                    <SidebarSection title="Tournament">
+                        <TournamentPanel
+                            tournament={ self.tournament.clone() }
+                            playing={ self.tournament_game }
+                            onaction={ ctx.link().callback(Msg::Tournament) }
+                        />
+                    </SidebarSection>
+                    <SidebarSection title="Teaching">
+                        <TeachingSection />
+                    </SidebarSection>
+                </>
+            }
+        };
+
+        // Return full html
+        html! {
+            <>
+                <Sidebar
+                    open={ self.sidebar_shown }
+                    docked={ self.layout == Layout::Wide }
+                    title="go-rs"
+                    onopen={ ctx.link().callback(|_| Msg::ShowSidebar) }
+                    onclose={ ctx.link().callback(|_| Msg::HideSidebar) }
+                >
+                    { sidebar_sections }
+                </Sidebar>
+                if let Some(comparison) = &self.comparison {
+                    { self.render_comparison(ctx, comparison) }
+                } else {
+                    <main>
+                        <BoardView
+                            width={ self.board.width() }
+                            height={ self.board.height() }
+                            onclick={ board_onclick }
+                            onhover={ board_onhover }
+                            ontouch={ board_ontouch }
+                            onkeydown={ board_onkeydown }
+                            orientation={ self.shown_orientation() }
+                        >
+                            <StoneLayer
+                                position={ self.shown_position().clone() }
+                                move_numbers={ self.board.move_numbers(shown_moves) }
+                                moves={ shown_moves }
+                                numbers={ self.move_numbers }
+                                show_liberties={ self.show_liberties }
+                                dead={
+                                    if self.viewing.is_some() {
+                                        vec![]
+                                    } else {
+                                        self.board.dead_stones().to_vec()
+                                    }
+                                }
+                                territory={
+                                    if self.viewing.is_some() || !self.board.is_scoring() {
+                                        vec![]
+                                    } else {
+                                        self.board.territory()
+                                    }
+                                }
+                                ownership={ self.shown_ownership() }
+                                show_eyes={ self.show_eyes }
+                                influence={
+                                    if self.show_influence {
+                                        influence(self.shown_position())
+                                    } else {
+                                        vec![]
+                                    }
+                                }
+                                display={
+                                    if self.stones_revealed {
+                                        StoneDisplay::Normal
+                                    } else {
+                                        self.stone_display
+                                    }
+                                }
+                            />
+                            <MarkupLayer
+                                position={ self.shown_position().clone() }
+                                markup={ self.board.markup_at(shown_moves) }
+                            />
+                            { self.render_locked() }
+                            { self.render_fuseki() }
+                            { self.render_joseki() }
+                            { self.render_candidates() }
+                            { self.render_hover() }
+                            { self.render_hint() }
+                            { self.render_tactic() }
+                            { self.render_preview() }
+                            { self.render_rejection() }
+                            { self.render_cursor() }
+                        </BoardView>
+                        if let Some(demo) = &self.demo {
+                            <DemoBar
+                                tool={ demo.tool }
+                                onaction={ ctx.link().callback(Msg::Demo) }
+                            />
+                        } else {
+                            <ControlsBar
+                                next_player={ self.board.next_player }
+                                over={ self.board.is_over() }
+                                analyzing={ self.analysis.is_some() }
+                                move_pending={ self.preview.is_some() && self.settings.confirm_mode == ConfirmMode::Button }
+                                show_liberties={ self.show_liberties }
+                                show_joseki={ self.show_joseki }
+                                show_fuseki={ self.show_fuseki }
+                                show_ownership={ self.show_ownership }
+                                show_influence={ self.show_influence }
+                                show_eyes={ self.show_eyes }
+                                orientation={ self.orientation }
+                                move_numbers={ self.move_numbers }
+                                stone_display={ self.stone_display }
+                                stones_revealed={ self.stones_revealed }
+                                hints_used={ self.hints_used }
+                                viewing={ self.viewing.is_some() }
+                                watching={ self.watching.is_some() }
+                                onaction={ controls_onaction }
+                            >
+                                { self.render_clock(ctx) }
+                            </ControlsBar>
+                        }
+                        <CommentPane
+                            moves={ shown_moves }
+                            annotation={ self.board.annotation_at(shown_moves) }
+                            readonly={ self.watching.is_some() }
+                            onchange={ ctx.link().callback(Msg::Annotate) }
+                        />
+                    </main>
+                }
+                { self.render_spectating() }
+                { self.render_capture_result() }
+                { self.render_scoring(ctx) }
+                { self.render_result(ctx) }
+                { self.render_practice_result(ctx) }
+                { self.render_idle_offer(ctx) }
+                { self.render_kifu() }
+                { toast }
+            </>
+        }
+    }
+
+    /// How the board is turned on screen. With hot-seat on, the board of a
+    /// game between two people at this device faces whoever is to move.
+    fn shown_orientation(&self) -> Orientation {
+        let local = self.watching.is_none()
+            && self.bot.is_none()
+            && self.practice.is_none()
+            && self.problem.is_none()
+            && self.demo.is_none()
+            && self.correspondence.is_none()
+            && self.ogs.is_none();
+        if self.settings.hot_seat
+            && local
+            && !self.board.is_over()
+            && self.board.next_player == Player::White
+        {
+            self.orientation.flipped()
+        } else {
+            self.orientation
+        }
+    }
+
+    fn render_toast(&self, ctx: &AppContext) -> Html {
+        match &self.toast {
+            None => html! {},
+            Some(message) => {
+                let dismiss_cb = ctx.link().callback(|_: MouseEvent| Msg::DismissToast);
+                html! {
+                    <div class="toast" onclick={ dismiss_cb }>{ message }</div>
+                }
+            }
+        }
+    }
+
+    /// Renders the time each player has used, with a button to pause the
+    /// clock.
+    fn render_clock(&self, ctx: &AppContext) -> Html {
+        let now = js_sys::Date::now();
+        let times = [Player::Black, Player::White].map(|player| {
+            let mut class = classes!();
+            if self.board.next_player == player && !self.board.is_over() {
+                class.push("clock-to-move");
+                if self.clock.is_running() {
+                    class.push("clock-running");
+                }
+            }
+            // Timed games count down from the main time
+            let time = match self.board.info.main_time {
+                Some(main_time) => {
+                    let left = main_time * 1000.0 - self.clock.used(player, now);
+                    if left <= 0.0 {
+                        class.push("clock-flagged");
+                    }
+                    left.max(0.0)
+                }
+                None => self.clock.used(player, now),
+            };
+            html! {
+                <span class={ class }>
+                    { format!("{} {}", player_name(player), format_time(time)) }
+                </span>
+            }
+        });
+        let pause_cb = ctx.link().callback(|_: MouseEvent| Msg::ToggleClockPause);
+        let pause_text = match self.clock.paused() {
+            Some(_) => "Resume clock",
+            None => "Pause clock",
+        };
+        html! {
+            <div class="clock">
+                { for times }
+                <button onclick={ pause_cb }>{ pause_text }</button>
+            </div>
+        }
+    }
+
+    /// Asks whether to pause the clock after a long time without input.
+    fn render_idle_offer(&self, ctx: &AppContext) -> Html {
+        if !self.idle_offer || self.watching.is_some() {
+            return html! {};
+        }
+        let pause_cb = ctx.link().callback(|_: MouseEvent| Msg::AcceptIdlePause);
+        html! {
+            <div class="toast">
+                { "No input for a while. " }
+                <button onclick={ pause_cb }>{ "Pause the clock" }</button>
+            </div>
+        }
+    }
+
+    /// Stores the settings and shows their theme and language.
+    fn apply_settings(&self) {
+        self.settings.save();
+        theme::apply(&self.settings);
+        self.settings.language.apply();
+    }
+
+    /// Plays the sounds of the moves made since the last render.
+    fn play_move_sounds(&mut self) {
+        let new_moves = self.sounded_moves..self.board.move_count();
+        self.sounded_moves = self.board.move_count();
+        // Taking back moves and loading whole games stays quiet
+        if self.settings.muted || new_moves.len() > 2 {
+            return;
+        }
+        for half_turn in new_moves {
+            let events = self
+                .board
+                .events()
+                .iter()
+                .filter(|event| event.half_turn() == half_turn);
+            let mut sounds = vec![Sound::Place];
+            for event in events {
+                match event {
+                    GameEvent::Pass { .. } => sounds = vec![Sound::Pass],
+                    GameEvent::Resign { .. } => sounds.clear(),
+                    GameEvent::Capture { .. } => sounds.push(Sound::Capture),
+                    GameEvent::KoStarted { .. } | GameEvent::KoResolved { .. } => {}
+                }
+            }
+            for sound in sounds {
+                self.audio.play(sound);
+            }
+        }
+    }
+
+    /// Sends the game to the viewers of the broadcast if it changed since the
+    /// last frame.
+    fn send_broadcast_frame(&mut self) {
+        let broadcast = match &mut self.broadcast {
+            Some(broadcast) => broadcast,
+            None => return,
+        };
+        // Viewers follow the game, not the moves tried out in analysis, but
+        // they do see a demo board
+        let game = match &self.analysis {
+            Some(analysis) => &analysis.game,
+            None => &self.board,
+        };
+        // Three-player games can't be written, viewers keep the last frame
+        let current = match write_sgf(game) {
+            Ok(sgf) => (sgf, broadcast.share_liberties),
+            Err(_) => return,
+        };
+        if broadcast.last_sent.as_ref() == Some(&current) {
+            return;
+        }
+        broadcast.sequence += 1;
+        broadcast.connection.send(&Frame {
+            sequence: broadcast.sequence,
+            show_liberties: current.1,
+            sgf: current.0.clone(),
+        });
+        broadcast.last_sent = Some(current);
+    }
+
+    /// Finds the point closest to `(x, y)`, given in board units, if it is no
+    /// further than `radius` away. Clicks in the margin go to the nearest
+    /// point on the edge, within the same distance.
+    fn snap(&self, x: f64, y: f64, radius: f64) -> Option<(usize, usize)> {
+        let last_x = (self.board.width() - 1) as f64;
+        let last_y = (self.board.height() - 1) as f64;
+        let (point_x, point_y) = (x.round().clamp(0.0, last_x), y.round().clamp(0.0, last_y));
+        let distance = (x - point_x).hypot(y - point_y);
+        // A position that couldn't be measured is NaN
+        if distance.is_nan() || distance > radius {
+            return None;
+        }
+        Some((point_x as usize, point_y as usize))
+    }
+
+    /// Whether the player to move could play at a point of the current game.
+    /// On the demo board any point can be used.
+    fn can_play_at(&self, x: usize, y: usize) -> bool {
+        self.viewing.is_none()
+            && self.watching.is_none()
+            && !self.problem_over()
+            && !self.waiting_for_opponent()
+            && (self.demo.is_some() || self.check_move(x, y).is_ok())
+    }
+
+    /// The region play is locked to, unless it was made for another board
+    /// size.
+    fn region(&self) -> Option<&Region> {
+        self.region
+            .as_ref()
+            .filter(|region| region.fits(&self.board))
+    }
+
+    /// Checks a move of the player to move, keeping it to the open points
+    /// when the board is locked.
+    fn check_move(&self, x: usize, y: usize) -> Result<(), MoveError> {
+        let player = self.board.next_player;
+        match self.region() {
+            Some(region) => region.check_move(&self.board, x, y, player),
+            None => self.board.check_move(x, y, player),
+        }
+    }
+
+    /// Previews a move at a point, or plays it if it was already being
+    /// previewed. Returns whether the board needs to be redrawn.
+    fn select_point(&mut self, ctx: &AppContext, x: usize, y: usize) -> bool {
+        // Earlier positions can only be looked at, broadcasts are read-only and
+        // problems are done once answered
+        if self.viewing.is_some() || self.watching.is_some() || self.problem_over() {
+            return false;
+        }
+
+        self.rejection = None;
+        self.rejection_timeout = None;
+        if self.board.is_scoring() {
+            let index = self.board.coord_to_index(x, y);
+            return self.board.toggle_dead(index);
+        }
+        if self.picking_region {
+            let index = self.board.coord_to_index(x, y);
+            let board = &self.board;
+            if let Some(region) = self.region.as_mut().filter(|region| region.fits(board)) {
+                region.toggle(index);
+            }
+            return true;
+        }
+        if let Some(demo) = &mut self.demo {
+            let changed = demo.apply(&mut self.board, x, y);
+            let placed = demo.tool.stone().is_some()
+                && self.board.position()[self.board.coord_to_index(x, y)] != Player::None;
+            if changed && placed && !self.settings.muted {
+                self.audio.play(Sound::Place);
+            }
+            return changed;
+        }
+        let mode = self.settings.confirm_mode;
+        match self.preview {
+            // With a confirm button, picking the previewed point again
+            // leaves it previewed
+            Some(preview_coords) if preview_coords == (x, y) && mode == ConfirmMode::Button => {
+                false
+            }
+            Some(preview_coords) if preview_coords == (x, y) => {
+                self.preview = None;
+                self.play_at(x, y)
+            }
+            _ => {
+                // Check if position can be played on
+                match self.check_move(x, y) {
+                    Ok(()) if mode == ConfirmMode::Direct => {
+                        self.preview = None;
+                        self.play_at(x, y);
+                    }
+                    Ok(()) => self.preview = Some((x, y)),
+                    Err(e) => {
+                        self.preview = None;
+                        if let MoveError::Ko { .. }
+                        | MoveError::Suicide { .. }
+                        | MoveError::Superko = e
+                        {
+                            self.rejection = Some((self.board.coord_to_index(x, y), e));
+                            let link = ctx.link().clone();
+                            self.rejection_timeout = Some(Timeout::new(1500, move || {
+                                link.send_message(Msg::ClearRejection)
+                            }));
+                        }
+                    }
+                }
+                true
+            }
+        }
+    }
+
+    /// Plays a move for the player to move, and lets the bot answer it.
+    /// Returns whether the move was legal.
+    fn play_at(&mut self, x: usize, y: usize) -> bool {
+        if self.problem.is_some() {
+            return self.answer_problem(x, y);
+        }
+        if self.waiting_for_opponent() || self.check_move(x, y) == Err(MoveError::Locked) {
+            return false;
+        }
+        let played = self.board.play_move(x, y).is_ok();
+        if played {
+            self.send_correspondence_move();
+            self.send_ogs_move(Some((x, y)));
+            self.play_bot_move();
+            self.pass_while_stuck();
+            self.suggest_dead_stones();
+            self.score_practice();
+            self.show_result();
+            self.settle_game();
+        }
+        played
+    }
+
+    /// Plays the solver's move on the problem and checks it, playing the
+    /// opponent's response when the answer is on track. Returns whether the
+    /// move was legal.
+    fn answer_problem(&mut self, x: usize, y: usize) -> bool {
+        let session = match &mut self.problem {
+            Some(session) if session.solved.is_none() => session,
+            _ => return false,
+        };
+        if self.board.play_move(x, y).is_err() {
+            return false;
+        }
+        let response = session.attempt.answer(&self.board, x, y);
+        if let Some((x, y)) = response.reply {
+            // A response the problem has wrong is left off
+            let _ = self.board.play_move(x, y);
+        }
+        match response.outcome {
+            Outcome::Continue => {}
+            Outcome::Solved(comment) => {
+                session.solved = Some(true);
+                self.toast = Some(match comment.as_str() {
+                    "" => String::from("Correct!"),
+                    _ => comment,
+                });
+            }
+            Outcome::Failed(comment) => {
+                session.solved = Some(false);
+                self.toast = Some(match comment.as_str() {
+                    "" => String::from("That's not it. Try again."),
+                    _ => format!("{} Try again.", comment),
+                });
+            }
+        }
+        true
+    }
+
+    /// Whether a problem is being solved and has already been answered.
+    fn problem_over(&self) -> bool {
+        self.problem
+            .as_ref()
+            .is_some_and(|session| session.solved.is_some())
+    }
+
+    /// Builds a bot playing `color` from the games of `bot_player` in the
+    /// library, or shows why it can't.
+    fn library_bot(&mut self, color: Player) -> Option<Bot> {
+        let player = match &self.bot_player {
+            Some(player) => player.clone(),
+            None => {
+                self.toast = Some(String::from(match self.library_players.is_empty() {
+                    true => "Save or import games to the library for the bot to learn from.",
+                    false => "Pick the player for the bot to imitate in the library.",
+                }));
+                return None;
+            }
+        };
+        let mut mimic = MimicBot::new();
+        for game in self.library.iter().filter_map(LibraryEntry::game) {
+            mimic.learn(&game, &player);
+        }
+        let name = match mimic.games() {
+            1 => format!("{}, 1 game", player),
+            games => format!("{}, {} games", player, games),
+        };
+        Some(Bot {
+            color,
+            engine: Box::new(mimic),
+            name,
+        })
+    }
+
+    fn practice_finished(&self) -> bool {
+        matches!(
+            &self.practice,
+            Some(Practice {
+                margin: Some(_),
+                ..
+            })
+        )
+    }
+
+    /// Scores the practice game once it is over and updates the streak.
+    fn score_practice(&mut self) {
+        let practice = match &mut self.practice {
+            Some(practice)
+                if practice.margin.is_none() && self.board.is_over() && self.analysis.is_none() =>
+            {
+                practice
+            }
+            _ => return,
+        };
+        let margin = match (self.board.resigned(), self.board.capture_winner()) {
+            // Resigning misses any goal
+            (Some(_), _) => f64::NEG_INFINITY,
+            // Winning by capture meets any goal, losing misses it
+            (None, Some(Player::Black)) => f64::INFINITY,
+            (None, Some(_)) => f64::NEG_INFINITY,
+            (None, None) => self
+                .board
+                .score()
+                .margin(Player::Black, self.board.compensation()),
+        };
+        practice.margin = Some(margin);
+        if margin >= practice.goal as f64 {
+            self.practice_streak += 1;
+        } else {
+            self.practice_streak = 0;
+        }
+    }
+
+    /// Shows the outcome of a finished practice game against its goal.
+    fn render_practice_result(&self, ctx: &AppContext) -> Html {
+        let (goal, margin) = match &self.practice {
+            Some(Practice {
+                goal,
+                margin: Some(margin),
+            }) => (*goal, *margin),
+            _ => return html! {},
+        };
+        let outcome = if let Some(winner) = self.board.capture_winner() {
+            match winner {
+                Player::Black => String::from("You captured first."),
+                _ => String::from("The bot captured first."),
+            }
+        } else if margin == f64::NEG_INFINITY {
+            String::from("You resigned.")
+        } else {
+            let score = self.board.score();
+            let result = if margin > 0.0 {
+                format!("You won by {}.", margin)
+            } else {
+                format!("You lost by {}.", -margin)
+            };
+            format!(
+                "Black {}, White {} + {} komi. {}",
+                score.black,
+                score.white,
+                self.board.compensation(),
+                result
+            )
+        };
+        let verdict = if margin >= goal as f64 {
+            "Goal met!"
+        } else {
+            "Goal missed."
+        };
+        let again_cb = ctx.link().callback(|_: MouseEvent| Msg::StartPractice);
+        let end_cb = ctx.link().callback(|_: MouseEvent| Msg::EndPractice);
+        html! {
+            <div class="practice-result">
+                <h2>{ verdict }</h2>
+                <p>{ outcome }</p>
+                <p>{ format!("Goal: {}", goal_text(goal)) }</p>
+                <p>{ format!("Goals met in a row: {}", self.practice_streak) }</p>
+                <button onclick={ again_cb }>{ "Play again" }</button>
+                <button onclick={ end_cb }>{ "Done" }</button>
+            </div>
+        }
+    }
+
+    /// Whether this board is an online game and the opponent is to move.
+    /// Moves tried out in analysis are fine.
+    fn waiting_for_opponent(&self) -> bool {
+        self.analysis.is_none()
+            && (self
+                .correspondence
+                .as_ref()
+                .is_some_and(|correspondence| !correspondence.our_turn(&self.board))
+                || self
+                    .ogs
+                    .as_ref()
+                    .is_some_and(|ogs| !ogs.our_turn(&self.board)))
+    }
+
+    /// Renders the chat of the correspondence game on this board, if any, as
+    /// a sidebar section.
+    fn render_chat(&self, ctx: &AppContext) -> Html {
+        let correspondence = match &self.correspondence {
+            Some(correspondence) => correspondence,
+            None => return html! {},
+        };
+        let (messages, channel) = match (&correspondence.spectator_chat, self.chat_channel) {
+            (Some(spectators), ChatChannel::Spectators) => {
+                (&spectators.messages, ChatChannel::Spectators)
+            }
+            _ => (&correspondence.chat, ChatChannel::Players),
+        };
+        let channels = correspondence.spectator_chat.as_ref().map(|_| {
+            [ChatChannel::Players, ChatChannel::Spectators]
+                .into_iter()
+                .map(|option| {
+                    let onclick = ctx
+                        .link()
+                        .callback(move |_: MouseEvent| Msg::ShowChatChannel(option));
+                    html! {
+                        <button
+                            class={ classes!((option == channel).then_some("chat-channel-shown")) }
+                            onclick={ onclick }
+                        >
+                            { match option {
+                                ChatChannel::Players => "Players",
+                                ChatChannel::Spectators => "Spectators",
+                            } }
+                        </button>
+                    }
+                })
+                .collect::<Html>()
+        });
+        let follow_cb = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::ToggleSpectatorChat);
+        let follow = html! {
+            <button onclick={ follow_cb }>
+                { if correspondence.spectator_chat.is_some() {
+                    "Stop following spectators"
+                } else {
+                    "Follow spectator chat"
+                } }
+            </button>
+        };
+        self.render_chat_pane(ctx, messages, html! { { for channels } }, follow)
+    }
+
+    /// Renders the spectators' chat while spectating a correspondence game,
+    /// as a sidebar section.
+    fn render_spectator_chat(&self, ctx: &AppContext) -> Html {
+        match &self.watching {
+            Some(Watching::Room(room)) => {
+                self.render_chat_pane(ctx, &room.chat, html! {}, html! {})
+            }
+            _ => html! {},
+        }
+    }
+
+    /// The chat section with `messages`, a box to write in and the quick
+    /// replies, with `header` and `footer` around them.
+    fn render_chat_pane(
+        &self,
+        ctx: &AppContext,
+        messages: &[ChatMessage],
+        header: Html,
+        footer: Html,
+    ) -> Html {
+        let log = messages.iter().map(|message| {
+            html! {
+                <li>
+                    <span class="chat-sender">{ &message.sender }</span>
+                    { " " }
+                    { &message.text }
+                </li>
+            }
+        });
+        let quick_replies = QUICK_REPLIES.iter().map(|reply| {
+            let onclick = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::SendChat(String::from(*reply)));
+            html! {
+                <button class="chat-quick-reply" onclick={ onclick }>{ *reply }</button>
+            }
+        });
+        let draft_cb = ctx.link().callback(|e: InputEvent| {
+            Msg::SetChatDraft(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let draft = self.chat_draft.clone();
+        let send_cb = ctx.link().callback(move |e: SubmitEvent| {
+            e.prevent_default();
+            Msg::SendChat(draft.clone())
+        });
+        html! {
+            <SidebarSection title="Chat">
+                <div class="chat">
+                    { header }
+                    <ol class="chat-log" aria-live="polite">{ for log }</ol>
+                    <form class="chat-form" onsubmit={ send_cb }>
+                        <input
+                            type="text"
+                            placeholder="Message"
+                            aria-label="Message"
+                            value={ self.chat_draft.clone() }
+                            oninput={ draft_cb }
+                        />
+                        <button type="submit">{ "Send" }</button>
+                    </form>
+                    { for quick_replies }
+                    { footer }
+                </div>
+            </SidebarSection>
+        }
+    }
+
+    /// Shows that the board is a game being spectated, and how many are
+    /// watching it.
+    fn render_spectating(&self) -> Html {
+        match &self.watching {
+            Some(Watching::Room(room)) => html! {
+                <div class="spectating-banner" role="status">
+                    { format!(
+                        "Spectating game {}, {} watching",
+                        room.id,
+                        room.viewers.count(js_sys::Date::now())
+                    ) }
+                </div>
+            },
+            _ => html! {},
+        }
+    }
+
+    /// Marks the stones that look dead once both players passed, for them
+    /// to correct by clicking.
+    fn suggest_dead_stones(&mut self) {
+        if self.board.is_scoring() && self.board.dead_stones().is_empty() {
+            let dead = probably_dead(&self.board, self.board.next_player);
+            self.board.set_dead_stones(dead);
+        }
+    }
+
+    /// Starts a new game on the same board size under the same rules, komi
+    /// and handicap, keeping the players' names and ranks with them and
+    /// swapping their colors if `swap` is set.
+    fn rematch(&mut self, swap: bool) {
+        let mut info = self.board.info.clone();
+        if swap {
+            std::mem::swap(&mut info.black_name, &mut info.white_name);
+            std::mem::swap(&mut info.black_rank, &mut info.white_rank);
+            if let Some(Bot { color, .. }) = &mut self.bot {
+                *color = match color {
+                    Player::Black => Player::White,
+                    _ => Player::Black,
+                };
+            }
+        }
+        info.recorded_result = None;
+        self.board = GoGame::rectangular(self.board.width(), self.board.height())
+            .with_rules(self.board.rules())
+            .with_handicap(info.handicap, self.new_handicap_placement);
+        self.board.info = info;
+        self.correspondence = None;
+        self.ogs = None;
+        self.result_shown = false;
+        self.rated = false;
+        self.tournament_game = None;
+        self.hints_used = 0;
+        self.preview = None;
+        self.viewing = None;
+        self.play_bot_move();
+    }
+
+    /// Shows the result of a game that ended by resigning or capturing.
+    /// Games ended by passing are shown once the dead stones are agreed on,
+    /// and practice games have a result of their own.
+    fn show_result(&mut self) {
+        if self.practice.is_none() && self.board.is_over() && !self.board.is_scoring() {
+            self.result_shown = true;
+        }
+    }
+
+    /// Shows the count of a game ended by passing while the dead stones are
+    /// being marked, with a button to accept it.
+    fn render_scoring(&self, ctx: &AppContext) -> Html {
+        if !self.board.is_scoring()
+            || self.viewing.is_some()
+            || self.watching.is_some()
+            || self.result_shown
+        {
+            return html! {};
+        }
+        let score = self.board.score();
+        let accept_cb = ctx.link().callback(|_: MouseEvent| Msg::AcceptScore);
+        html! {
+            <div class="result-banner" role="status">
+                { format!(
+                    "Black {}, White {} + {} komi. Click a group to mark it dead or alive. ",
+                    score.black,
+                    score.white,
+                    self.board.compensation()
+                ) }
+                <button onclick={ accept_cb }>{ "Accept" }</button>
+            </div>
+        }
+    }
+
+    /// Renders the result of the finished game, with how it was counted
+    /// when it was played out, and what to do next.
+    fn render_result(&self, ctx: &AppContext) -> Html {
+        if !self.result_shown {
+            return html! {};
+        }
+        let (headline, breakdown) = if let Some(loser) = self.board.resigned() {
+            let winner = match loser {
+                Player::Black => Player::White,
+                _ => Player::Black,
+            };
+            (
+                format!("{} wins by resignation", player_name(winner)),
+                html! {},
+            )
+        } else if let Some(winner) = self.board.capture_winner() {
+            (format!("{} wins by capture", player_name(winner)), html! {})
+        } else {
+            let breakdown = self.board.breakdown();
+            let margin = self.board.score().margin(Player::Black, breakdown.komi);
+            let headline = if margin > 0.0 {
+                format!("Black wins by {}", margin)
+            } else if margin < 0.0 {
+                format!("White wins by {}", -margin)
+            } else {
+                String::from("Draw")
+            };
+            (headline, render_breakdown(&breakdown))
+        };
+        let review_cb = ctx.link().callback(|_: MouseEvent| Msg::ReviewGame);
+        let save_cb = ctx.link().callback(|_: MouseEvent| Msg::SaveSgf);
+        let rematch_cb = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::Rematch { nigiri: false });
+        let nigiri_cb = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::Rematch { nigiri: true });
+        html! {
+            <div class="result-dialog" role="dialog" aria-label="Game result">
+                <h2>{ headline }</h2>
+                { breakdown }
+                <div class="result-actions">
+                    <button onclick={ review_cb }>{ "Review game" }</button>
+                    <button onclick={ save_cb }>{ "Save SGF" }</button>
+                    <button onclick={ rematch_cb }>{ "Rematch" }</button>
+                    <button onclick={ nigiri_cb }>{ "Rematch with nigiri" }</button>
+                </div>
+            </div>
+        }
+    }
 
-impl Component for App {
-    type Message = Msg;
-    type Properties = ();
+    /// Announces the winner of a game decided by capturing, see
+    /// `GoGame::capture_winner`.
+    fn render_capture_result(&self) -> Html {
+        match self.board.capture_winner() {
+            Some(winner) => html! {
+                <div class="result-banner" role="status">
+                    { format!("{} captured first and wins!", player_name(winner)) }
+                </div>
+            },
+            None => html! {},
+        }
+    }
 
-    fn create(_ctx: &Context<Self>) -> Self {
-        App {
-            board_ref: NodeRef::default(),
-            board: GoGame::new(0),
-            preview: None,
-            sidebar_shown: false,
+    /// Lets the bot answer if it is its turn.
+    fn play_bot_move(&mut self) {
+        let bot = match &self.bot {
+            // The bot leaves analysis to the player
+            Some(bot) if self.analysis.is_none() => bot,
+            _ => return,
+        };
+        if self.board.is_over() || self.board.next_player != bot.color {
+            return;
+        }
+        match bot.engine.choose_move(&self.board) {
+            Some((x, y)) => {
+                if self.board.play_move(x, y).is_err() {
+                    self.board.pass();
+                }
+            }
+            None => self.board.pass(),
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
-            // TODO: implement creations for every size
-            // currently must be odd so dots work properly
-            Msg::MakeBoard { size: x } if [5, 7, 9, 13].contains(&x) => {
-                self.board = GoGame::new(x);
-                true
+    /// Passes for the player to move for as long as they have no legal move
+    /// left, letting the bot answer each pass, so nobody is left clicking
+    /// points that can't be played. Problems, demos and games waiting for
+    /// the opponent are left alone.
+    fn pass_while_stuck(&mut self) {
+        while self.problem.is_none()
+            && self.demo.is_none()
+            && !self.board.is_over()
+            && !self.waiting_for_opponent()
+            && !self.has_legal_move()
+        {
+            self.toast = Some(format!(
+                "{} has no legal move left and passes.",
+                player_name(self.board.next_player)
+            ));
+            self.board.pass();
+            self.send_correspondence_move();
+            self.send_ogs_move(None);
+            self.play_bot_move();
+        }
+    }
+
+    /// Whether the player to move may play anywhere, see
+    /// `GoGame::legal_moves`, within the region play is limited to.
+    fn has_legal_move(&self) -> bool {
+        self.board
+            .legal_moves()
+            .any(|(x, y)| self.check_move(x, y).is_ok())
+    }
+
+    /// The profile the game on the board is rated under: the OGS player for
+    /// OGS games played here, the local one otherwise.
+    fn rating_profile(&self) -> String {
+        match (&self.ogs, &self.ogs_login) {
+            (Some(ogs), Some(login)) if ogs.color != Player::None => login.username.clone(),
+            _ => String::from(LOCAL_PROFILE),
+        }
+    }
+
+    /// The rating of the player of the game on the board for its board
+    /// size, once they have one.
+    fn rating_now(&self) -> Option<f64> {
+        let size = (self.board.width(), self.board.height());
+        let history = self.ratings.history(&self.rating_profile(), size);
+        (history.games() > 0).then(|| history.current())
+    }
+
+    /// Rates the game on the board once it has a result: games against the
+    /// library bot, practice included, under the local profile with the bot
+    /// rated as the opponent, and OGS games played here when the record
+    /// gives the opponent's rank. Games ended by passing are rated once the
+    /// count is accepted, and a game is only rated once, also when moves
+    /// are taken back after.
+    fn rate_game(&mut self) {
+        if self.rated || self.analysis.is_some() {
+            return;
+        }
+        let size = (self.board.width(), self.board.height());
+        let profile = self.rating_profile();
+        let (color, opponent, result) = if let Some(bot) = &self.bot {
+            let counted = self.practice.is_some() || !self.board.is_scoring() || self.result_shown;
+            if !self.board.is_over() || !counted {
+                return;
             }
-            Msg::MakeBoard { .. } => {
-                self.board = GoGame::default();
-                true
+            let color = match bot.color {
+                Player::White => Player::Black,
+                _ => Player::White,
+            };
+            let opponent = self.ratings.history(BOT_PROFILE, size).current();
+            (color, opponent, self.board.result())
+        } else if let (Some(ogs), Some(_)) = (&self.ogs, &self.ogs_login) {
+            // OGS says how its games ended, counted or not
+            let result = self.board.info.recorded_result.clone();
+            let rank = match ogs.color {
+                Player::Black => &self.board.info.white_rank,
+                _ => &self.board.info.black_rank,
+            };
+            match rank_rating(rank) {
+                Some(opponent)
+                    if ogs.color != Player::None
+                        && (result.is_some() || self.board.resigned().is_some()) =>
+                {
+                    (ogs.color, opponent, result.or_else(|| self.board.result()))
+                }
+                _ => return,
             }
-            Msg::Click { x, y } => {
-                let border_width = self.get_tile_border_width() as f64;
-                let tile_size = self.get_tile_size() as f64;
-                let board_padding = self.get_board_padding() as f64;
+        } else {
+            return;
+        };
+        let score = match result.as_deref() {
+            Some("0") | Some("Draw") => 0.5,
+            Some(result) => match result_winner(result) {
+                Some(winner) if winner == color => 1.0,
+                Some(_) => 0.0,
+                None => return,
+            },
+            None => return,
+        };
+        let rating = self.ratings.history(&profile, size).current();
+        self.ratings
+            .history_mut(&profile, size)
+            .record(opponent, score);
+        if self.bot.is_some() {
+            self.ratings
+                .history_mut(BOT_PROFILE, size)
+                .record(rating, 1.0 - score);
+        }
+        self.ratings.save();
+        self.rated = true;
+    }
 
-                let x = x as usize - (board_padding - tile_size / 2.0) as usize;
-                let y = y as usize - (board_padding - tile_size / 2.0) as usize;
-                let end_limit = tile_size as usize * self.board.board_size()
-                    + border_width as usize * self.board.board_size();
-                if !(0..end_limit).contains(&x) || !(0..end_limit).contains(&y) {
-                    return false;
-                }
+    /// Fills in the handicap and komi of the next game from the ranks
+    /// entered on the start screen, by the table picked in the settings.
+    fn suggest_handicap(&mut self) {
+        if let Some(difference) = rank_difference(&self.new_ranks.0, &self.new_ranks.1) {
+            // Black being the stronger player is left as an even game, with
+            // a note to swap colors
+            let handicap = self
+                .settings
+                .handicap_table
+                .handicap(difference.max(0) as usize);
+            self.new_handicap = handicap.stones;
+            self.new_komi = handicap.komi;
+        }
+    }
 
-                let x = x / (tile_size + border_width) as usize;
-                let y = y / (tile_size + border_width) as usize;
+    /// Says what the ranks entered on the start screen call for.
+    fn render_handicap_suggestion(&self) -> Html {
+        let difference = match rank_difference(&self.new_ranks.0, &self.new_ranks.1) {
+            Some(difference) => difference,
+            None => return html! {},
+        };
+        let text = if difference < 0 {
+            String::from("Black is the stronger player: swap the ranks to give a handicap.")
+        } else {
+            let handicap = self.settings.handicap_table.handicap(difference as usize);
+            let komi = handicap
+                .komi
+                .unwrap_or_else(|| self.settings.rules.ruleset().komi());
+            match handicap.stones {
+                0 => format!("Suggested: no handicap, komi {}", komi),
+                stones => format!("Suggested: {} stones, komi {}", stones, komi),
+            }
+        };
+        html! { <p class="handicap-suggestion">{ text }</p> }
+    }
 
-                match self.preview {
-                    Some(preview_coords) if preview_coords == (x, y) => {
-                        self.preview = None;
-                        // Play the move on the board
-                        self.board.play_move(x, y).is_ok()
-                    }
-                    _ => {
-                        // Check if position can be played on
-                        let next_player = self.board.next_player;
-                        if self.board.is_valid_move(x, y, next_player) {
-                            self.preview = Some((x, y));
-                            true
-                        } else {
-                            self.preview = None;
-                            true
-                        }
-                    }
+    /// Rates the game on the board and records its result in the
+    /// tournament, once it has ended.
+    fn settle_game(&mut self) {
+        self.rate_game();
+        self.record_tournament_result();
+    }
+
+    /// Records the result of the tournament game on the board once it is
+    /// known, like `rate_game` does for ratings.
+    fn record_tournament_result(&mut self) {
+        let (round, game) = match self.tournament_game {
+            Some(game) => game,
+            None => return,
+        };
+        if !self.board.is_over() || (self.board.is_scoring() && !self.result_shown) {
+            return;
+        }
+        let result = match self.board.result().as_deref() {
+            Some("0") | Some("Draw") => GameResult::Jigo,
+            Some(result) => match result_winner(result) {
+                Some(Player::Black) => GameResult::BlackWins,
+                Some(Player::White) => GameResult::WhiteWins,
+                _ => return,
+            },
+            None => return,
+        };
+        if let Some(tournament) = &mut self.tournament {
+            tournament.set_result(round, game, Some(result));
+            tournament_panel::save(Some(tournament));
+        }
+        self.tournament_game = None;
+    }
+
+    fn control_tournament(&mut self, control: TournamentControl) -> bool {
+        match control {
+            TournamentControl::Start {
+                entrants,
+                format,
+                handicaps,
+                board_size,
+            } => match Tournament::new(
+                entrants,
+                format,
+                handicaps.then_some(self.settings.handicap_table),
+                board_size,
+            ) {
+                Ok(tournament) => {
+                    tournament_panel::save(Some(&tournament));
+                    self.tournament = Some(tournament);
                 }
-            }
-            Msg::Pass => {
+                Err(e) => self.toast = Some(e),
+            },
+            TournamentControl::Play { round, game } => {
+                let tournament = match &self.tournament {
+                    Some(tournament) if tournament.is_pending(round, game) => tournament,
+                    _ => return false,
+                };
+                if self.watching.is_some() || self.analysis.is_some() || self.demo.is_some() {
+                    return false;
+                }
+                let pairing = &tournament.rounds[round][game];
+                let (black, white) = (
+                    &tournament.entrants[pairing.black],
+                    &tournament.entrants[pairing.white],
+                );
+                let mut board = GoGame::rectangular(tournament.board_size, tournament.board_size)
+                    .with_rules(self.settings.rules.ruleset())
+                    .with_handicap(pairing.handicap, self.new_handicap_placement);
+                board.info.black_name = black.name.clone();
+                board.info.black_rank = black.rank.clone();
+                board.info.white_name = white.name.clone();
+                board.info.white_rank = white.rank.clone();
+                if let Some(komi) = pairing.komi {
+                    board.info.komi = komi;
+                }
+                self.result_shown = false;
+                self.rated = false;
+                self.hints_used = 0;
+                self.board = board;
+                self.correspondence = None;
+                self.ogs = None;
+                self.problem = None;
+                self.bot = None;
+                self.practice = None;
+                self.viewing = None;
                 self.preview = None;
-                self.board.pass();
-                true
-            }
-            Msg::HideSidebar => {
+                self.tournament_game = Some((round, game));
                 self.sidebar_shown = false;
-                true
             }
-            Msg::ShowSidebar => {
-                self.sidebar_shown = true;
-                true
+            TournamentControl::SetResult {
+                round,
+                game,
+                result,
+            } => {
+                if let Some(tournament) = &mut self.tournament {
+                    tournament.set_result(round, game, result);
+                    tournament_panel::save(Some(tournament));
+                }
+                if self.tournament_game == Some((round, game)) {
+                    self.tournament_game = None;
+                }
+            }
+            TournamentControl::End => {
+                self.tournament = None;
+                self.tournament_game = None;
+                tournament_panel::save(None);
             }
         }
+        true
     }
 
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        match self.board.board_size() {
-            0 => {
-                let button_onclick = ctx.link().callback(move |_| Msg::MakeBoard { size: 19 });
-                html! {
-                    <main>
-                        <button onclick={ button_onclick }>{ "Default" }</button>
-                        <table class="g-board" style="display: none;">
-                            <td></td>
-                        </table>
-                    </main>
+    /// Adds a game to the library and counts it in the statistics.
+    fn add_to_library(&mut self, entry: LibraryEntry) {
+        if let Some(game) = entry.game() {
+            self.library_stats.add(&game);
+            for name in [&game.info.black_name, &game.info.white_name] {
+                if !name.is_empty() {
+                    self.library_players.insert(name.clone());
                 }
             }
-            _ => {
-                let board_ref = self.board_ref.clone();
-                let board_oncontext = ctx.link().callback(move |e: MouseEvent| {
-                    e.prevent_default();
-                    let board = board_ref.cast::<HtmlElement>().unwrap();
-                    let rect = board.get_bounding_client_rect();
-                    let mouse_x = ((e.client_x() as f64) - rect.left()) as i32;
-                    let mouse_y = ((e.client_y() as f64) - rect.top()) as i32;
-                    Msg::Click {
-                        x: mouse_x,
-                        y: mouse_y,
-                    }
-                });
-                let board = self.make_board_ref();
-                let dots = self.make_dots_html();
-                let preview = self.render_preview();
-                let tiles = self.render_moves();
-
-                let control_panel = self.control_panel(ctx);
-
-                let hide_sidebar_callback = ctx.link().callback(|_: MouseEvent| Msg::HideSidebar);
-                let sidebar_children = html! {
-                    <>
-                        <h1 style="flex: 0 0 100%;">{ "Hello, world!" }</h1>
-                    </>
-                };
-                let sidebar_html =
-                    make_sidebar(sidebar_children, self.sidebar_shown, hide_sidebar_callback);
-
-                let show_sidebar_callback = ctx.link().callback(|_: MouseEvent| Msg::ShowSidebar);
+        }
+        self.library.push(entry);
+    }
 
-                // Return full html
-                html! {
-                    <>
-                        // sidebar icon
-                        <img class="menu-icon" src="imgs/menu.svg" onclick={ show_sidebar_callback } />
-                        { sidebar_html }
-                        <main>
-                            <div
-                                ref={ self.board_ref.clone() }
-                                onclick={ board_oncontext }
-                                class="g-container"
-                            >
-                                { dots }
-                                { preview }
-                                { tiles }
-                                { board }
-                            </div>
-                            { control_panel }
-                        </main>
-                    </>
-                }
+    /// Renders two library games side by side, each with its own move
+    /// navigation.
+    fn render_comparison(&self, ctx: &AppContext, comparison: &Comparison) -> Html {
+        let boards = comparison.games.iter().enumerate().map(|(side, game)| {
+            let back_cb = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::CompareStep { side, delta: -1 });
+            let forward_cb = ctx
+                .link()
+                .callback(move |_: MouseEvent| Msg::CompareStep { side, delta: 1 });
+            html! {
+                <div class="compared-game">
+                    <h2>{ &game.name }</h2>
+                    { render_static_board(game.position(), None) }
+                    <div class="compare-nav">
+                        <button onclick={ back_cb } disabled={ game.moves() == 0 }>
+                            { "Back" }
+                        </button>
+                        <span>{ format!("Move {} of {}", game.moves(), game.total_moves()) }</span>
+                        <button
+                            onclick={ forward_cb }
+                            disabled={ game.moves() == game.total_moves() }
+                        >
+                            { "Forward" }
+                        </button>
+                    </div>
+                </div>
             }
+        });
+        let linked_cb = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::ToggleLinkedNavigation);
+        let close_cb = ctx.link().callback(|_: MouseEvent| Msg::CloseComparison);
+        html! {
+            <main class="comparison">
+                { for boards }
+                <div class="control-panel">
+                    <button onclick={ linked_cb }>
+                        { if comparison.linked { "Unlink navigation" } else { "Link navigation" } }
+                    </button>
+                    <button onclick={ close_cb }>{ "Close comparison" }</button>
+                </div>
+            </main>
         }
     }
-}
 
-impl App {
-    fn control_panel(&self, ctx: &Context<Self>) -> Html {
-        let pass_cb = ctx.link().callback(|_: MouseEvent| Msg::Pass);
+    /// Renders the actions for setting up a study position
+    fn render_edit_actions(&self, ctx: &AppContext) -> Html {
+        let corner_cb = ctx.link().callback(|_: MouseEvent| Msg::CycleCorner);
+        let corner_text = match self.transplant_corner {
+            Corner::TopLeft => "Corner: top left",
+            Corner::TopRight => "Corner: top right",
+            Corner::BottomLeft => "Corner: bottom left",
+            Corner::BottomRight => "Corner: bottom right",
+        };
+        let size_buttons = [9, 13, 19]
+            .into_iter()
+            .filter(|size| !self.board.is_square() || *size != self.board.board_size())
+            .map(|size| {
+                let transplant_cb = ctx
+                    .link()
+                    .callback(move |_: MouseEvent| Msg::Transplant(size));
+                html! {
+                    <button onclick={ transplant_cb }>{ format!("Copy to {0}x{0}", size) }</button>
+                }
+            });
+        let demo_cb = ctx.link().callback(|_: MouseEvent| Msg::StartDemo);
+        let coordinates_cb = ctx.link().callback(|_: Event| Msg::ToggleImageCoordinates);
+        let numbers_cb = ctx.link().callback(|_: Event| Msg::ToggleImageMoveNumbers);
+        let png_cb = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::ExportImage { png: true });
+        let svg_cb = ctx
+            .link()
+            .callback(|_: MouseEvent| Msg::ExportImage { png: false });
+        let kifu_cb = ctx.link().callback(|_: MouseEvent| Msg::PrintKifu);
         html! {
-            <div class="control-panel">
-                <button onclick={ pass_cb }>{ "Pass" }</button>
+            <div class="edit-actions">
+                <button onclick={ corner_cb }>{ corner_text }</button>
+                { for size_buttons }
+                if self.demo.is_none() && self.analysis.is_none() {
+                    <button onclick={ demo_cb }>{ "Demo board" }</button>
+                }
+                <div class="export-image">
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked={ self.image_options.coordinates }
+                            onchange={ coordinates_cb }
+                        />
+                        { "Coordinates" }
+                    </label>
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked={ self.image_options.move_numbers }
+                            onchange={ numbers_cb }
+                        />
+                        { "Move numbers" }
+                    </label>
+                    <button onclick={ png_cb }>{ "Export PNG" }</button>
+                    <button onclick={ svg_cb }>{ "Export SVG" }</button>
+                </div>
+                <button onclick={ kifu_cb }>{ "Print kifu" }</button>
             </div>
         }
     }
 
-    fn render_preview(&self) -> Html {
-        match self.preview {
-            None => {
-                html! {}
-            }
-            Some((x, y)) => {
-                let tile_size = self.get_tile_size();
-                let shift_size = tile_size + self.get_tile_border_width();
-                let offset: i32 = tile_size as i32 / 2;
-                match self.board.next_player {
-                    Player::None => {
-                        html! {}
-                    }
-                    Player::White => {
-                        html! {
-                            <div
-                                class="g-preview-white"
-                                style={ format!(
-                                    "position: absolute; transform: translate({}.5px, {}.5px)",
-                                    (shift_size * x) as i32 - offset,
-                                    (shift_size * y) as i32 - offset,
-                                )}>
-                            </div>
-                        }
-                    }
-                    Player::Black => {
-                        html! {
-                            <div
-                                class="g-preview-black"
-                                style={ format!(
-                                    "position: absolute; transform: translate({}.5px, {}.5px)",
-                                    (shift_size * x) as i32 - offset,
-                                    (shift_size * y) as i32 - offset,
-                                )}>
-                            </div>
-                        }
-                    }
+    /// Lays out the game for printing: who played, a diagram with every
+    /// move numbered and a table of the moves the diagram can't show. Only
+    /// drawn while printing, the print stylesheet hides everything else.
+    fn render_kifu(&self) -> Html {
+        if !self.printing_kifu {
+            return html! {};
+        }
+        let kifu = Kifu::new(&self.board);
+        let info = &self.board.info;
+        let name = |name: &str, player| match name {
+            "" => player_name(player).to_owned(),
+            name => name.to_owned(),
+        };
+        let title = format!(
+            "{} (Black) vs {} (White)",
+            name(&info.black_name, Player::Black),
+            name(&info.white_name, Player::White)
+        );
+        let details = format!(
+            "{} rules, komi {}{}",
+            self.board.rules().name(),
+            info.komi,
+            self.board
+                .result()
+                .map_or_else(String::new, |result| format!(", result {}", result))
+        );
+        let diagram = board_svg(
+            &kifu.diagram,
+            &kifu.numbers,
+            ImageOptions {
+                coordinates: true,
+                move_numbers: true,
+            },
+        );
+        let notes = kifu.notes.iter().map(|note| {
+            let text = match note.kind {
+                NoteKind::At(first) => format!("at {}", first),
+                NoteKind::AtStartingStone(index) => {
+                    let (x, y) = kifu.diagram.index_to_coord(index);
+                    format!("at {}", kifu.diagram.point_name(x, y))
                 }
+                NoteKind::Pass => String::from("pass"),
+                NoteKind::Resign => String::from("resigns"),
+            };
+            html! {
+                <tr>
+                    <td>{ note.number }</td>
+                    <td>{ player_name(note.player) }</td>
+                    <td>{ text }</td>
+                </tr>
             }
+        });
+        html! {
+            <div class="kifu">
+                <h1>{ title }</h1>
+                <p>{ details }</p>
+                { Html::from_html_unchecked(diagram.into()) }
+                if !kifu.notes.is_empty() {
+                    <table class="kifu-notes">{ for notes }</table>
+                }
+            </div>
         }
     }
-    /// Renders moves, but it preparational style
-    fn render_moves(&self) -> Html {
-        const TILE_MODIFIER: f64 = 0.45;
 
-        let board_size = self.board.board_size();
-        let board_padding = self.get_board_padding();
-        let mut tiles = Vec::with_capacity(board_size);
-        for (i, player) in self.board.position().iter().enumerate() {
-            let (x, y) = self.board.index_to_coord(i);
-            let tile_size = self.get_tile_size();
-            let shift_size = tile_size + self.get_tile_border_width();
+    /// The stones and territory of each color of a three-player game once
+    /// it is counted, which has no result of its own.
+    fn area_summary(&self) -> Option<String> {
+        if self.board.colors() < 3 || !self.board.is_scoring() {
+            return None;
+        }
+        let areas: Vec<String> = self
+            .board
+            .areas()
+            .into_iter()
+            .map(|(player, area)| format!("{} {}", player_name(player), area))
+            .collect();
+        Some(areas.join(", "))
+    }
+
+    /// Renders the notable events of the game, each of which can be clicked
+    /// to view the position after that move.
+    fn render_game_info(&self, ctx: &AppContext) -> Html {
+        let now = js_sys::Date::now();
+        html! {
+            <GameInfoPanel
+                info={ self.board.info.clone() }
+                rules={ self.board.rules().name() }
+                handicap_to_place={ self.board.handicap_to_place() }
+                next_player={ self.board.next_player }
+                result={ self.board.result().or_else(|| self.area_summary()) }
+                phase={ phase(&self.board, self.board.move_count()) }
+                neutral_points={ self.board.is_scoring().then(|| self.board.neutral_points()) }
+                colors={ self.board.colors() }
+                captures={ [Player::Black, Player::White, Player::Red].map(|player| self.board.captures(player)) }
+                times={ [Player::Black, Player::White, Player::Red].map(|player| self.clock.used(player, now)) }
+                events={ self.board.events().to_vec() }
+                onjump={ ctx.link().callback(Msg::JumpTo) }
+            />
+        }
+    }
 
-            let shift_x = shift_size * x + board_padding;
-            let shift_y = shift_size * y + board_padding;
+    /// Renders the numbered moves of the game, each of which can be clicked
+    /// to view the position after it.
+    fn render_move_list(&self, ctx: &AppContext, review: bool) -> Html {
+        // The ladders and snapbacks played are tagged while reviewing
+        let tags = match &self.tactics {
+            Some((_, tactics)) if review => tactics
+                .iter()
+                .map(|tactic| (tactic.moves, tactic.kind.name()))
+                .collect(),
+            _ => vec![],
+        };
+        html! {
+            <MoveList
+                moves={ self.board.move_names() }
+                times={ self.board.history().iter().map(|m| m.thinking).collect::<Vec<_>>() }
+                shown={ self.shown_moves() }
+                onjump={ ctx.link().callback(Msg::ShowMoves) }
+                tags={ tags }
+                onexplain={ ctx.link().callback(Msg::ExplainTactic) }
+            />
+        }
+    }
 
-            // Get computed style
-            let body_style = window().get_computed_style(&body()).unwrap().unwrap();
+    /// What the tactics found depend on: the moves of the game and where
+    /// they led.
+    fn tactics_key(&self) -> (usize, u64) {
+        (self.board.move_count(), self.board.save().hash())
+    }
 
-            match *player {
-                Player::None => {}
-                Player::White => {
-                    let white = body_style.get_property_value("--fg-white").unwrap();
-                    let white = self.convert_color_to_hex(white);
-                    let tile = html! {
-                        <circle
-                            cx={ shift_x.to_string() }
-                            cy={ shift_y.to_string() }
-                            r={ format!("{:.2}", (tile_size as f64 * TILE_MODIFIER)) }
-                            fill={ white }
-                        >
-                        </circle>
-                    };
-                    tiles.push(tile);
-                }
-                Player::Black => {
-                    let black = body_style.get_property_value("--fg-black").unwrap();
-                    let black = self.convert_color_to_hex(black);
-                    let tile = html! {
-                        <circle
-                            cx={ shift_x.to_string() }
-                            cy={ shift_y.to_string() }
-                            r={ format!("{:.2}", (tile_size as f64 * TILE_MODIFIER)) }
-                            fill={ black }
-                        >
-                        </circle>
-                    };
-                    tiles.push(tile);
+    /// Numbers the moves of the explained tactic on faint stones, over the
+    /// position it starts from.
+    fn render_tactic(&self) -> Html {
+        let tactic = match (&self.tactics, self.tactic_shown) {
+            (Some((_, tactics)), Some(index)) => match tactics.get(index) {
+                Some(tactic) if tactic.moves == self.shown_moves() => tactic,
+                _ => return html! {},
+            },
+            _ => return html! {},
+        };
+        // A point played twice, like the throw-in of a snapback, gets both
+        // numbers
+        let mut points: Vec<(usize, Player, Vec<usize>)> = vec![];
+        for (number, (player, index)) in tactic.sequence.iter().enumerate() {
+            match points.iter_mut().find(|(point, _, _)| point == index) {
+                Some((_, last, numbers)) => {
+                    *last = *player;
+                    numbers.push(number + 1);
                 }
+                None => points.push((*index, *player, vec![number + 1])),
             }
         }
-        let svg_size = self.get_tile_size() as usize * (self.board.board_size() - 1)
-            + self.get_tile_border_width() as usize * self.board.board_size();
+        let stones = points.into_iter().map(|(index, player, numbers)| {
+            let (x, y) = self.board.index_to_coord(index);
+            let (class, fill, label) = match player {
+                Player::White => ("g-preview-white", WHITE, BLACK),
+                _ => ("g-preview-black", BLACK, WHITE),
+            };
+            let numbers: Vec<String> = numbers.iter().map(usize::to_string).collect();
+            html! {
+                <g>
+                    <circle
+                        class={ class }
+                        cx={ x.to_string() }
+                        cy={ y.to_string() }
+                        r={ STONE_RADIUS.to_string() }
+                        fill={ fill }
+                        fill-opacity="0.6"
+                    />
+                    <text
+                        x={ x.to_string() }
+                        y={ y.to_string() }
+                        fill={ label }
+                        font-size="0.35"
+                        text-anchor="middle"
+                        dominant-baseline="central"
+                    >
+                        { numbers.join("/") }
+                    </text>
+                </g>
+            }
+        });
         html! {
-            <svg width={ (svg_size + 2 * board_padding).to_string() }
-                height={ (svg_size + 2 * board_padding).to_string() }
-                style={ format!("transform: translate(-{0}px, -{0}px);", board_padding) }
-                fill="none" xmlns="http://www.w3.org/2000/svg">
-                { for tiles }
-            </svg>
-        }
-    }
-
-    /// Converts a comma-space-separated list of rgb values into the hexadecimal color
-    /// equivalent. The function also adds a '#' to the front.
-    ///
-    /// Example:
-    /// ```rust
-    /// let result = self.convert_color_to_hex("0, 0, 0".to_owned());
-    /// assert_eq!(result, "#000000");
-    /// ```
-    fn convert_color_to_hex(&self, color_str: String) -> String {
-        "#".to_owned()
-            + &color_str
-                .split(",")
-                .map(|part| format!("{:X}", part.trim().parse::<usize>().unwrap()))
-                .collect::<Vec<String>>()
-                .join("")
-    }
-
-    fn get_tile_size(&self) -> usize {
-        let tile = document().query_selector(".g-board td").unwrap().unwrap();
-        let tile_style = window().get_computed_style(&tile).unwrap().unwrap();
-        let tile_size = tile_style
-            .get_property_value("width")
-            .unwrap()
-            // Get rid of the unit on the end, presumably "px"
-            .chars()
-            .filter(|c| c.is_numeric())
-            .collect::<String>()
-            // convert to f64
-            .parse::<usize>()
-            .unwrap();
-        tile_size
-    }
-
-    /// Assumes there is a <td> element under an element with class
-    /// `g-board`. Panics otherwise.
-    fn get_tile_border_width(&self) -> usize {
-        let tile = document().query_selector(".g-board td").unwrap().unwrap();
-        let tile_style = window().get_computed_style(&tile).unwrap().unwrap();
-        let border_width = tile_style
-            .get_property_value("border-top-width")
-            .unwrap()
-            // Get rid of the unit on the end, presumably "px"
-            .chars()
-            .filter(|c| c.is_numeric())
-            .collect::<String>()
-            // convert to usize
-            .parse::<usize>()
-            .unwrap();
-        border_width
-    }
-
-    fn get_board_padding(&self) -> usize {
-        let board = document().query_selector(".g-container").unwrap();
-        if board.is_none() {
-            return 0;
-        }
-        let board = board.unwrap();
-        let board_style = window().get_computed_style(&board).unwrap().unwrap();
-        let board_padding = board_style
-            .get_property_value("padding-left")
-            .unwrap()
-            // Get rid of the unit on the end, presumably "px"
-            .chars()
-            .filter(|c| c.is_numeric())
-            .collect::<String>()
-            // convert to usize
-            .parse::<usize>()
-            .unwrap();
-        board_padding
-    }
-
-    fn make_board_ref(&self) -> Html {
-        let mut board = Vec::with_capacity(self.board.board_size() - 1);
-        for _ in 0..self.board.board_size() - 1 {
-            let mut board_row = Vec::with_capacity(self.board.board_size() - 1);
-            for _ in 0..self.board.board_size() - 1 {
-                let tile_html = html! {
-                    <td></td>
-                };
-                board_row.push(tile_html);
+            <g class="g-tactic" pointer-events="none">{ for stones }</g>
+        }
+    }
+
+    /// Dims the points locked by the teacher.
+    fn render_locked(&self) -> Html {
+        let region = match self.region() {
+            Some(region) if self.demo.is_none() => region,
+            _ => return html! {},
+        };
+        let cells = region.locked().map(|index| {
+            let (x, y) = self.board.index_to_coord(index);
+            html! {
+                <rect
+                    class="g-locked"
+                    x={ (x as f64 - 0.5).to_string() }
+                    y={ (y as f64 - 0.5).to_string() }
+                    width="1"
+                    height="1"
+                />
             }
-            let row_html = html! {
-                <tr>{ for board_row }</tr>
-            };
-            board.push(row_html);
+        });
+        html! {
+            <g>{ for cells }</g>
+        }
+    }
+
+    /// Draws a faint stone of the player to move at the point under the
+    /// mouse, unless that point is already being previewed. On the demo board
+    /// the stone is the color being put down.
+    fn render_hover(&self) -> Html {
+        let (x, y) = match self.hover {
+            Some(hover) if self.preview != Some(hover) && self.can_play_at(hover.0, hover.1) => {
+                hover
+            }
+            _ => return html! {},
+        };
+        let color = match &self.demo {
+            Some(demo) => demo.tool.stone().unwrap_or(Player::None),
+            None => self.board.next_player,
+        };
+        let (class, fill) = match color {
+            Player::None => return html! {},
+            Player::White => ("g-preview-white", WHITE),
+            Player::Black => ("g-preview-black", BLACK),
+            Player::Red => ("g-preview-red", RED),
+        };
+        html! {
+            <circle
+                class={ class }
+                cx={ x.to_string() }
+                cy={ y.to_string() }
+                r={ STONE_RADIUS.to_string() }
+                fill={ fill }
+                fill-opacity="0.25"
+                pointer-events="none"
+            />
         }
+    }
+
+    /// Pulses the point of the hint, see `suggest_move`.
+    fn render_hint(&self) -> Html {
+        let (x, y) = match &self.hint {
+            Some((point, _)) => *point,
+            None => return html! {},
+        };
         html! {
-            <table class="g-board">{ for board }</table>
+            <circle
+                class="g-hint"
+                cx={ x.to_string() }
+                cy={ y.to_string() }
+                r={ STONE_RADIUS.to_string() }
+                fill="none"
+                stroke="#36c"
+                stroke-width="0.1"
+                pointer-events="none"
+            />
         }
     }
 
-    fn make_dots_html(&self) -> Html {
-        let tile = document().query_selector(".g-board td").unwrap().unwrap();
-        let tile_width = "var(--tile-width)";
-        let tile_style = window().get_computed_style(&tile).unwrap().unwrap();
-        let border_width = tile_style.get_property_value("border-top-width").unwrap();
-        if self.board.board_size() % 2 == 0 {
-            html! {}
-        } else {
-            let coords = [
-                self.board.board_size() / 4 - 1,
-                self.board.board_size() / 2,
-                self.board.board_size() - (self.board.board_size() / 4),
-            ];
-            let coords = coords
-                .into_iter()
-                .flat_map(|x| coords.into_iter().map(|y| (x, y)).collect::<Vec<_>>())
-                .collect::<Vec<_>>();
-            let mut dots_html = Vec::with_capacity(9);
-            for (x, y) in coords {
-                let translate_x = format!(
-                    "calc({0} * {1} + {0} * {2} - 3px)",
-                    x, tile_width, border_width
-                );
-                let translate_y = format!(
-                    "calc({0} * {1} + {0} * {2} - 3px)",
-                    y, tile_width, border_width
-                );
-                let dot = html! {
-                    <div
-                        class="dot"
-                        style={ format!("transform: translate({}, {})", translate_x, translate_y)}>
+    /// A reasonable move for the player to move, for a hint: the engine's
+    /// favorite when it has looked at the position, else the bot's choice
+    /// when playing one, else the best move of a quick search.
+    fn suggest_move(&self) -> Option<(usize, usize)> {
+        let favorite = self
+            .engine
+            .as_ref()
+            .and_then(|engine| engine.evaluation(self.board.move_count()))
+            .and_then(|evaluation| evaluation.candidates.first());
+        if let Some(candidate) = favorite {
+            return Some(candidate.point);
+        }
+        if let Some(bot) = &self.bot {
+            if let Some(point) = bot.engine.choose_move(&self.board) {
+                return Some(point);
+            }
+        }
+        SearchEngine {
+            visits: phase(&self.board, self.board.move_count()).visits(HINT_VISITS),
+        }
+        .choose_move(&self.board)
+    }
+
+    /// Draws the known continuations of the corners of the shown position as
+    /// faint stones, the most common ones a little stronger.
+    fn render_joseki(&self) -> Html {
+        if !self.show_joseki {
+            return html! {};
+        }
+        let continuations = self.joseki.continuations(self.shown_position());
+        let most = continuations
+            .first()
+            .map_or(1, |continuation| continuation.count);
+        let stones = continuations.iter().map(|continuation| {
+            let (x, y) = continuation.point;
+            let (class, fill) = match continuation.player {
+                Player::White => ("g-preview-white", WHITE),
+                _ => ("g-preview-black", BLACK),
+            };
+            let opacity = 0.2 + 0.3 * continuation.count as f64 / most as f64;
+            html! {
+                <circle
+                    class={ classes!(class, "g-joseki") }
+                    cx={ x.to_string() }
+                    cy={ y.to_string() }
+                    r={ STONE_RADIUS.to_string() }
+                    fill={ fill }
+                    fill-opacity={ opacity.to_string() }
+                    pointer-events="none"
+                >
+                    <title>{ format!("Played in {} known sequences", continuation.count) }</title>
+                </circle>
+            }
+        });
+        html! {
+            <g>{ for stones }</g>
+        }
+    }
+
+    /// Lets the built-in engine go through the game, showing its evaluation
+    /// of the shown position and a graph of the winrate over the game.
+    fn render_engine(&self, ctx: &AppContext) -> Html {
+        let engine = match &self.engine {
+            Some(engine) => engine,
+            None => {
+                let url_cb = ctx.link().callback(|e: InputEvent| {
+                    Msg::SetKataGoUrl(e.target_unchecked_into::<HtmlInputElement>().value())
+                });
+                return html! {
+                    <div class="engine">
+                        <button onclick={ ctx.link().callback(|_| Msg::ToggleEngine) }>
+                            { "Start engine" }
+                        </button>
+                        <input
+                            type="url"
+                            placeholder="KataGo proxy address (ws:// or wss://)"
+                            value={ self.katago_url.clone() }
+                            oninput={ url_cb }
+                        />
+                        <button onclick={ ctx.link().callback(|_| Msg::ConnectKataGo) }>
+                            { "Use KataGo" }
+                        </button>
                     </div>
                 };
-                dots_html.push(dot)
             }
-            html! { <div class="dots">{ for dots_html }</div> }
+        };
+        let shown = self.shown_moves();
+        let summary = match engine.evaluation(shown) {
+            Some(evaluation) => {
+                let (leader, lead) = if evaluation.score < 0.0 {
+                    ("W", -evaluation.score)
+                } else {
+                    ("B", evaluation.score)
+                };
+                format!(
+                    "Black wins {:.0}% of the playouts, {}+{:.1}",
+                    evaluation.winrate * 100.0,
+                    leader,
+                    lead,
+                )
+            }
+            None => String::from("Thinking…"),
+        };
+        html! {
+            <div class="engine">
+                <button onclick={ ctx.link().callback(|_| Msg::ToggleEngine) }>
+                    { if engine.is_katago() { "Stop KataGo" } else { "Stop engine" } }
+                </button>
+                <p class="engine-summary">{ summary }</p>
+                <WinrateGraph
+                    winrates={ engine.winrates() }
+                    shown={ shown }
+                    onjump={ ctx.link().callback(Msg::ShowMoves) }
+                />
+                if self.mistake_review.is_some() {
+                    { self.render_mistake_review(ctx) }
+                } else if engine.complete() {
+                    <button onclick={ ctx.link().callback(|_| Msg::ReviewMistakes) }>
+                        { "Review mistakes" }
+                    </button>
+                }
+            </div>
         }
     }
 
-    /// Old way to make dots, using svg
-    fn _make_dots_html(&self) -> Html {
-        // Retrieve some values from the stylesheet
-        let border_width = self.get_tile_border_width() as f64;
-        let box_size = self.get_tile_size() as f64;
+    /// Shows the mistake being reviewed: the position after it next to the
+    /// one after the engine's move instead, with buttons to go to the other
+    /// mistakes and to practice the position. The boards come from the real
+    /// game, so they stay while practicing.
+    fn render_mistake_review(&self, ctx: &AppContext) -> Html {
+        let review = match &self.mistake_review {
+            Some(review) => review,
+            None => return html! {},
+        };
+        let (mistake, suggestion) = &review.mistakes[review.current];
+        let game = self.game();
+        let played = game.position_at(mistake.moves + 1);
+        let played_name = game
+            .move_names()
+            .get(mistake.moves)
+            .map_or_else(String::new, |(_, name)| name.clone());
+        let engine_board = match suggestion {
+            Some((x, y)) => {
+                let mut position = game.position_at(mistake.moves);
+                position.play(*x, *y, mistake.player);
+                html! {
+                    <figure>
+                        { render_static_board(&position, Some((*x, *y))) }
+                        <figcaption>{ format!("Engine: {}", position.point_name(*x, *y)) }</figcaption>
+                    </figure>
+                }
+            }
+            None => html! {
+                <figure>
+                    <figcaption>{ "The engine had no move to suggest" }</figcaption>
+                </figure>
+            },
+        };
+        let current = review.current;
+        let practicing = self.analysis.is_some();
+        html! {
+            <div class="mistake-review">
+                <p>
+                    { format!(
+                        "Mistake {} of {}: move {} by {} lost {:.0}% of the winrate",
+                        current + 1,
+                        review.mistakes.len(),
+                        mistake.moves + 1,
+                        player_name(mistake.player),
+                        mistake.loss * 100.0,
+                    ) }
+                </p>
+                <div class="mistake-boards">
+                    <figure>
+                        { render_static_board(&played, played.parse_point_name(&played_name)) }
+                        <figcaption>{ format!("Played: {}", played_name) }</figcaption>
+                    </figure>
+                    { engine_board }
+                </div>
+                <button
+                    onclick={ ctx.link().callback(move |_| Msg::ShowMistake(current.saturating_sub(1))) }
+                    disabled={ practicing || current == 0 }
+                >
+                    { "Previous" }
+                </button>
+                <button
+                    onclick={ ctx.link().callback(move |_| Msg::ShowMistake(current + 1)) }
+                    disabled={ practicing || current + 1 == review.mistakes.len() }
+                >
+                    { "Next" }
+                </button>
+                <button
+                    onclick={ ctx.link().callback(|_| Msg::PracticeMistake) }
+                    disabled={ practicing }
+                >
+                    { "Practice" }
+                </button>
+                <button onclick={ ctx.link().callback(|_| Msg::EndMistakeReview) }>
+                    { "Done" }
+                </button>
+            </div>
+        }
+    }
 
-        let svg_size = box_size as usize * (self.board.board_size() - 1)
-            + border_width as usize * self.board.board_size();
+    /// Draws the moves the engine likes in the shown position as faint
+    /// stones, from red to green by how often they won, with how many
+    /// playouts started with each and the lead of the player to move after
+    /// them. Engines that tell also get the moves they would first think of
+    /// marked with dots.
+    fn render_candidates(&self) -> Html {
+        let shown = self.shown_moves();
+        let reviewed = match self
+            .engine
+            .as_ref()
+            .and_then(|engine| engine.reviewed(shown))
+        {
+            Some(reviewed) => reviewed,
+            None => return html! {},
+        };
+        let position = self.shown_position();
+        let candidates = &reviewed.evaluation.candidates;
+        let most = reviewed.policy.iter().copied().fold(0.0, f64::max);
+        let policy = reviewed
+            .policy
+            .iter()
+            .enumerate()
+            .filter(|(index, chance)| {
+                **chance >= 0.01
+                    && !candidates
+                        .iter()
+                        .any(|candidate| candidate.point == position.index_to_coord(*index))
+            })
+            .map(|(index, chance)| {
+                let (x, y) = position.index_to_coord(index);
+                html! {
+                    <circle
+                        class="g-policy"
+                        cx={ x.to_string() }
+                        cy={ y.to_string() }
+                        r={ (0.08 + 0.2 * (chance / most).sqrt()).to_string() }
+                        fill="#36c"
+                        fill-opacity="0.7"
+                        pointer-events="none"
+                    >
+                        <title>{ format!("First thought of {:.0}% of the time", chance * 100.0) }</title>
+                    </circle>
+                }
+            });
+        let to_move = self.player_after(shown);
+        let stones = candidates.iter().map(|candidate| {
+            let (x, y) = candidate.point;
+            let lead = match to_move {
+                Player::White => -candidate.score,
+                _ => candidate.score,
+            };
+            html! {
+                <g class="g-candidate" pointer-events="none">
+                    <circle
+                        cx={ x.to_string() }
+                        cy={ y.to_string() }
+                        r={ STONE_RADIUS.to_string() }
+                        fill={ format!("hsl({}, 70%, 45%)", 120.0 * candidate.winrate) }
+                        fill-opacity="0.6"
+                    />
+                    <text
+                        x={ x.to_string() }
+                        y={ (y as f64 - 0.13).to_string() }
+                        fill="#fff"
+                        font-size="0.28"
+                        text-anchor="middle"
+                        dominant-baseline="central"
+                    >
+                        { candidate.visits }
+                    </text>
+                    <text
+                        x={ x.to_string() }
+                        y={ (y as f64 + 0.15).to_string() }
+                        fill="#fff"
+                        font-size="0.22"
+                        text-anchor="middle"
+                        dominant-baseline="central"
+                    >
+                        { format!("{:+.1}", lead) }
+                    </text>
+                    <title>
+                        { format!(
+                            "Won {:.0}% of {} visits, leading by {:.1} after it",
+                            candidate.winrate * 100.0,
+                            candidate.visits,
+                            lead,
+                        ) }
+                    </title>
+                </g>
+            }
+        });
+        html! {
+            <g>
+                { for policy }
+                { for stones }
+            </g>
+        }
+    }
 
-        // Make circle svgs
-        let coords = [
-            self.board.board_size() / 4 - 1,
-            self.board.board_size() / 2,
-            self.board.board_size() - (self.board.board_size() / 4),
-        ];
-        let coords_iter = coords
-            .into_iter()
-            .flat_map(|x| coords.into_iter().map(|y| (x, y)).collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        let mut circles_svg = Vec::with_capacity(9);
-        for (x, y) in coords_iter {
-            let x: f64 = 0.5 + (box_size + border_width) * x as f64;
-            let y: f64 = 0.5 + (box_size + border_width) * y as f64;
-            let r: usize = 3;
-
-            let circle = html! {
-                <circle cx={ x.to_string() }
+    /// Who is likely to end up with each point of the shown position, for
+    /// the heatmap: what the engine says, or else the estimate, empty while
+    /// neither is there or the heatmap is hidden.
+    fn shown_ownership(&self) -> Vec<f64> {
+        if !self.show_ownership {
+            return vec![];
+        }
+        let told = self
+            .engine
+            .as_ref()
+            .and_then(|engine| engine.reviewed(self.shown_moves()))
+            .map(|reviewed| &reviewed.ownership)
+            .filter(|ownership| !ownership.is_empty());
+        if let Some(ownership) = told {
+            return ownership.clone();
+        }
+        match &self.ownership_estimate {
+            Some((hash, ownership)) if *hash == self.shown_position().save().hash() => {
+                ownership.clone()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The player to move after `moves` moves of the game.
+    fn player_after(&self, moves: usize) -> Player {
+        match self.board.history().get(moves) {
+            Some(m) => m.player,
+            None => self.board.next_player,
+        }
+    }
+
+    /// Has the engine think a little more once the page is drawn, while it
+    /// has positions left to evaluate.
+    fn schedule_engine_step(&mut self, ctx: &AppContext) {
+        let busy = match &mut self.engine {
+            Some(engine) => {
+                engine.sync(&self.board);
+                engine.busy()
+            }
+            None => false,
+        };
+        if busy && self.engine_step.is_none() {
+            let link = ctx.link().clone();
+            self.engine_step = Some(Timeout::new(0, move || link.send_message(Msg::EngineStep)));
+        }
+    }
+
+    /// Marks the points played next in the openings that reached the shown
+    /// position, with bigger and warmer dots for the more popular ones.
+    fn render_fuseki(&self) -> Html {
+        if !self.show_fuseki || self.shown_moves() >= FUSEKI_MOVES {
+            return html! {};
+        }
+        let candidates = self.fuseki.candidates(self.shown_position());
+        let most = candidates.first().map_or(1.0, |candidate| candidate.share);
+        let dots = candidates.iter().map(|candidate| {
+            let (x, y) = candidate.point;
+            let strength = candidate.share / most;
+            html! {
+                <circle
+                    class="g-fuseki"
+                    cx={ x.to_string() }
+                    cy={ y.to_string() }
+                    r={ (0.1 + 0.25 * strength).to_string() }
+                    fill={ format!("hsl({}, 80%, 50%)", 220.0 - 220.0 * strength) }
+                    fill-opacity="0.8"
+                    pointer-events="none"
+                >
+                    <title>
+                        { format!(
+                            "Played in {} of the games ({:.0}%)",
+                            candidate.count,
+                            candidate.share * 100.0,
+                        ) }
+                    </title>
+                </circle>
+            }
+        });
+        html! {
+            <g>{ for dots }</g>
+        }
+    }
+
+    /// Outlines the point the keyboard cursor is on
+    fn render_cursor(&self) -> Html {
+        match self.cursor {
+            Some((x, y)) => html! {
+                <rect
+                    class="g-cursor"
+                    x={ (x as f64 - 0.5).to_string() }
+                    y={ (y as f64 - 0.5).to_string() }
+                    width="1"
+                    height="1"
+                    fill="none"
+                    stroke="#36c"
+                    stroke-width="0.06"
+                />
+            },
+            None => html! {},
+        }
+    }
+
+    fn render_preview(&self) -> Html {
+        let (x, y) = match self.preview {
+            Some(preview) => preview,
+            None => return html! {},
+        };
+        let (class, fill) = match self.board.next_player {
+            Player::None => return html! {},
+            Player::White => ("g-preview-white", WHITE),
+            Player::Black => ("g-preview-black", BLACK),
+            Player::Red => ("g-preview-red", RED),
+        };
+        html! {
+            <>
+                <circle
+                    class={ class }
+                    cx={ x.to_string() }
                     cy={ y.to_string() }
-                    r={ r.to_string() }
-                    fill="black" />
+                    r={ STONE_RADIUS.to_string() }
+                    fill={ fill }
+                    fill-opacity="0.5"
+                />
+                { self.render_capture_preview(x, y) }
+            </>
+        }
+    }
+
+    /// Crosses out the stones the previewed move at (`x`, `y`) would
+    /// capture, see `GoPosition::move_effects`, so the move can be
+    /// reconsidered before it is confirmed.
+    fn render_capture_preview(&self, x: usize, y: usize) -> Html {
+        let captures = match self.board.move_effects(x, y, self.board.next_player) {
+            Ok(effects) => effects.captures,
+            Err(_) => return html! {},
+        };
+        let size = STONE_RADIUS / 2.0;
+        let crosses = captures.into_iter().map(|index| {
+            let (x, y) = self.board.index_to_coord(index);
+            let (x, y) = (x as f64, y as f64);
+            let stroke = match self.board[index] {
+                Player::Black => WHITE,
+                _ => BLACK,
             };
-            circles_svg.push(circle);
+            html! {
+                <path
+                    d={ format!(
+                        "M {} {} L {} {} M {} {} L {} {}",
+                        x - size, y - size, x + size, y + size,
+                        x - size, y + size, x + size, y - size,
+                    ) }
+                    stroke={ stroke }
+                />
+            }
+        });
+        html! {
+            <g class="g-capture-preview" fill="none" stroke-width="0.08" stroke-linecap="round">
+                { for crosses }
+            </g>
+        }
+    }
+
+    /// The real game, which is put aside during analysis and demonstrations.
+    fn game(&self) -> &GoGame {
+        match (&self.analysis, &self.demo) {
+            (Some(analysis), _) => &analysis.game,
+            (_, Some(demo)) => &demo.game,
+            _ => &self.board,
         }
+    }
+
+    /// How many moves led to the shown position.
+    fn shown_moves(&self) -> usize {
+        match &self.viewing {
+            Some((moves, _)) => *moves,
+            None => self.board.move_count(),
+        }
+    }
+
+    /// The position that should be drawn, either the current one or an earlier
+    /// one picked from the timeline.
+    fn shown_position(&self) -> &GoPosition {
+        match &self.viewing {
+            Some((_, position)) => position,
+            None => &self.board,
+        }
+    }
+
+    /// Labels the point of a rejected move with the reason, and rings the
+    /// stones responsible for it.
+    fn render_rejection(&self) -> Html {
+        let (index, error) = match &self.rejection {
+            Some(rejection) => rejection,
+            None => return html! {},
+        };
+        let (label, highlighted) = match error {
+            MoveError::Ko { stone } => ("ko", vec![*stone]),
+            MoveError::Suicide { capturing } => ("suicide", capturing.clone()),
+            MoveError::Superko => ("superko", vec![]),
+            _ => return html! {},
+        };
+
+        let rings = highlighted.into_iter().map(|stone| {
+            let (x, y) = self.board.index_to_coord(stone);
+            html! {
+                <circle
+                    cx={ x.to_string() }
+                    cy={ y.to_string() }
+                    r="0.5"
+                    fill="none"
+                    stroke="#d33"
+                    stroke-width="0.06"
+                />
+            }
+        });
+        let (x, y) = self.board.index_to_coord(*index);
+        html! {
+            <g>
+                { for rings }
+                <text
+                    class="rejection-label"
+                    x={ x.to_string() }
+                    y={ y.to_string() }
+                    fill="#d33"
+                    font-size="0.4"
+                    font-weight="bold"
+                    text-anchor="middle"
+                    dominant-baseline="central"
+                >
+                    { label }
+                </text>
+            </g>
+        }
+    }
+}
+
+/// Describes a practice goal, see `Practice::goal`.
+fn goal_text(goal: i32) -> String {
+    match goal {
+        0 => String::from("Win the game"),
+        goal if goal > 0 => format!("Win by at least {} points", goal),
+        goal => format!("Lose by no more than {} points", -goal),
+    }
+}
 
+/// Lays out how a counted game adds up, one column per player.
+fn render_breakdown(breakdown: &Breakdown) -> Html {
+    let row = |label: &str, black: String, white: String| {
         html! {
-            <svg width={ svg_size.to_string() } height={ svg_size.to_string() } fill="none" xmlns="http://www.w3.org/2000/svg">
-                { for circles_svg }
-            </svg>
+            <tr>
+                <th scope="row">{ label.to_owned() }</th>
+                <td>{ black }</td>
+                <td>{ white }</td>
+            </tr>
         }
+    };
+    let counted = match breakdown.scoring {
+        Scoring::Territory => row(
+            "Captures",
+            breakdown.captures.black.to_string(),
+            breakdown.captures.white.to_string(),
+        ),
+        Scoring::Area => row(
+            "Stones",
+            breakdown.stones.black.to_string(),
+            breakdown.stones.white.to_string(),
+        ),
+    };
+    let points = breakdown.points();
+    html! {
+        <table class="result-breakdown">
+            <tr>
+                <td />
+                <th scope="col">{ "Black" }</th>
+                <th scope="col">{ "White" }</th>
+            </tr>
+            { row(
+                "Territory",
+                breakdown.territory.black.to_string(),
+                breakdown.territory.white.to_string(),
+            ) }
+            { counted }
+            { row("Komi", String::new(), breakdown.komi.to_string()) }
+            { row(
+                "Total",
+                points.black.to_string(),
+                (points.white as f64 + breakdown.komi).to_string(),
+            ) }
+        </table>
+    }
+}
+
+/// The game an online game is played on, which is put aside while
+/// analyzing.
+fn online_game<'a>(board: &'a mut GoGame, analysis: &'a mut Option<Analysis>) -> &'a mut GoGame {
+    match analysis {
+        Some(analysis) => &mut analysis.game,
+        None => board,
+    }
+}
+
+fn player_name(player: Player) -> &'static str {
+    match player {
+        Player::Black => "Black",
+        Player::White => "White",
+        Player::Red => "Red",
+        Player::None => "Nobody",
+    }
+}
+
+/// Installs the service worker that keeps the app working offline, see
+/// `service-worker.js`. Browsers without service workers, and pages not
+/// served securely, go without.
+fn register_service_worker() {
+    let navigator = window().navigator();
+    if js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        // Failing only costs the offline copy, the app works the same
+        let _ = navigator.service_worker().register("service-worker.js");
+    }
+}
+
+#[derive(Clone, Properties, PartialEq)]
+struct AppProps {
+    /// The hash of the address the page was opened at, read before the
+    /// router replaces it with that of a route
+    hash: String,
+}
+
+#[function_component]
+fn Root(props: &AppProps) -> Html {
+    html! {
+        <HashRouter>
+            <AppView hash={ props.hash.clone() } />
+        </HashRouter>
     }
 }
 
 fn main() {
-    yew::start_app::<App>();
+    register_service_worker();
+    let hash = window().location().hash().unwrap_or_default();
+    yew::Renderer::<Root>::with_props(AppProps { hash }).render();
 }
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+/// A mark drawn on a point of the board, on top of any stone there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mark {
+    Triangle,
+    Square,
+    Circle,
+    Cross,
+    /// A short text, usually a letter or a number
+    Label(String),
+}
+
+/// The marks drawn on one position of a game, by point index. Each point
+/// holds at most one mark.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Markup {
+    marks: BTreeMap<usize, Mark>,
+}
+
+impl Markup {
+    pub fn new() -> Self {
+        Markup::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Mark> {
+        self.marks.get(&index)
+    }
+
+    /// The marked points in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Mark)> {
+        self.marks.iter().map(|(index, mark)| (*index, mark))
+    }
+
+    /// Puts `mark` on a point, replacing the mark that was there, or clears
+    /// the point when `mark` is `None`.
+    pub fn set(&mut self, index: usize, mark: Option<Mark>) {
+        match mark {
+            Some(mark) => self.marks.insert(index, mark),
+            None => self.marks.remove(&index),
+        };
+    }
+
+    /// Puts `mark` on a point, or clears the point if it already holds a mark
+    /// of the same kind. Any label counts as the same kind as another.
+    pub fn toggle(&mut self, index: usize, mark: Mark) {
+        match self.marks.get(&index) {
+            Some(old) if mem::discriminant(old) == mem::discriminant(&mark) => {
+                self.set(index, None)
+            }
+            _ => self.set(index, Some(mark)),
+        }
+    }
+
+    /// The first letter, upper case before lower case, that no label uses
+    /// yet. Starts over at `A` once all of them are used.
+    pub fn next_letter(&self) -> String {
+        ('A'..='Z')
+            .chain('a'..='z')
+            .map(String::from)
+            .find(|letter| !self.has_label(letter))
+            .unwrap_or_else(|| String::from("A"))
+    }
+
+    /// The smallest number from 1 up that no label uses yet.
+    pub fn next_number(&self) -> String {
+        (1..)
+            .map(|number: usize| number.to_string())
+            .find(|number| !self.has_label(number))
+            .expect("there are fewer labels than numbers")
+    }
+
+    fn has_label(&self, text: &str) -> bool {
+        self.marks
+            .values()
+            .any(|mark| matches!(mark, Mark::Label(label) if label == text))
+    }
+}
@@ -0,0 +1,97 @@
+use go_rs::game::{GoPosition, Player};
+use go_rs::markup::{Mark, Markup};
+use yew::prelude::*;
+
+use crate::board::{BLACK, BOARD_COLOR, WHITE};
+
+#[derive(Properties, PartialEq)]
+pub struct MarkupLayerProps {
+    /// The position the marks are drawn on, to pick colors that stand out
+    /// from the stones
+    pub position: GoPosition,
+    pub markup: Markup,
+}
+
+/// Draws the triangles, squares, circles, crosses and labels of a position
+/// on top of its stones.
+#[function_component]
+pub fn MarkupLayer(props: &MarkupLayerProps) -> Html {
+    let position = &props.position;
+    let marks = props.markup.iter().map(|(index, mark)| {
+        let (x, y) = position.index_to_coord(index);
+        let (x, y) = (x as f64, y as f64);
+        // Draw in the opposite color of the stone, and black on empty points
+        let (class, label_class, color) = match position[index] {
+            Player::Black => ("g-mark-white", "g-label-white", WHITE),
+            _ => ("g-mark-black", "g-label-black", BLACK),
+        };
+        let shape = match mark {
+            Mark::Triangle => {
+                let points = [(0.0, -0.28), (0.25, 0.15), (-0.25, 0.15)]
+                    .map(|(dx, dy)| format!("{},{}", x + dx, y + dy))
+                    .join(" ");
+                html! { <polygon points={ points } /> }
+            }
+            Mark::Square => html! {
+                <rect
+                    x={ (x - 0.2).to_string() }
+                    y={ (y - 0.2).to_string() }
+                    width="0.4"
+                    height="0.4"
+                />
+            },
+            Mark::Circle => html! {
+                <circle cx={ x.to_string() } cy={ y.to_string() } r="0.22" />
+            },
+            Mark::Cross => {
+                let d = format!(
+                    "M{} {}L{} {}M{} {}L{} {}",
+                    x - 0.2,
+                    y - 0.2,
+                    x + 0.2,
+                    y + 0.2,
+                    x + 0.2,
+                    y - 0.2,
+                    x - 0.2,
+                    y + 0.2,
+                );
+                html! { <path d={ d } /> }
+            }
+            Mark::Label(text) => {
+                return html! {
+                    <>
+                        // Hide the grid lines behind labels on empty points
+                        if position[index] == Player::None {
+                            <circle
+                                class="g-mark-backdrop"
+                                cx={ x.to_string() }
+                                cy={ y.to_string() }
+                                r="0.35"
+                                fill={ BOARD_COLOR }
+                            />
+                        }
+                        <text
+                            class={ label_class }
+                            x={ x.to_string() }
+                            y={ y.to_string() }
+                            fill={ color }
+                            font-size={ if text.chars().count() < 3 { "0.5" } else { "0.35" } }
+                            text-anchor="middle"
+                            dominant-baseline="central"
+                        >
+                            { text }
+                        </text>
+                    </>
+                };
+            }
+        };
+        html! {
+            <g class={ class } fill="none" stroke={ color } stroke-width="0.06">
+                { shape }
+            </g>
+        }
+    });
+    html! {
+        <g class="g-markup" pointer-events="none">{ for marks }</g>
+    }
+}
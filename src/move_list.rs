@@ -0,0 +1,112 @@
+use go_rs::game::Player;
+use web_sys::HtmlElement;
+use yew::prelude::*;
+
+use crate::clock::format_time;
+
+#[derive(Properties, PartialEq)]
+pub struct MoveListProps {
+    /// Who made each move and what it was, see `GoGame::move_names`
+    pub moves: Vec<(Player, String)>,
+    /// How long each move was thought about in milliseconds, where it is
+    /// known, see `Move::thinking`
+    pub times: Vec<Option<f64>>,
+    /// How many moves led to the shown position
+    pub shown: usize,
+    /// Called with the number of moves that lead to the position picked
+    pub onjump: Callback<usize>,
+    /// Tags for the moves that start a tactic, as the index of the move and
+    /// the name of the tactic, see `tactics::find_tactics`
+    #[prop_or_default]
+    pub tags: Vec<(usize, &'static str)>,
+    /// Called with the index in `tags` of a tag picked, to explain it
+    #[prop_or_default]
+    pub onexplain: Callback<usize>,
+}
+
+/// Every move of the game, numbered, like `17. B Q16`, with the time it took
+/// when known. Picking one shows the position right after it, and the shown
+/// move is kept in view.
+#[function_component]
+pub fn MoveList(props: &MoveListProps) -> Html {
+    let list_ref = use_node_ref();
+    let shown_ref = use_node_ref();
+    {
+        let list_ref = list_ref.clone();
+        let shown_ref = shown_ref.clone();
+        use_effect_with((props.shown, props.moves.len()), move |_| {
+            if let (Some(list), Some(shown)) = (
+                list_ref.cast::<HtmlElement>(),
+                shown_ref.cast::<HtmlElement>(),
+            ) {
+                scroll_into_view(&list, &shown);
+            }
+        });
+    }
+
+    let entries = props.moves.iter().enumerate().map(|(i, (player, name))| {
+        let moves = i + 1;
+        let shown = moves == props.shown;
+        let color = match player {
+            Player::White => "W",
+            _ => "B",
+        };
+        let onclick = props.onjump.reform(move |_: MouseEvent| moves);
+        let time = props.times.get(i).copied().flatten().map(|time| {
+            html! { <span class="ml-time">{ format_time(time) }</span> }
+        });
+        let tags = props
+            .tags
+            .iter()
+            .enumerate()
+            .filter(|(_, (tagged, _))| *tagged == i)
+            .map(|(tag, (_, name))| {
+                let onclick = props.onexplain.reform(move |e: MouseEvent| {
+                    // Explaining shows the position itself
+                    e.stop_propagation();
+                    tag
+                });
+                html! {
+                    <button class="ml-tag" title="Show how it plays out" onclick={ onclick }>
+                        { *name }
+                    </button>
+                }
+            });
+        html! {
+            <li
+                class={ classes!(shown.then_some("ml-shown")) }
+                aria-current={ shown.then_some("step") }
+                ref={ if shown { shown_ref.clone() } else { NodeRef::default() } }
+                onclick={ onclick }
+            >
+                { format!("{}. {} {}", moves, color, name) }
+                { for tags }
+                { for time }
+            </li>
+        }
+    });
+    let start_cb = props.onjump.reform(|_: MouseEvent| 0);
+    html! {
+        <ol class="move-list" ref={ list_ref }>
+            <li
+                class={ classes!((props.shown == 0).then_some("ml-shown")) }
+                ref={ if props.shown == 0 { shown_ref.clone() } else { NodeRef::default() } }
+                onclick={ start_cb }
+            >
+                { "Start" }
+            </li>
+            { for entries }
+        </ol>
+    }
+}
+
+/// Scrolls `list` just far enough for `item` to be fully visible.
+fn scroll_into_view(list: &HtmlElement, item: &HtmlElement) {
+    let top = item.offset_top();
+    let bottom = top + item.offset_height();
+    if top < list.scroll_top() {
+        list.set_scroll_top(top);
+    } else if bottom > list.scroll_top() + list.client_height() {
+        list.set_scroll_top(bottom - list.client_height());
+    }
+}
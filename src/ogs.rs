@@ -0,0 +1,239 @@
+use gloo_net::http::{Request, RequestBuilder, Response};
+use gloo_storage::{LocalStorage, Storage};
+use gloo_timers::callback::Interval;
+use go_rs::game::{GoGame, Player};
+use go_rs::sgf::sgf_point;
+use serde::{Deserialize, Serialize};
+use yew::platform::spawn_local;
+use yew::Callback;
+
+/// Where the OGS API is served from
+const OGS_URL: &str = "https://online-go.com";
+
+/// The local storage key the OGS login is kept under. The password is not
+/// kept, only the token it was traded for.
+const STORAGE_KEY: &str = "go-rs-ogs";
+
+/// How often an open OGS game is fetched again for the opponent's moves
+const POLL_MS: u32 = 15 * 1000;
+
+/// A login to online-go.com.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OgsLogin {
+    pub username: String,
+    access_token: String,
+}
+
+impl OgsLogin {
+    /// Reads the stored login, if there is one.
+    pub fn load() -> Option<Self> {
+        LocalStorage::get(STORAGE_KEY).ok()
+    }
+
+    pub fn save(&self) {
+        let _ = LocalStorage::set(STORAGE_KEY, self);
+    }
+
+    pub fn forget() {
+        LocalStorage::delete(STORAGE_KEY);
+    }
+
+    /// The color `username` plays in `game`, going by the player names of
+    /// the record, `Player::None` when it's someone else's game.
+    pub fn color_in(&self, game: &GoGame) -> Player {
+        if game.info.black_name.eq_ignore_ascii_case(&self.username) {
+            Player::Black
+        } else if game.info.white_name.eq_ignore_ascii_case(&self.username) {
+            Player::White
+        } else {
+            Player::None
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header("Authorization", &format!("Bearer {}", self.access_token))
+    }
+}
+
+/// A game listed by OGS.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct OgsGame {
+    pub id: u64,
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    players: OgsPlayers,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct OgsPlayers {
+    black: OgsPlayer,
+    white: OgsPlayer,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct OgsPlayer {
+    username: String,
+}
+
+impl OgsGame {
+    /// Describes the game like `Title (Alice vs Bob, 19x19)`.
+    pub fn label(&self) -> String {
+        format!(
+            "{} ({} vs {}, {}x{})",
+            self.name,
+            self.players.black.username,
+            self.players.white.username,
+            self.width,
+            self.height
+        )
+    }
+}
+
+/// One page of a listing of the OGS API.
+#[derive(Deserialize)]
+struct Page<T> {
+    results: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Trades the `username` and `password` of an OGS account for a token,
+/// through the OAuth application registered on OGS as `client_id`.
+pub async fn log_in(client_id: &str, username: &str, password: &str) -> Result<OgsLogin, String> {
+    let form = [
+        ("grant_type", "password"),
+        ("client_id", client_id),
+        ("username", username),
+        ("password", password),
+    ]
+    .iter()
+    .map(|(key, value)| format!("{}={}", key, js_sys::encode_uri_component(value)))
+    .collect::<Vec<_>>()
+    .join("&");
+    let response = send(
+        Request::post(&format!("{}/oauth2/token/", OGS_URL))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(form),
+    )
+    .await?;
+    let token: TokenResponse = response
+        .json()
+        .await
+        .map_err(|_| String::from("OGS sent back an unexpected login"))?;
+    Ok(OgsLogin {
+        username: username.to_owned(),
+        access_token: token.access_token,
+    })
+}
+
+/// Lists the games of the logged in player, the ongoing ones or the
+/// finished ones, newest first.
+pub async fn list_games(login: &OgsLogin, finished: bool) -> Result<Vec<OgsGame>, String> {
+    let url = format!(
+        "{}/api/v1/me/games/?ended__isnull={}&ordering=-id",
+        OGS_URL, !finished
+    );
+    let response = send(login.authorize(Request::get(&url)).build()).await?;
+    let page: Page<OgsGame> = response
+        .json()
+        .await
+        .map_err(|_| String::from("OGS sent back an unexpected list of games"))?;
+    Ok(page.results)
+}
+
+/// Fetches the record of game `id` as it stands.
+pub async fn fetch_sgf(login: &OgsLogin, id: u64) -> Result<String, String> {
+    let url = format!("{}/api/v1/games/{}/sgf/", OGS_URL, id);
+    let response = send(login.authorize(Request::get(&url)).build()).await?;
+    response
+        .text()
+        .await
+        .map_err(|_| String::from("Could not read the game from OGS"))
+}
+
+/// Plays at `point` in game `id`, or passes for `None`.
+async fn play_move(login: &OgsLogin, id: u64, point: Option<(usize, usize)>) -> Result<(), String> {
+    // OGS writes a pass as two dots
+    let value = point.map_or_else(|| String::from(".."), |(x, y)| sgf_point(x, y));
+    let url = format!("{}/api/v1/games/{}/move/", OGS_URL, id);
+    let request = login
+        .authorize(Request::post(&url))
+        .json(&MoveBody { value: &value });
+    send(request).await.map(|_| ())
+}
+
+/// Resigns game `id`.
+async fn resign(login: &OgsLogin, id: u64) -> Result<(), String> {
+    let url = format!("{}/api/v1/games/{}/resign/", OGS_URL, id);
+    send(login.authorize(Request::post(&url)).build())
+        .await
+        .map(|_| ())
+}
+
+#[derive(Serialize)]
+struct MoveBody<'a> {
+    #[serde(rename = "move")]
+    value: &'a str,
+}
+
+/// Sends `request`, turning failures and error statuses into a message.
+async fn send(request: Result<Request, gloo_net::Error>) -> Result<Response, String> {
+    let request = request.map_err(|_| String::from("Could not build the request to OGS"))?;
+    let response = request
+        .send()
+        .await
+        .map_err(|_| String::from("Could not reach OGS"))?;
+    match response.status() {
+        200..=299 => Ok(response),
+        401 | 403 => Err(String::from("OGS refused the login, log in again")),
+        status => Err(format!("OGS answered with error {}", status)),
+    }
+}
+
+/// An OGS game being played on this board, fetched again every so often
+/// for the opponent's moves.
+pub struct OgsPlay {
+    pub id: u64,
+    /// The color played on this device, `Player::None` when only watching
+    pub color: Player,
+    /// Called with how sending a move or resignation went
+    on_sent: Callback<Result<(), String>>,
+    _poll: Interval,
+}
+
+impl OgsPlay {
+    /// Starts following game `id`, calling `on_poll` whenever it's time to
+    /// fetch it again.
+    pub fn new(
+        id: u64,
+        color: Player,
+        on_poll: Callback<()>,
+        on_sent: Callback<Result<(), String>>,
+    ) -> Self {
+        OgsPlay {
+            id,
+            color,
+            on_sent,
+            _poll: Interval::new(POLL_MS, move || on_poll.emit(())),
+        }
+    }
+
+    /// Sends a move made on this device in the background, see `play_move`.
+    pub fn send_move(&self, login: &OgsLogin, point: Option<(usize, usize)>) {
+        let (login, id, on_sent) = (login.clone(), self.id, self.on_sent.clone());
+        spawn_local(async move { on_sent.emit(play_move(&login, id, point).await) });
+    }
+
+    pub fn send_resignation(&self, login: &OgsLogin) {
+        let (login, id, on_sent) = (login.clone(), self.id, self.on_sent.clone());
+        spawn_local(async move { on_sent.emit(resign(&login, id).await) });
+    }
+
+    pub fn our_turn(&self, game: &GoGame) -> bool {
+        !game.is_over() && game.next_player == self.color
+    }
+}
@@ -0,0 +1,219 @@
+use std::rc::Rc;
+
+use web_sys::HtmlInputElement;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::ogs::{list_games, log_in, OgsGame, OgsLogin};
+
+/// What is asked of the app from the OGS panel.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OgsControl {
+    /// Logged in to an account, or out of it when `None`
+    Login(Option<OgsLogin>),
+    /// Play the OGS game with the given id on this board
+    Open(u64),
+    /// Add the OGS game with the given id to the library
+    Import(u64),
+    /// Talking to OGS failed, with the reason
+    Failed(String),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct OgsPanelProps {
+    /// The account logged into, see `ogs::log_in`
+    pub login: Option<OgsLogin>,
+    pub onaction: Callback<OgsControl>,
+}
+
+/// What the panel keeps between renders: the login form as it is filled
+/// in and the games of the account.
+#[derive(Clone, Default, PartialEq)]
+struct OgsPanelState {
+    /// The OAuth client id, name and password entered to log in
+    client_id: String,
+    username: String,
+    password: String,
+    /// The ongoing games of the account
+    ongoing: Vec<OgsGame>,
+    /// The finished games of the account, to import
+    finished: Vec<OgsGame>,
+}
+
+enum OgsPanelAction {
+    SetClientId(String),
+    SetUsername(String),
+    SetPassword(String),
+    /// The password was sent off to log in, it isn't kept any longer
+    ForgetPassword,
+    /// The ongoing and then the finished games of the account were listed
+    Listed(Vec<OgsGame>, Vec<OgsGame>),
+    /// Logged out, the games of the account go with it
+    LoggedOut,
+}
+
+impl Reducible for OgsPanelState {
+    type Action = OgsPanelAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            OgsPanelAction::SetClientId(client_id) => state.client_id = client_id,
+            OgsPanelAction::SetUsername(username) => state.username = username,
+            OgsPanelAction::SetPassword(password) => state.password = password,
+            OgsPanelAction::ForgetPassword => state.password.clear(),
+            OgsPanelAction::Listed(ongoing, finished) => {
+                state.ongoing = ongoing;
+                state.finished = finished;
+            }
+            OgsPanelAction::LoggedOut => {
+                state.ongoing.clear();
+                state.finished.clear();
+            }
+        }
+        Rc::new(state)
+    }
+}
+
+/// Fetches the lists of ongoing and finished games of `login` in the
+/// background.
+fn refresh(
+    login: OgsLogin,
+    state: UseReducerDispatcher<OgsPanelState>,
+    onaction: Callback<OgsControl>,
+) {
+    spawn_local(async move {
+        let games = match list_games(&login, false).await {
+            Ok(ongoing) => list_games(&login, true)
+                .await
+                .map(|finished| (ongoing, finished)),
+            Err(e) => Err(e),
+        };
+        match games {
+            Ok((ongoing, finished)) => state.dispatch(OgsPanelAction::Listed(ongoing, finished)),
+            Err(e) => onaction.emit(OgsControl::Failed(e)),
+        }
+    });
+}
+
+/// The OGS login, or the games of the account once logged in, to play or
+/// import.
+#[function_component]
+pub fn OgsPanel(props: &OgsPanelProps) -> Html {
+    let state = use_reducer(OgsPanelState::default);
+
+    // The games are listed as soon as there is an account to list them for
+    {
+        let dispatcher = state.dispatcher();
+        let onaction = props.onaction.clone();
+        use_effect_with(props.login.clone(), move |login| match login {
+            Some(login) => refresh(login.clone(), dispatcher, onaction),
+            None => dispatcher.dispatch(OgsPanelAction::LoggedOut),
+        });
+    }
+
+    let login = match &props.login {
+        Some(login) => login,
+        None => {
+            let input_cb = |action: fn(String) -> OgsPanelAction| {
+                let state = state.clone();
+                Callback::from(move |e: InputEvent| {
+                    state.dispatch(action(
+                        e.target_unchecked_into::<HtmlInputElement>().value(),
+                    ))
+                })
+            };
+            let log_in_cb = {
+                let state = state.clone();
+                let onaction = props.onaction.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let client_id = state.client_id.trim().to_owned();
+                    let username = state.username.trim().to_owned();
+                    let password = state.password.clone();
+                    state.dispatch(OgsPanelAction::ForgetPassword);
+                    let onaction = onaction.clone();
+                    spawn_local(async move {
+                        onaction.emit(match log_in(&client_id, &username, &password).await {
+                            Ok(login) => {
+                                login.save();
+                                OgsControl::Login(Some(login))
+                            }
+                            Err(e) => OgsControl::Failed(e),
+                        });
+                    });
+                })
+            };
+            return html! {
+                <div class="ogs">
+                    <input
+                        type="text"
+                        placeholder="OAuth client id"
+                        value={ state.client_id.clone() }
+                        oninput={ input_cb(OgsPanelAction::SetClientId) }
+                    />
+                    <input
+                        type="text"
+                        placeholder="Username"
+                        autocomplete="username"
+                        value={ state.username.clone() }
+                        oninput={ input_cb(OgsPanelAction::SetUsername) }
+                    />
+                    <input
+                        type="password"
+                        placeholder="Password"
+                        autocomplete="current-password"
+                        value={ state.password.clone() }
+                        oninput={ input_cb(OgsPanelAction::SetPassword) }
+                    />
+                    <button onclick={ log_in_cb }>{ "Log in to OGS" }</button>
+                </div>
+            };
+        }
+    };
+    let ongoing = state.ongoing.iter().map(|game| {
+        let id = game.id;
+        let open_cb = props
+            .onaction
+            .reform(move |_: MouseEvent| OgsControl::Open(id));
+        html! {
+            <li>
+                <span>{ game.label() }</span>
+                <button onclick={ open_cb }>{ "Play" }</button>
+            </li>
+        }
+    });
+    let finished = state.finished.iter().map(|game| {
+        let id = game.id;
+        let import_cb = props
+            .onaction
+            .reform(move |_: MouseEvent| OgsControl::Import(id));
+        html! {
+            <li>
+                <span>{ game.label() }</span>
+                <button onclick={ import_cb }>{ "Import" }</button>
+            </li>
+        }
+    });
+    let refresh_cb = {
+        let (login, dispatcher) = (login.clone(), state.dispatcher());
+        let onaction = props.onaction.clone();
+        Callback::from(move |_: MouseEvent| {
+            refresh(login.clone(), dispatcher.clone(), onaction.clone())
+        })
+    };
+    let log_out_cb = props.onaction.reform(|_: MouseEvent| {
+        OgsLogin::forget();
+        OgsControl::Login(None)
+    });
+    html! {
+        <div class="ogs">
+            <span>{ format!("Logged in as {}", login.username) }</span>
+            <button onclick={ refresh_cb }>{ "Refresh games" }</button>
+            <button onclick={ log_out_cb }>{ "Log out" }</button>
+            <span>{ "Ongoing" }</span>
+            <ul class="ogs-games">{ for ongoing }</ul>
+            <span>{ "Finished" }</span>
+            <ul class="ogs-games">{ for finished }</ul>
+        </div>
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use crate::game::Player;
+
+/// A point of an area and the color of the stone on it.
+pub type Stone = ((usize, usize), Player);
+
+/// One of the eight ways to turn or mirror a square area onto itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Symmetry {
+    /// Swap the axes, mirroring along the main diagonal, before flipping
+    pub transpose: bool,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry {
+        transpose: false,
+        flip_x: false,
+        flip_y: false,
+    };
+    /// Mirroring along the main diagonal, the one symmetry that keeps the
+    /// top left corner in place
+    pub const TRANSPOSE: Symmetry = Symmetry {
+        transpose: true,
+        flip_x: false,
+        flip_y: false,
+    };
+
+    pub const ALL: [Symmetry; 8] = {
+        let mut all = [Symmetry::IDENTITY; 8];
+        let mut i = 0;
+        while i < 8 {
+            all[i] = Symmetry {
+                transpose: i & 1 != 0,
+                flip_x: i & 2 != 0,
+                flip_y: i & 4 != 0,
+            };
+            i += 1;
+        }
+        all
+    };
+
+    /// Moves a point of a square area with sides of `size` points.
+    pub fn apply(self, (x, y): (usize, usize), size: usize) -> (usize, usize) {
+        let (x, y) = if self.transpose { (y, x) } else { (x, y) };
+        (
+            if self.flip_x { size - 1 - x } else { x },
+            if self.flip_y { size - 1 - y } else { y },
+        )
+    }
+
+    /// Moves a point back to where it was before `apply`.
+    pub fn invert(self, (x, y): (usize, usize), size: usize) -> (usize, usize) {
+        let x = if self.flip_x { size - 1 - x } else { x };
+        let y = if self.flip_y { size - 1 - y } else { y };
+        if self.transpose {
+            (y, x)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+/// A hash of some stones that is the same for every arrangement they can
+/// be turned into, along with how to turn these stones into the arrangement
+/// that was hashed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CanonicalKey {
+    pub hash: u64,
+    pub symmetry: Symmetry,
+    /// Whether black and white were swapped
+    pub swap_colors: bool,
+}
+
+impl CanonicalKey {
+    /// Finds the key of `stones`, given as points of a square area with
+    /// sides of `size` points and their colors. Arrangements that one of
+    /// `symmetries` turns into each other, with colors swapped when
+    /// `swap_colors` is set, get the same hash.
+    pub fn new(
+        stones: &[Stone],
+        size: usize,
+        symmetries: &[Symmetry],
+        swap_colors: bool,
+    ) -> CanonicalKey {
+        let swaps: &[bool] = if swap_colors {
+            &[false, true]
+        } else {
+            &[false]
+        };
+        symmetries
+            .iter()
+            .flat_map(|symmetry| swaps.iter().map(move |swap| (*symmetry, *swap)))
+            .map(|(symmetry, swap)| {
+                let mut turned: Vec<Stone> = stones
+                    .iter()
+                    .map(|(point, player)| (symmetry.apply(*point, size), swapped(*player, swap)))
+                    .collect();
+                turned.sort_by_key(|((x, y), _)| (*y, *x));
+                CanonicalKey {
+                    hash: hash_stones(&turned),
+                    symmetry,
+                    swap_colors: swap,
+                }
+            })
+            .min_by_key(|key| key.hash)
+            .expect("there is at least the identity")
+    }
+
+    /// Turns a point and color of the hashed arrangement back into those of
+    /// the stones the key was made from.
+    pub fn to_original(self, point: (usize, usize), player: Player, size: usize) -> Stone {
+        (
+            self.symmetry.invert(point, size),
+            swapped(player, self.swap_colors),
+        )
+    }
+
+    /// Turns a point and color of the stones the key was made from into
+    /// those of the hashed arrangement.
+    pub fn to_canonical(self, point: (usize, usize), player: Player, size: usize) -> Stone {
+        (
+            self.symmetry.apply(point, size),
+            swapped(player, self.swap_colors),
+        )
+    }
+}
+
+fn swapped(player: Player, swap: bool) -> Player {
+    match player {
+        Player::Black if swap => Player::White,
+        Player::White if swap => Player::Black,
+        player => player,
+    }
+}
+
+/// FNV-1a over the coordinates and colors of stones in a fixed order, which
+/// stays the same across builds, unlike the hasher of the standard library.
+fn hash_stones(stones: &[Stone]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for ((x, y), player) in stones {
+        let color = match player {
+            Player::Black => 1,
+            Player::White => 2,
+            Player::Red => 3,
+            Player::None => 0,
+        };
+        for byte in [*x as u8, *y as u8, color] {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// How often each move was played after the positions with a canonical key,
+/// with the moves kept in the canonical arrangement.
+#[derive(Debug, Default)]
+pub struct MoveTable {
+    moves: HashMap<u64, Vec<(Stone, u32)>>,
+}
+
+impl MoveTable {
+    /// Counts a move played after the position with `key`, given as it was
+    /// played on that position.
+    pub fn record(
+        &mut self,
+        key: CanonicalKey,
+        point: (usize, usize),
+        player: Player,
+        size: usize,
+    ) {
+        let stone = key.to_canonical(point, player, size);
+        let moves = self.moves.entry(key.hash).or_default();
+        match moves.iter_mut().find(|(known, _)| *known == stone) {
+            Some((_, count)) => *count += 1,
+            None => moves.push((stone, 1)),
+        }
+    }
+
+    /// The moves played after the position with `key` and how often, turned
+    /// to fit that position.
+    pub fn lookup(
+        &self,
+        key: CanonicalKey,
+        size: usize,
+    ) -> impl Iterator<Item = (Stone, u32)> + '_ {
+        self.moves
+            .get(&key.hash)
+            .into_iter()
+            .flatten()
+            .map(move |((point, player), count)| (key.to_original(*point, *player, size), *count))
+    }
+}
@@ -0,0 +1,426 @@
+use crate::engine::Search;
+use crate::estimate::{ownership, PLAYOUTS};
+use crate::eye::is_real_eye;
+use crate::game::{GoGame, GoPosition, Player};
+use crate::sgf::{
+    parse_point, property, read_markup, read_root, read_tree, GameTree, Node, SgfError,
+};
+
+/// The problems that come with the app, as names and SGF records, in the
+/// order a beginner would take them.
+pub const BUNDLED: [(&str, &str); 6] = [
+    ("capture.sgf", include_str!("../problems/capture.sgf")),
+    (
+        "capture-three.sgf",
+        include_str!("../problems/capture-three.sgf"),
+    ),
+    ("edge.sgf", include_str!("../problems/edge.sgf")),
+    ("live.sgf", include_str!("../problems/live.sgf")),
+    (
+        "live-corner.sgf",
+        include_str!("../problems/live-corner.sgf"),
+    ),
+    (
+        "kill-corner.sgf",
+        include_str!("../problems/kill-corner.sgf"),
+    ),
+];
+
+/// How many playouts the engine gets for each response in a problem with a
+/// goal
+const REPLY_VISITS: usize = 400;
+
+/// How many nodes and variations deep the solution tree may go. Reading
+/// and walking it recurses a level at a time, so records from elsewhere
+/// can't run the stack out.
+const MAX_ANSWER_DEPTH: usize = 1_000;
+
+/// A life and death or tactics problem: a position, the color to play, and
+/// either a tree of the answers to try and how the opponent responds to
+/// them, or a goal to reach against the engine.
+#[derive(Debug)]
+pub struct Problem {
+    pub name: String,
+    /// What the problem asks, from the comment of the root
+    pub question: String,
+    /// The color the solver plays
+    pub player: Player,
+    /// What the solver has to reach, for problems without a solution tree
+    pub goal: Option<Goal>,
+    /// How many moves the solver has to reach the goal in, `None` for as
+    /// many as it takes
+    pub moves: Option<usize>,
+    /// The first moves of the solution tree
+    answers: Vec<Branch>,
+    start: GoGame,
+}
+
+/// What a problem asks of the solver when it has no solution tree. The
+/// engine plays the opponent, and judges the position when the solver runs
+/// out of moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// Capture at least this many stones
+    Capture { stones: usize },
+    /// Make the group of the stone at the given index live
+    Live { stone: usize },
+    /// Take or kill the group of the stone at the given index
+    Kill { stone: usize },
+}
+
+impl Goal {
+    /// Reads a goal written as `GL[capture 3]`, `GL[live]` or `GL[kill]`,
+    /// where the group to make live or to kill has a stone marked with `MA`.
+    fn read(root: &Node, size: (usize, usize)) -> Result<Option<Goal>, SgfError> {
+        let value = match property(root, "GL") {
+            Some(value) => value.trim(),
+            None => return Ok(None),
+        };
+        let marked = || -> Result<usize, SgfError> {
+            let (x, y) = property(root, "MA")
+                .map(|point| parse_point(point, size))
+                .transpose()?
+                .flatten()
+                .ok_or_else(|| SgfError::Syntax(String::from("the goal has no marked stone")))?;
+            Ok(y * size.0 + x)
+        };
+        let goal = match value.split_once(' ') {
+            Some(("capture", stones)) => Goal::Capture {
+                stones: stones
+                    .trim()
+                    .parse()
+                    .map_err(|_| SgfError::Syntax(format!("bad goal {}", value)))?,
+            },
+            None if value == "live" => Goal::Live { stone: marked()? },
+            None if value == "kill" => Goal::Kill { stone: marked()? },
+            _ => return Err(SgfError::Syntax(format!("unknown goal {}", value))),
+        };
+        Ok(Some(goal))
+    }
+
+    /// What the goal asks of `player`, for problems without a question.
+    pub fn describe(self, player: Player) -> String {
+        let color = match player {
+            Player::White => "White",
+            _ => "Black",
+        };
+        match self {
+            Goal::Capture { stones: 1 } => format!("{} to capture a stone.", color),
+            Goal::Capture { stones } => format!("{} to capture {} stones.", color, stones),
+            Goal::Live { .. } => format!("{} to make the marked group live.", color),
+            Goal::Kill { .. } => format!("{} to kill the marked group.", color),
+        }
+    }
+
+    /// Whether the goal is reached on `position` by `player`, who captured
+    /// `captures` stones so far. Groups only count as alive once they have
+    /// two real eyes.
+    fn reached(self, position: &GoPosition, player: Player, captures: usize) -> bool {
+        match self {
+            Goal::Capture { stones } => captures >= stones,
+            Goal::Live { stone } => {
+                let group = position.group_at(stone);
+                group.player == player
+                    && group
+                        .liberties
+                        .iter()
+                        .filter(|liberty| is_real_eye(position, **liberty, player))
+                        .count()
+                        >= 2
+            }
+            Goal::Kill { stone } => position[stone] != opponent(player),
+        }
+    }
+
+    /// Whether the goal can't be reached anymore on `position`.
+    fn missed(self, position: &GoPosition, player: Player) -> bool {
+        match self {
+            Goal::Live { stone } => position[stone] != player,
+            _ => false,
+        }
+    }
+
+    /// Whether the engine expects the goal to be reached in the end, when
+    /// the solver has no moves left on `position`, `next` to move.
+    fn judge(self, position: &GoPosition, player: Player, next: Player) -> bool {
+        // Who ends up with the marked point in most playouts
+        let owner = |stone: usize| match ownership(position, next, PLAYOUTS)[stone] {
+            owned if owned > 0.0 => Player::Black,
+            owned if owned < 0.0 => Player::White,
+            _ => Player::None,
+        };
+        match self {
+            Goal::Capture { .. } => false,
+            Goal::Live { stone } => owner(stone) == player,
+            Goal::Kill { stone } => owner(stone) == player,
+        }
+    }
+
+    /// Why the goal wasn't reached.
+    fn failure(self) -> String {
+        String::from(match self {
+            Goal::Capture { .. } => "Not enough stones were captured.",
+            Goal::Live { .. } => "The group doesn't live.",
+            Goal::Kill { .. } => "The group isn't dead.",
+        })
+    }
+}
+
+/// A move of the solution tree and the moves that may follow it.
+#[derive(Clone, Debug)]
+struct Branch {
+    point: (usize, usize),
+    comment: String,
+    /// Whether the line ending here solves the problem, only meaningful
+    /// when nothing follows
+    correct: bool,
+    next: Vec<Branch>,
+}
+
+/// What came of an answer, see `Attempt::answer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    /// Where the opponent responds, if the problem has a response
+    pub reply: Option<(usize, usize)>,
+    pub outcome: Outcome,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The answer is on track, the problem goes on
+    Continue,
+    /// The problem is solved, with the comment of the solution
+    Solved(String),
+    /// The answer is wrong, with the comment explaining why when the
+    /// problem has one
+    Failed(String),
+}
+
+impl Problem {
+    /// Reads a problem from an SGF record. The set up stones are the
+    /// position, `PL` the color to play and the variations the solution
+    /// tree. A line is correct when its last node has a comment with
+    /// `RIGHT` or starting with `Correct`, as most problem collections
+    /// write them, or is marked as a good move.
+    pub fn read(name: String, sgf: &str) -> Result<Problem, SgfError> {
+        let tree = read_tree(sgf)?;
+        let root = tree
+            .nodes
+            .first()
+            .ok_or_else(|| SgfError::Syntax(String::from("the record is empty")))?;
+        let mut start = read_root(root, &tree.nodes)?;
+        let size = (start.width(), start.height());
+        // Marks on the root point out the stones the problem is about
+        start.set_markup_at(0, read_markup(root, size)?);
+        let name = property(root, "GN").map_or(name, str::to_owned);
+        let goal = Goal::read(root, size)?;
+        let answers = branches(&tree.nodes[1..], &tree.variations, size, 0)?;
+        if answers.is_empty() && goal.is_none() {
+            return Err(SgfError::Syntax(String::from("the problem has no answers")));
+        }
+        let player = start.next_player;
+        let question = match (property(root, "C"), goal) {
+            (Some(question), _) => question.to_owned(),
+            (None, Some(goal)) => goal.describe(player),
+            (None, None) => String::new(),
+        };
+        Ok(Problem {
+            name,
+            question,
+            player,
+            goal,
+            moves: property(root, "ML").and_then(|moves| moves.trim().parse().ok()),
+            answers,
+            start,
+        })
+    }
+
+    /// The bundled problems, see `BUNDLED`.
+    pub fn bundled() -> Vec<Problem> {
+        BUNDLED
+            .iter()
+            .map(|(name, sgf)| {
+                Problem::read(name.to_string(), sgf).expect("bundled problems are valid")
+            })
+            .collect()
+    }
+
+    /// The position of the problem with the solver to move.
+    pub fn start(&self) -> GoGame {
+        self.start.clone()
+    }
+
+    /// Starts trying to solve the problem.
+    pub fn attempt(&self) -> Attempt {
+        Attempt {
+            answers: self.answers.clone(),
+            goal: self.goal,
+            player: self.player,
+            captures: self.start.captures(self.player),
+            moves_left: self.moves,
+        }
+    }
+}
+
+/// How far a solver got through a problem.
+#[derive(Clone, Debug)]
+pub struct Attempt {
+    /// The answers that are still on track
+    answers: Vec<Branch>,
+    goal: Option<Goal>,
+    player: Player,
+    /// The stones the solver had captured at the start
+    captures: usize,
+    moves_left: Option<usize>,
+}
+
+impl Attempt {
+    /// How many more moves the solver may make, for problems with a limit.
+    pub fn moves_left(&self) -> Option<usize> {
+        self.moves_left
+    }
+
+    /// Checks the solver's move at a point, and picks the opponent's
+    /// response. `game` is the problem with the move played. Problems with
+    /// a solution tree are checked against it, and moves the tree doesn't
+    /// know are wrong; problems with a goal are played out by the engine.
+    pub fn answer(&mut self, game: &GoGame, x: usize, y: usize) -> Response {
+        match self.goal {
+            Some(goal) => self.play_toward(goal, game),
+            None => self.follow_tree(x, y),
+        }
+    }
+
+    /// Checks a move of a problem with a goal, answering with the move the
+    /// engine likes best.
+    fn play_toward(&mut self, goal: Goal, game: &GoGame) -> Response {
+        if let Some(moves_left) = &mut self.moves_left {
+            *moves_left = moves_left.saturating_sub(1);
+        }
+        let captures = game.captures(self.player) - self.captures;
+        if goal.reached(game, self.player, captures) {
+            return Response {
+                reply: None,
+                outcome: Outcome::Solved(String::new()),
+            };
+        }
+        let opponent = opponent(self.player);
+        let mut search = Search::new(game, opponent, game.compensation());
+        search.run(REPLY_VISITS);
+        let reply = search
+            .evaluation()
+            .candidates
+            .first()
+            .map(|candidate| candidate.point);
+        let mut after = game.clone();
+        let reply = reply.filter(|(x, y)| after.play_move(*x, *y).is_ok());
+        // With nothing left to play the position is as settled as it gets
+        let outcome = if goal.missed(&after, self.player) {
+            Outcome::Failed(goal.failure())
+        } else if self.moves_left == Some(0) || reply.is_none() {
+            if goal.judge(&after, self.player, after.next_player) {
+                Outcome::Solved(String::new())
+            } else {
+                Outcome::Failed(goal.failure())
+            }
+        } else {
+            Outcome::Continue
+        };
+        Response { reply, outcome }
+    }
+
+    /// Checks a move against the solution tree.
+    fn follow_tree(&mut self, x: usize, y: usize) -> Response {
+        let answers = std::mem::take(&mut self.answers);
+        let branch = match answers.into_iter().find(|branch| branch.point == (x, y)) {
+            Some(branch) => branch,
+            None => {
+                return Response {
+                    reply: None,
+                    outcome: Outcome::Failed(String::new()),
+                }
+            }
+        };
+        // The first variation is the opponent's main response
+        let reply = match branch.next.into_iter().next() {
+            Some(reply) => reply,
+            None => {
+                return Response {
+                    reply: None,
+                    outcome: finished(branch.correct, branch.comment),
+                }
+            }
+        };
+        let outcome = if reply.next.is_empty() {
+            finished(reply.correct, reply.comment)
+        } else {
+            self.answers = reply.next;
+            Outcome::Continue
+        };
+        Response {
+            reply: Some(reply.point),
+            outcome,
+        }
+    }
+}
+
+fn finished(correct: bool, comment: String) -> Outcome {
+    if correct {
+        Outcome::Solved(comment)
+    } else {
+        Outcome::Failed(comment)
+    }
+}
+
+fn opponent(player: Player) -> Player {
+    match player {
+        Player::Black => Player::White,
+        _ => Player::Black,
+    }
+}
+
+/// Turns a sequence of nodes and the variations after it into branches of
+/// the solution tree, on a board of `size` as width and height. Nodes
+/// without a move are skipped. `depth` counts the levels above, see
+/// `MAX_ANSWER_DEPTH`.
+fn branches(
+    nodes: &[Node],
+    variations: &[GameTree],
+    size: (usize, usize),
+    depth: usize,
+) -> Result<Vec<Branch>, SgfError> {
+    if depth == MAX_ANSWER_DEPTH {
+        return Err(SgfError::Syntax(String::from("the answers go too deep")));
+    }
+    let (node, rest) = match nodes.split_first() {
+        Some(split) => split,
+        None => {
+            let mut all = Vec::new();
+            for variation in variations {
+                all.extend(branches(
+                    &variation.nodes,
+                    &variation.variations,
+                    size,
+                    depth + 1,
+                )?);
+            }
+            return Ok(all);
+        }
+    };
+    let point = match property(node, "B").or_else(|| property(node, "W")) {
+        Some(value) => parse_point(value, size)?,
+        None => return branches(rest, variations, size, depth + 1),
+    };
+    // Passing isn't an answer to a problem
+    let point = point.ok_or_else(|| SgfError::BadPoint(String::from("pass")))?;
+    let comment = property(node, "C").unwrap_or_default().to_owned();
+    let correct = comment.contains("RIGHT")
+        || comment.trim_start().starts_with("Correct")
+        || property(node, "TE").is_some();
+    Ok(vec![Branch {
+        point,
+        comment,
+        correct,
+        next: branches(rest, variations, size, depth + 1)?,
+    }])
+}
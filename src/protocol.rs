@@ -0,0 +1,443 @@
+//! Messages of a game kept by an authority, such as a server.
+//!
+//! Clients don't play moves on their own board and send the result.
+//! Instead they send an `Intent`. The authority checks the intent against
+//! its own record and answers with an `Update`: either the whole record, or
+//! a rejection with the reason the move was refused. Clients then replay the
+//! record on their board, so one whose engine disagrees with the authority
+//! over a move still ends up with the same game.
+//!
+//! Both ends start from the same setup, with any free handicap stones
+//! already placed, and only the moves after that go through the authority.
+//!
+//! Each player is known to the authority by a session token it keeps
+//! between visits. A client that lost its connection, or was reloaded,
+//! sends a `Resync` with its token and gets the record back along with the
+//! state of the clock.
+
+use std::fmt;
+
+use crate::game::{GoGame, MoveError, MoveKind, Player};
+
+/// The first line of every intent.
+const INTENT_HEADER: &str = "go-rs-intent 1";
+/// The first line of every update.
+const UPDATE_HEADER: &str = "go-rs-update 1";
+/// The first line of every request to resync.
+const RESYNC_HEADER: &str = "go-rs-resync 1";
+
+/// A move of a game, as the protocol names it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Play { x: usize, y: usize },
+    Pass,
+    Resign,
+}
+
+impl Action {
+    fn encode(self) -> String {
+        match self {
+            Action::Play { x, y } => format!("play {} {}", x, y),
+            Action::Pass => String::from("pass"),
+            Action::Resign => String::from("resign"),
+        }
+    }
+
+    fn decode(text: &str) -> Option<Action> {
+        let mut words = text.split(' ');
+        let action = match words.next()? {
+            "play" => Action::Play {
+                x: words.next()?.parse().ok()?,
+                y: words.next()?.parse().ok()?,
+            },
+            "pass" => Action::Pass,
+            "resign" => Action::Resign,
+            _ => return None,
+        };
+        words.next().is_none().then_some(action)
+    }
+
+    /// Makes the move on `game` for the player to move.
+    fn apply(self, game: &mut GoGame) -> Result<(), MoveError> {
+        if game.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        match self {
+            Action::Play { x, y } => game.play_move(x, y),
+            Action::Pass => {
+                game.pass();
+                Ok(())
+            }
+            Action::Resign => {
+                game.resign();
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The moves of `game` so far.
+pub fn record(game: &GoGame) -> Vec<Action> {
+    game.history()
+        .iter()
+        .map(|m| match m.kind {
+            MoveKind::Play(square) => Action::Play {
+                x: square.x,
+                y: square.y,
+            },
+            MoveKind::Pass => Action::Pass,
+            MoveKind::Resign => Action::Resign,
+        })
+        .collect()
+}
+
+/// A move a client would like to make.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Intent {
+    /// The number of moves the client's game had when it asked, so an
+    /// intent made on an outdated board isn't taken as a later move
+    pub move_number: usize,
+    pub action: Action,
+}
+
+impl Intent {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}\nmove {}\n{}",
+            INTENT_HEADER,
+            self.move_number,
+            self.action.encode()
+        )
+    }
+
+    /// Reads an intent from a message, returning `None` if it isn't one.
+    pub fn decode(text: &str) -> Option<Intent> {
+        let mut lines = text.split('\n');
+        if lines.next()? != INTENT_HEADER {
+            return None;
+        }
+        let move_number = lines.next()?.strip_prefix("move ")?.parse().ok()?;
+        let action = Action::decode(lines.next()?)?;
+        lines.next().is_none().then_some(Intent {
+            move_number,
+            action,
+        })
+    }
+}
+
+/// A client asking for the game as the authority has it, after it lost
+/// track of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Resync {
+    /// The token of the player asking, see `Authority::join`
+    pub session: String,
+    /// The number of moves the client has
+    pub move_number: usize,
+}
+
+impl Resync {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}\nsession {}\nmove {}",
+            RESYNC_HEADER, self.session, self.move_number
+        )
+    }
+
+    /// Reads a request to resync from a message, returning `None` if it
+    /// isn't one.
+    pub fn decode(text: &str) -> Option<Resync> {
+        let mut lines = text.split('\n');
+        if lines.next()? != RESYNC_HEADER {
+            return None;
+        }
+        let session = lines.next()?.strip_prefix("session ")?;
+        let move_number = lines.next()?.strip_prefix("move ")?.parse().ok()?;
+        (!session.is_empty() && lines.next().is_none()).then(|| Resync {
+            session: session.to_owned(),
+            move_number,
+        })
+    }
+}
+
+/// The time each player has used, in milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClockState {
+    pub black: u64,
+    pub white: u64,
+}
+
+impl ClockState {
+    /// Adds `ms` to the time of `player`.
+    fn charge(&mut self, player: Player, ms: u64) {
+        match player {
+            Player::Black => self.black += ms,
+            Player::White => self.white += ms,
+            Player::None | Player::Red => {}
+        }
+    }
+}
+
+/// Why the authority refused an intent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rejection {
+    /// The rules don't allow the move
+    Illegal(MoveError),
+    /// The other player is to move
+    NotYourTurn,
+    /// The session token isn't one of the game's players
+    UnknownSession,
+}
+
+impl Rejection {
+    /// The code a rejection is sent as, like `ko 42` or `occupied`.
+    fn code(&self) -> String {
+        match self {
+            Rejection::Illegal(MoveError::OutOfBounds) => String::from("out-of-bounds"),
+            Rejection::Illegal(MoveError::Occupied) => String::from("occupied"),
+            Rejection::Illegal(MoveError::NoPlayer) => String::from("no-player"),
+            Rejection::Illegal(MoveError::Ko { stone }) => format!("ko {}", stone),
+            Rejection::Illegal(MoveError::Suicide { capturing }) => capturing
+                .iter()
+                .fold(String::from("suicide"), |code, stone| {
+                    format!("{} {}", code, stone)
+                }),
+            Rejection::Illegal(MoveError::Superko) => String::from("superko"),
+            Rejection::Illegal(MoveError::GameOver) => String::from("game-over"),
+            Rejection::Illegal(MoveError::Locked) => String::from("locked"),
+            Rejection::NotYourTurn => String::from("not-your-turn"),
+            Rejection::UnknownSession => String::from("unknown-session"),
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Rejection> {
+        let mut words = code.split(' ');
+        let error = match words.next()? {
+            "out-of-bounds" => MoveError::OutOfBounds,
+            "occupied" => MoveError::Occupied,
+            "no-player" => MoveError::NoPlayer,
+            "ko" => MoveError::Ko {
+                stone: words.next()?.parse().ok()?,
+            },
+            "suicide" => MoveError::Suicide {
+                capturing: words
+                    .by_ref()
+                    .map(|stone| stone.parse().ok())
+                    .collect::<Option<_>>()?,
+            },
+            "superko" => MoveError::Superko,
+            "game-over" => MoveError::GameOver,
+            "locked" => MoveError::Locked,
+            "not-your-turn" => return words.next().is_none().then_some(Rejection::NotYourTurn),
+            "unknown-session" => {
+                return words.next().is_none().then_some(Rejection::UnknownSession)
+            }
+            _ => return None,
+        };
+        words.next().is_none().then_some(Rejection::Illegal(error))
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Illegal(error) => write!(f, "{}", error),
+            Rejection::NotYourTurn => write!(f, "It's not your turn"),
+            Rejection::UnknownSession => write!(f, "You don't play in this game"),
+        }
+    }
+}
+
+/// The authority's answer to an intent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Update {
+    /// Every move of the game as the authority has it, and the time used
+    /// so far when it was sent
+    Record {
+        moves: Vec<Action>,
+        clock: ClockState,
+    },
+    /// The intent made at `move_number` was refused
+    Rejected {
+        move_number: usize,
+        rejection: Rejection,
+    },
+}
+
+impl Update {
+    pub fn encode(&self) -> String {
+        match self {
+            Update::Record { moves, clock } => moves.iter().fold(
+                format!(
+                    "{}\nrecord {}\nclock {} {}",
+                    UPDATE_HEADER,
+                    moves.len(),
+                    clock.black,
+                    clock.white
+                ),
+                |text, action| format!("{}\n{}", text, action.encode()),
+            ),
+            Update::Rejected {
+                move_number,
+                rejection,
+            } => format!(
+                "{}\nrejected {}\n{}",
+                UPDATE_HEADER,
+                move_number,
+                rejection.code()
+            ),
+        }
+    }
+
+    /// Reads an update from a message, returning `None` if it isn't one.
+    pub fn decode(text: &str) -> Option<Update> {
+        let mut lines = text.split('\n');
+        if lines.next()? != UPDATE_HEADER {
+            return None;
+        }
+        let (kind, number) = lines.next()?.split_once(' ')?;
+        let number: usize = number.parse().ok()?;
+        let update = match kind {
+            "record" => {
+                let (black, white) = lines.next()?.strip_prefix("clock ")?.split_once(' ')?;
+                let clock = ClockState {
+                    black: black.parse().ok()?,
+                    white: white.parse().ok()?,
+                };
+                let moves = lines
+                    .by_ref()
+                    .map(Action::decode)
+                    .collect::<Option<Vec<_>>>()?;
+                if moves.len() != number {
+                    return None;
+                }
+                Update::Record { moves, clock }
+            }
+            "rejected" => Update::Rejected {
+                move_number: number,
+                rejection: Rejection::from_code(lines.next()?)?,
+            },
+            _ => return None,
+        };
+        lines.next().is_none().then_some(update)
+    }
+}
+
+/// The end of a game that keeps the authoritative record.
+pub struct Authority {
+    pub game: GoGame,
+    /// The session token of each player
+    sessions: Vec<(String, Player)>,
+    /// The time used before the running turn
+    clock: ClockState,
+    /// When the running turn started, in milliseconds
+    turn_started: f64,
+}
+
+impl Authority {
+    /// Keeps `game`, whose clock starts running at time `now`.
+    pub fn new(game: GoGame, now: f64) -> Self {
+        Authority {
+            game,
+            sessions: Vec::new(),
+            clock: ClockState::default(),
+            turn_started: now,
+        }
+    }
+
+    /// Lets the holder of `session` play `player`, replacing an earlier
+    /// session of that player.
+    pub fn join(&mut self, session: String, player: Player) {
+        self.sessions.retain(|(_, seated)| *seated != player);
+        self.sessions.push((session, player));
+    }
+
+    fn player_of(&self, session: &str) -> Option<Player> {
+        self.sessions
+            .iter()
+            .find(|(token, _)| token == session)
+            .map(|(_, player)| *player)
+    }
+
+    /// Answers an intent made by the holder of `session` at time `now`. An
+    /// accepted move, and an intent made on an outdated board, are both
+    /// answered with the whole record, so the client catches up either way.
+    pub fn handle(&mut self, session: &str, intent: &Intent, now: f64) -> Update {
+        let rejected = |rejection| Update::Rejected {
+            move_number: intent.move_number,
+            rejection,
+        };
+        let player = match self.player_of(session) {
+            Some(player) => player,
+            None => return rejected(Rejection::UnknownSession),
+        };
+        if intent.move_number != self.game.move_count() {
+            return self.record(now);
+        }
+        if self.game.is_over() {
+            return rejected(Rejection::Illegal(MoveError::GameOver));
+        }
+        if player != self.game.next_player {
+            return rejected(Rejection::NotYourTurn);
+        }
+        match intent.action.apply(&mut self.game) {
+            Ok(()) => {
+                self.clock.charge(player, elapsed(self.turn_started, now));
+                self.turn_started = now;
+                self.record(now)
+            }
+            Err(error) => rejected(Rejection::Illegal(error)),
+        }
+    }
+
+    /// Answers a client that lost track of the game.
+    pub fn resync(&self, resync: &Resync, now: f64) -> Update {
+        match self.player_of(&resync.session) {
+            Some(_) => self.record(now),
+            None => Update::Rejected {
+                move_number: resync.move_number,
+                rejection: Rejection::UnknownSession,
+            },
+        }
+    }
+
+    /// The record with the clock as it stands at time `now`, the running
+    /// turn included.
+    pub fn record(&self, now: f64) -> Update {
+        let mut clock = self.clock;
+        if !self.game.is_over() {
+            clock.charge(self.game.next_player, elapsed(self.turn_started, now));
+        }
+        Update::Record {
+            moves: record(&self.game),
+            clock,
+        }
+    }
+}
+
+/// The whole milliseconds from `start` to `end`.
+fn elapsed(start: f64, end: f64) -> u64 {
+    (end - start).max(0.0) as u64
+}
+
+/// Brings `game` in line with the authority's `moves`. The moves the two
+/// agree on are kept and the rest of `game` is replaced. Returns whether
+/// anything changed, or the error if this engine can't play one of the
+/// authority's moves, in which case `game` holds the moves before it.
+pub fn catch_up(game: &mut GoGame, moves: &[Action]) -> Result<bool, MoveError> {
+    let ours = record(game);
+    let agreed = ours
+        .iter()
+        .zip(moves)
+        .take_while(|(ours, theirs)| ours == theirs)
+        .count();
+    if agreed == ours.len() && agreed == moves.len() {
+        return Ok(false);
+    }
+    if agreed < ours.len() {
+        *game = game.truncated(agreed)?;
+    }
+    for action in &moves[agreed..] {
+        action.apply(game)?;
+    }
+    Ok(true)
+}
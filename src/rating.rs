@@ -0,0 +1,71 @@
+//! Elo ratings, for keeping track of how a player does over their games.
+//!
+//! Ratings are on the scale of the European Go Federation, where a 1 dan
+//! player is rated 2100 and every rank is worth 100 points, so ratings and
+//! the ranks of game records can be compared, see `rank_rating`.
+
+use serde::{Deserialize, Serialize};
+
+/// The rating of a player before their first rated game, a 6 kyu
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// How many games a rating moves quickly for, while it says little yet
+const PROVISIONAL_GAMES: usize = 20;
+
+/// The ratings of one player, after each of their rated games.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RatingHistory {
+    /// Oldest first
+    pub ratings: Vec<f64>,
+}
+
+impl RatingHistory {
+    /// The rating now, `INITIAL_RATING` before any games.
+    pub fn current(&self) -> f64 {
+        self.ratings.last().copied().unwrap_or(INITIAL_RATING)
+    }
+
+    pub fn games(&self) -> usize {
+        self.ratings.len()
+    }
+
+    /// Rates a game against an opponent rated `opponent`, where `score` is 1
+    /// for a win, 0 for a loss and 0.5 for a draw. Returns the new rating.
+    pub fn record(&mut self, opponent: f64, score: f64) -> f64 {
+        let k = if self.games() < PROVISIONAL_GAMES {
+            40.0
+        } else {
+            20.0
+        };
+        let rating = self.current() + k * (score - expected_score(self.current(), opponent));
+        self.ratings.push(rating);
+        rating
+    }
+}
+
+/// The score a player rated `rating` is expected to get on average against
+/// one rated `opponent`, from 0 to 1.
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// The rating of a rank as game records write it, like `3d`, `15k` or
+/// `2p`, ignoring a trailing `?` or `*`. Professional ranks are counted
+/// from just above 7 dan, a third of a rank apart.
+pub fn rank_rating(rank: &str) -> Option<f64> {
+    let rank = rank
+        .trim()
+        .trim_end_matches(['?', '*'])
+        .to_ascii_lowercase();
+    let kind = rank.chars().last()?;
+    let number: f64 = rank[..rank.len() - kind.len_utf8()].trim().parse().ok()?;
+    if number < 1.0 {
+        return None;
+    }
+    match kind {
+        'k' => Some(2100.0 - number * 100.0),
+        'd' => Some(2000.0 + number * 100.0),
+        'p' => Some(2700.0 + number * 100.0 / 3.0),
+        _ => None,
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use gloo_storage::{LocalStorage, Storage};
+use go_rs::rating::RatingHistory;
+use serde::{Deserialize, Serialize};
+
+/// The local storage key the ratings are kept under
+const STORAGE_KEY: &str = "go-rs-ratings";
+
+/// The profile of games played on this device without an account
+pub const LOCAL_PROFILE: &str = "Local";
+
+/// The profile the library bot is rated under, as the opponent of games
+/// against it
+pub const BOT_PROFILE: &str = "Library bot";
+
+/// The rating history of every profile on every board size, kept between
+/// visits.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ratings {
+    /// Keyed by profile name, then by board size like `19x19`
+    profiles: BTreeMap<String, BTreeMap<String, RatingHistory>>,
+}
+
+impl Ratings {
+    /// Reads the stored ratings, starting over when there are none or they
+    /// can't be read.
+    pub fn load() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let _ = LocalStorage::set(STORAGE_KEY, self);
+    }
+
+    /// The history of `profile` on boards of `size` as width and height,
+    /// empty before its first rated game there.
+    pub fn history(&self, profile: &str, size: (usize, usize)) -> RatingHistory {
+        self.profiles
+            .get(profile)
+            .and_then(|sizes| sizes.get(&size_name(size)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn history_mut(&mut self, profile: &str, size: (usize, usize)) -> &mut RatingHistory {
+        self.profiles
+            .entry(profile.to_owned())
+            .or_default()
+            .entry(size_name(size))
+            .or_default()
+    }
+
+    /// Every profile and board size with rated games, as the name of the
+    /// profile, the name of the size and the history.
+    pub fn all(&self) -> impl Iterator<Item = (&str, &str, &RatingHistory)> {
+        self.profiles.iter().flat_map(|(profile, sizes)| {
+            sizes
+                .iter()
+                .filter(|(_, history)| history.games() > 0)
+                .map(move |(size, history)| (profile.as_str(), size.as_str(), history))
+        })
+    }
+}
+
+fn size_name((width, height): (usize, usize)) -> String {
+    format!("{}x{}", width, height)
+}
@@ -0,0 +1,119 @@
+//! Parts of the board that play can be limited to, like a single corner for
+//! an exercise or a handful of points picked by a teacher.
+
+use crate::game::{Corner, GoGame, MoveError, Player, Position};
+
+/// The points of a board open for play, the rest are locked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    width: usize,
+    height: usize,
+    /// Whether each point is open, by index
+    open: Vec<bool>,
+}
+
+impl Region {
+    /// A region without any open points, for picking them one by one.
+    pub fn empty(width: usize, height: usize) -> Self {
+        Region {
+            width,
+            height,
+            open: vec![false; width * height],
+        }
+    }
+
+    /// The `size` by `size` points in `corner`, or as much of them as fits.
+    pub fn corner(width: usize, height: usize, corner: Corner, size: usize) -> Self {
+        let (size_x, size_y) = (size.min(width), size.min(height));
+        let xs = match corner {
+            Corner::TopLeft | Corner::BottomLeft => 0..size_x,
+            Corner::TopRight | Corner::BottomRight => width - size_x..width,
+        };
+        let ys = match corner {
+            Corner::TopLeft | Corner::TopRight => 0..size_y,
+            Corner::BottomLeft | Corner::BottomRight => height - size_y..height,
+        };
+        let mut region = Region::empty(width, height);
+        for y in ys {
+            for x in xs.clone() {
+                region.open[y * width + x] = true;
+            }
+        }
+        region
+    }
+
+    /// The points with the given indicies.
+    pub fn points(width: usize, height: usize, points: impl IntoIterator<Item = usize>) -> Self {
+        let mut region = Region::empty(width, height);
+        for index in points {
+            if let Some(open) = region.open.get_mut(index) {
+                *open = true;
+            }
+        }
+        region
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the point at `index` is open for play.
+    pub fn contains(&self, index: usize) -> bool {
+        self.open.get(index).copied().unwrap_or(false)
+    }
+
+    /// Opens the point at `index` when it is locked, and locks it when open.
+    pub fn toggle(&mut self, index: usize) {
+        if let Some(open) = self.open.get_mut(index) {
+            *open = !*open;
+        }
+    }
+
+    /// The indicies of the locked points.
+    pub fn locked(&self) -> impl Iterator<Item = usize> + '_ {
+        self.open
+            .iter()
+            .enumerate()
+            .filter(|(_, open)| !**open)
+            .map(|(index, _)| index)
+    }
+
+    /// Whether the region was made for a board of the size of `game`.
+    pub fn fits(&self, game: &GoGame) -> bool {
+        (self.width, self.height) == (game.width(), game.height())
+    }
+
+    /// Checks a move of `game` like `GoGame::check_move`, rejecting moves on
+    /// locked points as well.
+    pub fn check_move(
+        &self,
+        game: &GoGame,
+        x: usize,
+        y: usize,
+        player: Player,
+    ) -> Result<(), MoveError> {
+        if x < self.width && y < self.height && !self.contains(y * self.width + x) {
+            return Err(MoveError::Locked);
+        }
+        game.check_move(x, y, player)
+    }
+
+    /// Whether `player` may play at (`x`, `y`) on `position` and the point
+    /// is open, see `Position::is_valid_move`.
+    pub fn is_valid_move(
+        &self,
+        position: &impl Position,
+        x: usize,
+        y: usize,
+        player: Player,
+    ) -> bool {
+        x < self.width
+            && y < self.height
+            && self.contains(y * self.width + x)
+            && position.is_valid_move(x, y, player)
+    }
+}
@@ -0,0 +1,259 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use gloo_timers::callback::Timeout;
+use go_rs::broadcast::Frame;
+use go_rs::chat::ChatMessage;
+use go_rs::protocol::Resync;
+use go_rs::room::Presence;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{MessageEvent, WebSocket};
+use yew::Callback;
+
+/// What arrives from the relay on a watched channel.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Incoming {
+    Frame(Frame),
+    Chat(ChatMessage),
+    Presence(Presence),
+    Resync(Resync),
+    /// Not from the relay: the connection was lost and has been opened
+    /// again, so anything sent in between was missed
+    Reconnected,
+}
+
+impl Incoming {
+    fn decode(text: &str) -> Option<Incoming> {
+        Frame::decode(text)
+            .map(Incoming::Frame)
+            .or_else(|| ChatMessage::decode(text).map(Incoming::Chat))
+            .or_else(|| Presence::decode(text).map(Incoming::Presence))
+            .or_else(|| Resync::decode(text).map(Incoming::Resync))
+    }
+}
+
+/// How long to wait before connecting again after the connection was
+/// lost, doubled after every failed attempt up to `MAX_RECONNECT_MS`
+const RECONNECT_MS: u32 = 1000;
+const MAX_RECONNECT_MS: u32 = 30 * 1000;
+
+/// A connection to a broadcast relay, either publishing or watching a channel.
+/// A lost connection is opened again until this is dropped, which closes it.
+pub struct RelayConnection {
+    inner: Rc<Inner>,
+}
+
+/// What the handlers of the socket share with the connection.
+struct Inner {
+    url: String,
+    /// Sent first on every connection, to publish or watch the channel
+    greeting: String,
+    socket: RefCell<WebSocket>,
+    /// The messages held back until the socket has opened, oldest first
+    pending: RefCell<Vec<String>>,
+    on_message: Callback<Incoming>,
+    on_error: Callback<String>,
+    /// Whether the socket has been open before, so opening it again is a
+    /// reconnection
+    was_open: Cell<bool>,
+    /// The wait before the next attempt to reconnect
+    backoff: Cell<u32>,
+    /// Set once the connection is dropped, so it stays closed
+    dropped: Cell<bool>,
+    handlers: RefCell<Option<Handlers>>,
+    retry: RefCell<Option<Timeout>>,
+}
+
+struct Handlers {
+    _onopen: Closure<dyn FnMut()>,
+    _onmessage: Closure<dyn FnMut(MessageEvent)>,
+    _onerror: Closure<dyn FnMut()>,
+    _onclose: Closure<dyn FnMut()>,
+}
+
+impl RelayConnection {
+    /// Connects to the relay at `url` to publish frames on `channel`.
+    pub fn publish(url: &str, channel: &str, on_error: Callback<String>) -> Result<Self, String> {
+        RelayConnection::open(
+            url,
+            format!("publish {}", channel),
+            Callback::noop(),
+            on_error,
+        )
+    }
+
+    /// Connects to the relay at `url` and calls `on_message` with every
+    /// frame and chat message published on `channel`.
+    pub fn watch(
+        url: &str,
+        channel: &str,
+        on_message: Callback<Incoming>,
+        on_error: Callback<String>,
+    ) -> Result<Self, String> {
+        RelayConnection::open(url, format!("watch {}", channel), on_message, on_error)
+    }
+
+    fn open(
+        url: &str,
+        greeting: String,
+        on_message: Callback<Incoming>,
+        on_error: Callback<String>,
+    ) -> Result<Self, String> {
+        let inner = Rc::new(Inner {
+            url: url.to_owned(),
+            greeting,
+            socket: RefCell::new(new_socket(url)?),
+            pending: RefCell::new(Vec::new()),
+            on_message,
+            on_error,
+            was_open: Cell::new(false),
+            backoff: Cell::new(RECONNECT_MS),
+            dropped: Cell::new(false),
+            handlers: RefCell::new(None),
+            retry: RefCell::new(None),
+        });
+        attach(&inner);
+        Ok(RelayConnection { inner })
+    }
+
+    /// Sends a frame to the viewers, or keeps it until the connection opens.
+    pub fn send(&self, frame: &Frame) {
+        self.send_text(frame.encode());
+    }
+
+    /// Sends a chat message along with the frames, see `send`.
+    pub fn send_chat(&self, message: &ChatMessage) {
+        self.send_text(message.encode());
+    }
+
+    /// Announces a spectator, see `send`.
+    pub fn send_presence(&self, presence: &Presence) {
+        self.send_text(presence.encode());
+    }
+
+    /// Asks the other end for the game, see `send`.
+    pub fn send_resync(&self, resync: &Resync) {
+        self.send_text(resync.encode());
+    }
+
+    fn send_text(&self, text: String) {
+        let socket = self.inner.socket.borrow();
+        if socket.ready_state() == WebSocket::OPEN {
+            let _ = socket.send_with_str(&text);
+        } else {
+            let mut pending = self.inner.pending.borrow_mut();
+            // Each frame carries the whole game, so only the latest one
+            // needs to wait
+            if Frame::decode(&text).is_some() {
+                pending.retain(|earlier| Frame::decode(earlier).is_none());
+            }
+            pending.push(text);
+        }
+    }
+}
+
+impl Drop for RelayConnection {
+    fn drop(&mut self) {
+        self.inner.dropped.set(true);
+        self.inner.retry.borrow_mut().take();
+        let socket = self.inner.socket.borrow();
+        // The handlers go with the connection, so the socket mustn't call
+        // them while it closes
+        socket.set_onopen(None);
+        socket.set_onmessage(None);
+        socket.set_onerror(None);
+        socket.set_onclose(None);
+        let _ = socket.close();
+    }
+}
+
+fn new_socket(url: &str) -> Result<WebSocket, String> {
+    WebSocket::new(url).map_err(|_| format!("Could not connect to the relay at {}", url))
+}
+
+/// Hands the socket of `inner` handlers that hold on to it only weakly, so
+/// dropping the connection frees everything.
+fn attach(inner: &Rc<Inner>) {
+    let onopen = {
+        let weak = Rc::downgrade(inner);
+        Closure::wrap(Box::new(move || {
+            let inner = match weak.upgrade() {
+                Some(inner) => inner,
+                None => return,
+            };
+            let socket = inner.socket.borrow();
+            let _ = socket.send_with_str(&inner.greeting);
+            for text in inner.pending.borrow_mut().drain(..) {
+                let _ = socket.send_with_str(&text);
+            }
+            inner.backoff.set(RECONNECT_MS);
+            if inner.was_open.replace(true) {
+                inner.on_message.emit(Incoming::Reconnected);
+            }
+        }) as Box<dyn FnMut()>)
+    };
+    let onmessage = {
+        let on_message = inner.on_message.clone();
+        Closure::wrap(Box::new(move |e: MessageEvent| {
+            if let Some(message) = e.data().as_string().as_deref().and_then(Incoming::decode) {
+                on_message.emit(message);
+            }
+        }) as Box<dyn FnMut(MessageEvent)>)
+    };
+    let onerror = {
+        let weak = Rc::downgrade(inner);
+        Closure::wrap(Box::new(move || {
+            // Failed attempts to reconnect are only retried
+            if let Some(inner) = weak.upgrade().filter(|inner| !inner.was_open.get()) {
+                inner
+                    .on_error
+                    .emit(String::from("The connection to the relay failed"))
+            }
+        }) as Box<dyn FnMut()>)
+    };
+    let onclose = {
+        let weak = Rc::downgrade(inner);
+        Closure::wrap(Box::new(move || reconnect_later(&weak)) as Box<dyn FnMut()>)
+    };
+
+    let socket = inner.socket.borrow();
+    socket.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+    socket.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    socket.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+    // Only ever called outside of the handlers, which this drops
+    *inner.handlers.borrow_mut() = Some(Handlers {
+        _onopen: onopen,
+        _onmessage: onmessage,
+        _onerror: onerror,
+        _onclose: onclose,
+    });
+}
+
+/// Opens a new socket once the backoff has passed, unless the connection
+/// has been dropped in the meantime.
+fn reconnect_later(weak: &Weak<Inner>) {
+    let inner = match weak.upgrade() {
+        Some(inner) if !inner.dropped.get() => inner,
+        _ => return,
+    };
+    let backoff = inner.backoff.get();
+    inner.backoff.set((backoff * 2).min(MAX_RECONNECT_MS));
+    let weak = weak.clone();
+    *inner.retry.borrow_mut() = Some(Timeout::new(backoff, move || {
+        let inner = match weak.upgrade() {
+            Some(inner) if !inner.dropped.get() => inner,
+            _ => return,
+        };
+        // The address worked before, so this only fails if the browser
+        // gave up on it, and trying again won't help
+        match new_socket(&inner.url) {
+            Ok(socket) => {
+                *inner.socket.borrow_mut() = socket;
+                attach(&inner);
+            }
+            Err(e) => inner.on_error.emit(e),
+        }
+    }));
+}
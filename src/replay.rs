@@ -0,0 +1,136 @@
+//! Checking the incremental bookkeeping of positions against a slow, simple
+//! recomputation.
+//!
+//! A position keeps its chains and their liberties up to date move by move,
+//! see `GoPosition`, which is easy to get subtly wrong when making it
+//! faster. `check_replay` plays the moves of a game again two ways: through
+//! a fresh game as usual, and on a plain list of stones where captures are
+//! found by following each chain from scratch. After every move the stones
+//! and the chains of both have to agree, and the hash the game keeps up to
+//! date has to match one computed from the plain stones.
+
+use std::fmt;
+
+use crate::game::{GoGame, GoPosition, MoveKind, Player};
+
+/// Where a replay went wrong.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// How many moves were replayed when it went wrong
+    pub moves: usize,
+    pub problem: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "After {} moves: {}", self.moves, self.problem)
+    }
+}
+
+/// Replays the moves of `game` incrementally and from scratch, returning
+/// how many moves agreed, or where they first didn't.
+pub fn check_replay(game: &GoGame) -> Result<usize, Divergence> {
+    let start = game.initial_position();
+    let (width, height) = (start.width(), start.height());
+    let mut replayed = game.truncated(0).map_err(|e| Divergence {
+        moves: 0,
+        problem: format!("the start was refused: {}", e),
+    })?;
+    let mut stones = start.position().clone();
+    for (moves, m) in game.history().iter().enumerate() {
+        let diverged = |problem: String| Divergence {
+            moves: moves + 1,
+            problem,
+        };
+        replayed
+            .replay(m)
+            .map_err(|e| diverged(format!("the move was refused: {}", e)))?;
+        if let MoveKind::Play(square) = m.kind {
+            let index = square.y * width + square.x;
+            play_from_scratch(&mut stones, width, height, index, m.player);
+        }
+        if *replayed.position() != stones {
+            return Err(diverged(String::from("the stones differ")));
+        }
+        if replayed.save().hash() != GoPosition::key_of(width, height, &stones) {
+            return Err(diverged(String::from("the hashes differ")));
+        }
+        let rebuilt = GoPosition::from_stones(width, height, &stones);
+        for index in 0..stones.len() {
+            if replayed.group_at(index) != rebuilt.group_at(index) {
+                let (x, y) = rebuilt.index_to_coord(index);
+                return Err(diverged(format!(
+                    "the chain at {} differs",
+                    rebuilt.point_name(x, y)
+                )));
+            }
+        }
+    }
+    if replayed.position() != game.position() {
+        return Err(Divergence {
+            moves: game.history().len(),
+            problem: String::from("the replay ended on other stones than the game"),
+        });
+    }
+    Ok(game.history().len())
+}
+
+/// Plays a stone of `player` at `index` on a board of bare stones, taking
+/// off the opponent's chains left without liberties and then, after a
+/// suicide, the player's own.
+fn play_from_scratch(
+    stones: &mut [Player],
+    width: usize,
+    height: usize,
+    index: usize,
+    player: Player,
+) {
+    stones[index] = player;
+    if player == Player::None {
+        return;
+    }
+    for side in sides(index, width, height) {
+        // Any other color can be captured, in three-color games too
+        if stones[side] != player && stones[side] != Player::None {
+            remove_if_dead(stones, width, height, side);
+        }
+    }
+    remove_if_dead(stones, width, height, index);
+}
+
+/// Takes the chain at `index` off the board if it has no liberties, found
+/// by following the chain point by point.
+fn remove_if_dead(stones: &mut [Player], width: usize, height: usize, index: usize) {
+    let player = stones[index];
+    let mut chain = vec![index];
+    let mut seen = vec![false; stones.len()];
+    seen[index] = true;
+    let mut next = 0;
+    while next < chain.len() {
+        for side in sides(chain[next], width, height) {
+            if stones[side] == Player::None {
+                return;
+            }
+            if stones[side] == player && !seen[side] {
+                seen[side] = true;
+                chain.push(side);
+            }
+        }
+        next += 1;
+    }
+    for stone in chain {
+        stones[stone] = Player::None;
+    }
+}
+
+fn sides(index: usize, width: usize, height: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = (index % width, index / width);
+    [
+        (x > 0).then(|| index - 1),
+        (x + 1 < width).then(|| index + 1),
+        (y > 0).then(|| index - width),
+        (y + 1 < height).then(|| index + width),
+    ]
+    .into_iter()
+    .flatten()
+}
@@ -0,0 +1,39 @@
+use js_sys::Array;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, ResizeObserver, ResizeObserverEntry};
+use yew::Callback;
+
+/// Watches the size of an element, calling back with the width and height of
+/// its content box whenever it changes. Stops watching when dropped.
+pub struct SizeObserver {
+    observer: ResizeObserver,
+    _callback: Closure<dyn FnMut(Array)>,
+}
+
+impl SizeObserver {
+    /// Starts watching `element`. Browsers also report the size once right
+    /// after observing starts.
+    pub fn observe(element: &Element, on_resize: Callback<(f64, f64)>) -> Result<Self, String> {
+        let callback = Closure::wrap(Box::new(move |entries: Array| {
+            // Only one element is observed, so the last entry is the newest size
+            if let Ok(entry) = entries.pop().dyn_into::<ResizeObserverEntry>() {
+                let rect = entry.content_rect();
+                on_resize.emit((rect.width(), rect.height()));
+            }
+        }) as Box<dyn FnMut(Array)>);
+        let observer = ResizeObserver::new(callback.as_ref().unchecked_ref())
+            .map_err(|_| String::from("Resizing the board is not supported in this browser"))?;
+        observer.observe(element);
+        Ok(SizeObserver {
+            observer,
+            _callback: callback,
+        })
+    }
+}
+
+impl Drop for SizeObserver {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
@@ -0,0 +1,66 @@
+//! Who is watching an online game.
+//!
+//! Spectators follow the players' channels on the relay like the players do,
+//! and talk on a channel of their own, see `chat`. As the relay doesn't tell
+//! anyone who is connected, each spectator announces itself on that channel
+//! every `PRESENCE_INTERVAL_MS`, and a spectator that hasn't been heard from
+//! in a while is taken to have left.
+
+/// How often spectators announce themselves, in milliseconds
+pub const PRESENCE_INTERVAL_MS: u32 = 20 * 1000;
+
+/// How long a spectator counts as there after its last announcement, a few
+/// announcements' worth so a late one doesn't make it flicker
+const PRESENCE_TIMEOUT_MS: f64 = 3.0 * PRESENCE_INTERVAL_MS as f64;
+
+/// The first line of every announcement.
+const PRESENCE_HEADER: &str = "go-rs-presence 1";
+
+/// A spectator saying it is there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Presence {
+    /// Stands for the spectator, picked at random when it joins
+    pub viewer: String,
+}
+
+impl Presence {
+    pub fn encode(&self) -> String {
+        format!("{}\n{}", PRESENCE_HEADER, self.viewer)
+    }
+
+    /// Reads an announcement from a relay message, returning `None` if it
+    /// isn't one.
+    pub fn decode(text: &str) -> Option<Presence> {
+        let (header, viewer) = text.split_once('\n')?;
+        (header == PRESENCE_HEADER && !viewer.is_empty()).then(|| Presence {
+            viewer: viewer.to_owned(),
+        })
+    }
+}
+
+/// The spectators heard from lately.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Viewers {
+    /// Each spectator with the time it was last heard from, in milliseconds
+    last_seen: Vec<(String, f64)>,
+}
+
+impl Viewers {
+    /// Notes that `viewer` announced itself at time `now`.
+    pub fn seen(&mut self, viewer: &str, now: f64) {
+        match self.last_seen.iter_mut().find(|(seen, _)| seen == viewer) {
+            Some((_, time)) => *time = now,
+            None => self.last_seen.push((viewer.to_owned(), now)),
+        }
+        self.last_seen
+            .retain(|(_, time)| now - time <= PRESENCE_TIMEOUT_MS);
+    }
+
+    /// How many spectators are there at time `now`.
+    pub fn count(&self, now: f64) -> usize {
+        self.last_seen
+            .iter()
+            .filter(|(_, time)| now - time <= PRESENCE_TIMEOUT_MS)
+            .count()
+    }
+}
@@ -0,0 +1,70 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The views of the app, each at its own address after the `#`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Routable)]
+pub enum Route {
+    /// The start screen, to set up a new game
+    #[at("/")]
+    Home,
+    /// The board of the game being played
+    #[at("/play")]
+    Play,
+    /// The saved games and how they went
+    #[at("/library")]
+    Library,
+    #[at("/problems")]
+    Problems,
+    #[at("/settings")]
+    Settings,
+    /// The board of a game being gone over after it was played
+    #[at("/review")]
+    Review,
+    /// Anything else, shown as the start screen
+    #[not_found]
+    #[at("/404")]
+    NotFound,
+}
+
+impl Route {
+    /// The views linked to from the navigation bar, in order.
+    const LINKED: [Route; 6] = [
+        Route::Home,
+        Route::Play,
+        Route::Review,
+        Route::Library,
+        Route::Problems,
+        Route::Settings,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Route::Home | Route::NotFound => "Home",
+            Route::Play => "Play",
+            Route::Library => "Library",
+            Route::Problems => "Problems",
+            Route::Settings => "Settings",
+            Route::Review => "Review",
+        }
+    }
+}
+
+/// A link to each view, the one shown marked as current.
+#[function_component]
+pub fn NavBar() -> Html {
+    let current = use_route::<Route>().unwrap_or(Route::Home);
+    let links = Route::LINKED.iter().map(|route| {
+        let active = *route == current || (*route == Route::Home && current == Route::NotFound);
+        html! {
+            <Link<Route>
+                to={ *route }
+                classes={ classes!("nav-link", active.then_some("nav-active")) }
+            >
+                { route.name() }
+            </Link<Route>>
+        }
+    });
+    html! {
+        <nav class="nav-bar">{ for links }</nav>
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt;
+
+use crate::game::GoPosition;
+use crate::score::Score;
+
+/// Which repetitions of earlier positions are forbidden.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KoRule {
+    /// Only immediately retaking a single stone is forbidden, longer cycles
+    /// are allowed
+    Simple,
+    /// No move may recreate a board position seen before in the game
+    PositionalSuperko,
+    /// No move may recreate a board position seen before with the same
+    /// player to move
+    SituationalSuperko,
+}
+
+/// What counts as a player's points at the end of a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scoring {
+    /// Stones on the board plus surrounded empty points
+    Area,
+    /// Surrounded empty points plus captured stones
+    Territory,
+}
+
+/// The rules a game is played under. Everything the rulesets disagree on
+/// goes through here, so the game itself has no special cases for them.
+pub trait Ruleset: fmt::Debug + Sync {
+    fn name(&self) -> &'static str;
+
+    /// The value of the `RU` property of SGF records
+    fn sgf_name(&self) -> &'static str;
+
+    /// Whether a move may leave its own chain of `stones` stones, counting
+    /// the played one, without liberties, which then removes that chain
+    fn allows_suicide(&self, stones: usize) -> bool;
+
+    fn ko_rule(&self) -> KoRule;
+
+    fn scoring(&self) -> Scoring;
+
+    /// Points given to white for moving second in an even game
+    fn komi(&self) -> f64;
+
+    /// Extra points given to white for black's `handicap` stones
+    fn handicap_compensation(&self, handicap: usize) -> f64;
+
+    /// The number of captured stones that wins the game outright, for
+    /// variants that aren't played to the end
+    fn capture_goal(&self) -> Option<usize> {
+        None
+    }
+
+    /// Counts the score of `position`, where black and white captured
+    /// `captures` stones.
+    fn score(&self, position: &GoPosition, captures: Score) -> Score {
+        match self.scoring() {
+            Scoring::Area => Score::area(position),
+            Scoring::Territory => Score::territory(position, captures),
+        }
+    }
+}
+
+/// The Japanese rules, as the Nihon Ki-in plays them.
+#[derive(Debug)]
+pub struct Japanese;
+
+impl Ruleset for Japanese {
+    fn name(&self) -> &'static str {
+        "Japanese"
+    }
+
+    fn sgf_name(&self) -> &'static str {
+        "Japanese"
+    }
+
+    fn allows_suicide(&self, _stones: usize) -> bool {
+        false
+    }
+
+    fn ko_rule(&self) -> KoRule {
+        KoRule::Simple
+    }
+
+    fn scoring(&self) -> Scoring {
+        Scoring::Territory
+    }
+
+    fn komi(&self) -> f64 {
+        6.5
+    }
+
+    fn handicap_compensation(&self, _handicap: usize) -> f64 {
+        0.0
+    }
+}
+
+/// The Chinese rules of 2002.
+#[derive(Debug)]
+pub struct Chinese;
+
+impl Ruleset for Chinese {
+    fn name(&self) -> &'static str {
+        "Chinese"
+    }
+
+    fn sgf_name(&self) -> &'static str {
+        "Chinese"
+    }
+
+    fn allows_suicide(&self, _stones: usize) -> bool {
+        false
+    }
+
+    fn ko_rule(&self) -> KoRule {
+        KoRule::PositionalSuperko
+    }
+
+    fn scoring(&self) -> Scoring {
+        Scoring::Area
+    }
+
+    fn komi(&self) -> f64 {
+        7.5
+    }
+
+    fn handicap_compensation(&self, handicap: usize) -> f64 {
+        handicap as f64
+    }
+}
+
+/// The rules of the American Go Association. Counting by area with pass
+/// stones gives the same result as area scoring, so it is scored that way.
+#[derive(Debug)]
+pub struct Aga;
+
+impl Ruleset for Aga {
+    fn name(&self) -> &'static str {
+        "AGA"
+    }
+
+    fn sgf_name(&self) -> &'static str {
+        "AGA"
+    }
+
+    fn allows_suicide(&self, _stones: usize) -> bool {
+        false
+    }
+
+    fn ko_rule(&self) -> KoRule {
+        KoRule::SituationalSuperko
+    }
+
+    fn scoring(&self) -> Scoring {
+        Scoring::Area
+    }
+
+    fn komi(&self) -> f64 {
+        7.5
+    }
+
+    fn handicap_compensation(&self, handicap: usize) -> f64 {
+        handicap.saturating_sub(1) as f64
+    }
+}
+
+/// The Ing rules, also known as the Goe rules.
+#[derive(Debug)]
+pub struct Ing;
+
+impl Ruleset for Ing {
+    fn name(&self) -> &'static str {
+        "Ing"
+    }
+
+    fn sgf_name(&self) -> &'static str {
+        "GOE"
+    }
+
+    /// Only suicide of more than one stone, as a single stone changes
+    /// nothing but the turn
+    fn allows_suicide(&self, stones: usize) -> bool {
+        stones > 1
+    }
+
+    fn ko_rule(&self) -> KoRule {
+        KoRule::SituationalSuperko
+    }
+
+    fn scoring(&self) -> Scoring {
+        Scoring::Area
+    }
+
+    fn komi(&self) -> f64 {
+        8.0
+    }
+
+    fn handicap_compensation(&self, handicap: usize) -> f64 {
+        handicap as f64
+    }
+}
+
+/// The rules of the New Zealand Go Society.
+#[derive(Debug)]
+pub struct NewZealand;
+
+impl Ruleset for NewZealand {
+    fn name(&self) -> &'static str {
+        "New Zealand"
+    }
+
+    fn sgf_name(&self) -> &'static str {
+        "NZ"
+    }
+
+    fn allows_suicide(&self, _stones: usize) -> bool {
+        true
+    }
+
+    fn ko_rule(&self) -> KoRule {
+        KoRule::SituationalSuperko
+    }
+
+    fn scoring(&self) -> Scoring {
+        Scoring::Area
+    }
+
+    fn komi(&self) -> f64 {
+        7.0
+    }
+
+    fn handicap_compensation(&self, _handicap: usize) -> f64 {
+        0.0
+    }
+}
+
+/// Capture Go, also known as Atari Go, a teaching game where whoever
+/// captures first wins.
+#[derive(Debug)]
+pub struct CaptureGo;
+
+impl Ruleset for CaptureGo {
+    fn name(&self) -> &'static str {
+        "Capture Go"
+    }
+
+    /// Not a standard value, SGF has none for this variant
+    fn sgf_name(&self) -> &'static str {
+        "Capture"
+    }
+
+    fn allows_suicide(&self, _stones: usize) -> bool {
+        false
+    }
+
+    fn ko_rule(&self) -> KoRule {
+        KoRule::Simple
+    }
+
+    /// Only used when both players pass before anything is captured
+    fn scoring(&self) -> Scoring {
+        Scoring::Area
+    }
+
+    fn komi(&self) -> f64 {
+        0.0
+    }
+
+    fn handicap_compensation(&self, _handicap: usize) -> f64 {
+        0.0
+    }
+
+    fn capture_goal(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+/// Every ruleset, the first being the default of new games.
+pub const ALL: [&dyn Ruleset; 6] = [&Japanese, &Chinese, &Aga, &Ing, &NewZealand, &CaptureGo];
+
+/// The ruleset games are played under unless another one is picked.
+pub const DEFAULT: &dyn Ruleset = ALL[0];
+
+/// Finds the ruleset of an SGF `RU` value. Names are compared ignoring
+/// case, and the full names are accepted as well.
+pub fn from_sgf_name(name: &str) -> Option<&'static dyn Ruleset> {
+    let name = name.trim();
+    ALL.into_iter().find(|rules| {
+        rules.sgf_name().eq_ignore_ascii_case(name) || rules.name().eq_ignore_ascii_case(name)
+    })
+}
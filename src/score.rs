@@ -0,0 +1,130 @@
+use crate::game::{GoPosition, Player};
+use crate::rules::Scoring;
+
+/// Points given to white for moving second under the default rules, see
+/// `rules::DEFAULT`
+pub const KOMI: f64 = 6.5;
+
+/// The area of the board each player controls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub black: usize,
+    pub white: usize,
+}
+
+/// What the score of a counted game is made of, see `GoGame::breakdown`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Breakdown {
+    pub scoring: Scoring,
+    /// The empty points each player surrounds, see `Score::territory_owners`
+    pub territory: Score,
+    /// The stones each player captured, counting the dead stones
+    pub captures: Score,
+    /// The living stones each player has on the board
+    pub stones: Score,
+    /// The points given to white
+    pub komi: f64,
+}
+
+impl Breakdown {
+    /// The points each player has before komi: territory plus captures or
+    /// plus stones, depending on `scoring`.
+    pub fn points(&self) -> Score {
+        let counted = match self.scoring {
+            Scoring::Territory => self.captures,
+            Scoring::Area => self.stones,
+        };
+        Score {
+            black: self.territory.black + counted.black,
+            white: self.territory.white + counted.white,
+        }
+    }
+}
+
+impl Score {
+    /// Counts the area score of `position`: every stone on the board, plus
+    /// every empty region that borders stones of only one color. All stones
+    /// are taken to be alive, so dead stones have to be captured before the
+    /// game ends.
+    pub fn area(position: &GoPosition) -> Self {
+        let territory = Score::empty_regions(position);
+        let stones = |player| position.iter().filter(|stone| **stone == player).count();
+        Score {
+            black: territory.black + stones(Player::Black),
+            white: territory.white + stones(Player::White),
+        }
+    }
+
+    /// Counts the territory score of `position`: every empty region that
+    /// borders stones of only one color, plus the stones each player
+    /// captured, given by `captures`. As with `area`, dead stones have to be
+    /// captured before the game ends.
+    pub fn territory(position: &GoPosition, captures: Score) -> Self {
+        let territory = Score::empty_regions(position);
+        Score {
+            black: territory.black + captures.black,
+            white: territory.white + captures.white,
+        }
+    }
+
+    /// Counts the empty points in regions that border stones of only one
+    /// color.
+    fn empty_regions(position: &GoPosition) -> Self {
+        let owners = Score::territory_owners(position);
+        let points = |player| owners.iter().filter(|owner| **owner == player).count();
+        Score {
+            black: points(Player::Black),
+            white: points(Player::White),
+        }
+    }
+
+    /// The player whose territory each point of `position` is, the one
+    /// whose stones alone border the empty region the point is in. Stones
+    /// and regions bordering more than one color or none are
+    /// `Player::None`. These are exactly the points `area` and `territory`
+    /// count as territory.
+    pub fn territory_owners(position: &GoPosition) -> Vec<Player> {
+        let mut owners = vec![Player::None; position.len()];
+        let mut visited = vec![false; position.len()];
+        for start in 0..position.len() {
+            if position[start] != Player::None || visited[start] {
+                continue;
+            }
+            // Flood fill the empty region, noting whose stones border it
+            let (mut region, mut border, mut shared) = (vec![], Player::None, false);
+            let mut stack = vec![start];
+            visited[start] = true;
+            while let Some(index) = stack.pop() {
+                region.push(index);
+                for side in position.neighbors(index) {
+                    match position[side] {
+                        Player::None if !visited[side] => {
+                            visited[side] = true;
+                            stack.push(side);
+                        }
+                        Player::None => {}
+                        color if border == Player::None => border = color,
+                        color => shared |= color != border,
+                    }
+                }
+            }
+            if border == Player::None || shared {
+                continue;
+            }
+            for index in region {
+                owners[index] = border;
+            }
+        }
+        owners
+    }
+
+    /// How many points `player` is ahead by once white is given `komi`,
+    /// negative when behind.
+    pub fn margin(&self, player: Player, komi: f64) -> f64 {
+        let black_lead = self.black as f64 - self.white as f64 - komi;
+        match player {
+            Player::White => -black_lead,
+            _ => black_lead,
+        }
+    }
+}
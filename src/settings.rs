@@ -0,0 +1,240 @@
+use gloo_storage::{LocalStorage, Storage};
+use gloo_utils::document;
+use go_rs::handicap::HandicapTable;
+use go_rs::rules::{Aga, CaptureGo, Chinese, Ing, Japanese, NewZealand, Ruleset};
+use serde::{Deserialize, Serialize};
+
+use crate::theme::{BoardTheme, StoneStyle, UiTheme};
+
+/// The local storage key the settings are kept under
+const STORAGE_KEY: &str = "go-rs-settings";
+
+/// The language of the interface.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+}
+
+impl Language {
+    pub const ALL: [Language; 1] = [Language::English];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+        }
+    }
+
+    /// The BCP 47 tag of the language, as used by the `lang` attribute.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Language::English => "en",
+        }
+    }
+
+    /// Marks the page as being in this language.
+    pub fn apply(self) {
+        if let Some(root) = document().document_element() {
+            let _ = root.set_attribute("lang", self.tag());
+        }
+    }
+}
+
+/// How a move is played once a point is picked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfirmMode {
+    /// Play on the first click or tap
+    Direct,
+    /// Preview the move, and play it when the same point is picked again
+    #[default]
+    SecondClick,
+    /// Preview the move, and play it with a button next to the board
+    Button,
+}
+
+impl ConfirmMode {
+    pub const ALL: [ConfirmMode; 3] = [
+        ConfirmMode::Direct,
+        ConfirmMode::SecondClick,
+        ConfirmMode::Button,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfirmMode::Direct => "Play directly",
+            ConfirmMode::SecondClick => "Confirm with a second click",
+            ConfirmMode::Button => "Confirm with a button",
+        }
+    }
+}
+
+/// How many hints can be asked for in each game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HintLimit {
+    Off,
+    One,
+    #[default]
+    Three,
+    Five,
+    Unlimited,
+}
+
+impl HintLimit {
+    pub const ALL: [HintLimit; 5] = [
+        HintLimit::Off,
+        HintLimit::One,
+        HintLimit::Three,
+        HintLimit::Five,
+        HintLimit::Unlimited,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HintLimit::Off => "No hints",
+            HintLimit::One => "1 per game",
+            HintLimit::Three => "3 per game",
+            HintLimit::Five => "5 per game",
+            HintLimit::Unlimited => "Unlimited",
+        }
+    }
+
+    /// How many hints are left after `used` were given, `None` when there
+    /// is no limit.
+    pub fn left(self, used: usize) -> Option<usize> {
+        let limit = match self {
+            HintLimit::Off => 0,
+            HintLimit::One => 1,
+            HintLimit::Three => 3,
+            HintLimit::Five => 5,
+            HintLimit::Unlimited => return None,
+        };
+        Some(limit - used.min(limit))
+    }
+}
+
+/// The rules new games are played under, see `go_rs::rules`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rules {
+    #[default]
+    Japanese,
+    Chinese,
+    Aga,
+    Ing,
+    NewZealand,
+    CaptureGo,
+}
+
+impl Rules {
+    pub const ALL: [Rules; 6] = [
+        Rules::Japanese,
+        Rules::Chinese,
+        Rules::Aga,
+        Rules::Ing,
+        Rules::NewZealand,
+        Rules::CaptureGo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        self.ruleset().name()
+    }
+
+    pub fn ruleset(self) -> &'static dyn Ruleset {
+        match self {
+            Rules::Japanese => &Japanese,
+            Rules::Chinese => &Chinese,
+            Rules::Aga => &Aga,
+            Rules::Ing => &Ing,
+            Rules::NewZealand => &NewZealand,
+            Rules::CaptureGo => &CaptureGo,
+        }
+    }
+}
+
+/// A kind of game that can be started from the start screen in one tap.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub rules: Rules,
+    pub handicap: usize,
+    /// The main time of each player in minutes, `None` for untimed games
+    pub minutes: Option<u32>,
+}
+
+/// The presets offered on the start screen, in the order shown.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Presets(pub Vec<Preset>);
+
+impl Default for Presets {
+    fn default() -> Self {
+        Presets(vec![
+            Preset {
+                name: String::from("Blitz 9x9"),
+                width: 9,
+                height: 9,
+                rules: Rules::Japanese,
+                handicap: 0,
+                minutes: Some(5),
+            },
+            Preset {
+                name: String::from("Standard 19x19"),
+                width: 19,
+                height: 19,
+                rules: Rules::Japanese,
+                handicap: 0,
+                minutes: None,
+            },
+            Preset {
+                name: String::from("Teaching 13x13"),
+                width: 13,
+                height: 13,
+                rules: Rules::Japanese,
+                handicap: 0,
+                minutes: None,
+            },
+        ])
+    }
+}
+
+/// Preferences that are kept between visits.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Turns off all sound effects
+    pub muted: bool,
+    pub board_theme: BoardTheme,
+    pub stone_style: StoneStyle,
+    pub ui_theme: UiTheme,
+    /// Shows letters and numbers along the edges of the board
+    pub show_coordinates: bool,
+    /// Draws the board and stones in plain colors that stand apart more,
+    /// with outlined stones and stronger previews
+    pub high_contrast: bool,
+    /// Puts a dot on the white stones, so the colors can be told apart by
+    /// more than their shade
+    pub stone_symbols: bool,
+    pub confirm_mode: ConfirmMode,
+    /// For two players sharing one device: the board turns to face whoever
+    /// is to move, and passing or resigning takes a second tap
+    pub hot_seat: bool,
+    pub hints: HintLimit,
+    pub language: Language,
+    pub rules: Rules,
+    /// How handicaps are suggested from the ranks of the players
+    pub handicap_table: HandicapTable,
+    pub presets: Presets,
+}
+
+impl Settings {
+    /// Reads the stored settings, falling back to the defaults when there are
+    /// none or they can't be read.
+    pub fn load() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        // Not being able to store them only means they are lost on reload
+        let _ = LocalStorage::set(STORAGE_KEY, self);
+    }
+}
@@ -0,0 +1,143 @@
+use go_rs::handicap::HandicapTable;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::settings::{ConfirmMode, HintLimit, Language, Rules, Settings};
+use crate::theme::{BoardTheme, StoneStyle, UiTheme};
+
+#[derive(Properties, PartialEq)]
+pub struct SettingsPanelProps {
+    /// Called with the settings after every change
+    pub onchange: Callback<Settings>,
+}
+
+/// Lets the user change the settings provided by the nearest
+/// `ContextProvider<Settings>`. The panel keeps no state of its own, each
+/// change is handed up as a whole new `Settings`.
+#[function_component]
+pub fn SettingsPanel(props: &SettingsPanelProps) -> Html {
+    let settings = use_context::<Settings>().unwrap_or_default();
+    let onchange = &props.onchange;
+
+    html! {
+        <div class="settings">
+            { select(
+                "Board",
+                &BoardTheme::ALL,
+                settings.board_theme,
+                BoardTheme::name,
+                edit(&settings, onchange, |settings, theme| settings.board_theme = theme),
+            ) }
+            { select(
+                "Stones",
+                &StoneStyle::ALL,
+                settings.stone_style,
+                StoneStyle::name,
+                edit(&settings, onchange, |settings, style| settings.stone_style = style),
+            ) }
+            { select(
+                "Colors",
+                &UiTheme::ALL,
+                settings.ui_theme,
+                UiTheme::name,
+                edit(&settings, onchange, |settings, theme| settings.ui_theme = theme),
+            ) }
+            { select(
+                "Moves",
+                &ConfirmMode::ALL,
+                settings.confirm_mode,
+                ConfirmMode::name,
+                edit(&settings, onchange, |settings, mode| settings.confirm_mode = mode),
+            ) }
+            { select(
+                "Hints",
+                &HintLimit::ALL,
+                settings.hints,
+                HintLimit::name,
+                edit(&settings, onchange, |settings, hints| settings.hints = hints),
+            ) }
+            { select(
+                "Rules for new games",
+                &Rules::ALL,
+                settings.rules,
+                Rules::name,
+                edit(&settings, onchange, |settings, rules| settings.rules = rules),
+            ) }
+            { select(
+                "Handicaps",
+                &HandicapTable::ALL,
+                settings.handicap_table,
+                HandicapTable::name,
+                edit(&settings, onchange, |settings, table| settings.handicap_table = table),
+            ) }
+            { select(
+                "Language",
+                &Language::ALL,
+                settings.language,
+                Language::name,
+                edit(&settings, onchange, |settings, language| settings.language = language),
+            ) }
+            <button onclick={ edit(&settings, onchange, |settings, _: MouseEvent| settings.muted = !settings.muted) }>
+                { if settings.muted { "Sound: off" } else { "Sound: on" } }
+            </button>
+            <button onclick={ edit(&settings, onchange, |settings, _: MouseEvent| settings.show_coordinates = !settings.show_coordinates) }>
+                { if settings.show_coordinates { "Coordinates: on" } else { "Coordinates: off" } }
+            </button>
+            <button onclick={ edit(&settings, onchange, |settings, _: MouseEvent| settings.high_contrast = !settings.high_contrast) }>
+                { if settings.high_contrast { "High contrast: on" } else { "High contrast: off" } }
+            </button>
+            <button onclick={ edit(&settings, onchange, |settings, _: MouseEvent| settings.stone_symbols = !settings.stone_symbols) }>
+                { if settings.stone_symbols { "Symbols on stones: on" } else { "Symbols on stones: off" } }
+            </button>
+            <button onclick={ edit(&settings, onchange, |settings, _: MouseEvent| settings.hot_seat = !settings.hot_seat) }>
+                { if settings.hot_seat { "Hot-seat: on" } else { "Hot-seat: off" } }
+            </button>
+        </div>
+    }
+}
+
+/// Makes a callback that applies `edit` to a copy of the current settings
+/// and hands the result up.
+fn edit<T: 'static>(
+    settings: &Settings,
+    onchange: &Callback<Settings>,
+    edit: fn(&mut Settings, T),
+) -> Callback<T> {
+    let settings = settings.clone();
+    let onchange = onchange.clone();
+    Callback::from(move |value| {
+        let mut settings = settings.clone();
+        edit(&mut settings, value);
+        onchange.emit(settings);
+    })
+}
+
+/// Renders a labelled drop-down of `options`, calling `onchange` with the
+/// picked one.
+fn select<T: Copy + PartialEq + 'static>(
+    label: &str,
+    options: &'static [T],
+    selected: T,
+    name: fn(T) -> &'static str,
+    onchange: Callback<T>,
+) -> Html {
+    let change_cb = Callback::from(move |e: Event| {
+        let index = e
+            .target_unchecked_into::<HtmlSelectElement>()
+            .selected_index();
+        if let Some(option) = usize::try_from(index).ok().and_then(|i| options.get(i)) {
+            onchange.emit(*option);
+        }
+    });
+    let options = options.iter().map(|option| {
+        html! {
+            <option selected={ *option == selected }>{ name(*option) }</option>
+        }
+    });
+    html! {
+        <label>
+            { label }
+            <select onchange={ change_cb }>{ for options }</select>
+        </label>
+    }
+}
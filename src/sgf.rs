@@ -0,0 +1,588 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::annotation::{Annotation, Evaluation};
+use crate::game::{GoGame, GoPosition, MoveKind, Player, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use crate::markup::{Mark, Markup};
+use crate::rules;
+
+/// Serializes a game into an SGF (FF[4]) game record, including any set up
+/// stones of the starting position and the marks and comments of each
+/// position. SGF has no color for a third player, so three-player games
+/// are refused.
+pub fn write_sgf(game: &GoGame) -> Result<String, SgfError> {
+    if game.colors() > 2 {
+        return Err(SgfError::ThirdColor);
+    }
+    let initial = game.initial_position();
+    let size = match initial.is_square() {
+        true => initial.width().to_string(),
+        false => format!("{}:{}", initial.width(), initial.height()),
+    };
+    let mut root = format!("GM[1]FF[4]CA[UTF-8]AP[go-rs]SZ[{}]", size);
+
+    // Set up stones
+    for (property, color) in [("AB", Player::Black), ("AW", Player::White)] {
+        let points: Vec<String> = initial
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| **player == color)
+            .map(|(index, _)| {
+                let (x, y) = initial.index_to_coord(index);
+                format!("[{}]", sgf_point(x, y))
+            })
+            .collect();
+        if !points.is_empty() {
+            root += property;
+            root += &points.concat();
+        }
+    }
+    if initial.iter().any(|player| *player != Player::None) {
+        root += &format!("PL[{}]", sgf_color(game.first_player));
+    }
+
+    // Game info
+    let info = &game.info;
+    for (property, value) in [
+        ("PB", &info.black_name),
+        ("BR", &info.black_rank),
+        ("PW", &info.white_name),
+        ("WR", &info.white_rank),
+    ] {
+        if !value.is_empty() {
+            root += &format!("{}[{}]", property, sgf_text(value));
+        }
+    }
+    root += &format!("RU[{}]KM[{}]", game.rules().sgf_name(), info.komi);
+    if info.handicap > 0 {
+        root += &format!("HA[{}]", info.handicap);
+    }
+    if let Some(main_time) = info.main_time {
+        root += &format!("TM[{}]", main_time);
+    }
+    if let Some(result) = game.result() {
+        root += &format!("RE[{}]", sgf_text(&result));
+    }
+    root += &sgf_markup(&game.markup_at(0), initial.width());
+    root += &sgf_annotation(&game.annotation_at(0));
+
+    let mut moves = String::new();
+    let time_left = game.time_left();
+    for (i, m) in game.history().iter().enumerate() {
+        match m.kind {
+            MoveKind::Play(square) => {
+                moves += &format!(
+                    ";{}[{}]",
+                    sgf_color(m.player),
+                    sgf_point(square.x, square.y)
+                );
+            }
+            MoveKind::Pass => {
+                moves += &format!(";{}[]", sgf_color(m.player));
+            }
+            // Written as the result
+            MoveKind::Resign => continue,
+        }
+        // Records only know the time left, so thinking times are written
+        // against the main time
+        if let Some(left) = time_left[i] {
+            moves += &format!("{}L[{:.1}]", sgf_color(m.player), left);
+        }
+        moves += &sgf_markup(&game.markup_at(i + 1), initial.width());
+        moves += &sgf_annotation(&game.annotation_at(i + 1));
+    }
+
+    Ok(format!("(;{}{})", root, moves))
+}
+
+/// The markup properties of a node: TR, SQ, CR and MA list the points with
+/// each shape, LB pairs points with their label. `width` is the number of
+/// points across the board.
+fn sgf_markup(markup: &Markup, width: usize) -> String {
+    let point = |index: usize| sgf_point(index % width, index / width);
+    let mut properties = String::new();
+    for (property, shape) in [
+        ("TR", Mark::Triangle),
+        ("SQ", Mark::Square),
+        ("CR", Mark::Circle),
+        ("MA", Mark::Cross),
+    ] {
+        let points: Vec<String> = markup
+            .iter()
+            .filter(|(_, mark)| **mark == shape)
+            .map(|(index, _)| format!("[{}]", point(index)))
+            .collect();
+        if !points.is_empty() {
+            properties += property;
+            properties += &points.concat();
+        }
+    }
+    let labels: Vec<String> = markup
+        .iter()
+        .filter_map(|(index, mark)| match mark {
+            Mark::Label(text) => Some(format!("[{}:{}]", point(index), sgf_text(text))),
+            _ => None,
+        })
+        .collect();
+    if !labels.is_empty() {
+        properties += "LB";
+        properties += &labels.concat();
+    }
+    properties
+}
+
+/// The comment (C) and move evaluation (TE, BM or IT) of a node.
+fn sgf_annotation(annotation: &Annotation) -> String {
+    let mut properties = String::new();
+    if !annotation.comment.is_empty() {
+        properties += &format!("C[{}]", sgf_text(&annotation.comment));
+    }
+    properties += match annotation.evaluation {
+        Some(Evaluation::Good) => "TE[1]",
+        Some(Evaluation::Mistake) => "BM[1]",
+        Some(Evaluation::Interesting) => "IT[]",
+        None => "",
+    };
+    properties
+}
+
+/// The color of `player`, who is black or white as `write_sgf` refuses
+/// games with a third color.
+fn sgf_color(player: Player) -> &'static str {
+    match player {
+        Player::White => "W",
+        _ => "B",
+    }
+}
+
+/// Escapes the characters that would end or break a property value.
+fn sgf_text(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+/// SGF points are a pair of lowercase letters, starting from `a` in the top
+/// left corner.
+pub fn sgf_point(x: usize, y: usize) -> String {
+    format!("{}{}", (b'a' + x as u8) as char, (b'a' + y as u8) as char)
+}
+
+/// Why an SGF record couldn't be read or written.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SgfError {
+    /// The text isn't valid SGF
+    Syntax(String),
+    /// The board size is missing or unsupported
+    BoardSize,
+    /// A move or set up stone is off the board
+    BadPoint(String),
+    /// A move in the record is illegal, the field is the move number
+    IllegalMove(usize),
+    /// The game has a third color, which records can't hold
+    ThirdColor,
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SgfError::Syntax(reason) => write!(f, "Invalid SGF: {}", reason),
+            SgfError::BoardSize => write!(f, "Unsupported board size"),
+            SgfError::BadPoint(point) => write!(f, "Point \"{}\" is off the board", point),
+            SgfError::IllegalMove(number) => write!(f, "Move {} is illegal", number),
+            SgfError::ThirdColor => write!(f, "SGF can't record games of three players"),
+        }
+    }
+}
+
+/// A node of an SGF record, as a list of properties and their values.
+pub(crate) type Node = Vec<(String, Vec<String>)>;
+
+/// A game tree of an SGF record: a sequence of nodes followed by the
+/// variations that branch off after the last one.
+#[derive(Debug, Default)]
+pub(crate) struct GameTree {
+    pub nodes: Vec<Node>,
+    pub variations: Vec<GameTree>,
+}
+
+impl GameTree {
+    /// The nodes of the tree, following the first variation at every branch.
+    fn main_line(self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut tree = Some(self);
+        while let Some(GameTree {
+            nodes: more,
+            variations,
+        }) = tree
+        {
+            nodes.extend(more);
+            tree = variations.into_iter().next();
+        }
+        nodes
+    }
+}
+
+/// Reads an SGF game record into a game, following the main line when the
+/// record has variations.
+pub fn read_sgf(text: &str) -> Result<GoGame, SgfError> {
+    let nodes = read_tree(text)?.main_line();
+    let root = nodes
+        .first()
+        .ok_or_else(|| SgfError::Syntax(String::from("the record is empty")))?;
+    let mut game = read_root(root, &nodes)?;
+    let size = (game.width(), game.height());
+
+    let mut move_number = 0;
+    // The time each player had left after their last move, in seconds
+    let mut time_left = [game.info.main_time; 2];
+    for node in nodes.iter() {
+        for (prop, values) in node {
+            let (color, left) = match prop.as_str() {
+                "B" => (Player::Black, &mut time_left[0]),
+                "W" => (Player::White, &mut time_left[1]),
+                _ => continue,
+            };
+            move_number += 1;
+            // Moves out of turn are recorded by switching the player first
+            game.next_player = color;
+            let value = values.first().map(String::as_str).unwrap_or("");
+            match parse_point(value, size)? {
+                Some((x, y)) => game
+                    .play_move(x, y)
+                    .map_err(|_| SgfError::IllegalMove(move_number))?,
+                None => game.pass(),
+            }
+            // The time a move took is what it took off the time left. Byo-yomi
+            // periods starting over give no time for the move.
+            let property = format!("{}L", sgf_color(color));
+            let now_left = property_value(node, &property);
+            if let (Some(before), Some(after)) = (*left, now_left) {
+                if after <= before {
+                    game.time_last_move((before - after) * 1000.0);
+                }
+            }
+            if now_left.is_some() {
+                *left = now_left;
+            }
+        }
+        // The marks and comments of a node belong to the position after its
+        // move
+        let markup = read_markup(node, size)?;
+        if !markup.is_empty() {
+            game.set_markup_at(move_number, markup);
+        }
+        let annotation = read_annotation(node);
+        if !annotation.is_empty() {
+            game.set_annotation_at(move_number, annotation);
+        }
+    }
+
+    if let Some(result) = property(root, "RE") {
+        game.info.recorded_result = Some(result.to_owned());
+        if result.ends_with("+R") || result.ends_with("+Resign") {
+            // The loser resigns on their turn
+            game.next_player = if result.starts_with('B') {
+                Player::White
+            } else {
+                Player::Black
+            };
+            game.resign();
+        }
+    }
+    Ok(game)
+}
+
+/// Parses the first game tree of an SGF record, with all its variations.
+pub(crate) fn read_tree(text: &str) -> Result<GameTree, SgfError> {
+    Parser {
+        chars: text.chars().peekable(),
+    }
+    .record()
+}
+
+/// Reads the board size, set up stones, player to move first and game info
+/// of a root node into a game without moves. `nodes` are searched for the
+/// first move when the root doesn't say who moves first.
+pub(crate) fn read_root(root: &Node, nodes: &[Node]) -> Result<GoGame, SgfError> {
+    // Rectangular boards are written as `SZ[width:height]`
+    let (width, height) = match property(root, "SZ") {
+        Some(size) => {
+            let side = |side: &str| side.trim().parse().map_err(|_| SgfError::BoardSize);
+            match size.split_once(':') {
+                Some((width, height)) => (side(width)?, side(height)?),
+                None => (side(size)?, side(size)?),
+            }
+        }
+        None => (19, 19),
+    };
+    let valid = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+    if !valid.contains(&width) || !valid.contains(&height) {
+        return Err(SgfError::BoardSize);
+    }
+
+    // Set up stones and who moves first. Points can be given as rectangles,
+    // `AB[aa:cc]`, and AE clears them again.
+    let mut setup = vec![Player::None; width * height];
+    for (id, color) in [
+        ("AB", Player::Black),
+        ("AW", Player::White),
+        ("AE", Player::None),
+    ] {
+        for (_, values) in root.iter().filter(|(prop, _)| prop == id) {
+            for value in values {
+                for (x, y) in parse_point_list(value, (width, height))? {
+                    setup[y * width + x] = color;
+                }
+            }
+        }
+    }
+    let first_player = match property(root, "PL") {
+        Some("W") => Player::White,
+        _ => nodes
+            .iter()
+            .find_map(|node| {
+                node.iter().find_map(|(prop, _)| match prop.as_str() {
+                    "B" => Some(Player::Black),
+                    "W" => Some(Player::White),
+                    _ => None,
+                })
+            })
+            .unwrap_or(Player::Black),
+    };
+    let mut game =
+        GoGame::from_position(GoPosition::from_stones(width, height, &setup), first_player);
+    // Unknown rules are played as the default ones
+    if let Some(rules) = property(root, "RU").and_then(rules::from_sgf_name) {
+        game = game.with_rules(rules);
+    }
+    let text = |id| property(root, id).unwrap_or_default().to_owned();
+    game.info.black_name = text("PB");
+    game.info.white_name = text("PW");
+    game.info.black_rank = text("BR");
+    game.info.white_rank = text("WR");
+    if let Some(komi) = property(root, "KM").and_then(|komi| komi.trim().parse().ok()) {
+        game.info.komi = komi;
+    }
+    if let Some(handicap) = property(root, "HA").and_then(|handicap| handicap.trim().parse().ok()) {
+        game.info.handicap = handicap;
+    }
+    game.info.main_time = property_value(root, "TM");
+
+    Ok(game)
+}
+
+/// The first value of `id` in `node`.
+pub(crate) fn property<'a>(node: &'a Node, id: &str) -> Option<&'a str> {
+    node.iter()
+        .find(|(prop, _)| prop == id)
+        .and_then(|(_, values)| values.first())
+        .map(String::as_str)
+}
+
+/// The first value of `id` in `node` as a number.
+fn property_value(node: &Node, id: &str) -> Option<f64> {
+    property(node, id).and_then(|value| value.trim().parse().ok())
+}
+
+/// Reads the markup properties of a node, see `sgf_markup`, on a board of
+/// `size` as width and height.
+pub(crate) fn read_markup(node: &Node, size: (usize, usize)) -> Result<Markup, SgfError> {
+    let mut markup = Markup::new();
+    for (prop, values) in node {
+        let shape = match prop.as_str() {
+            "TR" => Mark::Triangle,
+            "SQ" => Mark::Square,
+            "CR" => Mark::Circle,
+            "MA" => Mark::Cross,
+            "LB" => {
+                for value in values {
+                    let (point, text) = value
+                        .split_once(':')
+                        .ok_or_else(|| SgfError::BadPoint(value.clone()))?;
+                    let (x, y) = parse_point(point, size)?
+                        .ok_or_else(|| SgfError::BadPoint(value.clone()))?;
+                    markup.set(y * size.0 + x, Some(Mark::Label(text.to_owned())));
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        for value in values {
+            for (x, y) in parse_point_list(value, size)? {
+                markup.set(y * size.0 + x, Some(shape.clone()));
+            }
+        }
+    }
+    Ok(markup)
+}
+
+/// Reads the comment and move evaluation of a node, see `sgf_annotation`.
+/// Evaluations other than good, bad and interesting are left out.
+fn read_annotation(node: &Node) -> Annotation {
+    let evaluation = node.iter().find_map(|(prop, _)| match prop.as_str() {
+        "TE" => Some(Evaluation::Good),
+        "BM" => Some(Evaluation::Mistake),
+        "IT" => Some(Evaluation::Interesting),
+        _ => None,
+    });
+    Annotation {
+        comment: property(node, "C").unwrap_or_default().to_owned(),
+        evaluation,
+    }
+}
+
+/// Parses a value of a list of points, which is either a single point or a
+/// rectangle given by two corners like `aa:cc`.
+fn parse_point_list(value: &str, size: (usize, usize)) -> Result<Vec<(usize, usize)>, SgfError> {
+    let corner =
+        |point: &str| parse_point(point, size)?.ok_or_else(|| SgfError::BadPoint(value.to_owned()));
+    let ((x1, y1), (x2, y2)) = match value.split_once(':') {
+        Some((from, to)) => (corner(from)?, corner(to)?),
+        None => (corner(value)?, corner(value)?),
+    };
+    Ok((y1.min(y2)..=y1.max(y2))
+        .flat_map(|y| (x1.min(x2)..=x1.max(x2)).map(move |x| (x, y)))
+        .collect())
+}
+
+/// Parses an SGF point on a board of `size` as width and height, returning
+/// `None` for a pass (an empty value, or `tt` on boards up to 19x19).
+pub(crate) fn parse_point(
+    value: &str,
+    (width, height): (usize, usize),
+) -> Result<Option<(usize, usize)>, SgfError> {
+    if value.is_empty() || (value == "tt" && width.max(height) <= 19) {
+        return Ok(None);
+    }
+    let mut chars = value.chars();
+    let coord = |c: Option<char>| match c {
+        Some(c @ 'a'..='z') => Some(c as usize - 'a' as usize),
+        _ => None,
+    };
+    match (coord(chars.next()), coord(chars.next()), chars.next()) {
+        (Some(x), Some(y), None) if x < width && y < height => Ok(Some((x, y))),
+        _ => Err(SgfError::BadPoint(value.to_owned())),
+    }
+}
+
+/// How deeply variations may be nested. Parsing doesn't recurse, but
+/// walking and dropping the tree does, one level at a time.
+const MAX_DEPTH: usize = 10_000;
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Parses the first game tree of the record.
+    fn record(&mut self) -> Result<GameTree, SgfError> {
+        self.skip_whitespace();
+        // Skip anything before the record starts
+        while self.chars.peek().is_some_and(|c| *c != '(') {
+            self.chars.next();
+        }
+        self.game_tree()
+    }
+
+    /// Parses a game tree and its variations, keeping the trees still open
+    /// on a stack of their own rather than recursing.
+    fn game_tree(&mut self) -> Result<GameTree, SgfError> {
+        self.expect('(')?;
+        // Innermost last
+        let mut open = vec![GameTree::default()];
+        loop {
+            self.skip_whitespace();
+            let tree = open.last_mut().expect("the outermost tree is open");
+            match self.chars.peek() {
+                Some(';') if tree.variations.is_empty() => {
+                    self.chars.next();
+                    tree.nodes.push(self.node()?);
+                }
+                Some('(') => {
+                    if open.len() == MAX_DEPTH {
+                        return Err(SgfError::Syntax(String::from(
+                            "variations nested too deeply",
+                        )));
+                    }
+                    self.chars.next();
+                    open.push(GameTree::default());
+                }
+                Some(')') => {
+                    self.chars.next();
+                    let tree = open.pop().expect("the outermost tree is open");
+                    match open.last_mut() {
+                        Some(parent) => parent.variations.push(tree),
+                        None => return Ok(tree),
+                    }
+                }
+                Some(c) => return Err(SgfError::Syntax(format!("unexpected '{}'", c))),
+                None => return Err(SgfError::Syntax(String::from("unexpected end"))),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, SgfError> {
+        let mut node = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some(c) if c.is_ascii_uppercase() => {
+                    let mut id = String::new();
+                    while let Some(c) = self.chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                        // Old SGF versions allow lowercase letters in ids
+                        if c.is_ascii_uppercase() {
+                            id.push(*c);
+                        }
+                        self.chars.next();
+                    }
+                    let mut values = Vec::new();
+                    self.skip_whitespace();
+                    while self.chars.peek() == Some(&'[') {
+                        values.push(self.value()?);
+                        self.skip_whitespace();
+                    }
+                    if values.is_empty() {
+                        return Err(SgfError::Syntax(format!("property {} has no value", id)));
+                    }
+                    node.push((id, values));
+                }
+                _ => return Ok(node),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<String, SgfError> {
+        self.expect('[')?;
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') => {
+                    if let Some(c) = self.chars.next() {
+                        value.push(c);
+                    }
+                }
+                Some(']') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(SgfError::Syntax(String::from("unclosed value"))),
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SgfError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(SgfError::Syntax(format!(
+                "expected '{}' but found '{}'",
+                expected, c
+            ))),
+            None => Err(SgfError::Syntax(format!("expected '{}'", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+}
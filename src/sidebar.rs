@@ -1,23 +1,216 @@
+use gloo_utils::document;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
 use yew::prelude::*;
 
-pub fn make_sidebar(children: Html, shown: bool, hide_callback: Callback<MouseEvent>) -> Html {
-    let mut sidebar_classes = classes!("sidebar");
-    if !shown {
-        sidebar_classes.push("hide-sidebar");
+/// How far the sidebar has to be dragged to the left before letting go
+/// closes it, in pixels
+const SWIPE_CLOSE_DISTANCE: f64 = 80.0;
+/// Elements that can take focus, for keeping it inside the open sidebar
+const FOCUSABLE: &str = "a[href], button, input, select, textarea, [tabindex]:not([tabindex='-1'])";
+
+#[derive(Properties, PartialEq)]
+pub struct SidebarProps {
+    /// Whether the sidebar is shown, the parent decides this in answer to
+    /// `onopen` and `onclose`
+    pub open: bool,
+    /// Shown at the top of the sidebar
+    pub title: AttrValue,
+    /// Called when the user asks for the sidebar with the menu button
+    pub onopen: Callback<()>,
+    /// Called when the user dismisses the sidebar: by the close button, the
+    /// backdrop, the Escape key or swiping it away
+    pub onclose: Callback<()>,
+    /// Keeps the sidebar shown next to the page instead of over it, on
+    /// windows wide enough for both. `open` doesn't matter then.
+    #[prop_or_default]
+    pub docked: bool,
+    /// The sidebar's contents, usually `SidebarSection`s
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// A panel that slides in from the left over the rest of the page, along
+/// with the menu button that opens it, or one always shown on the right
+/// when docked.
+///
+/// While it is open over the page, Tab and Shift+Tab cycle through its
+/// contents only, and focus goes back where it was once it closes.
+#[function_component]
+pub fn Sidebar(props: &SidebarProps) -> Html {
+    let sidebar_ref = use_node_ref();
+    // Where a touch on the sidebar started and where it is now, in client
+    // pixels
+    let drag = use_state_eq(|| None::<(f64, f64)>);
+    // Where the focus was before the sidebar opened
+    let return_focus = use_mut_ref(|| None::<HtmlElement>);
+
+    {
+        let sidebar_ref = sidebar_ref.clone();
+        use_effect_with(props.open && !props.docked, move |open| {
+            if *open {
+                *return_focus.borrow_mut() = document()
+                    .active_element()
+                    .and_then(|element| element.dyn_into::<HtmlElement>().ok());
+                if let Some(first) = focusable(&sidebar_ref).first() {
+                    let _ = first.focus();
+                }
+            } else if let Some(element) = return_focus.borrow_mut().take() {
+                let _ = element.focus();
+            }
+        });
     }
 
+    if props.docked {
+        return html! {
+            <aside class="sidebar sidebar-docked" aria-label={ props.title.clone() }>
+                <header class="sd-header">
+                    <h2>{ &props.title }</h2>
+                </header>
+                { props.children.clone() }
+            </aside>
+        };
+    }
+
+    let mut sidebar_classes = classes!("sidebar");
     let mut back_classes = classes!("sd-back");
-    if !shown {
+    if !props.open {
+        sidebar_classes.push("hide-sidebar");
         back_classes.push("hide-sidebar");
     }
+    // Follow the finger while the sidebar is being swiped to the left
+    let style = match *drag {
+        Some((start, current)) if current < start => format!(
+            "transform: translateX({}px); transition: none;",
+            current - start
+        ),
+        _ => String::new(),
+    };
+
+    let open_cb = props.onopen.reform(|_: MouseEvent| ());
+    let close_cb = props.onclose.reform(|_: MouseEvent| ());
+    let keydown_cb = {
+        let sidebar_ref = sidebar_ref.clone();
+        let onclose = props.onclose.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => {
+                e.prevent_default();
+                onclose.emit(());
+            }
+            "Tab" => trap_focus(&sidebar_ref, &e),
+            _ => {}
+        })
+    };
+    let touchstart_cb = {
+        let drag = drag.clone();
+        Callback::from(move |e: TouchEvent| {
+            if let Some(touch) = e.touches().get(0) {
+                let x = touch.client_x() as f64;
+                drag.set(Some((x, x)));
+            }
+        })
+    };
+    let touchmove_cb = {
+        let drag = drag.clone();
+        Callback::from(move |e: TouchEvent| {
+            if let (Some((start, _)), Some(touch)) = (*drag, e.touches().get(0)) {
+                drag.set(Some((start, touch.client_x() as f64)));
+            }
+        })
+    };
+    let touchend_cb = {
+        let drag = drag.clone();
+        let onclose = props.onclose.clone();
+        Callback::from(move |_: TouchEvent| {
+            if let Some((start, current)) = *drag {
+                if start - current > SWIPE_CLOSE_DISTANCE {
+                    onclose.emit(());
+                }
+            }
+            drag.set(None);
+        })
+    };
 
     html! {
         <>
-            <div class={ sidebar_classes }>
-                { children }
-            </div>
-            <div class={ back_classes } onclick={ hide_callback }>
+            <img class="menu-icon" src="imgs/menu.svg" onclick={ open_cb } />
+            <aside
+                ref={ sidebar_ref }
+                class={ sidebar_classes }
+                style={ style }
+                role="dialog"
+                aria-modal="true"
+                aria-label={ props.title.clone() }
+                aria-hidden={ (!props.open).to_string() }
+                onkeydown={ keydown_cb }
+                ontouchstart={ touchstart_cb }
+                ontouchmove={ touchmove_cb }
+                ontouchend={ touchend_cb.clone() }
+                ontouchcancel={ touchend_cb }
+            >
+                <header class="sd-header">
+                    <h2>{ &props.title }</h2>
+                    <button class="sd-close" aria-label="Close" onclick={ close_cb.clone() }>
+                        { "✕" }
+                    </button>
+                </header>
+                { props.children.clone() }
+            </aside>
+            <div class={ back_classes } onclick={ close_cb }>
             </div>
         </>
     }
 }
+
+/// The elements in the sidebar that can take focus, in tab order.
+fn focusable(sidebar_ref: &NodeRef) -> Vec<HtmlElement> {
+    let sidebar = match sidebar_ref.cast::<Element>() {
+        Some(sidebar) => sidebar,
+        None => return Vec::new(),
+    };
+    let nodes = match sidebar.query_selector_all(FOCUSABLE) {
+        Ok(nodes) => nodes,
+        Err(_) => return Vec::new(),
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i))
+        .filter_map(|node| node.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
+
+/// Wraps Tab from the last element around to the first, and Shift+Tab from
+/// the first to the last, so focus can't leave the sidebar.
+fn trap_focus(sidebar_ref: &NodeRef, e: &KeyboardEvent) {
+    let focusable = focusable(sidebar_ref);
+    let (first, last) = match (focusable.first(), focusable.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return,
+    };
+    let active = document().active_element();
+    let is_active = |element: &HtmlElement| active.as_ref() == Some(element.as_ref());
+    if e.shift_key() && is_active(first) {
+        e.prevent_default();
+        let _ = last.focus();
+    } else if !e.shift_key() && is_active(last) {
+        e.prevent_default();
+        let _ = first.focus();
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SidebarSectionProps {
+    pub title: AttrValue,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// A titled part of the sidebar.
+#[function_component]
+pub fn SidebarSection(props: &SidebarSectionProps) -> Html {
+    html! {
+        <section class="sd-section">
+            <h1>{ &props.title }</h1>
+            { props.children.clone() }
+        </section>
+    }
+}
@@ -0,0 +1,117 @@
+//! Numbers gathered over many games, like the games of the library: who
+//! wins with which color and on which board, how long games last and how
+//! far into them the first mistake comes.
+
+use std::collections::BTreeMap;
+
+use crate::annotation::Evaluation;
+use crate::game::{GoGame, Player};
+
+/// Wins counted over some games.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub games: usize,
+    /// Games won by black and by white, the rest have no known winner
+    pub black_wins: usize,
+    pub white_wins: usize,
+}
+
+impl Tally {
+    /// The share of the games with a known winner that `player` won, `None`
+    /// before there are any.
+    pub fn win_rate(&self, player: Player) -> Option<f64> {
+        let decided = self.black_wins + self.white_wins;
+        let wins = match player {
+            Player::Black => self.black_wins,
+            Player::White => self.white_wins,
+            Player::None | Player::Red => return None,
+        };
+        (decided > 0).then(|| wins as f64 / decided as f64)
+    }
+
+    fn add(&mut self, winner: Option<Player>) {
+        self.games += 1;
+        match winner {
+            Some(Player::Black) => self.black_wins += 1,
+            Some(Player::White) => self.white_wins += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Statistics over the games added so far, see `add`.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub overall: Tally,
+    /// The games of each board size, as width and height
+    pub by_size: BTreeMap<(usize, usize), Tally>,
+    /// Moves made over all games, passes and resignations included
+    moves: usize,
+    /// Black's captures minus white's, summed over all games
+    capture_difference: i64,
+    /// The number of the first move marked a mistake, for each game with one
+    first_mistakes: Vec<usize>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Stats::default()
+    }
+
+    pub fn add(&mut self, game: &GoGame) {
+        let winner = winner(game);
+        self.overall.add(winner);
+        self.by_size
+            .entry((game.width(), game.height()))
+            .or_default()
+            .add(winner);
+        self.moves += game.move_count();
+        self.capture_difference +=
+            game.captures(Player::Black) as i64 - game.captures(Player::White) as i64;
+        let first_mistake = (1..=game.move_count())
+            .find(|moves| game.annotation_at(*moves).evaluation == Some(Evaluation::Mistake));
+        self.first_mistakes.extend(first_mistake);
+    }
+
+    /// The average number of moves of a game, `None` without games.
+    pub fn average_length(&self) -> Option<f64> {
+        self.average(self.moves as f64, self.overall.games)
+    }
+
+    /// How many more stones black captured than white in an average game,
+    /// negative when white captured more.
+    pub fn average_capture_difference(&self) -> Option<f64> {
+        self.average(self.capture_difference as f64, self.overall.games)
+    }
+
+    /// The average number of the first move marked a mistake, over the
+    /// games reviewed with one marked.
+    pub fn average_first_mistake(&self) -> Option<f64> {
+        let sum: usize = self.first_mistakes.iter().sum();
+        self.average(sum as f64, self.first_mistakes.len())
+    }
+
+    /// The number of games with a move marked a mistake.
+    pub fn games_with_mistakes(&self) -> usize {
+        self.first_mistakes.len()
+    }
+
+    fn average(&self, sum: f64, count: usize) -> Option<f64> {
+        (count > 0).then(|| sum / count as f64)
+    }
+}
+
+/// The player who won `game`, from its result. `None` for draws, unfinished
+/// games and results that don't say.
+pub fn winner(game: &GoGame) -> Option<Player> {
+    result_winner(&game.result()?)
+}
+
+/// The player a result like `B+R` or `W+3.5` names as the winner.
+pub fn result_winner(result: &str) -> Option<Player> {
+    match result.split_once('+')?.0 {
+        "B" => Some(Player::Black),
+        "W" => Some(Player::White),
+        _ => None,
+    }
+}
@@ -0,0 +1,434 @@
+use go_rs::eye::{eyes, Eye};
+use go_rs::game::{GoPosition, Player};
+use yew::prelude::*;
+
+use crate::board::{render_stones, BLACK, RED, WHITE};
+use crate::settings::Settings;
+
+/// How many of the latest moves are numbered in `MoveNumbers::Last`
+pub const LAST_MOVE_NUMBERS: usize = 10;
+
+/// The colors of the ownership heatmap and of the influence shading, for
+/// points likely to end up black's and white's
+const OWNERSHIP_BLACK: &str = "#2a6fdb";
+const OWNERSHIP_WHITE: &str = "#d8453b";
+
+/// The influence at which a point is shaded the deepest
+const INFLUENCE_FULL: u32 = 16;
+
+/// Which stones are labelled with the number of the move that placed them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveNumbers {
+    Off,
+    /// Only the stones of the last `LAST_MOVE_NUMBERS` moves
+    Last,
+    All,
+}
+
+/// How the stones are drawn, for the blind and one-color exercises. Only
+/// the drawing changes, the game goes on with the real position.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StoneDisplay {
+    #[default]
+    Normal,
+    /// No stones at all: the players keep the position in their heads
+    Blind,
+    /// Every stone black: the players remember whose is whose
+    OneColor,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct StoneLayerProps {
+    pub position: GoPosition,
+    /// The number of the move that placed each stone of `position`, see
+    /// `GoGame::move_numbers`
+    pub move_numbers: Vec<Option<usize>>,
+    /// How many moves led to `position`
+    pub moves: usize,
+    pub numbers: MoveNumbers,
+    pub show_liberties: bool,
+    /// The stones marked dead while scoring, see `GoGame::dead_stones`
+    #[prop_or_default]
+    pub dead: Vec<usize>,
+    /// Whose territory each point is while scoring, see
+    /// `GoGame::territory`, empty when not scoring
+    #[prop_or_default]
+    pub territory: Vec<Player>,
+    /// How likely each point is to end up black's, from -1.0 for white to
+    /// 1.0 for black, see `estimate::ownership`, empty to draw no heatmap
+    #[prop_or_default]
+    pub ownership: Vec<f64>,
+    /// Whether the eyes of both players are marked, real or false, see
+    /// `go_rs::eye`
+    #[prop_or_default]
+    pub show_eyes: bool,
+    /// How strongly each point is under a player's influence, see
+    /// `go_rs::influence`, empty to shade nothing
+    #[prop_or_default]
+    pub influence: Vec<i32>,
+    #[prop_or_default]
+    pub display: StoneDisplay,
+}
+
+/// Draws the stones of a position, with the marks that go on them: the last
+/// move, move numbers, liberty counts, dead stones and territory, and the
+/// symbols of the stones when the settings ask for them.
+#[function_component]
+pub fn StoneLayer(props: &StoneLayerProps) -> Html {
+    let settings = use_context::<Settings>().unwrap_or_default();
+    match props.display {
+        StoneDisplay::Normal => {}
+        // Any mark on the board would give the stones away
+        StoneDisplay::Blind => return html! {},
+        StoneDisplay::OneColor => {
+            let stones: Vec<Player> = props
+                .position
+                .iter()
+                .map(|player| match player {
+                    Player::None => Player::None,
+                    _ => Player::Black,
+                })
+                .collect();
+            let position =
+                GoPosition::from_stones(props.position.width(), props.position.height(), &stones);
+            // Only the marks that don't tell the colors apart
+            return html! {
+                <>
+                    { render_stones(&position) }
+                    { render_move_marks(props, &position) }
+                </>
+            };
+        }
+    }
+    html! {
+        <>
+            { render_territory_shading(props) }
+            { render_ownership(props) }
+            { render_influence(props) }
+            { render_stones(&props.position) }
+            if settings.stone_symbols {
+                { render_symbols(props) }
+            }
+            { render_move_marks(props, &props.position) }
+            { render_liberties(props) }
+            { render_eyes(props) }
+            { render_dead(props) }
+            { render_territory_marks(props) }
+        </>
+    }
+}
+
+/// The number of the first move whose stone is numbered. Numbers give way
+/// to the liberty counts when both are on.
+fn first_numbered(props: &StoneLayerProps) -> usize {
+    match props.numbers {
+        _ if props.show_liberties => usize::MAX,
+        MoveNumbers::Off => usize::MAX,
+        MoveNumbers::Last => props.moves.saturating_sub(LAST_MOVE_NUMBERS) + 1,
+        MoveNumbers::All => 1,
+    }
+}
+
+/// Puts a dot on each white stone that isn't labelled already
+fn render_symbols(props: &StoneLayerProps) -> Html {
+    // Every stone carries its liberty count then
+    if props.show_liberties {
+        return html! {};
+    }
+    let position = &props.position;
+    let first_numbered = first_numbered(props);
+    let dots = position
+        .position()
+        .iter()
+        .enumerate()
+        .filter(|(i, player)| {
+            let numbered = props
+                .move_numbers
+                .get(*i)
+                .copied()
+                .flatten()
+                .is_some_and(|number| number >= first_numbered);
+            **player == Player::White && !numbered
+        })
+        .map(|(i, _)| {
+            let (x, y) = position.index_to_coord(i);
+            html! {
+                <circle
+                    class="g-stone-symbol"
+                    cx={ x.to_string() }
+                    cy={ y.to_string() }
+                    r="0.09"
+                    fill={ BLACK }
+                />
+            }
+        });
+    html! {
+        <g>{ for dots }</g>
+    }
+}
+
+/// Marks the stone of the last move, and numbers the stones according to
+/// `numbers`, in the opposite color of the stones of `position`.
+fn render_move_marks(props: &StoneLayerProps, position: &GoPosition) -> Html {
+    let moves = props.moves;
+    let first_numbered = first_numbered(props);
+    let marks = props
+        .move_numbers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, number)| {
+            let number = (*number)?;
+            let (x, y) = position.index_to_coord(i);
+            // Draw in the opposite color of the stone
+            let (class, fill) = match position[i] {
+                Player::White => ("g-label-black", BLACK),
+                _ => ("g-label-white", WHITE),
+            };
+            if number >= first_numbered {
+                Some(html! {
+                    <text
+                        class={ class }
+                        x={ x.to_string() }
+                        y={ y.to_string() }
+                        fill={ fill }
+                        font-size={ if number < 100 { "0.45" } else { "0.35" } }
+                        text-anchor="middle"
+                        dominant-baseline="central"
+                    >
+                        { number }
+                    </text>
+                })
+            } else if number == moves {
+                Some(html! {
+                    <circle
+                        class="g-last-move"
+                        cx={ x.to_string() }
+                        cy={ y.to_string() }
+                        r="0.2"
+                        fill="none"
+                        stroke={ fill }
+                        stroke-width="0.06"
+                    />
+                })
+            } else {
+                None
+            }
+        });
+    html! {
+        <g>{ for marks }</g>
+    }
+}
+
+/// The territory points with the classes and fill of their owner
+fn territory_points(
+    props: &StoneLayerProps,
+) -> impl Iterator<Item = (usize, usize, &'static str, &'static str)> + '_ {
+    let position = &props.position;
+    props
+        .territory
+        .iter()
+        .enumerate()
+        .filter_map(move |(i, owner)| {
+            let (x, y) = position.index_to_coord(i);
+            match owner {
+                Player::Black => Some((x, y, "g-territory-black", BLACK)),
+                Player::White => Some((x, y, "g-territory-white", WHITE)),
+                Player::Red => Some((x, y, "g-territory-red", RED)),
+                Player::None => None,
+            }
+        })
+}
+
+/// Shades the territory of each player in their color, under the stones
+fn render_territory_shading(props: &StoneLayerProps) -> Html {
+    let shades = territory_points(props).map(|(x, y, class, fill)| {
+        html! {
+            <rect
+                class={ class }
+                x={ (x as f64 - 0.5).to_string() }
+                y={ (y as f64 - 0.5).to_string() }
+                width="1"
+                height="1"
+                fill={ fill }
+                fill-opacity="0.25"
+            />
+        }
+    });
+    html! {
+        <g>{ for shades }</g>
+    }
+}
+
+/// Tints each point blue the more likely it is to end up black's and red
+/// the more likely it is to end up white's, under the stones
+fn render_ownership(props: &StoneLayerProps) -> Html {
+    let cells = props.ownership.iter().enumerate().map(|(index, owner)| {
+        let (x, y) = props.position.index_to_coord(index);
+        html! {
+            <rect
+                class="g-ownership"
+                x={ (x as f64 - 0.5).to_string() }
+                y={ (y as f64 - 0.5).to_string() }
+                width="1"
+                height="1"
+                fill={ if *owner > 0.0 { OWNERSHIP_BLACK } else { OWNERSHIP_WHITE } }
+                fill-opacity={ (0.6 * owner.abs()).to_string() }
+            />
+        }
+    });
+    html! {
+        <g>{ for cells }</g>
+    }
+}
+
+/// Shades the empty points under a player's influence in their color of
+/// the heatmap, deeper the stronger the hold, so frameworks stand out
+fn render_influence(props: &StoneLayerProps) -> Html {
+    let position = &props.position;
+    let cells = props
+        .influence
+        .iter()
+        .enumerate()
+        .filter(|(index, value)| **value != 0 && position[*index] == Player::None)
+        .map(|(index, value)| {
+            let (x, y) = position.index_to_coord(index);
+            let strength = value.unsigned_abs().min(INFLUENCE_FULL) as f64 / INFLUENCE_FULL as f64;
+            html! {
+                <rect
+                    class="g-influence"
+                    x={ (x as f64 - 0.5).to_string() }
+                    y={ (y as f64 - 0.5).to_string() }
+                    width="1"
+                    height="1"
+                    fill={ if *value > 0 { OWNERSHIP_BLACK } else { OWNERSHIP_WHITE } }
+                    fill-opacity={ (0.15 + 0.35 * strength).to_string() }
+                />
+            }
+        });
+    html! {
+        <g>{ for cells }</g>
+    }
+}
+
+/// Draws a small square of its owner's color on each territory point, on
+/// top of the stones so the points of dead stones show as well
+fn render_territory_marks(props: &StoneLayerProps) -> Html {
+    let squares = territory_points(props).map(|(x, y, class, fill)| {
+        html! {
+            <rect
+                class={ class }
+                x={ (x as f64 - 0.15).to_string() }
+                y={ (y as f64 - 0.15).to_string() }
+                width="0.3"
+                height="0.3"
+                fill={ fill }
+            />
+        }
+    });
+    html! {
+        <g>{ for squares }</g>
+    }
+}
+
+/// Crosses out the stones marked dead
+fn render_dead(props: &StoneLayerProps) -> Html {
+    let position = &props.position;
+    let crosses = props.dead.iter().map(|stone| {
+        let (x, y) = position.index_to_coord(*stone);
+        // Draw in the opposite color of the stone
+        let stroke = match position[*stone] {
+            Player::White => BLACK,
+            _ => WHITE,
+        };
+        html! {
+            <path
+                class="g-dead"
+                d={ format!("M{} {}l0.4 0.4m0 -0.4l-0.4 0.4", x as f64 - 0.2, y as f64 - 0.2) }
+                stroke={ stroke }
+                stroke-width="0.08"
+            />
+        }
+    });
+    html! {
+        <g>{ for crosses }</g>
+    }
+}
+
+/// Rings each eye in the color of the player it belongs to, a solid ring
+/// for real eyes and a dashed one for false eyes
+fn render_eyes(props: &StoneLayerProps) -> Html {
+    if !props.show_eyes {
+        return html! {};
+    }
+
+    let position = &props.position;
+    let rings = [Player::Black, Player::White]
+        .into_iter()
+        .flat_map(|player| {
+            eyes(position, player)
+                .into_iter()
+                .map(move |(index, eye)| (index, eye, player))
+        })
+        .map(|(index, eye, player)| {
+            let (x, y) = position.index_to_coord(index);
+            let (class, title, dashes) = match eye {
+                Eye::Real => ("g-eye", "Real eye", None),
+                Eye::False => ("g-eye g-false-eye", "False eye", Some("0.1 0.08")),
+            };
+            html! {
+                <circle
+                    class={ class }
+                    cx={ x.to_string() }
+                    cy={ y.to_string() }
+                    r="0.22"
+                    fill="none"
+                    stroke={ if player == Player::Black { BLACK } else { WHITE } }
+                    stroke-width="0.06"
+                    stroke-dasharray={ dashes }
+                >
+                    <title>{ title }</title>
+                </circle>
+            }
+        });
+    html! {
+        <g>{ for rings }</g>
+    }
+}
+
+/// Renders the liberty count of each group on top of its stones
+fn render_liberties(props: &StoneLayerProps) -> Html {
+    if !props.show_liberties {
+        return html! {};
+    }
+
+    let position = &props.position;
+    let mut labels = Vec::new();
+    for group in position.groups() {
+        // Draw the count in the opposite color of the stones
+        let (class, fill) = match group.player {
+            Player::White => ("g-label-black", BLACK),
+            _ => ("g-label-white", WHITE),
+        };
+        let liberties = group.liberty_count();
+        for stone in group.stones {
+            let (x, y) = position.index_to_coord(stone);
+            let label = html! {
+                <text
+                    class={ class }
+                    x={ x.to_string() }
+                    y={ y.to_string() }
+                    fill={ fill }
+                    font-size="0.5"
+                    text-anchor="middle"
+                    dominant-baseline="central"
+                >
+                    { liberties }
+                </text>
+            };
+            labels.push(label);
+        }
+    }
+    html! {
+        <g>{ for labels }</g>
+    }
+}
@@ -0,0 +1,50 @@
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct TabBarProps {
+    /// The name of each open game, in tab order
+    pub tabs: Vec<String>,
+    /// The index of the tab being shown
+    pub active: usize,
+    /// Called with the index of a tab picked to be shown
+    pub onselect: Callback<usize>,
+    /// Called with the index of a tab to close
+    pub onclose: Callback<usize>,
+    /// Called when a new tab is asked for
+    pub onnew: Callback<()>,
+}
+
+/// A tab for every open game, to switch between them, with a button that
+/// opens another.
+#[function_component]
+pub fn TabBar(props: &TabBarProps) -> Html {
+    let tabs = props.tabs.iter().enumerate().map(|(i, name)| {
+        let active = i == props.active;
+        let select_cb = props.onselect.reform(move |_: MouseEvent| i);
+        let close_cb = props.onclose.reform(move |e: MouseEvent| {
+            // Closing a tab doesn't also pick it
+            e.stop_propagation();
+            i
+        });
+        html! {
+            <div
+                class={ classes!("tab", active.then_some("tab-active")) }
+                role="tab"
+                aria-selected={ active.to_string() }
+                onclick={ select_cb }
+            >
+                <span class="tab-name">{ name }</span>
+                <button class="tab-close" aria-label={ format!("Close {}", name) } onclick={ close_cb }>
+                    { "×" }
+                </button>
+            </div>
+        }
+    });
+    let new_cb = props.onnew.reform(|_: MouseEvent| ());
+    html! {
+        <nav class="tab-bar" role="tablist">
+            { for tabs }
+            <button class="tab-new" aria-label="New game" onclick={ new_cb }>{ "+" }</button>
+        </nav>
+    }
+}
@@ -0,0 +1,186 @@
+//! Short tactical sequences worth pointing out when going over a game:
+//! ladders, where a chain in atari runs in a zigzag until it is taken, and
+//! snapbacks, where taking a stone thrown in loses more stones at once.
+
+use crate::game::{GoGame, GoPosition, MoveKind, Player, Position};
+
+/// How many positions reading a ladder looks at, far more than a ladder
+/// across a 19x19 board needs while keeping odd shapes from taking long
+const LADDER_READING: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TacticKind {
+    Ladder,
+    Snapback,
+}
+
+impl TacticKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TacticKind::Ladder => "Ladder",
+            TacticKind::Snapback => "Snapback",
+        }
+    }
+
+    /// What happens in the tactic, in a sentence for beginners.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            TacticKind::Ladder => {
+                "The stones in atari can run, but every step leaves them in atari again \
+                 until they reach the edge or a friendly stone of the chaser and are taken."
+            }
+            TacticKind::Snapback => {
+                "The stone thrown in can be taken, but taking it leaves the capturing \
+                 stones in atari, and they are all taken back at the same point."
+            }
+        }
+    }
+}
+
+/// A tactic played in a game, see `find_tactics`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tactic {
+    pub kind: TacticKind,
+    /// The moves played before the one that starts the tactic, so it starts
+    /// from `GoGame::position_at(moves)`
+    pub moves: usize,
+    /// The moves of the tactic as the player and the index of the point,
+    /// starting with the one played. A ladder goes on as read, up to the
+    /// capture, whether or not the game followed it.
+    pub sequence: Vec<(Player, usize)>,
+}
+
+/// Finds the snapbacks and working ladders played in `game`, in the order
+/// they were played. A ladder is noted at the move that starts it, not at
+/// each move that keeps chasing the same stones. Only two-player games are
+/// looked at.
+pub fn find_tactics(game: &GoGame) -> Vec<Tactic> {
+    if game.colors() > 2 {
+        return vec![];
+    }
+    let history = game.history();
+    let mut position = game.position_at(0);
+    let mut tactics = vec![];
+    // A stone of the chain being chased by the last ladder found
+    let mut chased: Option<usize> = None;
+    for (moves, played) in history.iter().enumerate() {
+        let MoveKind::Play(square) = played.kind else {
+            continue;
+        };
+        let index = position.coord_to_index(square.x, square.y);
+        position.play(square.x, square.y, played.player);
+        // A throw-in taken at once, and the stones that took it taken back
+        // at the same point
+        if let [taken, retaken, ..] = &history[moves + 1..] {
+            if let MoveKind::Play(taking) = taken.kind {
+                if played.captured.is_empty()
+                    && taken.captured == [index]
+                    && retaken.kind == played.kind
+                    && retaken.player == played.player
+                    && retaken.captured.len() > 1
+                {
+                    tactics.push(Tactic {
+                        kind: TacticKind::Snapback,
+                        moves,
+                        sequence: vec![
+                            (played.player, index),
+                            (taken.player, position.coord_to_index(taking.x, taking.y)),
+                            (played.player, index),
+                        ],
+                    });
+                    continue;
+                }
+            }
+        }
+        // A chain put in atari that can't get out
+        let still_chased = chased.filter(|stone| position[*stone] != Player::None);
+        for side in position.neighbors(index) {
+            if [Player::None, played.player].contains(&position[side]) {
+                continue;
+            }
+            let group = position.group_at(side);
+            if group.liberties.len() != 1
+                || still_chased.is_some_and(|stone| group.stones.contains(&stone))
+            {
+                continue;
+            }
+            // Two moves of running at least, anything shorter is a plain
+            // capture
+            match read_ladder(&position, side) {
+                Some(ladder) if ladder.len() >= 4 => {
+                    let mut sequence = vec![(played.player, index)];
+                    sequence.extend(ladder);
+                    tactics.push(Tactic {
+                        kind: TacticKind::Ladder,
+                        moves,
+                        sequence,
+                    });
+                    chased = Some(side);
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    tactics
+}
+
+/// Reads whether the chain at `stone`, in atari with its owner to move, is
+/// caught in a ladder: running at its last liberty only ever leaves it with
+/// two at most, and the chaser can always put it back in atari until it is
+/// taken. Returns the moves up to the capture, or `None` when the chain
+/// gets away, by running or by taking a stone of the chaser.
+pub fn read_ladder(position: &GoPosition, stone: usize) -> Option<Vec<(Player, usize)>> {
+    let mut budget = LADDER_READING;
+    chase(position, stone, &mut budget)
+}
+
+fn chase(position: &GoPosition, stone: usize, budget: &mut usize) -> Option<Vec<(Player, usize)>> {
+    *budget = budget.checked_sub(1)?;
+    let runner = position[stone];
+    let chaser = match runner {
+        Player::Black => Player::White,
+        Player::White => Player::Black,
+        Player::None | Player::Red => return None,
+    };
+    let group = position.group_at(stone);
+    if group.liberties.len() != 1 {
+        return None;
+    }
+    // Taking a neighboring stone of the chaser that is in atari itself
+    // gives the chain room to breathe
+    let breaks_out = group.stones.iter().any(|index| {
+        position
+            .neighbors(*index)
+            .any(|side| position[side] == chaser && position.group_at(side).liberties.len() == 1)
+    });
+    if breaks_out {
+        return None;
+    }
+    let run = group.liberties[0];
+    let (x, y) = position.index_to_coord(run);
+    if !position.is_valid_move(x, y, runner) {
+        // Running would be suicide, the chain is taken as it is
+        return Some(vec![(chaser, run)]);
+    }
+    let mut after = position.clone();
+    after.play(x, y, runner);
+    let liberties = after.group_at(stone).liberties;
+    match liberties.as_slice() {
+        [] => Some(vec![(runner, run)]),
+        [last] => Some(vec![(runner, run), (chaser, *last)]),
+        [a, b] => [*a, *b].into_iter().find_map(|atari| {
+            let (x, y) = after.index_to_coord(atari);
+            if !after.is_valid_move(x, y, chaser) {
+                return None;
+            }
+            let mut chased = after.clone();
+            chased.play(x, y, chaser);
+            let rest = chase(&chased, stone, budget)?;
+            let mut sequence = vec![(runner, run), (chaser, atari)];
+            sequence.extend(rest);
+            Some(sequence)
+        }),
+        _ => None,
+    }
+}
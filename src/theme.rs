@@ -0,0 +1,167 @@
+use gloo_utils::document;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+
+use crate::settings::Settings;
+
+/// The wood, or other material, the board is drawn on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoardTheme {
+    #[default]
+    Classic,
+    Kaya,
+    Bamboo,
+    Walnut,
+}
+
+/// How the stones are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StoneStyle {
+    /// Plain circles
+    #[default]
+    Flat,
+    /// Lit from the top left
+    Shaded,
+    /// Shaded with a glossy highlight and a shadow on the board
+    Realistic,
+}
+
+/// The colors of everything around the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UiTheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl BoardTheme {
+    pub const ALL: [BoardTheme; 4] = [
+        BoardTheme::Classic,
+        BoardTheme::Kaya,
+        BoardTheme::Bamboo,
+        BoardTheme::Walnut,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BoardTheme::Classic => "Classic",
+            BoardTheme::Kaya => "Kaya",
+            BoardTheme::Bamboo => "Bamboo",
+            BoardTheme::Walnut => "Walnut",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            BoardTheme::Classic => "#AA9E63",
+            BoardTheme::Kaya => "#DCB35C",
+            BoardTheme::Bamboo => "#E3C88B",
+            BoardTheme::Walnut => "#9C6B3F",
+        }
+    }
+}
+
+impl StoneStyle {
+    pub const ALL: [StoneStyle; 3] = [StoneStyle::Flat, StoneStyle::Shaded, StoneStyle::Realistic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StoneStyle::Flat => "Flat",
+            StoneStyle::Shaded => "Shaded",
+            StoneStyle::Realistic => "Realistic",
+        }
+    }
+
+    /// The fills of the black and white stones, the gradients are defined by
+    /// `board::render_stone_defs`.
+    fn fills(self) -> (&'static str, &'static str) {
+        match self {
+            StoneStyle::Flat => ("rgb(var(--fg-black))", "rgb(var(--fg-white))"),
+            StoneStyle::Shaded => ("url(#stone-black-shaded)", "url(#stone-white-shaded)"),
+            StoneStyle::Realistic => ("url(#stone-black-glossy)", "url(#stone-white-glossy)"),
+        }
+    }
+}
+
+impl UiTheme {
+    pub const ALL: [UiTheme; 2] = [UiTheme::Dark, UiTheme::Light];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UiTheme::Dark => "Dark",
+            UiTheme::Light => "Light",
+        }
+    }
+
+    /// The values of the `--bg-0`, `--bg-1`, `--bg-panel` and `--fg-text`
+    /// properties, as comma separated rgb values.
+    fn colors(self) -> [&'static str; 4] {
+        match self {
+            UiTheme::Dark => ["51, 51, 51", "85, 85, 85", "34, 34, 34", "204, 204, 204"],
+            UiTheme::Light => [
+                "236, 236, 236",
+                "208, 208, 208",
+                "250, 250, 250",
+                "34, 34, 34",
+            ],
+        }
+    }
+}
+
+/// The board color of the high contrast mode, which replaces that of the
+/// board theme
+const HIGH_CONTRAST_BOARD: &str = "#F3E3B0";
+/// The values of the `--fg-white` and `--fg-black` properties in high
+/// contrast mode, pure white and black
+const HIGH_CONTRAST_STONES: [(&str, &str); 2] =
+    [("--fg-white", "255, 255, 255"), ("--fg-black", "0, 0, 0")];
+
+/// Applies the theme in `settings` by setting the custom properties the
+/// stylesheet is written against on the root element. High contrast also
+/// marks the root with the `high-contrast` class, for the outlines.
+pub fn apply(settings: &Settings) {
+    let root = match document()
+        .document_element()
+        .and_then(|root| root.dyn_into::<HtmlElement>().ok())
+    {
+        Some(root) => root,
+        None => return,
+    };
+    let style = root.style();
+    let [bg_0, bg_1, bg_panel, fg_text] = settings.ui_theme.colors();
+    // Shading would take away from the contrast
+    let (board, stone_style) = if settings.high_contrast {
+        (HIGH_CONTRAST_BOARD, StoneStyle::Flat)
+    } else {
+        (settings.board_theme.color(), settings.stone_style)
+    };
+    let (black, white) = stone_style.fills();
+    let shadow = match stone_style {
+        StoneStyle::Realistic => "url(#stone-shadow)",
+        _ => "none",
+    };
+    for (property, value) in [
+        ("--bg-0", bg_0),
+        ("--bg-1", bg_1),
+        ("--bg-panel", bg_panel),
+        ("--fg-text", fg_text),
+        ("--board-color", board),
+        ("--stone-black-fill", black),
+        ("--stone-white-fill", white),
+        ("--stone-filter", shadow),
+    ] {
+        let _ = style.set_property(property, value);
+    }
+    // Otherwise the colors of the stylesheet apply
+    for (property, value) in HIGH_CONTRAST_STONES {
+        if settings.high_contrast {
+            let _ = style.set_property(property, value);
+        } else {
+            let _ = style.remove_property(property);
+        }
+    }
+    let _ = root
+        .class_list()
+        .toggle_with_force("high-contrast", settings.high_contrast);
+}
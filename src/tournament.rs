@@ -0,0 +1,291 @@
+//! Matches and small round-robins between players taking turns at one
+//! board: the schedule of games, who plays which color with how many
+//! handicap stones, the results and the standings.
+
+use serde::{Deserialize, Serialize};
+
+use crate::handicap::{rank_difference, HandicapTable};
+
+/// How the games of a tournament are scheduled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    /// Two players play up to `games` games, until one has won more than
+    /// half of them
+    Match { games: usize },
+    /// Every player plays every other player once
+    RoundRobin,
+}
+
+/// A player of a tournament.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entrant {
+    pub name: String,
+    /// The rank the way game records write it, like `3d` or `15k`, empty
+    /// when not known
+    pub rank: String,
+}
+
+/// How a game of a tournament ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    BlackWins,
+    WhiteWins,
+    Jigo,
+}
+
+/// A game of a tournament, between two entrants by their index.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pairing {
+    pub black: usize,
+    pub white: usize,
+    /// Handicap stones for black, 0 for no stones
+    pub handicap: usize,
+    /// The komi, `None` for that of the rules the game is played under
+    pub komi: Option<f64>,
+    pub result: Option<GameResult>,
+}
+
+impl Pairing {
+    /// The entrant who won, `None` before the game ends and after a jigo.
+    pub fn winner(&self) -> Option<usize> {
+        match self.result? {
+            GameResult::BlackWins => Some(self.black),
+            GameResult::WhiteWins => Some(self.white),
+            GameResult::Jigo => None,
+        }
+    }
+
+    fn involves(&self, entrant: usize) -> bool {
+        self.black == entrant || self.white == entrant
+    }
+}
+
+/// How an entrant is doing, see `Tournament::standings`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub entrant: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub jigo: usize,
+    /// A point for a win and half for a jigo
+    pub points: f64,
+    /// The points of the opponents played, to break ties
+    pub opponents_points: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tournament {
+    pub entrants: Vec<Entrant>,
+    pub format: Format,
+    /// The table the weaker player of a game is given a handicap by, when
+    /// both ranks are known, `None` for even games only
+    pub handicaps: Option<HandicapTable>,
+    pub board_size: usize,
+    /// The games of each round
+    pub rounds: Vec<Vec<Pairing>>,
+}
+
+impl Tournament {
+    /// Schedules the games of a tournament. A match needs exactly two
+    /// entrants, a round-robin at least three.
+    pub fn new(
+        entrants: Vec<Entrant>,
+        format: Format,
+        handicaps: Option<HandicapTable>,
+        board_size: usize,
+    ) -> Result<Self, String> {
+        let mut tournament = Tournament {
+            entrants,
+            format,
+            handicaps,
+            board_size,
+            rounds: vec![],
+        };
+        let players = tournament.entrants.len();
+        let rounds = match format {
+            Format::Match { games } if players == 2 && games > 0 => vec![vec![(0, 1)]; games],
+            Format::Match { .. } if players == 2 => {
+                return Err(String::from("A match needs at least one game"))
+            }
+            Format::Match { .. } => return Err(String::from("A match is between two players")),
+            Format::RoundRobin if players >= 3 => round_robin(players),
+            Format::RoundRobin => {
+                return Err(String::from("A round-robin needs at least three players"))
+            }
+        };
+        for round in rounds {
+            let pairings = round
+                .into_iter()
+                .map(|(a, b)| tournament.pair(a, b))
+                .collect();
+            tournament.rounds.push(pairings);
+        }
+        Ok(tournament)
+    }
+
+    /// Pairs `a` and `b` for their next game. With a handicap the weaker
+    /// player takes black; otherwise black goes to whoever has had it less
+    /// often so far, or didn't have it in their last game.
+    fn pair(&self, a: usize, b: usize) -> Pairing {
+        let difference = rank_difference(&self.entrants[a].rank, &self.entrants[b].rank);
+        if let (Some(table), Some(difference)) = (self.handicaps, difference) {
+            let handicap = table.handicap(difference.unsigned_abs() as usize);
+            if handicap.komi.is_some() {
+                // `difference` is how much stronger `b` is
+                let (black, white) = if difference > 0 { (a, b) } else { (b, a) };
+                return Pairing {
+                    black,
+                    white,
+                    handicap: handicap.stones,
+                    komi: handicap.komi,
+                    result: None,
+                };
+            }
+        }
+        let blacks = |entrant| {
+            self.games_of(entrant)
+                .filter(|pairing| pairing.black == entrant)
+                .count()
+        };
+        let last_black = |entrant| {
+            self.games_of(entrant)
+                .last()
+                .is_some_and(|pairing| pairing.black == entrant)
+        };
+        let a_black = match blacks(a).cmp(&blacks(b)) {
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Greater => false,
+            std::cmp::Ordering::Equal => !last_black(a),
+        };
+        let (black, white) = if a_black { (a, b) } else { (b, a) };
+        Pairing {
+            black,
+            white,
+            handicap: 0,
+            komi: None,
+            result: None,
+        }
+    }
+
+    fn games_of(&self, entrant: usize) -> impl Iterator<Item = &Pairing> {
+        self.rounds
+            .iter()
+            .flatten()
+            .filter(move |pairing| pairing.involves(entrant))
+    }
+
+    pub fn set_result(&mut self, round: usize, game: usize, result: Option<GameResult>) {
+        if let Some(pairing) = self
+            .rounds
+            .get_mut(round)
+            .and_then(|round| round.get_mut(game))
+        {
+            pairing.result = result;
+        }
+    }
+
+    /// Whether the game still has to be played: it has no result, and it
+    /// isn't part of a match that is already decided.
+    pub fn is_pending(&self, round: usize, game: usize) -> bool {
+        let unplayed = self
+            .rounds
+            .get(round)
+            .and_then(|round| round.get(game))
+            .is_some_and(|pairing| pairing.result.is_none());
+        unplayed && !self.match_decided()
+    }
+
+    /// Whether a player of a match has won more than half of its games.
+    fn match_decided(&self) -> bool {
+        match self.format {
+            Format::Match { games } => self
+                .standings()
+                .iter()
+                .any(|standing| 2.0 * standing.points > games as f64),
+            Format::RoundRobin => false,
+        }
+    }
+
+    /// The first game still to be played, as its round and its index in the
+    /// round.
+    pub fn next_game(&self) -> Option<(usize, usize)> {
+        self.rounds
+            .iter()
+            .enumerate()
+            .flat_map(|(round, pairings)| (0..pairings.len()).map(move |game| (round, game)))
+            .find(|(round, game)| self.is_pending(*round, *game))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_game().is_none()
+    }
+
+    /// Every entrant by points, then by the points of the opponents they
+    /// played, then in the order they entered.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = (0..self.entrants.len())
+            .map(|entrant| {
+                let mut standing = Standing {
+                    entrant,
+                    wins: 0,
+                    losses: 0,
+                    jigo: 0,
+                    points: 0.0,
+                    opponents_points: 0.0,
+                };
+                for pairing in self.games_of(entrant).filter(|p| p.result.is_some()) {
+                    match pairing.winner() {
+                        Some(winner) if winner == entrant => standing.wins += 1,
+                        Some(_) => standing.losses += 1,
+                        None => standing.jigo += 1,
+                    }
+                }
+                standing.points = standing.wins as f64 + standing.jigo as f64 / 2.0;
+                standing
+            })
+            .collect();
+        let points: Vec<f64> = standings.iter().map(|standing| standing.points).collect();
+        for standing in standings.iter_mut() {
+            standing.opponents_points = self
+                .games_of(standing.entrant)
+                .filter(|pairing| pairing.result.is_some())
+                .map(|pairing| {
+                    let opponent = if pairing.black == standing.entrant {
+                        pairing.white
+                    } else {
+                        pairing.black
+                    };
+                    points[opponent]
+                })
+                .sum();
+        }
+        standings.sort_by(|a, b| {
+            b.points
+                .total_cmp(&a.points)
+                .then(b.opponents_points.total_cmp(&a.opponents_points))
+                .then(a.entrant.cmp(&b.entrant))
+        });
+        standings
+    }
+}
+
+/// The rounds of a round-robin between `players` players, by the circle
+/// method: the first player stays in place while the others turn around
+/// them. With an odd number of players one of them sits each round out.
+fn round_robin(players: usize) -> Vec<Vec<(usize, usize)>> {
+    // `None` is the bye
+    let mut circle: Vec<Option<usize>> = (0..players).map(Some).collect();
+    if players % 2 == 1 {
+        circle.push(None);
+    }
+    let size = circle.len();
+    (0..size - 1)
+        .map(|_| {
+            let round = (0..size / 2)
+                .filter_map(|i| Some((circle[i]?, circle[size - 1 - i]?)))
+                .collect();
+            circle[1..].rotate_right(1);
+            round
+        })
+        .collect()
+}
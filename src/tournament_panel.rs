@@ -0,0 +1,305 @@
+use gloo_storage::{LocalStorage, Storage};
+use go_rs::tournament::{Entrant, Format, GameResult, Pairing, Tournament};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+/// The local storage key the tournament is kept under
+const STORAGE_KEY: &str = "go-rs-tournament";
+
+/// The board sizes a tournament can be played on
+const BOARD_SIZES: [usize; 3] = [9, 13, 19];
+
+/// Reads the tournament being run, if any, so a club night survives closing
+/// the page.
+pub fn load() -> Option<Tournament> {
+    LocalStorage::get(STORAGE_KEY).ok()
+}
+
+pub fn save(tournament: Option<&Tournament>) {
+    match tournament {
+        Some(tournament) => {
+            let _ = LocalStorage::set(STORAGE_KEY, tournament);
+        }
+        None => LocalStorage::delete(STORAGE_KEY),
+    }
+}
+
+/// What is asked of the tournament panel.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TournamentControl {
+    Start {
+        entrants: Vec<Entrant>,
+        format: Format,
+        handicaps: bool,
+        board_size: usize,
+    },
+    /// Start the game of the given round and index on the board
+    Play {
+        round: usize,
+        game: usize,
+    },
+    /// Set the result of a game by hand, for games played on a real board,
+    /// or clear it when `None`
+    SetResult {
+        round: usize,
+        game: usize,
+        result: Option<GameResult>,
+    },
+    End,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TournamentPanelProps {
+    pub tournament: Option<Tournament>,
+    /// The game of the tournament being played on the board, as its round
+    /// and its index in the round
+    pub playing: Option<(usize, usize)>,
+    pub onaction: Callback<TournamentControl>,
+}
+
+/// Sets up a match or round-robin between local players, then shows its
+/// schedule and standings.
+#[function_component]
+pub fn TournamentPanel(props: &TournamentPanelProps) -> Html {
+    match &props.tournament {
+        Some(tournament) => render_tournament(tournament, props.playing, &props.onaction),
+        None => html! { <TournamentSetup onaction={ props.onaction.clone() } /> },
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct TournamentSetupProps {
+    onaction: Callback<TournamentControl>,
+}
+
+/// The form for a new tournament, one entrant a line as a name optionally
+/// followed by a rank, like `Ann 3k`.
+#[function_component]
+fn TournamentSetup(props: &TournamentSetupProps) -> Html {
+    let entrants = use_state(String::new);
+    let round_robin = use_state(|| false);
+    let games = use_state(|| 3);
+    let handicaps = use_state(|| true);
+    let board_size = use_state(|| 19);
+
+    let entrants_cb = {
+        let entrants = entrants.clone();
+        Callback::from(move |e: InputEvent| {
+            entrants.set(e.target_unchecked_into::<HtmlTextAreaElement>().value());
+        })
+    };
+    let format_cb = {
+        let round_robin = round_robin.clone();
+        Callback::from(move |e: Event| {
+            round_robin.set(
+                e.target_unchecked_into::<HtmlSelectElement>()
+                    .selected_index()
+                    == 1,
+            );
+        })
+    };
+    let games_cb = {
+        let games = games.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            if let Ok(value) = value.trim().parse() {
+                games.set(value);
+            }
+        })
+    };
+    let handicaps_cb = {
+        let handicaps = handicaps.clone();
+        Callback::from(move |_: Event| handicaps.set(!*handicaps))
+    };
+    let size_cb = {
+        let board_size = board_size.clone();
+        Callback::from(move |e: Event| {
+            let index = e
+                .target_unchecked_into::<HtmlSelectElement>()
+                .selected_index();
+            if let Some(size) = usize::try_from(index).ok().and_then(|i| BOARD_SIZES.get(i)) {
+                board_size.set(*size);
+            }
+        })
+    };
+    let start_cb = {
+        let control = TournamentControl::Start {
+            entrants: parse_entrants(&entrants),
+            format: if *round_robin {
+                Format::RoundRobin
+            } else {
+                Format::Match { games: *games }
+            },
+            handicaps: *handicaps,
+            board_size: *board_size,
+        };
+        props.onaction.reform(move |_: MouseEvent| control.clone())
+    };
+
+    html! {
+        <div class="tournament-setup">
+            <label>
+                { "Players, one a line with an optional rank" }
+                <textarea
+                    rows="4"
+                    placeholder="Ann 3k\nBob 5k"
+                    value={ (*entrants).clone() }
+                    oninput={ entrants_cb }
+                />
+            </label>
+            <label>
+                { "Format " }
+                <select onchange={ format_cb }>
+                    <option selected={ !*round_robin }>{ "Match" }</option>
+                    <option selected={ *round_robin }>{ "Round-robin" }</option>
+                </select>
+            </label>
+            if !*round_robin {
+                <label>
+                    { "Best of " }
+                    <input type="number" min="1" value={ games.to_string() } onchange={ games_cb } />
+                </label>
+            }
+            <label>
+                { "Board " }
+                <select onchange={ size_cb }>
+                    { for BOARD_SIZES.iter().map(|size| html! {
+                        <option selected={ *size == *board_size }>{ format!("{0}x{0}", size) }</option>
+                    }) }
+                </select>
+            </label>
+            <label>
+                <input type="checkbox" checked={ *handicaps } onchange={ handicaps_cb } />
+                { "Handicaps from ranks" }
+            </label>
+            <button onclick={ start_cb }>{ "Start tournament" }</button>
+        </div>
+    }
+}
+
+/// Reads one entrant a line, taking the last word as the rank when it looks
+/// like one.
+fn parse_entrants(text: &str) -> Vec<Entrant> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.rsplit_once(char::is_whitespace) {
+            Some((name, rank)) if go_rs::rating::rank_rating(rank).is_some() => Entrant {
+                name: name.trim().to_owned(),
+                rank: rank.to_owned(),
+            },
+            _ => Entrant {
+                name: line.to_owned(),
+                rank: String::new(),
+            },
+        })
+        .collect()
+}
+
+fn render_tournament(
+    tournament: &Tournament,
+    playing: Option<(usize, usize)>,
+    onaction: &Callback<TournamentControl>,
+) -> Html {
+    let name = |entrant: usize| tournament.entrants[entrant].name.as_str();
+    let rounds = tournament.rounds.iter().enumerate().map(|(round, pairings)| {
+        let games = pairings.iter().enumerate().map(|(game, pairing)| {
+            let pending = tournament.is_pending(round, game);
+            let current = playing == Some((round, game));
+            let result_button = |result: Option<GameResult>, text: &str| {
+                let onclick = onaction.reform(move |_: MouseEvent| TournamentControl::SetResult {
+                    round,
+                    game,
+                    result,
+                });
+                html! { <button onclick={ onclick }>{ text }</button> }
+            };
+            let actions = if pairing.result.is_some() {
+                result_button(None, "Clear")
+            } else if pending && !current {
+                let play_cb =
+                    onaction.reform(move |_: MouseEvent| TournamentControl::Play { round, game });
+                html! {
+                    <>
+                        <button onclick={ play_cb }>{ "Play" }</button>
+                        { result_button(Some(GameResult::BlackWins), "B won") }
+                        { result_button(Some(GameResult::WhiteWins), "W won") }
+                        { result_button(Some(GameResult::Jigo), "Jigo") }
+                    </>
+                }
+            } else {
+                html! {}
+            };
+            html! {
+                <li class={ classes!(current.then_some("tournament-current")) }>
+                    <span>{ format!("{} (B) – {} (W)", name(pairing.black), name(pairing.white)) }</span>
+                    <span class="tournament-terms">{ terms(pairing) }</span>
+                    <span class="tournament-result">{ result_text(pairing, current) }</span>
+                    { actions }
+                </li>
+            }
+        });
+        html! {
+            <li>
+                { format!("Round {}", round + 1) }
+                <ul>{ for games }</ul>
+            </li>
+        }
+    });
+    let standings = tournament
+        .standings()
+        .into_iter()
+        .enumerate()
+        .map(|(place, standing)| {
+            html! {
+                <tr>
+                    <td>{ place + 1 }</td>
+                    <td>{ name(standing.entrant) }</td>
+                    <td>{ format!("{}/{}/{}", standing.wins, standing.jigo, standing.losses) }</td>
+                    <td>{ standing.points }</td>
+                    <td>{ standing.opponents_points }</td>
+                </tr>
+            }
+        });
+    let status = match tournament.next_game() {
+        None => String::from("Finished"),
+        Some((round, _)) => format!("Round {} of {}", round + 1, tournament.rounds.len()),
+    };
+    let end_cb = onaction.reform(|_: MouseEvent| TournamentControl::End);
+    html! {
+        <div class="tournament">
+            <span>{ status }</span>
+            <table class="tournament-standings">
+                <tr>
+                    <th>{ "#" }</th>
+                    <th>{ "Player" }</th>
+                    <th>{ "W/J/L" }</th>
+                    <th>{ "Points" }</th>
+                    <th>{ "SOS" }</th>
+                </tr>
+                { for standings }
+            </table>
+            <ol class="tournament-rounds">{ for rounds }</ol>
+            <button onclick={ end_cb }>{ "End tournament" }</button>
+        </div>
+    }
+}
+
+/// The handicap and komi of a game, when they aren't the usual ones.
+fn terms(pairing: &Pairing) -> String {
+    match (pairing.handicap, pairing.komi) {
+        (0, None) => String::new(),
+        (0, Some(komi)) => format!("komi {}", komi),
+        (stones, _) => format!("{} stones", stones),
+    }
+}
+
+fn result_text(pairing: &Pairing, current: bool) -> &'static str {
+    match pairing.result {
+        Some(GameResult::BlackWins) => "B+",
+        Some(GameResult::WhiteWins) => "W+",
+        Some(GameResult::Jigo) => "Jigo",
+        None if current => "Playing",
+        None => "",
+    }
+}
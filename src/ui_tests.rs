@@ -0,0 +1,206 @@
+//! Tests of the app running in a browser: it is mounted on a page, clicked
+//! at the pixels where points are drawn, and the stones it then draws are
+//! checked, so mistakes in mapping clicks to points show up.
+//!
+//! Run with `wasm-pack test --headless --firefox` or `--chrome`.
+
+use std::time::Duration;
+
+use gloo_storage::{LocalStorage, Storage};
+use gloo_utils::{document, window};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use web_sys::{Element, HtmlElement, MouseEvent, MouseEventInit};
+use yew::AppHandle;
+
+use crate::{AppProps, Root};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// The app mounted on a part of the page of its own.
+struct Mounted {
+    root: Element,
+    app: Option<AppHandle<Root>>,
+}
+
+impl Mounted {
+    /// Mounts the app with the default settings on a fixed size part of
+    /// the page, and starts a 19x19 game from the start screen it opens on.
+    async fn new() -> Mounted {
+        LocalStorage::clear();
+        // Start from the start screen, whatever view the last test left
+        window().location().set_hash("").unwrap();
+        let root = document().create_element("div").unwrap();
+        root.set_attribute("style", "width: 800px; height: 800px;")
+            .unwrap();
+        document().body().unwrap().append_child(&root).unwrap();
+        let props = AppProps {
+            hash: String::new(),
+        };
+        let app = yew::Renderer::<Root>::with_root_and_props(root.clone(), props).render();
+        let mounted = Mounted {
+            root,
+            app: Some(app),
+        };
+        mounted.settle().await;
+        mounted.start_game().await;
+        mounted
+    }
+
+    /// Picks the default game on the start screen.
+    async fn start_game(&self) {
+        let buttons = self.root.query_selector_all("main button").unwrap();
+        let default = (0..buttons.length())
+            .filter_map(|i| buttons.get(i)?.dyn_into::<HtmlElement>().ok())
+            .find(|button| button.text_content().as_deref() == Some("Default"))
+            .expect("the start screen offers a default game");
+        default.click();
+        self.settle().await;
+    }
+
+    /// Lets the app handle what happened and draw again.
+    async fn settle(&self) {
+        yew::platform::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    /// Clicks the first element matching `selector`.
+    async fn click_on(&self, selector: &str) {
+        self.root
+            .query_selector(selector)
+            .unwrap()
+            .and_then(|element| element.dyn_into::<HtmlElement>().ok())
+            .unwrap_or_else(|| panic!("{} is drawn", selector))
+            .click();
+        self.settle().await;
+    }
+
+    /// Shows the game of the tab at `index`, counting from the left.
+    async fn switch_tab(&self, index: usize) {
+        self.click_on(&format!(".tab-bar .tab:nth-child({})", index + 1))
+            .await;
+    }
+
+    fn board(&self) -> Element {
+        self.root
+            .query_selector("svg.g-board")
+            .unwrap()
+            .expect("the board is drawn")
+    }
+
+    /// Clicks the board at point (`x`, `y`), in board units, counting from
+    /// the point at the top left, at the pixel it is drawn at.
+    async fn click(&self, x: f64, y: f64) {
+        let board = self.board();
+        let rect = board.get_bounding_client_rect();
+        let view_box: Vec<f64> = board
+            .get_attribute("viewBox")
+            .unwrap()
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        let scale = rect.width() / view_box[2];
+        let client_x = rect.left() + (x + crate::board::BOARD_MARGIN - view_box[0]) * scale;
+        let client_y = rect.top() + (y + crate::board::BOARD_MARGIN - view_box[1]) * scale;
+        let init = MouseEventInit::new();
+        init.set_bubbles(true);
+        init.set_client_x(client_x.round() as i32);
+        init.set_client_y(client_y.round() as i32);
+        let click = MouseEvent::new_with_mouse_event_init_dict("click", &init).unwrap();
+        board.dispatch_event(&click).unwrap();
+        self.settle().await;
+    }
+
+    /// Clicks the point twice, which plays it with the default settings,
+    /// see `ConfirmMode::SecondClick`.
+    async fn play(&self, x: f64, y: f64) {
+        self.click(x, y).await;
+        self.click(x, y).await;
+    }
+
+    /// The points of the stones drawn of one color, `stone-black` or
+    /// `stone-white`, sorted.
+    fn stones(&self, class: &str) -> Vec<(usize, usize)> {
+        let found = self
+            .root
+            .query_selector_all(&format!("svg.g-board .g-stones .{}", class))
+            .unwrap();
+        let mut stones: Vec<(usize, usize)> = (0..found.length())
+            .filter_map(|i| found.get(i)?.dyn_into::<Element>().ok())
+            .map(|stone| {
+                let coord = |name: &str| stone.get_attribute(name).unwrap().parse().unwrap();
+                (coord("cx"), coord("cy"))
+            })
+            .collect();
+        stones.sort_unstable();
+        stones
+    }
+}
+
+impl Drop for Mounted {
+    fn drop(&mut self) {
+        if let Some(app) = self.app.take() {
+            app.destroy();
+        }
+        self.root.remove();
+    }
+}
+
+#[wasm_bindgen_test]
+async fn a_move_is_played_on_the_second_click() {
+    let app = Mounted::new().await;
+    app.click(3.0, 15.0).await;
+    assert_eq!(app.stones("stone-black"), vec![]);
+    app.click(3.0, 15.0).await;
+    assert_eq!(app.stones("stone-black"), vec![(3, 15)]);
+}
+
+#[wasm_bindgen_test]
+async fn clicks_land_on_the_points_they_are_drawn_at() {
+    let app = Mounted::new().await;
+    // The corners and edges are where off-by-one mistakes show
+    app.play(0.0, 0.0).await;
+    app.play(18.0, 18.0).await;
+    app.play(18.0, 0.0).await;
+    app.play(0.0, 18.0).await;
+    app.play(9.0, 9.0).await;
+    assert_eq!(app.stones("stone-black"), vec![(0, 0), (9, 9), (18, 0)]);
+    assert_eq!(app.stones("stone-white"), vec![(0, 18), (18, 18)]);
+}
+
+#[wasm_bindgen_test]
+async fn clicks_near_a_point_snap_to_it() {
+    let app = Mounted::new().await;
+    app.play(3.3, 2.8).await;
+    app.play(15.7, 16.2).await;
+    assert_eq!(app.stones("stone-black"), vec![(3, 3)]);
+    assert_eq!(app.stones("stone-white"), vec![(16, 16)]);
+}
+
+#[wasm_bindgen_test]
+async fn occupied_points_are_not_played() {
+    let app = Mounted::new().await;
+    app.play(4.0, 4.0).await;
+    app.play(4.0, 4.0).await;
+    assert_eq!(app.stones("stone-black"), vec![(4, 4)]);
+    assert_eq!(app.stones("stone-white"), vec![]);
+}
+
+#[wasm_bindgen_test]
+async fn each_tab_keeps_its_own_game() {
+    let app = Mounted::new().await;
+    app.play(3.0, 3.0).await;
+    app.click_on(".tab-bar .tab-new").await;
+    app.start_game().await;
+    assert_eq!(app.stones("stone-black"), vec![]);
+    app.play(15.0, 15.0).await;
+
+    app.switch_tab(0).await;
+    assert_eq!(app.stones("stone-black"), vec![(3, 3)]);
+    app.switch_tab(1).await;
+    assert_eq!(app.stones("stone-black"), vec![(15, 15)]);
+    // Playing on goes on in the shown tab only
+    app.play(3.0, 15.0).await;
+    assert_eq!(app.stones("stone-white"), vec![(3, 15)]);
+    app.switch_tab(0).await;
+    assert_eq!(app.stones("stone-white"), vec![]);
+}
@@ -0,0 +1,80 @@
+use web_sys::Element;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct WinrateGraphProps {
+    /// Black's winrate after each number of moves, `None` where the engine
+    /// hasn't looked yet
+    pub winrates: Vec<Option<f64>>,
+    /// How many moves led to the shown position
+    pub shown: usize,
+    /// Called with the number of moves that lead to the position picked
+    pub onjump: Callback<usize>,
+}
+
+/// Black's winrate over the game, from the top for black to the bottom for
+/// white, with the shown position marked. Clicking the graph shows the
+/// position under the pointer.
+#[function_component]
+pub fn WinrateGraph(props: &WinrateGraphProps) -> Html {
+    // At least one move wide, so an empty game still has a graph
+    let last = props.winrates.len().saturating_sub(1).max(1);
+    let points = props
+        .winrates
+        .iter()
+        .enumerate()
+        .filter_map(|(moves, winrate)| {
+            winrate.map(|winrate| format!("{},{}", moves, 1.0 - winrate))
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let graph_ref = use_node_ref();
+    let onclick = {
+        let onjump = props.onjump.clone();
+        let graph_ref = graph_ref.clone();
+        Callback::from(move |e: MouseEvent| {
+            let rect = match graph_ref.cast::<Element>() {
+                Some(graph) => graph.get_bounding_client_rect(),
+                None => return,
+            };
+            if rect.width() > 0.0 {
+                let share = (e.client_x() as f64 - rect.left()) / rect.width();
+                onjump.emit((share * last as f64).round().clamp(0.0, last as f64) as usize);
+            }
+        })
+    };
+    html! {
+        <svg
+            class="winrate-graph"
+            ref={ graph_ref }
+            viewBox={ format!("0 0 {} 1", last) }
+            preserveAspectRatio="none"
+            onclick={ onclick }
+            role="img"
+            aria-label="Black's winrate over the game"
+        >
+            <line
+                class="wg-even"
+                x1="0"
+                y1="0.5"
+                x2={ last.to_string() }
+                y2="0.5"
+                vector-effect="non-scaling-stroke"
+            />
+            <line
+                class="wg-shown"
+                x1={ props.shown.to_string() }
+                y1="0"
+                x2={ props.shown.to_string() }
+                y2="1"
+                vector-effect="non-scaling-stroke"
+            />
+            <polyline
+                class="wg-winrate"
+                points={ points }
+                fill="none"
+                vector-effect="non-scaling-stroke"
+            />
+        </svg>
+    }
+}
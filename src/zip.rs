@@ -0,0 +1,86 @@
+//! A minimal zip archive writer. Files are stored without compression, which
+//! keeps the writer small and is plenty for text like SGF records.
+
+/// Packs `files`, given as (name, contents) pairs, into a zip archive.
+pub fn write_zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut archive = Vec::new();
+    let mut central_directory = Vec::new();
+
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(contents);
+        let size = contents.len() as u32;
+        let name_len = name.len() as u16;
+
+        // Local file header
+        push_u32(&mut archive, 0x0403_4b50);
+        push_u16(&mut archive, 20); // version needed to extract
+        push_u16(&mut archive, 0x0800); // flags: UTF-8 names
+        push_u16(&mut archive, 0); // compression: stored
+        push_u16(&mut archive, 0); // modification time
+        push_u16(&mut archive, 0x0021); // modification date: 1980-01-01
+        push_u32(&mut archive, crc);
+        push_u32(&mut archive, size);
+        push_u32(&mut archive, size);
+        push_u16(&mut archive, name_len);
+        push_u16(&mut archive, 0); // extra field length
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(contents);
+
+        // Central directory entry
+        push_u32(&mut central_directory, 0x0201_4b50);
+        push_u16(&mut central_directory, 20); // version made by
+        push_u16(&mut central_directory, 20); // version needed to extract
+        push_u16(&mut central_directory, 0x0800);
+        push_u16(&mut central_directory, 0);
+        push_u16(&mut central_directory, 0);
+        push_u16(&mut central_directory, 0x0021);
+        push_u32(&mut central_directory, crc);
+        push_u32(&mut central_directory, size);
+        push_u32(&mut central_directory, size);
+        push_u16(&mut central_directory, name_len);
+        push_u16(&mut central_directory, 0); // extra field length
+        push_u16(&mut central_directory, 0); // comment length
+        push_u16(&mut central_directory, 0); // disk number
+        push_u16(&mut central_directory, 0); // internal attributes
+        push_u32(&mut central_directory, 0); // external attributes
+        push_u32(&mut central_directory, offset);
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    let directory_size = central_directory.len() as u32;
+    archive.extend(central_directory);
+
+    // End of central directory record
+    push_u32(&mut archive, 0x0605_4b50);
+    push_u16(&mut archive, 0); // this disk
+    push_u16(&mut archive, 0); // disk with the central directory
+    push_u16(&mut archive, files.len() as u16);
+    push_u16(&mut archive, files.len() as u16);
+    push_u32(&mut archive, directory_size);
+    push_u32(&mut archive, directory_offset);
+    push_u16(&mut archive, 0); // comment length
+    archive
+}
+
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// CRC-32 (IEEE) as used by zip.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,28 @@
+//! The bot that imitates one player of the library games.
+
+use go_rs::bot::MimicBot;
+use go_rs::engine::Engine;
+use go_rs::sgf::read_sgf;
+
+#[test]
+fn only_the_named_player_is_learned_from() {
+    // Ann plays the 3-3 point as black in one game and as white in the
+    // other, while her opponents take the 4-4 point
+    let as_black = read_sgf("(;GM[1]SZ[9]PB[Ann]PW[Bob];B[cc];W[gg])").unwrap();
+    let as_white = read_sgf("(;GM[1]SZ[9]PB[Cid]PW[Ann];B[gg];W[cc])").unwrap();
+    let without = read_sgf("(;GM[1]SZ[9]PB[Bob]PW[Cid];B[dd];W[ff])").unwrap();
+    let mut bot = MimicBot::new();
+    for game in [&as_black, &as_white, &without] {
+        bot.learn(game, "Ann");
+    }
+    assert_eq!(bot.games(), 2);
+
+    // On an empty board the learned corner pattern is the only one known,
+    // and the lowest index is taken among its four points
+    let empty = read_sgf("(;GM[1]SZ[9])").unwrap();
+    assert_eq!(bot.choose_move(&empty), Some((2, 2)));
+
+    let mut other = MimicBot::new();
+    other.learn(&without, "Bob");
+    assert_eq!(other.choose_move(&empty), Some((3, 3)));
+}
@@ -0,0 +1,19 @@
+//! Fixtures shared by the integration tests.
+
+use go_rs::game::{GoPosition, Player};
+
+/// A position from its rows, top first: `X` for black, `O` for white, `R`
+/// for red and `.` for empty points.
+pub fn position(board: &[&str]) -> GoPosition {
+    let stones: Vec<Player> = board
+        .iter()
+        .flat_map(|row| row.chars())
+        .map(|point| match point {
+            'X' => Player::Black,
+            'O' => Player::White,
+            'R' => Player::Red,
+            _ => Player::None,
+        })
+        .collect();
+    GoPosition::from_stones(board[0].len(), board.len(), &stones)
+}
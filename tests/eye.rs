@@ -0,0 +1,99 @@
+//! Real and false eyes, in the middle of the board, on the edge and in the
+//! corner.
+
+mod common;
+
+use common::position;
+use go_rs::eye::{classify, eyes, is_real_eye, Eye};
+use go_rs::game::Player;
+
+/// What the point at (`x`, `y`) of `board` is to black.
+fn black_eye(board: &[&str], x: usize, y: usize) -> Option<Eye> {
+    let position = position(board);
+    classify(&position, position.coord_to_index(x, y), Player::Black)
+}
+
+#[test]
+fn eyes_in_the_middle_allow_one_cutting_diagonal() {
+    let open = [
+        ".....", //
+        "..X..", //
+        ".X.X.", //
+        "..X..", //
+        ".....",
+    ];
+    assert_eq!(black_eye(&open, 2, 2), Some(Eye::Real));
+    let one_cut = [
+        ".....", //
+        ".OX..", //
+        ".X.X.", //
+        "..X..", //
+        ".....",
+    ];
+    assert_eq!(black_eye(&one_cut, 2, 2), Some(Eye::Real));
+    let two_cuts = [
+        ".....", //
+        ".OX..", //
+        ".X.X.", //
+        "..XO.", //
+        ".....",
+    ];
+    assert_eq!(black_eye(&two_cuts, 2, 2), Some(Eye::False));
+}
+
+#[test]
+fn eyes_on_the_edge_allow_no_cutting_diagonal() {
+    let open = [
+        ".X.X.", //
+        "..X..", //
+        ".....",
+    ];
+    assert_eq!(black_eye(&open, 2, 0), Some(Eye::Real));
+    let cut = [
+        ".X.X.", //
+        "..XO.", //
+        ".....",
+    ];
+    assert_eq!(black_eye(&cut, 2, 0), Some(Eye::False));
+}
+
+#[test]
+fn eyes_in_the_corner_allow_no_cutting_diagonal() {
+    let open = [
+        ".X.", //
+        "X..", //
+        "...",
+    ];
+    assert_eq!(black_eye(&open, 0, 0), Some(Eye::Real));
+    let cut = [
+        ".X.", //
+        "XO.", //
+        "...",
+    ];
+    assert_eq!(black_eye(&cut, 0, 0), Some(Eye::False));
+}
+
+#[test]
+fn only_empty_points_surrounded_by_one_player_are_eyes() {
+    let board = [
+        ".X.O.", //
+        "X.X..", //
+        ".X...",
+    ];
+    // An empty neighbor, an opponent's neighbor, a stone
+    assert_eq!(black_eye(&board, 3, 1), None);
+    assert_eq!(black_eye(&board, 2, 0), None);
+    assert_eq!(black_eye(&board, 1, 0), None);
+
+    let position = position(&board);
+    let eye = position.coord_to_index(1, 1);
+    assert_eq!(classify(&position, eye, Player::White), None);
+    assert_eq!(classify(&position, eye, Player::None), None);
+    assert!(is_real_eye(&position, eye, Player::Black));
+    // Both left corners are eyes as well
+    let corner = position.coord_to_index(0, 2);
+    assert_eq!(
+        eyes(&position, Player::Black),
+        [(0, Eye::Real), (eye, Eye::Real), (corner, Eye::Real)]
+    );
+}
@@ -0,0 +1,142 @@
+//! Handicaps worked out from ranks, and where their stones go.
+
+use go_rs::game::{fixed_handicap_points, GoGame, GoPosition, HandicapPlacement, Player};
+use go_rs::handicap::{rank_difference, HandicapTable, HANDICAP_KOMI, MAX_STONES};
+
+/// The names of the fixed handicap points for `stones` stones, sorted.
+fn handicap_names(size: usize, stones: usize) -> Vec<String> {
+    let position = GoPosition::new(size);
+    let mut names: Vec<String> = fixed_handicap_points(size, size, stones)
+        .into_iter()
+        .map(|(x, y)| position.point_name(x, y))
+        .collect();
+    names.sort();
+    names
+}
+
+/// The fixed handicap points of a board for 2 to 9 stones, by name: the
+/// corners, then the center, the sides and the ends of the middle lines.
+fn check_fixed_points(size: usize, corners: [&str; 4], center: &str, sides: [&str; 4]) {
+    let [upper_right, lower_left, lower_right, _] = corners;
+    let [left, right, _, _] = sides;
+    let expected: [&[&str]; 8] = [
+        &[upper_right, lower_left],
+        &[upper_right, lower_left, lower_right],
+        &corners,
+        &[&corners[..], &[center]].concat(),
+        &[&corners[..], &[left, right]].concat(),
+        &[&corners[..], &[left, right, center]].concat(),
+        &[&corners[..], &sides].concat(),
+        &[&corners[..], &sides, &[center]].concat(),
+    ];
+    for (stones, expected) in (2..=9).zip(expected) {
+        let mut expected: Vec<String> = expected.iter().map(|name| name.to_string()).collect();
+        expected.sort();
+        assert_eq!(
+            handicap_names(size, stones),
+            expected,
+            "{stones} stones on {size}x{size}"
+        );
+    }
+}
+
+#[test]
+fn fixed_handicaps_go_on_the_star_points() {
+    check_fixed_points(
+        19,
+        ["Q16", "D4", "Q4", "D16"],
+        "K10",
+        ["D10", "Q10", "K16", "K4"],
+    );
+    check_fixed_points(
+        13,
+        ["K10", "D4", "K4", "D10"],
+        "G7",
+        ["D7", "K7", "G10", "G4"],
+    );
+    check_fixed_points(9, ["G7", "C3", "G3", "C7"], "E5", ["C5", "G5", "E7", "E3"]);
+}
+
+#[test]
+fn small_boards_take_few_fixed_stones() {
+    // Without a center point to speak of, only the corners are used
+    assert_eq!(handicap_names(7, 9), ["C3", "C5", "E3", "E5"]);
+    assert!(fixed_handicap_points(6, 6, 4).is_empty());
+}
+
+#[test]
+fn fixed_handicaps_are_placed_before_white_moves() {
+    let game = GoGame::new(19).with_handicap(4, HandicapPlacement::Fixed);
+    let black: Vec<usize> = (0..game.len())
+        .filter(|index| game[*index] == Player::Black)
+        .collect();
+    let mut expected: Vec<usize> = fixed_handicap_points(19, 19, 4)
+        .into_iter()
+        .map(|(x, y)| game.coord_to_index(x, y))
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(black, expected);
+    assert_eq!(*game.initial_position(), *game);
+    assert_eq!(game.info.handicap, 4);
+    assert_eq!(game.info.komi, HANDICAP_KOMI);
+    assert_eq!(game.handicap_to_place(), 0);
+    assert_eq!(game.first_player, Player::White);
+    assert_eq!(game.next_player, Player::White);
+    assert_eq!(game.move_count(), 0);
+}
+
+#[test]
+fn free_handicaps_are_left_to_black() {
+    let game = GoGame::new(13).with_handicap(3, HandicapPlacement::Free);
+    assert!((0..game.len()).all(|index| game[index] == Player::None));
+    assert_eq!(game.info.handicap, 3);
+    assert_eq!(game.handicap_to_place(), 3);
+    assert_eq!(game.next_player, Player::Black);
+
+    let even = GoGame::new(9).with_handicap(0, HandicapPlacement::Fixed);
+    assert_eq!(even.info.handicap, 0);
+    assert_eq!(even.next_player, Player::Black);
+}
+
+#[test]
+fn a_handicap_of_one_is_only_the_komi() {
+    let even = GoGame::new(19);
+    for placement in [HandicapPlacement::Fixed, HandicapPlacement::Free] {
+        let game = GoGame::new(19).with_handicap(1, placement);
+        assert!((0..game.len()).all(|index| game[index] == Player::None));
+        assert_eq!(game.info.handicap, 0);
+        assert_eq!(game.info.komi, HANDICAP_KOMI);
+        assert_eq!(game.handicap_to_place(), 0);
+        assert_eq!(game.next_player, Player::Black);
+    }
+    assert_ne!(even.info.komi, HANDICAP_KOMI);
+}
+
+#[test]
+fn boards_without_handicap_points_stay_even() {
+    let even = GoGame::new(5);
+    let game = GoGame::new(5).with_handicap(4, HandicapPlacement::Fixed);
+    assert!((0..game.len()).all(|index| game[index] == Player::None));
+    assert_eq!(game.info.handicap, 0);
+    assert_eq!(game.info.komi, even.info.komi);
+    assert_eq!(game.next_player, Player::Black);
+}
+
+#[test]
+fn handicaps_follow_the_rank_difference() {
+    assert_eq!(rank_difference("5k", "1d"), Some(5));
+    assert_eq!(rank_difference("1d", "5k"), Some(-5));
+    assert_eq!(rank_difference("5k", "?"), None);
+
+    let stones = |table: HandicapTable, ranks| table.handicap(ranks).stones;
+    assert_eq!(stones(HandicapTable::Full, 5), 5);
+    assert_eq!(stones(HandicapTable::Reduced, 5), 4);
+    assert_eq!(stones(HandicapTable::Half, 5), 2);
+    assert_eq!(stones(HandicapTable::Full, 20), MAX_STONES);
+
+    // A single rank is made up for by taking black without komi
+    let one_rank = HandicapTable::Full.handicap(1);
+    assert_eq!(one_rank.stones, 0);
+    assert_eq!(one_rank.komi, Some(HANDICAP_KOMI));
+    assert_eq!(HandicapTable::Full.handicap(0).komi, None);
+}
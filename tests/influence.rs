@@ -0,0 +1,118 @@
+//! Influence spreading from stones and walls, positive for black and
+//! negative for white.
+
+mod common;
+
+use go_rs::influence::influence;
+
+/// The influence of a position given as rows, see `common::position`, a
+/// row at a time.
+fn influence_rows(board: &[&str]) -> Vec<Vec<i32>> {
+    influence(&common::position(board))
+        .chunks(board[0].len())
+        .map(|row| row.to_vec())
+        .collect()
+}
+
+/// The sides the overlay shades each point for, as rows of `+` for black,
+/// `-` for white and `.` for neither.
+fn shading(board: &[&str]) -> Vec<String> {
+    influence_rows(board)
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value.signum() {
+                    1 => '+',
+                    -1 => '-',
+                    _ => '.',
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn an_empty_board_has_no_influence() {
+    let influence = influence_rows(&["....."; 5]);
+    assert!(influence.iter().flatten().all(|value| *value == 0));
+}
+
+#[test]
+fn walls_hold_the_side_behind_them() {
+    let influence = influence_rows(&["..X...O.."; 9]);
+    for row in &influence {
+        // Black's wall and the edge behind it are black's, white's are
+        // white's, and the space between them is contested
+        assert!(row[..=2].iter().all(|value| *value > 0), "{row:?}");
+        assert!(row[3..=5].iter().all(|value| *value == 0), "{row:?}");
+        assert!(row[6..].iter().all(|value| *value < 0), "{row:?}");
+        // The colors mirror each other
+        for x in 0..9 {
+            assert_eq!(row[x], -row[8 - x], "{row:?}");
+        }
+    }
+}
+
+#[test]
+fn influence_has_the_sign_of_the_stones_nearby() {
+    let influence = influence_rows(&[
+        "OO.......",
+        ".O.......",
+        ".........",
+        ".........",
+        "....X....",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ]);
+    let at = |x: usize, y: usize| influence[y][x];
+    assert!(at(0, 0) < 0 && at(1, 1) < 0);
+    assert!(at(4, 4) > 0);
+    // Nothing of a color reaches into the area of the other
+    for (y, row) in influence.iter().enumerate() {
+        for (x, value) in row.iter().enumerate() {
+            if x + y <= 4 {
+                assert!(*value <= 0, "{x}, {y}: {value}");
+            } else {
+                assert!(*value >= 0, "{x}, {y}: {value}");
+            }
+        }
+    }
+}
+
+#[test]
+fn frameworks_meet_in_a_contested_middle() {
+    // Black's framework on the left, white's on the right, each with a
+    // stone reaching toward the other
+    let board = [
+        ".........",
+        ".........",
+        "..X...O..",
+        "..X......",
+        "...X.O...",
+        "......O..",
+        "..X...O..",
+        ".........",
+        ".........",
+    ];
+    assert_eq!(
+        shading(&board),
+        [
+            "+.......-",
+            "++.....--",
+            "+++...---",
+            "+++...---",
+            "++++.----",
+            "+++...---",
+            "+++...---",
+            "++.....--",
+            "+.......-",
+        ]
+    );
+    // The hold is strongest on the stones and weakens away from them
+    let influence = influence_rows(&board);
+    assert!(influence[4][3] > influence[4][2]);
+    assert!(influence[4][2] > influence[4][1]);
+    assert!(influence[4][1] > influence[4][0]);
+}
@@ -0,0 +1,435 @@
+//! Known tricky positions, each with the moves tried in it and what should
+//! come of them.
+
+mod common;
+
+use go_rs::bitboard::BitPosition;
+use go_rs::game::{GoGame, GoPosition, MoveEffects, MoveError, Player, Position};
+use go_rs::tactics::{find_tactics, TacticKind};
+
+/// What should come of trying a move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Outcome {
+    /// The move is played, capturing this many stones
+    Captures(usize),
+    /// The move retakes a ko at once
+    Ko,
+    /// The move would leave its own stones without liberties
+    Suicide,
+}
+
+struct Case {
+    name: &'static str,
+    /// The board, a row per line: `X` for black, `O` for white, `R` for
+    /// red, `.` for empty points
+    board: &'static [&'static str],
+    to_move: Player,
+    /// The points tried in turn, as (x, y) from the top left. Refused moves
+    /// leave the turn with the same player.
+    moves: &'static [((usize, usize), Outcome)],
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "snapback",
+        board: &[
+            "..OX.", //
+            "OOOX.", //
+            "XXXX.", //
+            ".....", //
+            ".....",
+        ],
+        to_move: Player::Black,
+        moves: &[
+            // The throw-in is taken, which leaves white in atari
+            ((1, 0), Outcome::Captures(0)),
+            ((0, 0), Outcome::Captures(1)),
+            // Taking back is no ko, as more than one stone is captured
+            ((1, 0), Outcome::Captures(5)),
+        ],
+    },
+    Case {
+        name: "ko",
+        board: &[
+            ".XO..", //
+            "XO.O.", //
+            ".XO..", //
+            ".....", //
+            ".....",
+        ],
+        to_move: Player::Black,
+        moves: &[
+            ((2, 1), Outcome::Captures(1)),
+            ((1, 1), Outcome::Ko),
+            // A threat and its answer lift the ban
+            ((4, 4), Outcome::Captures(0)),
+            ((4, 3), Outcome::Captures(0)),
+            ((1, 1), Outcome::Captures(1)),
+            ((2, 1), Outcome::Ko),
+        ],
+    },
+    Case {
+        name: "double ko",
+        board: &[
+            ".XO......", //
+            "XO.O.....", //
+            ".XO......", //
+            ".........", //
+            ".OX......", //
+            "OX.X.....", //
+            ".OX......", //
+            ".........", //
+            ".........",
+        ],
+        to_move: Player::Black,
+        moves: &[
+            ((2, 1), Outcome::Captures(1)),
+            // The ban is only on the ko just taken
+            ((2, 5), Outcome::Captures(1)),
+            ((1, 5), Outcome::Ko),
+            ((8, 8), Outcome::Captures(0)),
+            ((1, 1), Outcome::Captures(1)),
+            ((2, 1), Outcome::Ko),
+        ],
+    },
+    Case {
+        name: "corner seki, black fills a shared liberty",
+        board: &[
+            ".XXX.X.", //
+            "OOOOOX.", //
+            "XXXXXX.", //
+            ".......", //
+            ".......", //
+            ".......", //
+            ".......",
+        ],
+        to_move: Player::Black,
+        moves: &[
+            ((0, 0), Outcome::Captures(0)),
+            ((4, 0), Outcome::Captures(4)),
+        ],
+    },
+    Case {
+        name: "corner seki, white fills a shared liberty",
+        board: &[
+            ".XXX.X.", //
+            "OOOOOX.", //
+            "XXXXXX.", //
+            ".......", //
+            ".......", //
+            ".......", //
+            ".......",
+        ],
+        to_move: Player::White,
+        moves: &[
+            ((0, 0), Outcome::Captures(0)),
+            ((4, 0), Outcome::Captures(6)),
+        ],
+    },
+    Case {
+        name: "suicide in an eye",
+        board: &[
+            ".X...", //
+            "X....", //
+            ".....", //
+            ".....", //
+            ".....",
+        ],
+        to_move: Player::White,
+        moves: &[((0, 0), Outcome::Suicide)],
+    },
+];
+
+fn setup(board: &[&str], to_move: Player) -> GoGame {
+    GoGame::from_position(common::position(board), to_move)
+}
+
+#[test]
+fn tricky_positions() {
+    for case in CASES {
+        let mut game = setup(case.board, case.to_move);
+        for (step, ((x, y), expected)) in case.moves.iter().enumerate() {
+            let player = game.next_player;
+            let captures = game.captures(player);
+            let outcome = match game.play_move(*x, *y) {
+                Ok(()) => Outcome::Captures(game.captures(player) - captures),
+                Err(MoveError::Ko { .. }) => Outcome::Ko,
+                Err(MoveError::Suicide { .. }) => Outcome::Suicide,
+                Err(e) => panic!("{}, move {}: unexpected {:?}", case.name, step + 1, e),
+            };
+            assert_eq!(outcome, *expected, "{}, move {}", case.name, step + 1);
+        }
+    }
+}
+
+#[test]
+fn seki_has_no_territory() {
+    let mut game = setup(CASES[3].board, Player::Black);
+    game.pass();
+    game.pass();
+    let territory = game.territory();
+    for (x, y) in [(0, 0), (4, 0)] {
+        assert_eq!(territory[game.coord_to_index(x, y)], Player::None);
+    }
+    // The black wall's own area is still counted
+    assert_eq!(territory[game.coord_to_index(0, 6)], Player::Black);
+}
+
+/// Plays `moves` in turn, panicking on refused ones, and returns the ko
+/// point left after them as (x, y).
+fn ko_after(board: &[&str], moves: &[(usize, usize)]) -> Option<(usize, usize)> {
+    let mut game = setup(board, Player::Black);
+    for (x, y) in moves {
+        game.play_move(*x, *y).unwrap();
+    }
+    game.ko().map(|index| game.index_to_coord(index))
+}
+
+#[test]
+fn only_a_lone_stone_taking_a_lone_stone_in_atari_is_ko() {
+    // The ko case: black takes and is left in atari at the taken point
+    assert_eq!(ko_after(CASES[1].board, &[(2, 1)]), Some((1, 1)));
+    // The snapback: white takes the throw-in with a stone that joins a
+    // chain, and black takes the whole chain back
+    assert_eq!(ko_after(CASES[0].board, &[(1, 0), (0, 0)]), None);
+    assert_eq!(ko_after(CASES[0].board, &[(1, 0), (0, 0), (1, 0)]), None);
+    // The taking stone has other liberties
+    let open = [
+        ".X...", //
+        "XO...", //
+        ".X...", //
+        ".....", //
+        ".....",
+    ];
+    assert_eq!(ko_after(&open, &[(2, 1)]), None);
+    // Two lone stones taken at once
+    let two = [
+        "XO.OX.", //
+        ".XOX..", //
+        "......", //
+        "......",
+    ];
+    assert_eq!(ko_after(&two, &[(2, 0)]), None);
+}
+
+#[test]
+fn snapback_can_be_taken_back_at_once() {
+    let mut game = setup(CASES[0].board, Player::Black);
+    game.play_move(1, 0).unwrap();
+    game.play_move(0, 0).unwrap();
+    assert_eq!(game.check_move(1, 0, Player::Black), Ok(()));
+    game.play_move(1, 0).unwrap();
+    // Nor is the white stone at the corner point banned for white
+    assert_eq!(game.check_move(0, 0, Player::White), Ok(()));
+}
+
+#[test]
+fn a_pass_clears_the_ko_point() {
+    let mut game = setup(CASES[1].board, Player::Black);
+    game.play_move(2, 1).unwrap();
+    game.pass();
+    assert_eq!(game.ko(), None);
+    // The position is then the same as one set up with the same stones
+    let stones = GoPosition::from_stones(5, 5, game.position());
+    assert_eq!(*game, stones);
+}
+
+#[test]
+fn undoing_a_pass_puts_the_ko_ban_back() {
+    let mut game = setup(CASES[1].board, Player::Black);
+    game.play_move(2, 1).unwrap();
+    game.pass();
+    game.undo();
+    assert!(matches!(
+        game.check_move(1, 1, Player::White),
+        Err(MoveError::Ko { .. })
+    ));
+}
+
+#[test]
+fn red_takes_turns_and_captures_either_color() {
+    let board = [
+        ".X...", //
+        "XO...", //
+        ".X...", //
+        ".....", //
+        ".....",
+    ];
+    let mut game = setup(&board, Player::Black).with_three_players();
+    game.play_move(4, 4).unwrap();
+    game.play_move(3, 3).unwrap();
+    assert_eq!(game.next_player, Player::Red);
+    // Red fills white's last liberty, which black alone had surrounded
+    game.play_move(2, 1).unwrap();
+    assert_eq!(game[game.coord_to_index(1, 1)], Player::None);
+    assert_eq!(game.next_player, Player::Black);
+    game.pass();
+    game.pass();
+    assert!(!game.is_scoring());
+    game.pass();
+    assert!(game.is_scoring());
+    assert_eq!(game.result(), None);
+}
+
+#[test]
+fn a_filled_board_leaves_no_legal_moves() {
+    // Black's only points are its own eyes, and white may not play in them
+    let board = [
+        "X.X", //
+        "XXX", //
+        "X.X",
+    ];
+    let game = setup(&board, Player::White);
+    assert_eq!(game.legal_moves().count(), 0);
+    let game = setup(&board, Player::Black);
+    assert_eq!(game.legal_moves().collect::<Vec<_>>(), [(1, 0), (1, 2)]);
+}
+
+#[test]
+fn move_effects_tell_what_a_move_would_do() {
+    let mut game = setup(CASES[0].board, Player::Black);
+    // The throw-in is in atari itself and takes nothing
+    let throw_in = game.move_effects(1, 0, Player::Black).unwrap();
+    assert_eq!(
+        throw_in,
+        MoveEffects {
+            captures: vec![],
+            self_atari: true,
+            ko_created: None,
+        }
+    );
+    game.play_move(1, 0).unwrap();
+    let take = game.move_effects(0, 0, Player::White).unwrap();
+    assert_eq!(take.captures, [1]);
+    // Nothing was played while looking
+    assert_eq!(game[1], Player::Black);
+
+    let game = setup(CASES[1].board, Player::Black);
+    let ko = game.move_effects(2, 1, Player::Black).unwrap();
+    assert_eq!(ko.captures, [game.coord_to_index(1, 1)]);
+    assert_eq!(ko.ko_created, Some(game.coord_to_index(1, 1)));
+    assert!(ko.self_atari);
+    assert_eq!(
+        game.move_effects(1, 1, Player::Black),
+        Err(MoveError::Occupied)
+    );
+}
+
+#[test]
+fn ladders_and_snapbacks_are_found_in_the_game() {
+    let board = [
+        ".......", //
+        ".......", //
+        "..XX...", //
+        ".XO....", //
+        ".......", //
+        ".......", //
+        ".......",
+    ];
+    let mut game = setup(&board, Player::Black);
+    game.play_move(2, 4).unwrap();
+    let tactics = find_tactics(&game);
+    assert_eq!(tactics.len(), 1);
+    assert_eq!(tactics[0].kind, TacticKind::Ladder);
+    assert_eq!(tactics[0].moves, 0);
+    // The white stones run to the edge before they are taken
+    assert!(tactics[0].sequence.len() > 6);
+
+    // A white stone on the way breaks the ladder
+    let mut broken: Vec<String> = board.iter().map(|row| row.to_string()).collect();
+    broken[5].replace_range(5..6, "O");
+    let broken: Vec<&str> = broken.iter().map(|row| row.as_str()).collect();
+    let mut game = setup(&broken, Player::Black);
+    game.play_move(2, 4).unwrap();
+    assert!(find_tactics(&game).is_empty());
+
+    let mut game = setup(CASES[0].board, Player::Black);
+    for (x, y) in [(1, 0), (0, 0), (1, 0)] {
+        game.play_move(x, y).unwrap();
+    }
+    let tactics = find_tactics(&game);
+    assert_eq!(tactics.len(), 1);
+    assert_eq!(tactics[0].kind, TacticKind::Snapback);
+    assert_eq!(tactics[0].sequence.len(), 3);
+}
+
+/// A move as the point played and the player playing it.
+type Turn = ((usize, usize), Player);
+
+/// Puts the stones of `board` on an empty `position`, which must be as
+/// large as the board.
+fn placed<P: Position>(mut position: P, board: &[&str]) -> P {
+    for (y, row) in board.iter().enumerate() {
+        for (x, point) in row.chars().enumerate() {
+            match point {
+                'X' => position.play(x, y, Player::Black),
+                'O' => position.play(x, y, Player::White),
+                _ => continue,
+            };
+        }
+    }
+    position
+}
+
+/// The stones of `position` and the points each color may play at.
+fn legal_moves<P: Position>(position: &P) -> (Vec<Player>, Vec<usize>, Vec<usize>) {
+    let size = position.board_size();
+    let legal = |player| {
+        (0..size * size)
+            .filter(|index| position.is_valid_move(index % size, index / size, player))
+            .collect()
+    };
+    let stones = (0..size * size)
+        .map(|index| position.player_at(index))
+        .collect();
+    (stones, legal(Player::Black), legal(Player::White))
+}
+
+#[test]
+fn bit_positions_play_ko_snapback_and_suicide_alike() {
+    // Every two-color case, plus a suicide played as if the rules allowed
+    // it, which takes the stone straight off again
+    let suicide = [((0, 0), Player::White), ((2, 2), Player::Black)];
+    let sequences: Vec<(&[&str], Vec<Turn>)> = CASES
+        .iter()
+        .map(|case| {
+            let mut player = case.to_move;
+            let moves = case
+                .moves
+                .iter()
+                .filter(|(_, outcome)| matches!(outcome, Outcome::Captures(_)))
+                .map(|(point, _)| {
+                    let turn = (*point, player);
+                    player = player.next(2);
+                    turn
+                })
+                .collect();
+            (case.board, moves)
+        })
+        .chain([(CASES[5].board, suicide.to_vec())])
+        .collect();
+    for (board, moves) in sequences {
+        let size = board.len();
+        let mut go = placed(GoPosition::new(size), board);
+        let mut bit = placed(BitPosition::new(size), board);
+        assert_eq!(legal_moves(&go), legal_moves(&bit), "{board:?}");
+        for ((x, y), player) in moves {
+            let mut go_captures = go.play(x, y, player);
+            let mut bit_captures = bit.play(x, y, player);
+            go_captures.sort_unstable();
+            bit_captures.sort_unstable();
+            assert_eq!(go_captures, bit_captures, "{board:?} at ({x}, {y})");
+            assert_eq!(
+                legal_moves(&go),
+                legal_moves(&bit),
+                "{board:?} at ({x}, {y})"
+            );
+            let (stones, _, _) = legal_moves(&bit);
+            assert_eq!(
+                bit.key(),
+                GoPosition::key_of(size, size, &stones),
+                "{board:?} at ({x}, {y})"
+            );
+        }
+    }
+}
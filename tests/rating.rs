@@ -0,0 +1,63 @@
+//! Ratings moving with the results of games, and the ratings of ranks.
+
+use go_rs::rating::{expected_score, rank_rating, RatingHistory, INITIAL_RATING};
+
+#[test]
+fn ratings_move_toward_the_result() {
+    let mut history = RatingHistory::default();
+    assert_eq!(history.current(), INITIAL_RATING);
+    // Beating an equal player while provisional is worth half the K of 40
+    assert_eq!(history.record(INITIAL_RATING, 1.0), INITIAL_RATING + 20.0);
+
+    let mut history = RatingHistory::default();
+    assert_eq!(history.record(INITIAL_RATING, 0.0), INITIAL_RATING - 20.0);
+
+    let mut history = RatingHistory::default();
+    assert_eq!(history.record(INITIAL_RATING, 0.5), INITIAL_RATING);
+}
+
+#[test]
+fn upsets_move_ratings_more() {
+    let gain = |opponent: f64| {
+        let mut history = RatingHistory::default();
+        history.record(opponent, 1.0) - INITIAL_RATING
+    };
+    let stronger = gain(INITIAL_RATING + 200.0);
+    let weaker = gain(INITIAL_RATING - 200.0);
+    assert!(stronger > 20.0 && stronger < 40.0, "{stronger}");
+    assert!(weaker > 0.0 && weaker < 20.0, "{weaker}");
+    assert!((stronger + weaker - 40.0).abs() < 1e-9);
+}
+
+#[test]
+fn ratings_settle_after_the_provisional_games() {
+    let mut history = RatingHistory::default();
+    for _ in 0..20 {
+        history.record(history.current(), 0.5);
+    }
+    let before = history.current();
+    assert_eq!(history.record(before, 1.0), before + 10.0);
+    assert_eq!(history.games(), 21);
+}
+
+#[test]
+fn expected_scores_add_up_to_one() {
+    assert_eq!(expected_score(1800.0, 1800.0), 0.5);
+    let (a, b) = (
+        expected_score(2100.0, 1700.0),
+        expected_score(1700.0, 2100.0),
+    );
+    assert!((a - 10.0 / 11.0).abs() < 1e-9, "{a}");
+    assert!((a + b - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn ranks_are_rated_a_hundred_apart() {
+    assert_eq!(rank_rating("1d"), Some(2100.0));
+    assert_eq!(rank_rating("1k"), Some(2000.0));
+    assert_eq!(rank_rating("15k?"), Some(600.0));
+    assert_eq!(rank_rating("3D*"), Some(2300.0));
+    assert_eq!(rank_rating("3p"), Some(2800.0));
+    assert_eq!(rank_rating("0k"), None);
+    assert_eq!(rank_rating("strong"), None);
+}
@@ -0,0 +1,142 @@
+//! Game records read back the way they were written, and records from
+//! elsewhere that are read in.
+
+use go_rs::game::{GoGame, MoveKind, Player};
+use go_rs::problem::Problem;
+use go_rs::sgf::{read_sgf, write_sgf, SgfError};
+
+/// Writes `game` out and reads it back in.
+fn round_trip(game: &GoGame) -> GoGame {
+    read_sgf(&write_sgf(game).unwrap()).unwrap()
+}
+
+/// Who made each move of `game` and what it was.
+fn moves(game: &GoGame) -> Vec<(Player, MoveKind)> {
+    game.history().iter().map(|m| (m.player, m.kind)).collect()
+}
+
+#[test]
+fn moves_out_of_turn_survive_truncating() {
+    // White plays twice in a row at `ea`
+    let record =
+        "(;GM[1]SZ[5];B[be];W[de];B[ce];B[bc];B[ee];W[da];B[ae];W[eb];B[aa];W[ab];W[ea];B[bb])";
+    let game = read_sgf(record).unwrap();
+    for moves in 0..=game.move_count() {
+        let truncated = game.truncated(moves).unwrap();
+        assert_eq!(*truncated.position(), *game.position_at(moves).position());
+        for (kept, played) in truncated.history().iter().zip(game.history()) {
+            assert_eq!(kept.player, played.player);
+        }
+    }
+    assert_eq!(game.history()[10].player, Player::White);
+}
+
+#[test]
+fn setup_stones_survive_a_round_trip() {
+    // A rectangle of black stones with a hole cleared by AE, and white to
+    // move first
+    let record = "(;GM[1]SZ[5]AB[aa:bc]AW[ee][de]AE[ab];W[cc];B[dd])";
+    let game = read_sgf(record).unwrap();
+    let initial = game.initial_position();
+    let black: Vec<(usize, usize)> = (0..initial.len())
+        .filter(|index| initial[*index] == Player::Black)
+        .map(|index| initial.index_to_coord(index))
+        .collect();
+    assert_eq!(black, [(0, 0), (1, 0), (1, 1), (0, 2), (1, 2)]);
+    assert_eq!(initial[initial.coord_to_index(4, 4)], Player::White);
+    assert_eq!(initial[initial.coord_to_index(3, 4)], Player::White);
+    assert_eq!(game.first_player, Player::White);
+
+    let read = round_trip(&game);
+    assert_eq!(read.initial_position(), game.initial_position());
+    assert_eq!(read.first_player, Player::White);
+    assert_eq!(moves(&read), moves(&game));
+    assert_eq!(*read.position(), *game.position());
+}
+
+#[test]
+fn moves_out_of_turn_survive_a_round_trip() {
+    let record = "(;GM[1]SZ[5];B[cc];B[dd];W[bb];W[]W[aa];B[ee])";
+    let game = read_sgf(record).unwrap();
+    let players: Vec<Player> = moves(&game).iter().map(|(player, _)| *player).collect();
+    assert_eq!(
+        players,
+        [
+            Player::Black,
+            Player::Black,
+            Player::White,
+            Player::White,
+            Player::White,
+            Player::Black,
+        ]
+    );
+
+    let read = round_trip(&game);
+    assert_eq!(moves(&read), moves(&game));
+    assert_eq!(*read.position(), *game.position());
+}
+
+#[test]
+fn time_left_survives_a_round_trip() {
+    let record = "(;GM[1]SZ[9]TM[300];B[aa]BL[290];W[bb]WL[280.5];B[cc]BL[250];W[dd])";
+    let game = read_sgf(record).unwrap();
+    assert_eq!(game.info.main_time, Some(300.0));
+    assert_eq!(
+        game.time_left(),
+        [Some(290.0), Some(280.5), Some(250.0), None]
+    );
+
+    let read = round_trip(&game);
+    assert_eq!(read.info.main_time, Some(300.0));
+    assert_eq!(read.time_left(), game.time_left());
+}
+
+#[test]
+fn three_player_games_are_not_written() {
+    let mut game = GoGame::new(9).with_three_players();
+    for (x, y) in [(2, 2), (6, 6), (4, 4)] {
+        game.play_move(x, y).unwrap();
+    }
+    assert_eq!(game[game.coord_to_index(4, 4)], Player::Red);
+    assert_eq!(write_sgf(&game), Err(SgfError::ThirdColor));
+    assert!(write_sgf(&GoGame::new(9)).is_ok());
+}
+
+#[test]
+fn deeply_nested_variations_are_refused() {
+    // Unclosed, and nested far past what the stack holds
+    assert!(matches!(
+        read_sgf(&"(".repeat(200_000)),
+        Err(SgfError::Syntax(_))
+    ));
+    let nested = |depth: usize| format!("{}{}", "(;GM[1]SZ[9]".repeat(depth), ")".repeat(depth));
+    assert!(matches!(
+        read_sgf(&nested(20_000)),
+        Err(SgfError::Syntax(_))
+    ));
+    // Up to the limit the record reads as usual
+    assert!(read_sgf(&nested(10_000)).is_ok());
+}
+
+#[test]
+fn problems_with_answers_too_deep_are_refused() {
+    let problem = |line: &str, depth: usize| {
+        let record = format!(
+            "(;GM[1]SZ[9]AB[aa]{}{})",
+            line.repeat(depth),
+            ")".repeat(depth)
+        );
+        Problem::read(String::new(), &record)
+    };
+    // Nested variations, and a single line of many moves
+    assert!(matches!(
+        problem("(;B[bb]", 9_999),
+        Err(SgfError::Syntax(_))
+    ));
+    let long_line = format!("(;GM[1]SZ[9]AB[aa]{})", ";B[bb]C[RIGHT]".repeat(5_000));
+    assert!(matches!(
+        Problem::read(String::new(), &long_line),
+        Err(SgfError::Syntax(_))
+    ));
+    assert!(problem("(;B[bb]C[RIGHT]", 10).is_ok());
+}
@@ -0,0 +1,157 @@
+//! Tournament schedules, pairings and standings.
+
+use std::collections::HashSet;
+
+use go_rs::handicap::{HandicapTable, HANDICAP_KOMI};
+use go_rs::tournament::{Entrant, Format, GameResult, Tournament};
+
+fn entrants(ranks: &[&str]) -> Vec<Entrant> {
+    ranks
+        .iter()
+        .enumerate()
+        .map(|(i, rank)| Entrant {
+            name: format!("Player {}", i + 1),
+            rank: rank.to_string(),
+        })
+        .collect()
+}
+
+fn round_robin(players: usize) -> Tournament {
+    Tournament::new(entrants(&vec![""; players]), Format::RoundRobin, None, 19).unwrap()
+}
+
+#[test]
+fn round_robins_pair_everyone_once() {
+    for players in 3..=8 {
+        let tournament = round_robin(players);
+        let rounds = if players % 2 == 0 {
+            players - 1
+        } else {
+            players
+        };
+        assert_eq!(tournament.rounds.len(), rounds, "{players} players");
+
+        let mut pairs = HashSet::new();
+        for round in &tournament.rounds {
+            assert_eq!(round.len(), players / 2, "{players} players");
+            let mut seated = HashSet::new();
+            for pairing in round {
+                assert_ne!(pairing.black, pairing.white);
+                // Nobody plays twice in a round
+                assert!(seated.insert(pairing.black), "{players} players");
+                assert!(seated.insert(pairing.white), "{players} players");
+                let pair = (
+                    pairing.black.min(pairing.white),
+                    pairing.black.max(pairing.white),
+                );
+                assert!(pairs.insert(pair), "{pair:?} meet twice");
+            }
+        }
+        assert_eq!(
+            pairs.len(),
+            players * (players - 1) / 2,
+            "{players} players"
+        );
+    }
+}
+
+#[test]
+fn odd_round_robins_give_everyone_one_bye() {
+    for players in [3, 5, 7] {
+        let tournament = round_robin(players);
+        let mut byes = vec![0; players];
+        for round in &tournament.rounds {
+            for (entrant, byes) in byes.iter_mut().enumerate() {
+                if !round
+                    .iter()
+                    .any(|pairing| pairing.black == entrant || pairing.white == entrant)
+                {
+                    *byes += 1;
+                }
+            }
+        }
+        assert_eq!(byes, vec![1; players], "{players} players");
+    }
+}
+
+#[test]
+fn even_games_share_black_out_evenly() {
+    let tournament = round_robin(6);
+    for entrant in 0..6 {
+        let games = tournament.rounds.iter().flatten();
+        let blacks = games
+            .clone()
+            .filter(|pairing| pairing.black == entrant)
+            .count();
+        let whites = games.filter(|pairing| pairing.white == entrant).count();
+        assert!(
+            blacks.abs_diff(whites) <= 1,
+            "{entrant}: {blacks} to {whites}"
+        );
+    }
+}
+
+#[test]
+fn the_weaker_player_takes_black_with_the_handicap() {
+    let tournament = Tournament::new(
+        entrants(&["1d", "4k", "3k"]),
+        Format::RoundRobin,
+        Some(HandicapTable::Full),
+        19,
+    )
+    .unwrap();
+    let pairing = |a: usize, b: usize| {
+        tournament
+            .rounds
+            .iter()
+            .flatten()
+            .find(|p| (p.black, p.white) == (a, b) || (p.black, p.white) == (b, a))
+            .unwrap()
+    };
+    let four_stones = pairing(0, 1);
+    assert_eq!((four_stones.black, four_stones.white), (1, 0));
+    assert_eq!(four_stones.handicap, 4);
+    assert_eq!(four_stones.komi, Some(HANDICAP_KOMI));
+    // A single rank is only made up for by the komi
+    let one_rank = pairing(1, 2);
+    assert_eq!((one_rank.black, one_rank.white), (1, 2));
+    assert_eq!(one_rank.handicap, 0);
+    assert_eq!(one_rank.komi, Some(HANDICAP_KOMI));
+}
+
+#[test]
+fn tournaments_need_enough_players() {
+    assert!(Tournament::new(entrants(&["", ""]), Format::RoundRobin, None, 19).is_err());
+    assert!(Tournament::new(
+        entrants(&["", "", ""]),
+        Format::Match { games: 3 },
+        None,
+        19
+    )
+    .is_err());
+    assert!(Tournament::new(entrants(&["", ""]), Format::Match { games: 0 }, None, 19).is_err());
+}
+
+#[test]
+fn a_match_ends_once_decided() {
+    let mut tournament =
+        Tournament::new(entrants(&["", ""]), Format::Match { games: 3 }, None, 19).unwrap();
+    assert_eq!(tournament.rounds.len(), 3);
+    for round in 0..2 {
+        let (round_played, game) = tournament.next_game().unwrap();
+        assert_eq!(round_played, round);
+        let pairing = &tournament.rounds[round][game];
+        let result = if pairing.black == 0 {
+            GameResult::BlackWins
+        } else {
+            GameResult::WhiteWins
+        };
+        tournament.set_result(round, game, Some(result));
+    }
+    // Two wins out of three decide it, the last game isn't played
+    assert!(tournament.is_finished());
+    let standings = tournament.standings();
+    assert_eq!(standings[0].entrant, 0);
+    assert_eq!((standings[0].wins, standings[0].losses), (2, 0));
+    assert_eq!(standings[0].points, 2.0);
+}